@@ -0,0 +1,100 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Compares appending with `push_str`, which is what `String::from`
+// does, with copying the segments through raw pointers into the
+// reserved space and setting the length once. Run it with
+//
+//     cargo run --release --example append
+//
+// Every case prints the best time per conversion over a number of
+// runs.
+
+extern crate sconcat;
+
+use sconcat::{join, Cat, CAT};
+use std::hint::black_box;
+use std::ptr;
+use std::time::{Duration, Instant};
+
+const RUNS: usize = 15;
+const ITERS: u32 = 500;
+
+// Copies the segments into the reserved space of a new string.
+fn raw_copy<C: Cat>(cat: &C) -> String {
+    let capacity = cat.size_hint();
+    let mut v = Vec::<u8>::with_capacity(capacity);
+    let base = v.as_mut_ptr();
+    let mut len = 0;
+    cat.for_each_segment(&mut |segment| {
+        assert!(segment.len() <= capacity - len, "size hint too small");
+        // The assertion keeps the copy inside the reserved space.
+        unsafe {
+            ptr::copy_nonoverlapping(
+                segment.as_ptr(),
+                base.add(len),
+                segment.len(),
+            );
+        }
+        len += segment.len();
+    });
+    // The first `len` bytes were initialized from whole segments.
+    unsafe {
+        v.set_len(len);
+        String::from_utf8_unchecked(v)
+    }
+}
+
+fn best<F: FnMut() -> String>(mut f: F) -> Duration {
+    let mut best = Duration::MAX;
+    for _ in 0..RUNS {
+        let start = Instant::now();
+        for _ in 0..ITERS {
+            black_box(f());
+        }
+        best = best.min(start.elapsed() / ITERS);
+    }
+    best
+}
+
+fn compare<C: Cat>(name: &str, cat: &C) {
+    assert_eq!(raw_copy(cat), String::from(CAT + cat));
+    let push = best(|| String::from(CAT + black_box(cat)));
+    let raw = best(|| raw_copy(black_box(cat)));
+    let change = raw.as_secs_f64() / push.as_secs_f64() - 1.0;
+    println!(
+        "{:<24} push_str {:>10.2?}  raw copy {:>10.2?}  {:+.0}%",
+        name,
+        push,
+        raw,
+        change * 100.0
+    );
+}
+
+fn main() {
+    let words: Vec<String> = (0..10_000).map(|i| i.to_string()).collect();
+    let short = join(words.iter().map(String::as_str), " ");
+    compare("10,000 short items", &short);
+    let line = "x".repeat(64);
+    let long = join((0..10_000).map(|_| line.as_str()), " ");
+    compare("10,000 64-byte items", &long);
+    let chars =
+        join((0..10_000).map(|i| if i % 2 == 0 { 'a' } else { 'é' }), "");
+    compare("10,000 chars", &chars);
+    let tree = CAT
+        + "GET "
+        + "/index.html"
+        + ' '
+        + "HTTP/1.1"
+        + "\r\n"
+        + "Host: "
+        + "example.com"
+        + '\r'
+        + '\n';
+    compare("nine-piece tree", &tree);
+}
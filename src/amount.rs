@@ -0,0 +1,187 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use cat::{Cat, SegmentWriter};
+use group;
+use std::fmt::{self, Display};
+use std::str;
+
+const SATS_PER_BTC: u64 = 100_000_000;
+const BTC_DECIMALS: usize = 8;
+
+/// A piece that formats an amount of satoshis as bitcoins with eight
+/// decimal places.
+///
+/// This is created by the [`btc`](fn.btc.html) function.
+#[derive(Clone, Copy, Debug)]
+pub struct Btc {
+    sats: u64,
+}
+
+/// A piece that formats an amount of satoshis with thousands
+/// separators.
+///
+/// This is created by the [`sats`](fn.sats.html) function.
+#[derive(Clone, Copy, Debug)]
+pub struct Sats {
+    sats: u64,
+}
+
+/// Creates a piece that formats an amount of satoshis as bitcoins
+/// with exactly eight decimal places.
+///
+/// The size hint is exact, so the amount does not cause any extra
+/// allocations.
+///
+/// # Examples
+///
+/// ```rust
+/// use sconcat::{CAT, btc};
+///
+/// let cat = CAT + "sent " + btc(123_456_789) + " BTC";
+/// assert_eq!(String::from(cat), "sent 1.23456789 BTC");
+/// let cat = CAT + btc(1_000);
+/// assert_eq!(String::from(cat), "0.00001000");
+/// ```
+pub fn btc(sats: u64) -> Btc {
+    Btc { sats }
+}
+
+/// Creates a piece that formats an amount of satoshis as an integer
+/// with commas separating groups of thousands.
+///
+/// The size hint is exact, so the amount does not cause any extra
+/// allocations.
+///
+/// # Examples
+///
+/// ```rust
+/// use sconcat::{CAT, sats};
+///
+/// let cat = CAT + "fee: " + sats(1_234_567) + " sat";
+/// assert_eq!(String::from(cat), "fee: 1,234,567 sat");
+/// ```
+pub fn sats(sats: u64) -> Sats {
+    Sats { sats }
+}
+
+// Writes the decimal digits of `n` to the end of `buf` and returns
+// them.
+fn digits(n: u64, buf: &mut [u8; 20]) -> &str {
+    let mut n = n;
+    let mut start = buf.len();
+    loop {
+        start -= 1;
+        buf[start] = b'0' + (n % 10) as u8;
+        n /= 10;
+        if n == 0 {
+            break;
+        }
+    }
+    str::from_utf8(&buf[start..]).unwrap()
+}
+
+fn digit_count(n: u64) -> usize {
+    let mut n = n;
+    let mut count = 1;
+    while n >= 10 {
+        n /= 10;
+        count += 1;
+    }
+    count
+}
+
+impl Btc {
+    fn write<W: fmt::Write>(&self, w: &mut W) -> fmt::Result {
+        let mut buf = [0; 20];
+        w.write_str(digits(self.sats / SATS_PER_BTC, &mut buf))?;
+        w.write_char('.')?;
+        let frac = digits(self.sats % SATS_PER_BTC, &mut buf);
+        for _ in frac.len()..BTC_DECIMALS {
+            w.write_char('0')?;
+        }
+        w.write_str(frac)
+    }
+}
+
+impl Cat for Btc {
+    fn size_hint(&self) -> usize {
+        digit_count(self.sats / SATS_PER_BTC) + 1 + BTC_DECIMALS
+    }
+
+    fn append_to(&self, s: &mut String) {
+        self.write(s).unwrap();
+    }
+
+    fn for_each_segment(&self, f: &mut dyn FnMut(&str)) {
+        self.write(&mut SegmentWriter::new(f)).unwrap();
+    }
+}
+
+impl Display for Btc {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.write(f)
+    }
+}
+
+impl Sats {
+    fn write<W: fmt::Write>(&self, w: &mut W) -> fmt::Result {
+        let mut buf = [0; 20];
+        group::write_grouped(w, digits(self.sats, &mut buf), ',')
+    }
+}
+
+impl Cat for Sats {
+    fn size_hint(&self) -> usize {
+        let count = digit_count(self.sats);
+        count + (count - 1) / 3
+    }
+
+    fn append_to(&self, s: &mut String) {
+        self.write(s).unwrap();
+    }
+
+    fn for_each_segment(&self, f: &mut dyn FnMut(&str)) {
+        self.write(&mut SegmentWriter::new(f)).unwrap();
+    }
+}
+
+impl Display for Sats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.write(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use CAT;
+    use super::{btc, sats};
+
+    #[test]
+    fn btc_amounts() {
+        assert_eq!(String::from(CAT + btc(0)), "0.00000000");
+        assert_eq!(String::from(CAT + btc(1)), "0.00000001");
+        assert_eq!(String::from(CAT + btc(100_000_000)), "1.00000000");
+        let max = CAT + btc(u64::MAX);
+        let s = String::from(max);
+        assert_eq!(s, "184467440737.09551615");
+        assert_eq!(s.len(), s.capacity());
+        assert_eq!(max.to_string(), s);
+    }
+
+    #[test]
+    fn sats_amounts() {
+        assert_eq!(String::from(CAT + sats(0)), "0");
+        assert_eq!(String::from(CAT + sats(999)), "999");
+        assert_eq!(String::from(CAT + sats(1_000)), "1,000");
+        assert_eq!(String::from(CAT + sats(123_456)), "123,456");
+        let s = String::from(CAT + sats(12_345_678));
+        assert_eq!(s, "12,345,678");
+        assert_eq!(s.len(), s.capacity());
+    }
+}
@@ -0,0 +1,74 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// `format_args!` output as a piece. Arguments without any
+// placeholders are a plain string and are used directly; otherwise
+// the size hint is computed by formatting into a `CountingWriter`,
+// like for `Dbg`, so the piece is formatted twice but the result is
+// allocated once.
+
+use cat::{Cat, SegmentWriter};
+use count::CountingWriter;
+use std::fmt::{Arguments, Write};
+
+impl<'a> Cat for Arguments<'a> {
+    fn size_hint(&self) -> usize {
+        if let Some(s) = self.as_str() {
+            return s.len();
+        }
+        let mut w = CountingWriter::new();
+        w.write_fmt(*self).expect("capacity overflow");
+        w.count()
+    }
+
+    fn append_to(&self, s: &mut String) {
+        match self.as_str() {
+            Some(lit) => s.push_str(lit),
+            None => s.write_fmt(*self).unwrap(),
+        }
+    }
+
+    fn for_each_segment(&self, f: &mut dyn FnMut(&str)) {
+        match self.as_str() {
+            Some(lit) => f(lit),
+            None => SegmentWriter::new(f).write_fmt(*self).unwrap(),
+        }
+    }
+
+    fn single_str(&self) -> Option<&str> {
+        self.as_str()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use CAT;
+    use cat::Cat;
+
+    #[test]
+    fn format_args() {
+        let s =
+            String::from(CAT + "[" + format_args!("{}-{:03}", "id", 7) + ']');
+        assert_eq!(s, "[id-007]");
+        assert_eq!(s.len(), s.capacity());
+        let s = String::from(CAT + format_args!("plain") + '!');
+        assert_eq!(s, "plain!");
+        assert_eq!(format_args!("{:>4}", 'é').size_hint(), 5);
+        assert_eq!(format_args!("lit").single_str(), Some("lit"));
+        let mut segments = Vec::new();
+        format_args!("a{}c", 'b').for_each_segment(&mut |segment| {
+            segments.push(String::from(segment))
+        });
+        assert_eq!(segments.concat(), "abc");
+        let mut w = String::new();
+        (CAT + format_args!("{}", 1) + format_args!("{}", 2))
+            .write_to(&mut w)
+            .unwrap();
+        assert_eq!(w, "12");
+    }
+}
@@ -0,0 +1,400 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use cat::{Cat, CatMany, CatOne, CatStart};
+use segments::Cursor;
+use std::fmt::{self, Debug};
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+
+// Size of the buffer that short segments are staged in.
+const STAGE_SIZE: usize = 1024;
+
+// Tracks how much of a concatenation has been written, so that
+// writing can resume after the writer returns `Pending`. Runs of
+// short segments are copied into a stage first and written together,
+// while segments that do not fit in the stage are written directly.
+struct Progress<'a, C: ?Sized> {
+    cat: &'a C,
+    cursor: Cursor,
+    // Offset into the current segment when writing it directly.
+    offset: usize,
+    stage: [u8; STAGE_SIZE],
+    staged: usize,
+    written: usize,
+}
+
+// Passes `bytes` to `write`, treating a write of zero bytes as an
+// error.
+fn write_some<F>(write: &mut F, bytes: &[u8]) -> Poll<io::Result<usize>>
+where
+    F: FnMut(&[u8]) -> Poll<io::Result<usize>>,
+{
+    match write(bytes) {
+        Poll::Ready(Ok(0)) => Poll::Ready(Err(io::ErrorKind::WriteZero.into())),
+        other => other,
+    }
+}
+
+impl<'a, C: Cat + ?Sized> Progress<'a, C> {
+    fn new(cat: &'a C) -> Progress<'a, C> {
+        Progress {
+            cat,
+            cursor: Cursor::new(false),
+            offset: 0,
+            stage: [0; STAGE_SIZE],
+            staged: 0,
+            written: 0,
+        }
+    }
+
+    // Passes the staged bytes or the rest of the current segment to
+    // `write` until everything has been written, `write` is pending
+    // or `write` fails.
+    fn poll<F>(&mut self, mut write: F) -> Poll<io::Result<()>>
+    where
+        F: FnMut(&[u8]) -> Poll<io::Result<usize>>,
+    {
+        let cat = self.cat;
+        let root: &dyn Cat = &cat;
+        loop {
+            while self.written < self.staged {
+                let bytes = &self.stage[self.written..self.staged];
+                self.written += ready!(write_some(&mut write, bytes))?;
+            }
+            self.staged = 0;
+            self.written = 0;
+            let written = {
+                let segment = match self.cursor.peek(root) {
+                    Some(segment) => segment,
+                    None => return Poll::Ready(Ok(())),
+                };
+                let rest = &segment.as_bytes()[self.offset..];
+                if self.offset == 0 && rest.len() < STAGE_SIZE {
+                    None
+                } else {
+                    let n = ready!(write_some(&mut write, rest))?;
+                    Some((n, n == rest.len()))
+                }
+            };
+            match written {
+                Some((_, true)) => {
+                    self.cursor.advance();
+                    self.offset = 0;
+                }
+                Some((n, false)) => self.offset += n,
+                None => self.fill(root),
+            }
+        }
+    }
+
+    // Copies segments into the stage while they fit.
+    fn fill(&mut self, root: &dyn Cat) {
+        while let Some(segment) = self.cursor.peek(root) {
+            let end = self.staged + segment.len();
+            if end > STAGE_SIZE {
+                break;
+            }
+            self.stage[self.staged..end].copy_from_slice(segment.as_bytes());
+            self.staged = end;
+            self.cursor.advance();
+        }
+    }
+}
+
+macro_rules! write_future {
+    (
+        $(#[$attr:meta])*
+        $Future:ident, $AsyncWrite:path, $method:ident, $feature:expr
+    ) => {
+        $(#[$attr])*
+        pub struct $Future<'a, C: ?Sized, W: ?Sized> {
+            progress: Progress<'a, C>,
+            writer: &'a mut W,
+        }
+
+        impl<'a, C, W> Future for $Future<'a, C, W>
+        where
+            C: Cat + ?Sized,
+            W: $AsyncWrite + Unpin + ?Sized,
+        {
+            type Output = io::Result<()>;
+
+            fn poll(
+                self: Pin<&mut Self>,
+                cx: &mut Context,
+            ) -> Poll<io::Result<()>> {
+                let this = self.get_mut();
+                let writer = &mut this.writer;
+                this.progress
+                    .poll(|bytes| Pin::new(&mut **writer).poll_write(cx, bytes))
+            }
+        }
+
+        impl<'a, C: ?Sized, W: ?Sized> Debug for $Future<'a, C, W> {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                let progress = &self.progress;
+                f.debug_struct(stringify!($Future))
+                    .field("offset", &progress.offset)
+                    .field("staged", &(progress.staged - progress.written))
+                    .finish()
+            }
+        }
+
+        impl<L: Cat, R: Cat> CatMany<L, R> {
+            #[doc = concat!(
+                "Returns a future that writes the concatenation to an ",
+                "asynchronous writer from the `", $feature, "` crate."
+            )]
+            ///
+            /// The segments are written one after the other without
+            /// materializing the concatenation. The future borrows
+            /// the concatenation and the writer, and resolves after
+            /// every byte has been accepted by the writer or at the
+            /// first error. The writer is not flushed.
+            pub fn $method<'a, W>(
+                &'a self,
+                w: &'a mut W,
+            ) -> $Future<'a, CatMany<L, R>, W>
+            where
+                W: $AsyncWrite + Unpin + ?Sized,
+            {
+                $Future {
+                    progress: Progress::new(self),
+                    writer: w,
+                }
+            }
+        }
+
+        impl<T: Cat> CatOne<T> {
+            #[doc = concat!(
+                "Returns a future that writes the concatenation to an ",
+                "asynchronous writer from the `", $feature, "` crate."
+            )]
+            pub fn $method<'a, W>(&'a self, w: &'a mut W) -> $Future<'a, T, W>
+            where
+                W: $AsyncWrite + Unpin + ?Sized,
+            {
+                $Future {
+                    progress: Progress::new(&self.inner),
+                    writer: w,
+                }
+            }
+        }
+
+        impl CatStart {
+            /// Returns a future that writes nothing and resolves
+            /// immediately.
+            pub fn $method<'a, W>(
+                &'a self,
+                w: &'a mut W,
+            ) -> $Future<'a, &'static str, W>
+            where
+                W: $AsyncWrite + Unpin + ?Sized,
+            {
+                $Future {
+                    progress: Progress::new(&""),
+                    writer: w,
+                }
+            }
+        }
+    };
+}
+
+#[cfg(feature = "futures-io")]
+write_future! {
+    /// A future that writes a concatenation to a
+    /// `futures_io::AsyncWrite` writer.
+    ///
+    /// This is created by the `write_to_async` methods of the
+    /// concatenation types.
+    WriteAsync, ::futures_io::AsyncWrite, write_to_async, "futures-io"
+}
+
+#[cfg(feature = "tokio")]
+write_future! {
+    /// A future that writes a concatenation to a
+    /// `tokio::io::AsyncWrite` writer.
+    ///
+    /// This is created by the `write_to_tokio` methods of the
+    /// concatenation types.
+    WriteTokio, ::tokio::io::AsyncWrite, write_to_tokio, "tokio"
+}
+
+#[cfg(test)]
+mod tests {
+    use CAT;
+    use Dbg;
+    use std::future::Future;
+    use std::io;
+    use std::pin::Pin;
+    use std::task::{Context, Poll, Waker};
+
+    // Accepts at most `max` bytes per call, and is pending on every
+    // other call.
+    struct Slow {
+        bytes: Vec<u8>,
+        pending: bool,
+        max: usize,
+        writes: usize,
+    }
+
+    impl Slow {
+        fn poll_write(
+            &mut self,
+            cx: &mut Context,
+            buf: &[u8],
+        ) -> Poll<io::Result<usize>> {
+            self.pending = !self.pending;
+            if self.pending {
+                cx.waker().wake_by_ref();
+                return Poll::Pending;
+            }
+            let n = buf.len().min(self.max);
+            self.bytes.extend_from_slice(&buf[..n]);
+            self.writes += 1;
+            Poll::Ready(Ok(n))
+        }
+    }
+
+    #[cfg(feature = "futures-io")]
+    impl ::futures_io::AsyncWrite for Slow {
+        fn poll_write(
+            self: Pin<&mut Self>,
+            cx: &mut Context,
+            buf: &[u8],
+        ) -> Poll<io::Result<usize>> {
+            self.get_mut().poll_write(cx, buf)
+        }
+
+        fn poll_flush(
+            self: Pin<&mut Self>,
+            _cx: &mut Context,
+        ) -> Poll<io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_close(
+            self: Pin<&mut Self>,
+            _cx: &mut Context,
+        ) -> Poll<io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    #[cfg(feature = "tokio")]
+    impl ::tokio::io::AsyncWrite for Slow {
+        fn poll_write(
+            self: Pin<&mut Self>,
+            cx: &mut Context,
+            buf: &[u8],
+        ) -> Poll<io::Result<usize>> {
+            self.get_mut().poll_write(cx, buf)
+        }
+
+        fn poll_flush(
+            self: Pin<&mut Self>,
+            _cx: &mut Context,
+        ) -> Poll<io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_shutdown(
+            self: Pin<&mut Self>,
+            _cx: &mut Context,
+        ) -> Poll<io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    fn block_on<F: Future + Unpin>(mut f: F) -> F::Output {
+        let mut cx = Context::from_waker(Waker::noop());
+        loop {
+            if let Poll::Ready(output) = Pin::new(&mut f).poll(&mut cx) {
+                return output;
+            }
+        }
+    }
+
+    fn slow(max: usize) -> Slow {
+        Slow {
+            bytes: Vec::new(),
+            pending: false,
+            max,
+            writes: 0,
+        }
+    }
+
+    fn assert_send<T: Send>(_: &T) {}
+
+    #[cfg(feature = "futures-io")]
+    #[test]
+    fn futures_io() {
+        let cat = CAT + "ab" + 'é' + Dbg([1, 2]) + String::from("xyz");
+        let mut w = slow(3);
+        block_on(cat.write_to_async(&mut w)).unwrap();
+        assert_eq!(w.bytes, cat.to_string().as_bytes());
+        block_on((CAT + "!").write_to_async(&mut w)).unwrap();
+        block_on(CAT.write_to_async(&mut w)).unwrap();
+        assert!(w.bytes.ends_with(b"xyz!"));
+    }
+
+    #[cfg(feature = "futures-io")]
+    #[test]
+    fn async_batches() {
+        let items: Vec<String> = (0..40_000).map(|i| i.to_string()).collect();
+        let expected = items.concat();
+        let cat = CAT + '[' + items + ']';
+        let mut w = slow(usize::MAX);
+        let future = cat.write_to_async(&mut w);
+        assert_send(&future);
+        block_on(future).unwrap();
+        assert_eq!(w.bytes.len(), expected.len() + 2);
+        assert_eq!(&w.bytes[1..w.bytes.len() - 1], expected.as_bytes());
+        assert!(w.writes <= w.bytes.len() / 1000 + 1);
+        let mut w = slow(usize::MAX);
+        let long = "x".repeat(5000);
+        let cat = CAT + "a" + 'b' + &*long + "c";
+        block_on(cat.write_to_async(&mut w)).unwrap();
+        assert_eq!(w.writes, 3);
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn tokio() {
+        let cat = CAT + "ab" + 'é' + Dbg([1, 2]) + String::from("xyz");
+        let mut w = slow(3);
+        block_on(cat.write_to_tokio(&mut w)).unwrap();
+        assert_eq!(w.bytes, cat.to_string().as_bytes());
+        block_on((CAT + "!").write_to_tokio(&mut w)).unwrap();
+        block_on(CAT.write_to_tokio(&mut w)).unwrap();
+        assert!(w.bytes.ends_with(b"xyz!"));
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn tokio_batches() {
+        let items: Vec<String> = (0..40_000).map(|i| i.to_string()).collect();
+        let expected = items.concat();
+        let cat = CAT + '[' + items + ']';
+        let mut w = slow(usize::MAX);
+        let future = cat.write_to_tokio(&mut w);
+        assert_send(&future);
+        block_on(future).unwrap();
+        assert_eq!(w.bytes.len(), expected.len() + 2);
+        assert_eq!(&w.bytes[1..w.bytes.len() - 1], expected.as_bytes());
+        assert!(w.writes <= w.bytes.len() / 1000 + 1);
+        let mut w = slow(usize::MAX);
+        let long = "x".repeat(5000);
+        let cat = CAT + "a" + 'b' + &*long + "c";
+        block_on(cat.write_to_tokio(&mut w)).unwrap();
+        assert_eq!(w.writes, 3);
+    }
+}
@@ -0,0 +1,205 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use cat::Cat;
+use std::fmt::{self, Display};
+use std::str;
+
+const STANDARD: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+const URL_SAFE: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+// Input bytes encoded at a time by `for_each_segment`.
+const CHUNK: usize = 48;
+
+/// The alphabet of a [`Base64`](struct.Base64.html) piece.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Base64Alphabet {
+    /// The standard alphabet of RFC 4648, with `+` and `/`.
+    Standard,
+    /// The URL and filename safe alphabet of RFC 4648, with `-` and
+    /// `_`.
+    UrlSafe,
+}
+
+/// A piece that encodes bytes in base64.
+///
+/// This is created by the [`base64`](fn.base64.html) and
+/// [`base64_with`](fn.base64_with.html) functions.
+#[derive(Clone, Copy, Debug)]
+pub struct Base64<'a> {
+    bytes: &'a [u8],
+    alphabet: &'static [u8; 64],
+    pad: bool,
+}
+
+/// Creates a piece that encodes `bytes` in base64 with the standard
+/// alphabet and padding.
+///
+/// The size hint is exact.
+///
+/// # Examples
+///
+/// ```rust
+/// use sconcat::{CAT, base64};
+///
+/// let cat = CAT + "Basic " + base64(b"user:pass");
+/// assert_eq!(String::from(cat), "Basic dXNlcjpwYXNz");
+/// ```
+pub fn base64(bytes: &[u8]) -> Base64<'_> {
+    base64_with(bytes, Base64Alphabet::Standard, true)
+}
+
+/// Creates a piece that encodes `bytes` in base64 with the given
+/// alphabet, and with `=` padding to a multiple of four characters if
+/// `pad` is true.
+///
+/// # Examples
+///
+/// ```rust
+/// use sconcat::{Base64Alphabet, CAT, base64_with};
+///
+/// let token = [0xfb, 0xff];
+/// let cat = CAT + "t=" + base64_with(&token, Base64Alphabet::UrlSafe, false);
+/// assert_eq!(String::from(cat), "t=-_8");
+/// ```
+pub fn base64_with(
+    bytes: &[u8],
+    alphabet: Base64Alphabet,
+    pad: bool,
+) -> Base64<'_> {
+    let alphabet = match alphabet {
+        Base64Alphabet::Standard => STANDARD,
+        Base64Alphabet::UrlSafe => URL_SAFE,
+    };
+    Base64 {
+        bytes,
+        alphabet,
+        pad,
+    }
+}
+
+impl<'a> Base64<'a> {
+    // Encodes up to three bytes into `out`, returning the number of
+    // characters written.
+    fn encode_group(&self, group: &[u8], out: &mut [u8]) -> usize {
+        let b0 = group[0];
+        let b1 = group.get(1).cloned().unwrap_or(0);
+        let b2 = group.get(2).cloned().unwrap_or(0);
+        let indices = [
+            b0 >> 2,
+            (b0 & 0x03) << 4 | b1 >> 4,
+            (b1 & 0x0f) << 2 | b2 >> 6,
+            b2 & 0x3f,
+        ];
+        let used = group.len() + 1;
+        for (o, &i) in out.iter_mut().zip(&indices[..used]) {
+            *o = self.alphabet[usize::from(i)];
+        }
+        if !self.pad {
+            return used;
+        }
+        for o in &mut out[used..4] {
+            *o = b'=';
+        }
+        4
+    }
+}
+
+impl<'a> Cat for Base64<'a> {
+    fn size_hint(&self) -> usize {
+        let len = self.bytes.len();
+        let rem = match len % 3 {
+            0 => 0,
+            _ if self.pad => 4,
+            r => r + 1,
+        };
+        (len / 3)
+            .checked_mul(4)
+            .and_then(|full| full.checked_add(rem))
+            .expect("capacity overflow")
+    }
+
+    fn append_to(&self, s: &mut String) {
+        let mut out = [0u8; 4];
+        for group in self.bytes.chunks(3) {
+            let n = self.encode_group(group, &mut out);
+            for &c in &out[..n] {
+                s.push(char::from(c));
+            }
+        }
+    }
+
+    fn for_each_segment(&self, f: &mut dyn FnMut(&str)) {
+        let mut buf = [0u8; CHUNK / 3 * 4];
+        for chunk in self.bytes.chunks(CHUNK) {
+            let mut len = 0;
+            for group in chunk.chunks(3) {
+                len += self.encode_group(group, &mut buf[len..]);
+            }
+            // The alphabets contain only ASCII characters.
+            f(str::from_utf8(&buf[..len]).unwrap());
+        }
+    }
+}
+
+impl<'a> Display for Base64<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut result = Ok(());
+        self.for_each_segment(&mut |segment| {
+            if result.is_ok() {
+                result = f.write_str(segment);
+            }
+        });
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use CAT;
+    use cat::Cat;
+    use super::{Base64Alphabet, base64, base64_with};
+
+    #[test]
+    fn rfc4648_vectors() {
+        let vectors = [
+            ("", ""),
+            ("f", "Zg=="),
+            ("fo", "Zm8="),
+            ("foo", "Zm9v"),
+            ("foob", "Zm9vYg=="),
+            ("fooba", "Zm9vYmE="),
+            ("foobar", "Zm9vYmFy"),
+        ];
+        for &(input, output) in &vectors {
+            let s = String::from(CAT + base64(input.as_bytes()));
+            assert_eq!(s, output);
+            assert_eq!(s.len(), s.capacity());
+            let unpadded =
+                base64_with(input.as_bytes(), Base64Alphabet::Standard, false);
+            assert_eq!(unpadded.to_string(), output.trim_end_matches('='));
+            assert_eq!(
+                unpadded.size_hint(),
+                output.trim_end_matches('=').len()
+            );
+        }
+    }
+
+    #[test]
+    fn alphabets_and_chunks() {
+        let bytes = (0u8..=255).collect::<Vec<_>>();
+        let standard = base64(&bytes).to_string();
+        let url_safe = base64_with(&bytes, Base64Alphabet::UrlSafe, true);
+        let expected = standard.replace('+', "-").replace('/', "_");
+        assert_eq!(url_safe.to_string(), expected);
+        assert_eq!(String::from(CAT + url_safe), expected);
+        assert_eq!(standard.len(), 344);
+        assert!(standard.ends_with("+fr7/P3+/w=="));
+    }
+}
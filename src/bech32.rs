@@ -0,0 +1,328 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use cat::Cat;
+use std::fmt::{self, Display, Write};
+
+const CHARSET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+const GEN: [u32; 5] = [
+    0x3b6a_57b2,
+    0x2650_8e6d,
+    0x1ea1_19fa,
+    0x3d42_33dd,
+    0x2a14_62b3,
+];
+const CHECKSUM_LEN: usize = 6;
+// Maximum length of an encoded string from BIP 173.
+const MAX_LEN: usize = 90;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Variant {
+    Bech32,
+    Bech32m,
+}
+
+impl Variant {
+    fn constant(self) -> u32 {
+        match self {
+            Variant::Bech32 => 1,
+            Variant::Bech32m => 0x2bc8_30a3,
+        }
+    }
+}
+
+/// A piece that encodes bytes as a checksummed bech32 or bech32m
+/// string.
+///
+/// This is created by the [`bech32`](fn.bech32.html),
+/// [`bech32m`](fn.bech32m.html) and [`segwit`](fn.segwit.html)
+/// functions.
+#[derive(Clone, Copy, Debug)]
+pub struct Bech32<'a> {
+    hrp: &'a str,
+    // A 5-bit value encoded before the data, such as a witness
+    // version.
+    version: Option<u8>,
+    data: &'a [u8],
+    variant: Variant,
+}
+
+/// Creates a piece that encodes `data` as a bech32 string with the
+/// human-readable part `hrp`.
+///
+/// The bytes in `data` are regrouped into 5-bit values, padding the
+/// last value with zero bits if required. The encoding is streamed
+/// into the concatenation, so no temporary string is created. The
+/// human-readable part is written in lowercase.
+///
+/// # Panics
+///
+/// Panics if `hrp` is empty or contains characters outside the ASCII
+/// range 33 to 126, or if the encoding would be longer than 90
+/// characters.
+///
+/// # Examples
+///
+/// ```rust
+/// use sconcat::{CAT, bech32};
+///
+/// let cat = CAT + "address: " + bech32("a", b"");
+/// assert_eq!(String::from(cat), "address: a12uel5l");
+/// ```
+pub fn bech32<'a>(hrp: &'a str, data: &'a [u8]) -> Bech32<'a> {
+    Bech32::new(hrp, None, data, Variant::Bech32)
+}
+
+/// Creates a piece that encodes `data` as a bech32m string with the
+/// human-readable part `hrp`.
+///
+/// This is the same as [`bech32`](fn.bech32.html) except that the
+/// bech32m checksum constant is used.
+///
+/// # Panics
+///
+/// Panics if `hrp` is empty or contains characters outside the ASCII
+/// range 33 to 126, or if the encoding would be longer than 90
+/// characters.
+///
+/// # Examples
+///
+/// ```rust
+/// use sconcat::{CAT, bech32m};
+///
+/// let cat = CAT + "address: " + bech32m("a", b"");
+/// assert_eq!(String::from(cat), "address: a1lqfn3a");
+/// ```
+pub fn bech32m<'a>(hrp: &'a str, data: &'a [u8]) -> Bech32<'a> {
+    Bech32::new(hrp, None, data, Variant::Bech32m)
+}
+
+/// Creates a piece that encodes a segwit address with the
+/// human-readable part `hrp`, such as `bc` for Bitcoin.
+///
+/// The witness `version` is encoded as a single 5-bit value before
+/// the witness `program`, which is regrouped like the data of
+/// [`bech32`](fn.bech32.html). Version 0 uses the bech32 checksum
+/// from BIP 173 and later versions use the bech32m checksum from
+/// BIP 350.
+///
+/// # Panics
+///
+/// Panics if `hrp` is invalid as for [`bech32`](fn.bech32.html), if
+/// `version` is greater than 16, or if the program is not 2 to 40
+/// bytes long, or 20 or 32 bytes long for version 0.
+///
+/// # Examples
+///
+/// ```rust
+/// use sconcat::{CAT, segwit};
+///
+/// let program = [
+///     0x75, 0x1e, 0x76, 0xe8, 0x19, 0x91, 0x96, 0xd4, 0x54, 0x94,
+///     0x1c, 0x45, 0xd1, 0xb3, 0xa3, 0x23, 0xf1, 0x43, 0x3b, 0xd6,
+/// ];
+/// let cat = CAT + "pay to " + segwit("bc", 0, &program);
+/// let expected = "pay to bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4";
+/// assert_eq!(String::from(cat), expected);
+/// ```
+pub fn segwit<'a>(hrp: &'a str, version: u8, program: &'a [u8]) -> Bech32<'a> {
+    assert!(version <= 16, "invalid witness version");
+    let len_ok = match version {
+        0 => program.len() == 20 || program.len() == 32,
+        _ => (2..=40).contains(&program.len()),
+    };
+    assert!(len_ok, "invalid witness program length");
+    let variant = match version {
+        0 => Variant::Bech32,
+        _ => Variant::Bech32m,
+    };
+    Bech32::new(hrp, Some(version), program, variant)
+}
+
+impl<'a> Bech32<'a> {
+    fn new(
+        hrp: &'a str,
+        version: Option<u8>,
+        data: &'a [u8],
+        variant: Variant,
+    ) -> Bech32<'a> {
+        assert!(
+            !hrp.is_empty() && hrp.bytes().all(|b| (33..=126).contains(&b)),
+            "invalid bech32 human-readable part"
+        );
+        let piece = Bech32 {
+            hrp,
+            version,
+            data,
+            variant,
+        };
+        assert!(
+            piece.checked_len().is_some_and(|len| len <= MAX_LEN),
+            "bech32 string longer than {} characters",
+            MAX_LEN
+        );
+        piece
+    }
+
+    fn checked_len(&self) -> Option<usize> {
+        let bits = self.data.len().checked_mul(8)?;
+        let data_len = bits.div_ceil(5) + usize::from(self.version.is_some());
+        self.hrp.len().checked_add(1 + CHECKSUM_LEN + data_len)
+    }
+
+    // Passes every character of the encoding to `out`.
+    fn encode<F: FnMut(char)>(&self, mut out: F) {
+        let mut chk = 1;
+        for b in self.hrp.bytes() {
+            chk = polymod_step(chk, u32::from(b.to_ascii_lowercase() >> 5));
+        }
+        chk = polymod_step(chk, 0);
+        for b in self.hrp.bytes() {
+            let b = b.to_ascii_lowercase();
+            chk = polymod_step(chk, u32::from(b & 0x1f));
+            out(char::from(b));
+        }
+        out('1');
+        let mut emit = |value: u32, chk: &mut u32| {
+            *chk = polymod_step(*chk, value);
+            out(char::from(CHARSET[value as usize]));
+        };
+        if let Some(version) = self.version {
+            emit(u32::from(version), &mut chk);
+        }
+        let mut acc = 0u32;
+        let mut bits = 0;
+        for &b in self.data {
+            acc = (acc << 8 | u32::from(b)) & 0xfff;
+            bits += 8;
+            while bits >= 5 {
+                bits -= 5;
+                emit(acc >> bits & 0x1f, &mut chk);
+            }
+        }
+        if bits > 0 {
+            emit(acc << (5 - bits) & 0x1f, &mut chk);
+        }
+        for _ in 0..CHECKSUM_LEN {
+            chk = polymod_step(chk, 0);
+        }
+        chk ^= self.variant.constant();
+        for i in 0..CHECKSUM_LEN {
+            let value = chk >> (5 * (CHECKSUM_LEN - 1 - i)) & 0x1f;
+            out(char::from(CHARSET[value as usize]));
+        }
+    }
+}
+
+fn polymod_step(chk: u32, value: u32) -> u32 {
+    let top = chk >> 25;
+    let mut chk = (chk & 0x01ff_ffff) << 5 ^ value;
+    for (i, g) in GEN.iter().enumerate() {
+        if top >> i & 1 != 0 {
+            chk ^= g;
+        }
+    }
+    chk
+}
+
+impl<'a> Cat for Bech32<'a> {
+    // The length was checked when the piece was created.
+    fn size_hint(&self) -> usize {
+        self.checked_len().unwrap()
+    }
+
+    fn cheap_size_hint(&self) -> Option<usize> {
+        self.checked_len()
+    }
+
+    fn append_to(&self, s: &mut String) {
+        self.encode(|c| s.push(c));
+    }
+
+    fn for_each_segment(&self, f: &mut dyn FnMut(&str)) {
+        self.encode(|c| f(c.encode_utf8(&mut [0; 4])));
+    }
+}
+
+impl<'a> Display for Bech32<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut result = Ok(());
+        self.encode(|c| {
+            if result.is_ok() {
+                result = f.write_char(c);
+            }
+        });
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use CAT;
+    use super::{bech32, bech32m, segwit};
+
+    #[test]
+    fn checksums() {
+        assert_eq!(String::from(CAT + bech32("A", b"")), "a12uel5l");
+        assert_eq!(String::from(CAT + bech32m("a", b"")), "a1lqfn3a");
+        let data = [
+            0x00, 0x44, 0x32, 0x14, 0xc7, 0x42, 0x54, 0xb6, 0x35, 0xcf,
+            0x84, 0x65, 0x3a, 0x56, 0xd7, 0xc6, 0x75, 0xbe, 0x77, 0xdf,
+        ];
+        let cat = CAT + bech32("abcdef", &data);
+        let s = String::from(cat);
+        assert_eq!(s, "abcdef1qpzry9x8gf2tvdw0s3jn54khce6mua7lmqqqxw");
+        assert_eq!(s.capacity(), s.len());
+        assert_eq!(cat.to_string(), s);
+    }
+
+    #[test]
+    fn padding() {
+        let cat = CAT + bech32("bc", &[0xff]);
+        let s = String::from(cat);
+        assert_eq!(&s[..5], "bc1lu");
+        assert_eq!(s.len(), 3 + 2 + 6);
+    }
+
+    #[test]
+    fn segwit_addresses() {
+        // BIP 173 and BIP 350 test vectors
+        let p2wpkh = [
+            0x75, 0x1e, 0x76, 0xe8, 0x19, 0x91, 0x96, 0xd4, 0x54, 0x94,
+            0x1c, 0x45, 0xd1, 0xb3, 0xa3, 0x23, 0xf1, 0x43, 0x3b, 0xd6,
+        ];
+        let s = String::from(CAT + segwit("BC", 0, &p2wpkh));
+        assert_eq!(s, "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4");
+        assert_eq!(s.capacity(), s.len());
+        let p2tr = [
+            0x79, 0xbe, 0x66, 0x7e, 0xf9, 0xdc, 0xbb, 0xac, 0x55, 0xa0,
+            0x62, 0x95, 0xce, 0x87, 0x0b, 0x07, 0x02, 0x9b, 0xfc, 0xdb,
+            0x2d, 0xce, 0x28, 0xd9, 0x59, 0xf2, 0x81, 0x5b, 0x16, 0xf8,
+            0x17, 0x98,
+        ];
+        let expected =
+            "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqzk5jj0";
+        assert_eq!(String::from(CAT + segwit("bc", 1, &p2tr)), expected);
+        let v16 = segwit("bc", 16, &[0x75, 0x1e]);
+        assert_eq!(String::from(CAT + v16), "bc1sw50qgdz25j");
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid witness program length")]
+    fn segwit_v0_length() {
+        segwit("bc", 0, &[0; 21]);
+    }
+
+    #[test]
+    #[should_panic(expected = "bech32 string longer than 90 characters")]
+    fn too_long() {
+        // 51 bytes take 82 characters, one more than fits between
+        // "bc1" and the checksum.
+        bech32("bc", &[0; 51]);
+    }
+}
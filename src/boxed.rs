@@ -0,0 +1,181 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Conversions to exact strings and to shared and boxed string
+// slices. The buffer is allocated with exactly the size hint rather
+// than reusing the buffer of the first piece, so converting it to
+// `Box<str>` does not need to shrink it. `Rc<str>` and `Arc<str>`
+// keep their counts in the same allocation as the string, so the
+// exact buffer is copied into them once. Boxed errors wrap the
+// string, so that `Err((CAT + ...).into())` works like it does for a
+// `String`.
+
+use cat::{self, Cat, CatMany, CatOne, CatStart};
+use std::error::Error;
+use std::rc::Rc;
+use std::sync::Arc;
+
+fn exact_string<C: Cat>(src: &C) -> String {
+    let mut s = String::with_capacity(src.size_hint());
+    cat::append_cat(&mut s, src);
+    s
+}
+
+// Only shrinks if the size hint was larger than the result.
+fn into_string_exact<C: Cat>(src: &C) -> String {
+    let mut s = exact_string(src);
+    s.shrink_to_fit();
+    s
+}
+
+macro_rules! from_cat {
+    ($($T:ty => $from_string:expr;)*) => { $(
+        impl<L: Cat, R: Cat> From<CatMany<L, R>> for $T {
+            fn from(src: CatMany<L, R>) -> $T {
+                let from_string: fn(String) -> $T = $from_string;
+                from_string(exact_string(&src))
+            }
+        }
+
+        impl<T: Cat> From<CatOne<T>> for $T {
+            fn from(src: CatOne<T>) -> $T {
+                let from_string: fn(String) -> $T = $from_string;
+                from_string(exact_string(&src.inner))
+            }
+        }
+
+        impl From<CatStart> for $T {
+            fn from(_src: CatStart) -> $T {
+                <$T>::from("")
+            }
+        }
+    )* };
+}
+
+from_cat! {
+    Box<str> => String::into_boxed_str;
+    Rc<str> => |s| Rc::from(s.as_str());
+    Arc<str> => |s| Arc::from(s.as_str());
+    Box<dyn Error> => Box::from;
+    Box<dyn Error + Send + Sync> => Box::from;
+}
+
+impl<L: Cat, R: Cat> CatMany<L, R> {
+    /// Converts the concatenation to a `String` whose capacity is
+    /// equal to its length.
+    ///
+    /// Unlike `String::from`, the buffer of the first piece is not
+    /// reused, so any spare capacity it has is not kept. The pieces
+    /// are still copied only once.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use sconcat::CAT;
+    ///
+    /// let mut scratch = String::with_capacity(4096);
+    /// scratch.push_str("key");
+    /// let s = (CAT + scratch + '=' + "value").into_string_exact();
+    /// assert_eq!(s, "key=value");
+    /// assert_eq!(s.capacity(), s.len());
+    /// ```
+    pub fn into_string_exact(self) -> String {
+        into_string_exact(&self)
+    }
+
+    /// Converts the concatenation to a `Box<str>`.
+    ///
+    /// This is the same as `Box::<str>::from`, and the buffer is
+    /// allocated with exactly the size hint.
+    pub fn into_boxed_str(self) -> Box<str> {
+        Box::from(self)
+    }
+}
+
+impl<T: Cat> CatOne<T> {
+    /// Converts the concatenation to a `String` whose capacity is
+    /// equal to its length.
+    pub fn into_string_exact(self) -> String {
+        into_string_exact(&self.inner)
+    }
+
+    /// Converts the concatenation to a `Box<str>`.
+    pub fn into_boxed_str(self) -> Box<str> {
+        Box::from(self)
+    }
+}
+
+impl CatStart {
+    /// Converts the empty concatenation to an empty `String`, which
+    /// does not allocate.
+    pub fn into_string_exact(self) -> String {
+        String::new()
+    }
+
+    /// Converts the empty concatenation to an empty `Box<str>`.
+    pub fn into_boxed_str(self) -> Box<str> {
+        Box::from(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use CAT;
+    use Dbg;
+    use std::error::Error;
+    use std::rc::Rc;
+    use std::sync::Arc;
+
+    #[test]
+    fn boxed() {
+        let mut first = String::with_capacity(100);
+        first.push_str("ab");
+        let b = Box::<str>::from(CAT + first + 'é' + Dbg(7));
+        assert_eq!(&*b, "abé7");
+        assert_eq!(&*Box::<str>::from(CAT + "x"), "x");
+        assert_eq!(&*Box::<str>::from(CAT), "");
+    }
+
+    #[test]
+    fn shared() {
+        let cat = CAT + "ab" + String::from("cd");
+        let rc = Rc::<str>::from(cat.clone());
+        let arc = Arc::<str>::from(cat);
+        assert_eq!((&*rc, &*arc), ("abcd", "abcd"));
+        assert_eq!(&*Rc::<str>::from(CAT + 'x'), "x");
+        assert_eq!(&*Arc::<str>::from(CAT), "");
+    }
+
+    #[test]
+    fn exact() {
+        let mut first = String::with_capacity(100);
+        first.push_str("ab");
+        let s = (CAT + first + 'é').into_string_exact();
+        assert_eq!((s.as_str(), s.capacity()), ("abé", 4));
+        let s = (CAT + Dbg(12345)).into_string_exact();
+        assert_eq!((s.as_str(), s.capacity()), ("12345", 5));
+        assert_eq!(CAT.into_string_exact().capacity(), 0);
+        let b = (CAT + "x" + String::from("y")).into_boxed_str();
+        assert_eq!(&*b, "xy");
+        assert_eq!(&*(CAT + "z").into_boxed_str(), "z");
+        assert_eq!(&*CAT.into_boxed_str(), "");
+    }
+
+    #[test]
+    fn errors() {
+        fn open(path: &str) -> Result<(), Box<dyn Error + Send + Sync>> {
+            Err((CAT + "failed to open " + path).into())
+        }
+        let err = open("/etc/x").unwrap_err();
+        assert_eq!(err.to_string(), "failed to open /etc/x");
+        assert_eq!(format!("{:?}", err), "\"failed to open /etc/x\"");
+        let err = Box::<dyn Error>::from(CAT + Dbg(1));
+        assert_eq!(err.to_string(), "1");
+        assert_eq!(Box::<dyn Error>::from(CAT).to_string(), "");
+    }
+}
@@ -0,0 +1,250 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Integration with the bytes crate.
+
+use bytes::{Buf, BytesMut};
+use cat::{Cat, CatMany, CatOne, CatStart};
+use segments::Cursor;
+
+fn append_bytes<C: Cat>(cat: &C, b: &mut BytesMut) {
+    b.reserve(cat.size_hint());
+    cat.for_each_segment(&mut |segment| {
+        b.extend_from_slice(segment.as_bytes())
+    });
+}
+
+/// A `bytes::Buf` over the bytes of a concatenation.
+///
+/// This is created by the `into_buf` methods of the concatenation
+/// types. Segments borrowed from the concatenation, such as string
+/// slices and `String`s, are returned by `Buf::chunk` directly. Other
+/// segments, such as encoded characters, are copied into a buffer
+/// that is reused for all of them; the concatenation as a whole is
+/// never materialized.
+#[derive(Clone, Debug)]
+pub struct CatBuf<C: Cat> {
+    cat: C,
+    cursor: Cursor,
+    // Whether the cursor is at the current segment.
+    loaded: bool,
+    // Whether the current segment is borrowed from the concatenation
+    // instead of copied into `chunk`.
+    borrowed: bool,
+    chunk: String,
+    offset: usize,
+    remaining: usize,
+}
+
+impl<C: Cat> CatBuf<C> {
+    fn new(cat: C) -> CatBuf<C> {
+        let mut remaining = 0;
+        cat.for_each_segment(&mut |segment| remaining += segment.len());
+        let mut ret = CatBuf {
+            cat,
+            cursor: Cursor::new(false),
+            loaded: false,
+            borrowed: false,
+            chunk: String::new(),
+            offset: 0,
+            remaining,
+        };
+        ret.load();
+        ret
+    }
+
+    fn current(&self) -> &str {
+        if self.borrowed {
+            self.cursor
+                .borrowed(&self.cat)
+                .expect("segment not borrowed")
+        } else {
+            &self.chunk
+        }
+    }
+
+    // Loads the next non-empty segment once the current segment has
+    // been consumed.
+    fn load(&mut self) {
+        while self.offset == self.current().len() {
+            if self.loaded {
+                self.cursor.advance();
+            }
+            let root: &dyn Cat = &self.cat;
+            self.chunk.clear();
+            self.offset = 0;
+            self.borrowed = false;
+            self.loaded = self.cursor.peek(root).is_some();
+            if !self.loaded {
+                return;
+            }
+            self.borrowed = self.cursor.borrowed(root).is_some();
+            if !self.borrowed {
+                let segment = self.cursor.peek(root).unwrap_or("");
+                self.chunk.push_str(segment);
+            }
+        }
+    }
+
+    /// Returns the concatenation.
+    pub fn into_inner(self) -> C {
+        self.cat
+    }
+}
+
+impl<C: Cat> Buf for CatBuf<C> {
+    fn remaining(&self) -> usize {
+        self.remaining
+    }
+
+    fn chunk(&self) -> &[u8] {
+        &self.current().as_bytes()[self.offset..]
+    }
+
+    fn advance(&mut self, mut cnt: usize) {
+        assert!(
+            cnt <= self.remaining,
+            "cannot advance past the end of the concatenation"
+        );
+        self.remaining -= cnt;
+        while cnt > 0 {
+            let n = cnt.min(self.current().len() - self.offset);
+            self.offset += n;
+            cnt -= n;
+            self.load();
+        }
+    }
+}
+
+impl<L: Cat, R: Cat> CatMany<L, R> {
+    /// Appends the concatenation to a `BytesMut` buffer.
+    ///
+    /// The buffer is reserved once using the size hint, and then the
+    /// segments are appended one after the other.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # extern crate bytes;
+    /// # extern crate sconcat;
+    /// use bytes::BytesMut;
+    /// use sconcat::CAT;
+    ///
+    /// # fn main() {
+    /// let mut frame = BytesMut::new();
+    /// (CAT + "PING " + "42" + "\r\n").append_to_bytes(&mut frame);
+    /// assert_eq!(&frame[..], b"PING 42\r\n");
+    /// # }
+    /// ```
+    pub fn append_to_bytes(&self, b: &mut BytesMut) {
+        append_bytes(self, b);
+    }
+
+    /// Converts the concatenation into a `bytes::Buf`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # extern crate bytes;
+    /// # extern crate sconcat;
+    /// use bytes::Buf;
+    /// use sconcat::CAT;
+    ///
+    /// # fn main() {
+    /// let mut buf = (CAT + "ab" + 'c').into_buf();
+    /// assert_eq!(buf.remaining(), 3);
+    /// assert_eq!(buf.copy_to_bytes(3), "abc");
+    /// # }
+    /// ```
+    pub fn into_buf(self) -> CatBuf<CatMany<L, R>> {
+        CatBuf::new(self)
+    }
+}
+
+impl<T: Cat> CatOne<T> {
+    /// Appends the concatenation to a `BytesMut` buffer.
+    pub fn append_to_bytes(&self, b: &mut BytesMut) {
+        append_bytes(&self.inner, b);
+    }
+
+    /// Converts the concatenation into a `bytes::Buf`.
+    pub fn into_buf(self) -> CatBuf<T> {
+        CatBuf::new(self.inner)
+    }
+}
+
+impl CatStart {
+    /// Appends nothing to a `BytesMut` buffer.
+    pub fn append_to_bytes(&self, _b: &mut BytesMut) {}
+
+    /// Converts the empty concatenation into an empty `bytes::Buf`.
+    pub fn into_buf(self) -> CatBuf<&'static str> {
+        CatBuf::new("")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use CAT;
+    use Dbg;
+    use bytes::{Buf, BytesMut};
+
+    #[test]
+    fn append() {
+        let mut b = BytesMut::from(&b"<"[..]);
+        let cat = CAT + "ab" + 'é' + Dbg(7) + String::from("xyz");
+        cat.append_to_bytes(&mut b);
+        (CAT + '>').append_to_bytes(&mut b);
+        CAT.append_to_bytes(&mut b);
+        assert_eq!(&b[..], "<abé7xyz>".as_bytes());
+    }
+
+    #[test]
+    fn buf() {
+        let cat = CAT + "ab" + "" + 'é' + Dbg(7) + String::from("xyz");
+        let mut buf = cat.into_buf();
+        assert_eq!(buf.remaining(), 8);
+        assert_eq!(buf.chunk(), b"ab");
+        buf.advance(1);
+        assert_eq!(buf.chunk(), b"b");
+        buf.advance(2);
+        assert_eq!(buf.chunk(), b"\xa9");
+        assert_eq!(buf.remaining(), 5);
+        let mut out = [0; 5];
+        buf.copy_to_slice(&mut out);
+        assert_eq!(&out, b"\xa97xyz");
+        assert!(!buf.has_remaining());
+        assert_eq!(buf.chunk(), b"");
+
+        let mut buf = (CAT + "x").into_buf();
+        assert_eq!(buf.copy_to_bytes(1), "x");
+        assert_eq!(CAT.into_buf().remaining(), 0);
+    }
+
+    #[test]
+    fn borrows() {
+        let owned = String::from("xyz");
+        let ptr = owned.as_ptr();
+        let mut buf = (CAT + 'a' + owned).into_buf();
+        buf.advance(1);
+        assert_eq!(buf.chunk().as_ptr(), ptr);
+
+        let items: Vec<String> = (0..40_000).map(|i| i.to_string()).collect();
+        let expected = items.concat();
+        let mut buf = (CAT + '[' + items + ']').into_buf();
+        assert_eq!(buf.remaining(), expected.len() + 2);
+        let bytes = buf.copy_to_bytes(buf.remaining());
+        assert_eq!(&bytes[1..bytes.len() - 1], expected.as_bytes());
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot advance past the end")]
+    fn advance_past_end() {
+        (CAT + "ab").into_buf().advance(3);
+    }
+}
@@ -0,0 +1,146 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use cat::{Cat, CatMany, CatOne, CatStart};
+use std::error::Error;
+use std::fmt::{self, Display};
+use std::str;
+
+/// An error returned when a buffer is too small for a
+/// concatenation.
+///
+/// This is returned by the `write_to_slice` methods.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BufferTooSmall {
+    required: usize,
+    available: usize,
+}
+
+impl BufferTooSmall {
+    /// Returns the size in bytes that the buffer needs to have.
+    pub fn required(&self) -> usize {
+        self.required
+    }
+
+    /// Returns the size of the buffer in bytes.
+    pub fn available(&self) -> usize {
+        self.available
+    }
+}
+
+impl Display for BufferTooSmall {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "buffer of {} bytes is too small for concatenation of {} bytes",
+            self.available,
+            self.required
+        )
+    }
+}
+
+impl Error for BufferTooSmall {}
+
+// Copies the segments into `buf` while they fit. The size hint may
+// be inexact, so the required size is the total of the segments.
+fn write_slice<'b, C: Cat>(
+    cat: &C,
+    buf: &'b mut [u8],
+) -> Result<&'b str, BufferTooSmall> {
+    let mut len = 0;
+    cat.for_each_segment(&mut |segment| {
+        let bytes = segment.as_bytes();
+        if let Some(dst) = buf.get_mut(len..len + bytes.len()) {
+            dst.copy_from_slice(bytes);
+        }
+        len += bytes.len();
+    });
+    if len > buf.len() {
+        return Err(BufferTooSmall {
+            required: len,
+            available: buf.len(),
+        });
+    }
+    Ok(str::from_utf8(&buf[..len]).expect("segments are valid UTF-8"))
+}
+
+impl<L: Cat, R: Cat> CatMany<L, R> {
+    /// Writes the concatenation to the start of `buf` and returns
+    /// the written part as a string slice.
+    ///
+    /// No allocations take place. If `buf` is too small, the error
+    /// contains the required size, and the contents of `buf` are
+    /// unspecified.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use sconcat::CAT;
+    ///
+    /// let mut buf = [0u8; 16];
+    /// let cat = CAT + "pid=" + "1234";
+    /// assert_eq!(cat.write_to_slice(&mut buf).unwrap(), "pid=1234");
+    /// let err = cat.write_to_slice(&mut buf[..4]).unwrap_err();
+    /// assert_eq!(err.required(), 8);
+    /// ```
+    pub fn write_to_slice<'b>(
+        &self,
+        buf: &'b mut [u8],
+    ) -> Result<&'b str, BufferTooSmall> {
+        write_slice(self, buf)
+    }
+}
+
+impl<T: Cat> CatOne<T> {
+    /// Writes the concatenation to the start of `buf` and returns
+    /// the written part as a string slice.
+    ///
+    /// No allocations take place. If `buf` is too small, the error
+    /// contains the required size.
+    pub fn write_to_slice<'b>(
+        &self,
+        buf: &'b mut [u8],
+    ) -> Result<&'b str, BufferTooSmall> {
+        write_slice(&self.inner, buf)
+    }
+}
+
+impl CatStart {
+    /// Returns an empty string slice without writing to `buf`.
+    ///
+    /// This never fails.
+    pub fn write_to_slice<'b>(
+        &self,
+        _buf: &'b mut [u8],
+    ) -> Result<&'b str, BufferTooSmall> {
+        Ok("")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use CAT;
+    use Dbg;
+
+    #[test]
+    fn slice() {
+        let mut buf = [b'-'; 12];
+        let cat = CAT + "ab" + 'é' + Dbg(7) + String::from("xyz");
+        assert_eq!(cat.write_to_slice(&mut buf).unwrap(), "abé7xyz");
+        assert_eq!(&buf[8..], b"----");
+        assert_eq!(cat.write_to_slice(&mut buf[..8]).unwrap(), "abé7xyz");
+        let err = cat.write_to_slice(&mut buf[..7]).unwrap_err();
+        assert_eq!((err.required(), err.available()), (8, 7));
+        assert_eq!(
+            err.to_string(),
+            "buffer of 7 bytes is too small for concatenation of 8 bytes"
+        );
+        assert_eq!((CAT + 'x').write_to_slice(&mut buf).unwrap(), "x");
+        assert_eq!(CAT.write_to_slice(&mut []).unwrap(), "");
+    }
+}
@@ -0,0 +1,172 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use cat::Cat;
+use std::fmt::{self, Display};
+use std::str;
+
+// Size of the buffer of mapped characters in `for_each_segment`.
+const BUF_LEN: usize = 64;
+// A character maps to at most three characters, each of which takes
+// at most four bytes.
+const MAX_MAPPED: usize = 12;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Case {
+    Upper,
+    Lower,
+}
+
+/// A piece that converts another piece to uppercase or lowercase.
+///
+/// This is created by the [`upper`](fn.upper.html) and
+/// [`lower`](fn.lower.html) functions.
+#[derive(Clone, Copy, Debug)]
+pub struct CaseMap<T: Cat> {
+    inner: T,
+    case: Case,
+}
+
+/// Creates a piece that converts `value` to uppercase.
+///
+/// Every character is mapped with [`char::to_uppercase`], so a
+/// character can expand into more than one, as `ß` becomes `SS`. The
+/// size hint is exact; it is computed by walking the segments of
+/// `value` once.
+///
+/// [`char::to_uppercase`]: https://doc.rust-lang.org/std/primitive.char.html#method.to_uppercase
+///
+/// # Examples
+///
+/// ```rust
+/// use sconcat::{CAT, upper};
+///
+/// let cat = CAT + upper("content-type") + ": text/plain";
+/// assert_eq!(String::from(cat), "CONTENT-TYPE: text/plain");
+/// assert_eq!(upper("straße").to_string(), "STRASSE");
+/// ```
+pub fn upper<T: Cat>(value: T) -> CaseMap<T> {
+    CaseMap {
+        inner: value,
+        case: Case::Upper,
+    }
+}
+
+/// Creates a piece that converts `value` to lowercase.
+///
+/// Every character is mapped with [`char::to_lowercase`]. Unlike
+/// `str::to_lowercase`, the mapping does not depend on the
+/// surrounding characters, so a final `Σ` becomes `σ` rather than
+/// `ς`.
+///
+/// [`char::to_lowercase`]: https://doc.rust-lang.org/std/primitive.char.html#method.to_lowercase
+///
+/// # Examples
+///
+/// ```rust
+/// use sconcat::{CAT, lower};
+///
+/// let cat = CAT + "id: " + lower(CAT + "User" + '_' + "ID");
+/// assert_eq!(String::from(cat), "id: user_id");
+/// ```
+pub fn lower<T: Cat>(value: T) -> CaseMap<T> {
+    CaseMap {
+        inner: value,
+        case: Case::Lower,
+    }
+}
+
+impl Case {
+    fn push_mapped(self, c: char, s: &mut String) {
+        match self {
+            Case::Upper => s.extend(c.to_uppercase()),
+            Case::Lower => s.extend(c.to_lowercase()),
+        }
+    }
+
+    // Encodes the mapping of `c` into `buf`, returning the number of
+    // bytes written.
+    fn encode_mapped(self, c: char, buf: &mut [u8]) -> usize {
+        let mut len = 0;
+        let mut push = |m: char| len += m.encode_utf8(&mut buf[len..]).len();
+        match self {
+            Case::Upper => c.to_uppercase().for_each(&mut push),
+            Case::Lower => c.to_lowercase().for_each(&mut push),
+        }
+        len
+    }
+}
+
+impl<T: Cat> Cat for CaseMap<T> {
+    fn size_hint(&self) -> usize {
+        let mut len = 0usize;
+        self.for_each_segment(&mut |segment| len += segment.len());
+        len
+    }
+
+    fn append_to(&self, s: &mut String) {
+        self.inner.for_each_segment(&mut |segment| {
+            for c in segment.chars() {
+                self.case.push_mapped(c, s);
+            }
+        });
+    }
+
+    fn for_each_segment(&self, f: &mut dyn FnMut(&str)) {
+        let mut buf = [0u8; BUF_LEN];
+        let mut len = 0;
+        self.inner.for_each_segment(&mut |segment| {
+            for c in segment.chars() {
+                if len + MAX_MAPPED > BUF_LEN {
+                    // Only whole characters are encoded into the buffer.
+                    f(str::from_utf8(&buf[..len]).unwrap());
+                    len = 0;
+                }
+                len += self.case.encode_mapped(c, &mut buf[len..]);
+            }
+        });
+        if len > 0 {
+            f(str::from_utf8(&buf[..len]).unwrap());
+        }
+    }
+}
+
+impl<T: Cat> Display for CaseMap<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut result = Ok(());
+        self.for_each_segment(&mut |segment| {
+            if result.is_ok() {
+                result = f.write_str(segment);
+            }
+        });
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use CAT;
+    use cat::Cat;
+    use super::{lower, upper};
+
+    #[test]
+    fn maps_case() {
+        let long = "ǆ".repeat(40) + "ß";
+        let s = String::from(CAT + '<' + upper(long.as_str()) + '>');
+        let expected = format!("<{}SS>", "Ǆ".repeat(40));
+        assert_eq!(s, expected);
+        assert_eq!(s.len(), s.capacity());
+        assert_eq!(
+            upper(long.as_str()).to_string(),
+            &expected[1..expected.len() - 1]
+        );
+        assert_eq!(lower("İ").size_hint(), 3);
+        assert_eq!(lower(CAT + "ÀB" + 'Σ').to_string(), "àbσ");
+        assert_eq!(upper("").size_hint(), 0);
+    }
+}
@@ -6,83 +6,1030 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-use std::fmt::{self, Debug, Display};
+#[cfg(feature = "hooks")]
+use hooks;
+#[cfg(feature = "rope")]
+use rope::Rope;
+use std::any::type_name;
+use std::borrow::Cow;
+use std::fmt::{self, Debug, Display, Write};
 use std::ops::{Add, AddAssign};
+use std::rc::Rc;
+use std::str;
+use std::sync::Arc;
 
 /// Trait for types that can be concatenated.
+///
+/// Pieces such as `&str`, `char` and `String` implement this trait,
+/// and so does a concatenation of two or more pieces, which is a
+/// [`CatMany`](struct.CatMany.html). This can be used to write
+/// functions that accept any concatenation without materializing it
+/// first.
+///
+/// # Examples
+///
+/// ```rust
+/// use sconcat::{CAT, Cat};
+///
+/// struct Log {
+///     lines: Vec<String>,
+/// }
+///
+/// impl Log {
+///     fn log<C: Cat>(&mut self, c: C) {
+///         let mut line = String::from("[log] ");
+///         line += CAT + c;
+///         self.lines.push(line);
+///     }
+/// }
+///
+/// let mut log = Log { lines: Vec::new() };
+/// log.log(CAT + "user " + String::from("bob") + " logged in");
+/// log.log("done");
+/// assert_eq!(log.lines, ["[log] user bob logged in", "[log] done"]);
+/// ```
 pub trait Cat {
     /// Length of item in bytes.
     fn size_hint(&self) -> usize;
     /// Append item to String.
     fn append_to(&self, s: &mut String);
-    /// Converts item to a String.
-    fn into_string(self, capacity: usize) -> String;
+    /// Converts item to a String with at least `capacity` bytes
+    /// reserved. By default, item is appended to a new String.
+    fn into_string(self, capacity: usize) -> String
+    where
+        Self: Sized,
+    {
+        let mut s = String::with_capacity(capacity);
+        self.append_to(&mut s);
+        s
+    }
+    /// Passes item to `f` as a sequence of string slices.
+    fn for_each_segment(&self, f: &mut dyn FnMut(&str));
+    /// Length of item in bytes, or `None` if it overflows `usize`.
+    fn checked_size_hint(&self) -> Option<usize> {
+        Some(self.size_hint())
+    }
+    /// Exact length of item in bytes if it is known without
+    /// formatting item, such as for strings. Concatenations store
+    /// the sum of these lengths when they are built. Returning `None`
+    /// is always correct.
+    fn cheap_size_hint(&self) -> Option<usize> {
+        None
+    }
+    /// Number of pieces in item.
+    fn pieces(&self) -> usize {
+        1
+    }
+    /// Splits item into its two sides if it is a concatenation.
+    fn split(&self) -> Option<(&dyn Cat, &dyn Cat)> {
+        None
+    }
+    /// Number of child pieces if item is a list of pieces, such as a
+    /// `Vec`, whose segments are the segments of the children in
+    /// order. Zero means item is not a list.
+    fn child_count(&self) -> usize {
+        0
+    }
+    /// Child piece number `index` of item, which must be less than
+    /// `child_count()`.
+    fn child_at(&self, index: usize) -> &dyn Cat {
+        let _ = index;
+        unreachable!("item has no child pieces")
+    }
+    /// Item as a string slice borrowed from item, if at most one of
+    /// its segments is non-empty and that segment is borrowed from
+    /// item. Returning `None` is always correct.
+    fn single_str(&self) -> Option<&str> {
+        None
+    }
+    /// Number of segments passed by `for_each_segment`.
+    fn segment_count(&self) -> usize {
+        let mut count = 0;
+        self.for_each_segment(&mut |_| count += 1);
+        count
+    }
+    /// Segment number `index` of item, which must be less than
+    /// `segment_count()`. Segments not borrowed from item are stored
+    /// in `buf`.
+    fn segment_at<'s>(
+        &'s self,
+        index: usize,
+        buf: &'s mut SegmentBuf,
+    ) -> &'s str {
+        let mut i = 0;
+        buf.scratch.clear();
+        self.for_each_segment(&mut |segment| {
+            if i == index {
+                buf.scratch.push_str(segment);
+            }
+            i += 1;
+        });
+        &buf.scratch
+    }
+    /// Writes item to a `fmt::Write` sink, stopping at the first
+    /// error. Runs of short segments are passed to the sink together.
+    fn write_to<W: Write>(&self, w: &mut W) -> fmt::Result
+    where
+        Self: Sized,
+    {
+        write_coalesced(self, w)
+    }
+    /// Moves item into `rope`, adopting owned strings as chunks
+    /// instead of copying them.
+    #[cfg(feature = "rope")]
+    fn append_to_rope(self, rope: &mut Rope)
+    where
+        Self: Sized,
+    {
+        self.for_each_segment(&mut |segment| rope.push_str(segment));
+    }
+    /// Passes item to `f` like `for_each_segment`, but with the
+    /// plaintext of secret pieces instead of their redacted form.
+    #[cfg(feature = "secrecy")]
+    fn for_each_exposed_segment(&self, f: &mut dyn FnMut(&str)) {
+        self.for_each_segment(f);
+    }
+}
+
+// Size of the stack buffer that short segments are coalesced in.
+const COALESCE_LEN: usize = 256;
+// Segments shorter than this are coalesced.
+const COALESCE_MAX: usize = 32;
+
+// Writes the segments of an item to a sink, copying runs of short
+// segments such as characters and short literals into a stack buffer
+// first. A concatenation of many tiny pieces then costs one sink call
+// per run instead of one per piece, which matters for sinks like
+// `Formatter` that are called through a trait object. Longer segments
+// are written directly, as copying them would cost more than the
+// call.
+pub(crate) fn write_coalesced<C: Cat, W: Write>(src: &C, w: &mut W) -> fmt::Result {
+    let mut buf = [0u8; COALESCE_LEN];
+    let mut len = 0;
+    let mut result = Ok(());
+    src.for_each_segment(&mut |segment| {
+        if result.is_err() {
+            return;
+        }
+        let short = segment.len() < COALESCE_MAX;
+        if short && len + segment.len() <= COALESCE_LEN {
+            buf[len..len + segment.len()].copy_from_slice(segment.as_bytes());
+            len += segment.len();
+            return;
+        }
+        if len > 0 {
+            // Only whole segments are copied into the buffer.
+            result = w.write_str(str::from_utf8(&buf[..len]).unwrap());
+            len = 0;
+        }
+        if result.is_err() {
+            return;
+        }
+        if short {
+            buf[..segment.len()].copy_from_slice(segment.as_bytes());
+            len = segment.len();
+        } else {
+            result = w.write_str(segment);
+        }
+    });
+    result?;
+    if len > 0 {
+        w.write_str(str::from_utf8(&buf[..len]).unwrap())?;
+    }
+    Ok(())
+}
+
+// Appends an item to a String, reserving the space first.
+pub(crate) fn append_cat<C: Cat>(s: &mut String, src: &C) {
+    let additional = src.size_hint();
+    s.reserve(additional);
+    #[cfg(feature = "hooks")]
+    let reserved = s.capacity();
+    #[cfg(feature = "alloc-check")]
+    let before = (s.as_ptr(), s.capacity());
+    src.append_to(s);
+    #[cfg(feature = "hooks")]
+    hooks::record(src.pieces(), additional, reserved < s.capacity());
+    #[cfg(feature = "alloc-check")]
+    debug_assert!(
+        before == (s.as_ptr(), s.capacity()),
+        "concatenation reallocated, size hint of {} bytes too small",
+        additional
+    );
+}
+
+// Sums the lengths of the segments, which unlike the size hint is
+// exact.
+fn segments_len<C: Cat>(src: &C) -> usize {
+    let mut len = 0usize;
+    src.for_each_segment(&mut |segment| {
+        len = len.checked_add(segment.len()).expect("length overflow")
+    });
+    len
+}
+
+pub(crate) fn segments_empty<C: Cat>(src: &C) -> bool {
+    let mut empty = true;
+    src.for_each_segment(&mut |segment| empty &= segment.is_empty());
+    empty
+}
+
+// Formats the segments like `Formatter::pad` formats a string,
+// without materializing them. Without a width or precision, the
+// pieces are formatted one after the other with the same formatter.
+fn pad_segments<C: Cat>(src: &C, f: &mut fmt::Formatter) -> fmt::Result {
+    let mut chars = 0;
+    src.for_each_segment(&mut |segment| chars += segment.chars().count());
+    let shown = f.precision().map_or(chars, |p| p.min(chars));
+    let padding = f.width().map_or(0, |w| w.saturating_sub(shown));
+    let (pre, post) = match f.align() {
+        None | Some(fmt::Alignment::Left) => (0, padding),
+        Some(fmt::Alignment::Right) => (padding, 0),
+        Some(fmt::Alignment::Center) => (padding / 2, padding - padding / 2),
+    };
+    let fill = f.fill();
+    for _ in 0..pre {
+        f.write_char(fill)?;
+    }
+    let mut remaining = shown;
+    let mut result = Ok(());
+    src.for_each_segment(&mut |segment| {
+        if result.is_err() || remaining == 0 {
+            return;
+        }
+        let end = match segment.char_indices().nth(remaining) {
+            Some((end, _)) => {
+                remaining = 0;
+                end
+            }
+            None => {
+                remaining -= segment.chars().count();
+                segment.len()
+            }
+        };
+        result = f.write_str(&segment[..end]);
+    });
+    result?;
+    for _ in 0..post {
+        f.write_char(fill)?;
+    }
+    Ok(())
+}
+
+// Removes the module paths from a type name, so that for example
+// `alloc::string::String` becomes `String`.
+fn short_type_name(name: &str) -> String {
+    let mut short = String::with_capacity(name.len());
+    let mut ident = String::new();
+    let mut chars = name.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c.is_alphanumeric() || c == '_' {
+            ident.push(c);
+        } else if c == ':' && chars.peek() == Some(&':') {
+            chars.next();
+            ident.clear();
+        } else {
+            short.push_str(&ident);
+            ident.clear();
+            short.push(c);
+        }
+    }
+    short.push_str(&ident);
+    short
+}
+
+// A node of the alternate debug tree. Concatenations are formatted
+// as nested nodes, and other pieces are preceded by their type.
+struct TreeNode<'a, T: 'a>(&'a T);
+
+impl<'a, T: Cat + Debug> Debug for TreeNode<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.0.split().is_none() {
+            f.write_str(&short_type_name(type_name::<T>()))?;
+            f.write_char(' ')?;
+        }
+        Debug::fmt(self.0, f)
+    }
+}
+
+// Appends items to a String, reserving the space for every item as
+// it arrives. Summing the size hints first would need the items to be
+// collected, as the iterator cannot be walked twice, and `reserve`
+// grows the String geometrically, so the String is still reallocated
+// only a logarithmic number of times.
+fn extend_cats<C: Cat, I: IntoIterator<Item = C>>(s: &mut String, iter: I) {
+    for item in iter {
+        append_cat(s, &item);
+    }
+}
+
+// Converts an item to a String with the capacity from its size hint.
+pub(crate) fn cat_into_string<C: Cat>(src: C) -> String {
+    let capacity = src.size_hint();
+    #[cfg(feature = "hooks")]
+    let pieces = src.pieces();
+    let s = src.into_string(capacity);
+    #[cfg(feature = "hooks")]
+    hooks::record(pieces, capacity, capacity < s.len());
+    #[cfg(feature = "alloc-check")]
+    debug_assert!(
+        s.len() <= capacity,
+        "concatenation reallocated, size hint of {} bytes too small",
+        capacity
+    );
+    s
+}
+
+/// A `fmt::Write` that passes every string written to a closure.
+///
+/// This can be used to implement `Cat::for_each_segment` for items
+/// that are formatted on the fly.
+pub struct SegmentWriter<'a> {
+    f: &'a mut dyn FnMut(&str),
+}
+
+impl<'a> SegmentWriter<'a> {
+    /// Creates a writer that passes every string to `f`.
+    pub fn new(f: &'a mut dyn FnMut(&str)) -> SegmentWriter<'a> {
+        SegmentWriter { f }
+    }
+}
+
+impl<'a> Write for SegmentWriter<'a> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        (self.f)(s);
+        Ok(())
+    }
+}
+
+/// Storage for segments that are not borrowed from an item.
+#[derive(Clone, Debug, Default)]
+pub struct SegmentBuf {
+    chr: [u8; 4],
+    scratch: String,
+}
+
+// Passes `count` spaces to `f`.
+pub(crate) fn pad(f: &mut dyn FnMut(&str), mut count: usize) {
+    const SPACES: &str = "                ";
+    while count > 0 {
+        let n = count.min(SPACES.len());
+        f(&SPACES[..n]);
+        count -= n;
+    }
 }
 
-impl<'a> Cat for char {
-    fn size_hint(&self) -> usize {
-        self.len_utf8()
+impl Cat for char {
+    fn size_hint(&self) -> usize {
+        self.len_utf8()
+    }
+
+    fn cheap_size_hint(&self) -> Option<usize> {
+        Some(self.len_utf8())
+    }
+
+    fn append_to(&self, s: &mut String) {
+        s.push(*self);
+    }
+
+    fn for_each_segment(&self, f: &mut dyn FnMut(&str)) {
+        f(self.encode_utf8(&mut [0; 4]));
+    }
+
+    fn segment_count(&self) -> usize {
+        1
+    }
+
+    fn segment_at<'s>(
+        &'s self,
+        _index: usize,
+        buf: &'s mut SegmentBuf,
+    ) -> &'s str {
+        self.encode_utf8(&mut buf.chr)
+    }
+}
+
+impl Cat for &str {
+    fn size_hint(&self) -> usize {
+        self.len()
+    }
+
+    fn cheap_size_hint(&self) -> Option<usize> {
+        Some(self.len())
+    }
+
+    fn append_to(&self, s: &mut String) {
+        s.push_str(self);
+    }
+
+    fn for_each_segment(&self, f: &mut dyn FnMut(&str)) {
+        f(self);
+    }
+
+    fn segment_count(&self) -> usize {
+        1
+    }
+
+    fn segment_at<'s>(
+        &'s self,
+        _index: usize,
+        _buf: &'s mut SegmentBuf,
+    ) -> &'s str {
+        self
+    }
+
+    fn single_str(&self) -> Option<&str> {
+        Some(self)
+    }
+}
+
+impl Cat for String {
+    fn size_hint(&self) -> usize {
+        self.len()
+    }
+
+    fn cheap_size_hint(&self) -> Option<usize> {
+        Some(self.len())
+    }
+
+    fn append_to(&self, s: &mut String) {
+        s.push_str(self)
+    }
+
+    fn into_string(mut self, capacity: usize) -> String {
+        let len = self.len();
+        if capacity > len {
+            self.reserve(capacity - len);
+        }
+        self
+    }
+
+    fn for_each_segment(&self, f: &mut dyn FnMut(&str)) {
+        f(self);
+    }
+
+    fn segment_count(&self) -> usize {
+        1
+    }
+
+    fn segment_at<'s>(
+        &'s self,
+        _index: usize,
+        _buf: &'s mut SegmentBuf,
+    ) -> &'s str {
+        self
+    }
+
+    fn single_str(&self) -> Option<&str> {
+        Some(self)
+    }
+
+    #[cfg(feature = "rope")]
+    fn append_to_rope(self, rope: &mut Rope) {
+        rope.push_string(self);
+    }
+}
+
+// Reuses the buffer of the String if this is the only reference.
+impl Cat for Rc<String> {
+    fn size_hint(&self) -> usize {
+        self.len()
+    }
+
+    fn cheap_size_hint(&self) -> Option<usize> {
+        Some(self.len())
+    }
+
+    fn append_to(&self, s: &mut String) {
+        s.push_str(self)
+    }
+
+    fn into_string(self, capacity: usize) -> String {
+        match Rc::try_unwrap(self) {
+            Ok(s) => s.into_string(capacity),
+            Err(shared) => (&**shared).into_string(capacity),
+        }
+    }
+
+    fn for_each_segment(&self, f: &mut dyn FnMut(&str)) {
+        f(self);
+    }
+
+    fn segment_count(&self) -> usize {
+        1
+    }
+
+    fn segment_at<'s>(
+        &'s self,
+        _index: usize,
+        _buf: &'s mut SegmentBuf,
+    ) -> &'s str {
+        self
+    }
+
+    fn single_str(&self) -> Option<&str> {
+        Some(self)
+    }
+}
+
+// Reuses the buffer of the String if this is the only reference.
+impl Cat for Arc<String> {
+    fn size_hint(&self) -> usize {
+        self.len()
+    }
+
+    fn cheap_size_hint(&self) -> Option<usize> {
+        Some(self.len())
+    }
+
+    fn append_to(&self, s: &mut String) {
+        s.push_str(self)
+    }
+
+    fn into_string(self, capacity: usize) -> String {
+        match Arc::try_unwrap(self) {
+            Ok(s) => s.into_string(capacity),
+            Err(shared) => (&**shared).into_string(capacity),
+        }
+    }
+
+    fn for_each_segment(&self, f: &mut dyn FnMut(&str)) {
+        f(self);
+    }
+
+    fn segment_count(&self) -> usize {
+        1
+    }
+
+    fn segment_at<'s>(
+        &'s self,
+        _index: usize,
+        _buf: &'s mut SegmentBuf,
+    ) -> &'s str {
+        self
+    }
+
+    fn single_str(&self) -> Option<&str> {
+        Some(self)
+    }
+}
+
+// Reuses the buffer of the box, growing it if required.
+impl Cat for Box<str> {
+    fn size_hint(&self) -> usize {
+        self.len()
+    }
+
+    fn cheap_size_hint(&self) -> Option<usize> {
+        Some(self.len())
+    }
+
+    fn append_to(&self, s: &mut String) {
+        s.push_str(self)
+    }
+
+    fn into_string(self, capacity: usize) -> String {
+        String::from(self).into_string(capacity)
+    }
+
+    fn for_each_segment(&self, f: &mut dyn FnMut(&str)) {
+        f(self);
+    }
+
+    fn segment_count(&self) -> usize {
+        1
+    }
+
+    fn segment_at<'s>(
+        &'s self,
+        _index: usize,
+        _buf: &'s mut SegmentBuf,
+    ) -> &'s str {
+        self
+    }
+
+    fn single_str(&self) -> Option<&str> {
+        Some(self)
+    }
+
+    #[cfg(feature = "rope")]
+    fn append_to_rope(self, rope: &mut Rope) {
+        rope.push_string(String::from(self));
+    }
+}
+
+impl Cat for Rc<str> {
+    fn size_hint(&self) -> usize {
+        self.len()
+    }
+
+    fn cheap_size_hint(&self) -> Option<usize> {
+        Some(self.len())
+    }
+
+    fn append_to(&self, s: &mut String) {
+        s.push_str(self)
+    }
+
+    fn into_string(self, capacity: usize) -> String {
+        (&*self).into_string(capacity)
+    }
+
+    fn for_each_segment(&self, f: &mut dyn FnMut(&str)) {
+        f(self);
+    }
+
+    fn segment_count(&self) -> usize {
+        1
+    }
+
+    fn segment_at<'s>(
+        &'s self,
+        _index: usize,
+        _buf: &'s mut SegmentBuf,
+    ) -> &'s str {
+        self
+    }
+
+    fn single_str(&self) -> Option<&str> {
+        Some(self)
+    }
+}
+
+impl Cat for Arc<str> {
+    fn size_hint(&self) -> usize {
+        self.len()
+    }
+
+    fn cheap_size_hint(&self) -> Option<usize> {
+        Some(self.len())
+    }
+
+    fn append_to(&self, s: &mut String) {
+        s.push_str(self)
+    }
+
+    fn into_string(self, capacity: usize) -> String {
+        (&*self).into_string(capacity)
+    }
+
+    fn for_each_segment(&self, f: &mut dyn FnMut(&str)) {
+        f(self);
+    }
+
+    fn segment_count(&self) -> usize {
+        1
+    }
+
+    fn segment_at<'s>(
+        &'s self,
+        _index: usize,
+        _buf: &'s mut SegmentBuf,
+    ) -> &'s str {
+        self
+    }
+
+    fn single_str(&self) -> Option<&str> {
+        Some(self)
+    }
+}
+
+// Reuses the buffer of the String if the value is owned.
+impl<'a> Cat for Cow<'a, str> {
+    fn size_hint(&self) -> usize {
+        self.len()
+    }
+
+    fn cheap_size_hint(&self) -> Option<usize> {
+        Some(self.len())
+    }
+
+    fn append_to(&self, s: &mut String) {
+        s.push_str(self)
+    }
+
+    fn into_string(self, capacity: usize) -> String {
+        match self {
+            Cow::Borrowed(b) => b.into_string(capacity),
+            Cow::Owned(o) => o.into_string(capacity),
+        }
+    }
+
+    fn for_each_segment(&self, f: &mut dyn FnMut(&str)) {
+        f(self);
+    }
+
+    fn segment_count(&self) -> usize {
+        1
+    }
+
+    fn segment_at<'s>(
+        &'s self,
+        _index: usize,
+        _buf: &'s mut SegmentBuf,
+    ) -> &'s str {
+        self
+    }
+
+    fn single_str(&self) -> Option<&str> {
+        Some(self)
+    }
+
+    #[cfg(feature = "rope")]
+    fn append_to_rope(self, rope: &mut Rope) {
+        match self {
+            Cow::Borrowed(b) => rope.push_str(b),
+            Cow::Owned(o) => rope.push_string(o),
+        }
+    }
+}
+
+// Borrowed items are appended to a new String as they cannot be
+// moved out of.
+impl<T: Cat + ?Sized> Cat for &T {
+    fn size_hint(&self) -> usize {
+        (**self).size_hint()
+    }
+
+    fn append_to(&self, s: &mut String) {
+        (**self).append_to(s)
+    }
+
+    fn for_each_segment(&self, f: &mut dyn FnMut(&str)) {
+        (**self).for_each_segment(f)
+    }
+
+    fn checked_size_hint(&self) -> Option<usize> {
+        (**self).checked_size_hint()
+    }
+
+    fn cheap_size_hint(&self) -> Option<usize> {
+        (**self).cheap_size_hint()
     }
 
-    fn append_to(&self, s: &mut String) {
-        s.push(*self);
+    fn pieces(&self) -> usize {
+        (**self).pieces()
     }
 
-    fn into_string(self, capacity: usize) -> String {
-        let mut s = String::with_capacity(capacity);
-        s.push(self);
-        s
+    fn split(&self) -> Option<(&dyn Cat, &dyn Cat)> {
+        (**self).split()
+    }
+
+    fn child_count(&self) -> usize {
+        (**self).child_count()
+    }
+
+    fn child_at(&self, index: usize) -> &dyn Cat {
+        (**self).child_at(index)
+    }
+
+    fn segment_count(&self) -> usize {
+        (**self).segment_count()
+    }
+
+    fn segment_at<'s>(
+        &'s self,
+        index: usize,
+        buf: &'s mut SegmentBuf,
+    ) -> &'s str {
+        (**self).segment_at(index, buf)
+    }
+
+    fn single_str(&self) -> Option<&str> {
+        (**self).single_str()
+    }
+
+    #[cfg(feature = "secrecy")]
+    fn for_each_exposed_segment(&self, f: &mut dyn FnMut(&str)) {
+        (**self).for_each_exposed_segment(f)
     }
 }
 
-impl<'a> Cat for &'a str {
+// `None` is an empty item.
+impl<T: Cat> Cat for Option<T> {
     fn size_hint(&self) -> usize {
-        self.len()
+        self.as_ref().map_or(0, Cat::size_hint)
+    }
+
+    fn cheap_size_hint(&self) -> Option<usize> {
+        self.as_ref().map_or(Some(0), Cat::cheap_size_hint)
     }
 
     fn append_to(&self, s: &mut String) {
-        s.push_str(self);
+        if let Some(ref item) = *self {
+            item.append_to(s);
+        }
     }
 
     fn into_string(self, capacity: usize) -> String {
-        let mut s = String::with_capacity(capacity);
-        s.push_str(self);
-        s
+        match self {
+            Some(item) => item.into_string(capacity),
+            None => String::with_capacity(capacity),
+        }
     }
-}
 
-impl Cat for String {
-    fn size_hint(&self) -> usize {
-        self.len()
+    fn for_each_segment(&self, f: &mut dyn FnMut(&str)) {
+        if let Some(ref item) = *self {
+            item.for_each_segment(f);
+        }
     }
 
-    fn append_to(&self, s: &mut String) {
-        s.push_str(self)
+    fn pieces(&self) -> usize {
+        self.as_ref().map_or(1, Cat::pieces)
     }
 
-    fn into_string(mut self, capacity: usize) -> String {
-        let len = self.len();
-        if capacity > len {
-            self.reserve(capacity - len);
+    fn split(&self) -> Option<(&dyn Cat, &dyn Cat)> {
+        self.as_ref().and_then(Cat::split)
+    }
+
+    fn child_count(&self) -> usize {
+        self.as_ref().map_or(0, Cat::child_count)
+    }
+
+    fn child_at(&self, index: usize) -> &dyn Cat {
+        match *self {
+            Some(ref item) => item.child_at(index),
+            None => unreachable!("child index out of range"),
+        }
+    }
+
+    fn segment_count(&self) -> usize {
+        self.as_ref().map_or(0, Cat::segment_count)
+    }
+
+    fn segment_at<'s>(
+        &'s self,
+        index: usize,
+        buf: &'s mut SegmentBuf,
+    ) -> &'s str {
+        match *self {
+            Some(ref item) => item.segment_at(index, buf),
+            None => unreachable!("segment index out of range"),
+        }
+    }
+
+    fn single_str(&self) -> Option<&str> {
+        self.as_ref().map_or(Some(""), Cat::single_str)
+    }
+
+    #[cfg(feature = "rope")]
+    fn append_to_rope(self, rope: &mut Rope) {
+        if let Some(item) = self {
+            item.append_to_rope(rope);
+        }
+    }
+
+    #[cfg(feature = "secrecy")]
+    fn for_each_exposed_segment(&self, f: &mut dyn FnMut(&str)) {
+        if let Some(ref item) = *self {
+            item.for_each_exposed_segment(f);
         }
-        self
     }
 }
 
+/// A concatenation of two or more pieces.
+///
+/// This is created by adding pieces to a [`CatOne`](struct.CatOne.html)
+/// or to another `CatMany`. The left-hand side `L` holds every piece
+/// except the last one, which is `R`.
+///
+/// If the length of every piece is known without formatting it, as
+/// for strings and characters, the total length is stored when the
+/// concatenation is built, so sizing it does not walk its pieces
+/// again. Pieces that are formatted on the fly, such as
+/// [`fmt_piece!`](macro.fmt_piece.html) pieces, are only measured
+/// when the concatenation is materialized.
+///
+/// # Panics
+///
+/// Adding a piece panics if the stored length would overflow
+/// `usize`. The length of a concatenation with pieces that are
+/// measured later can still overflow when it is materialized, which
+/// fallible conversions such as
+/// [`try_into_string_limited`](#method.try_into_string_limited)
+/// report, while the conversions that cannot fail, such as
+/// `String::from`, and [`size_hint`](trait.Cat.html#tymethod.size_hint)
+/// panic with "capacity overflow".
 #[derive(Clone)]
 pub struct CatMany<L: Cat, R: Cat> {
-    lhs: L,
-    rhs: R,
+    pub(crate) lhs: L,
+    pub(crate) rhs: R,
+    // `None` if some piece is only measured when materialized.
+    len: Option<usize>,
+}
+
+impl<L: Cat, R: Cat> CatMany<L, R> {
+    pub(crate) fn new(lhs: L, rhs: R) -> CatMany<L, R> {
+        let len = match (lhs.cheap_size_hint(), rhs.cheap_size_hint()) {
+            (Some(l), Some(r)) => Some(
+                l.checked_add(r)
+                    .expect("length of concatenation overflows usize"),
+            ),
+            _ => None,
+        };
+        CatMany { lhs, rhs, len }
+    }
 }
 
 impl<L: Cat + Copy, R: Cat + Copy> Copy for CatMany<L, R> {}
 
+impl<L: Cat, R: Cat> CatMany<L, R> {
+    /// Returns a reference to the left-hand side, which is everything
+    /// except the last piece.
+    pub fn lhs(&self) -> &L {
+        &self.lhs
+    }
+
+    /// Returns a reference to the last piece.
+    pub fn rhs(&self) -> &R {
+        &self.rhs
+    }
+
+    /// Splits the concatenation into its left-hand side and its last
+    /// piece.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use sconcat::CAT;
+    ///
+    /// let cat = CAT + "Hello, " + "world" + '!';
+    /// let (greeting, bang) = cat.into_parts();
+    /// assert_eq!(bang, '!');
+    /// let cat = CAT + "¡" + greeting;
+    /// assert_eq!(String::from(cat), "¡Hello, world");
+    /// ```
+    pub fn into_parts(self) -> (L, R) {
+        (self.lhs, self.rhs)
+    }
+
+    /// Returns the size hint of the concatenation in bytes, which is
+    /// the capacity reserved when it is materialized.
+    ///
+    /// This is cheap if the length was stored when the concatenation
+    /// was built. Otherwise the pieces are walked and formatted values
+    /// are measured, and the hint is only an estimate if some pieces
+    /// do not know their exact length.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use sconcat::CAT;
+    ///
+    /// let body = CAT + "id=" + "42";
+    /// assert_eq!(body.size_hint(), 5);
+    /// let mut frame = Vec::with_capacity(4 + body.size_hint());
+    /// frame.extend_from_slice(&(body.len() as u32).to_be_bytes());
+    /// frame.extend_from_slice(String::from(body).as_bytes());
+    /// assert_eq!(frame, b"\0\0\0\x05id=42");
+    /// ```
+    pub fn size_hint(&self) -> usize {
+        Cat::size_hint(self)
+    }
+
+    /// Returns the exact length of the concatenation in bytes.
+    ///
+    /// This walks the segments of the concatenation, so pieces that
+    /// are formatted on the fly are formatted to find their length.
+    pub fn len(&self) -> usize {
+        segments_len(self)
+    }
+
+    /// Returns `true` if the concatenation has a length of zero
+    /// bytes.
+    pub fn is_empty(&self) -> bool {
+        segments_empty(self)
+    }
+
+    /// Appends the concatenation to `s`, reserving its size hint
+    /// first.
+    ///
+    /// This is the same as `*s += &self`, but it can be called where
+    /// the operator is awkward, such as through a `&mut String`
+    /// obtained from elsewhere or from a macro.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use sconcat::CAT;
+    ///
+    /// fn emit(buf: &mut String, key: &str, value: &str) {
+    ///     (CAT + key + '=' + value + ';').append_to(buf);
+    /// }
+    ///
+    /// let mut buf = String::new();
+    /// emit(&mut buf, "a", "1");
+    /// emit(&mut buf, "b", "2");
+    /// assert_eq!(buf, "a=1;b=2;");
+    /// ```
+    pub fn append_to(&self, s: &mut String) {
+        append_cat(s, self);
+    }
+}
+
 impl<L: Cat, R: Cat> Cat for CatMany<L, R> {
     fn size_hint(&self) -> usize {
-        self.lhs
-            .size_hint()
-            .checked_add(self.rhs.size_hint())
-            .expect("capacity overflow")
+        self.checked_size_hint().expect("capacity overflow")
     }
 
     fn append_to(&self, s: &mut String) {
@@ -95,6 +1042,70 @@ impl<L: Cat, R: Cat> Cat for CatMany<L, R> {
         self.rhs.append_to(&mut s);
         s
     }
+
+    fn for_each_segment(&self, f: &mut dyn FnMut(&str)) {
+        self.lhs.for_each_segment(f);
+        self.rhs.for_each_segment(f);
+    }
+
+    fn checked_size_hint(&self) -> Option<usize> {
+        match self.len {
+            Some(len) => Some(len),
+            None => self
+                .lhs
+                .checked_size_hint()?
+                .checked_add(self.rhs.checked_size_hint()?),
+        }
+    }
+
+    fn cheap_size_hint(&self) -> Option<usize> {
+        self.len
+    }
+
+    fn pieces(&self) -> usize {
+        self.lhs.pieces() + self.rhs.pieces()
+    }
+
+    fn split(&self) -> Option<(&dyn Cat, &dyn Cat)> {
+        Some((&self.lhs, &self.rhs))
+    }
+
+    fn segment_count(&self) -> usize {
+        self.lhs.segment_count() + self.rhs.segment_count()
+    }
+
+    fn segment_at<'s>(
+        &'s self,
+        index: usize,
+        buf: &'s mut SegmentBuf,
+    ) -> &'s str {
+        let lhs_count = self.lhs.segment_count();
+        if index < lhs_count {
+            self.lhs.segment_at(index, buf)
+        } else {
+            self.rhs.segment_at(index - lhs_count, buf)
+        }
+    }
+
+    fn single_str(&self) -> Option<&str> {
+        match (self.lhs.single_str(), self.rhs.single_str()) {
+            (Some(""), rhs) => rhs,
+            (lhs, Some("")) => lhs,
+            _ => None,
+        }
+    }
+
+    #[cfg(feature = "rope")]
+    fn append_to_rope(self, rope: &mut Rope) {
+        self.lhs.append_to_rope(rope);
+        self.rhs.append_to_rope(rope);
+    }
+
+    #[cfg(feature = "secrecy")]
+    fn for_each_exposed_segment(&self, f: &mut dyn FnMut(&str)) {
+        self.lhs.for_each_exposed_segment(f);
+        self.rhs.for_each_exposed_segment(f);
+    }
 }
 
 impl<L: Cat, R: Cat> Add<CatStart> for CatMany<L, R> {
@@ -107,66 +1118,139 @@ impl<L: Cat, R: Cat> Add<CatStart> for CatMany<L, R> {
 impl<L: Cat, R: Cat, RR: Cat> Add<CatOne<RR>> for CatMany<L, R> {
     type Output = CatMany<CatMany<L, R>, RR>;
     fn add(self, rhs: CatOne<RR>) -> CatMany<CatMany<L, R>, RR> {
-        CatMany {
-            lhs: self,
-            rhs: rhs.inner,
-        }
+        CatMany::new(self, rhs.inner)
     }
 }
 
 impl<L: Cat, R: Cat, RR: Cat> Add<RR> for CatMany<L, R> {
     type Output = CatMany<CatMany<L, R>, RR>;
     fn add(self, rhs: RR) -> CatMany<CatMany<L, R>, RR> {
-        CatMany {
-            lhs: self,
-            rhs: rhs,
-        }
+        CatMany::new(self, rhs)
     }
 }
 
 impl<L: Cat, R: Cat> AddAssign<CatMany<L, R>> for String {
     fn add_assign(&mut self, rhs: CatMany<L, R>) {
-        self.reserve(rhs.size_hint());
-        rhs.append_to(self);
+        append_cat(self, &rhs);
     }
 }
 
-impl<'a, L: Cat, R: Cat> AddAssign<&'a CatMany<L, R>> for String {
+impl<L: Cat, R: Cat> AddAssign<&CatMany<L, R>> for String {
     fn add_assign(&mut self, rhs: &CatMany<L, R>) {
-        self.reserve(rhs.size_hint());
-        rhs.append_to(self);
+        append_cat(self, rhs);
+    }
+}
+
+impl<'a, L: Cat, R: Cat> Add<CatMany<L, R>> for &'a str {
+    type Output = CatMany<&'a str, CatMany<L, R>>;
+    fn add(self, rhs: CatMany<L, R>) -> CatMany<&'a str, CatMany<L, R>> {
+        CatMany::new(self, rhs)
+    }
+}
+
+impl<L: Cat, R: Cat> Extend<CatMany<L, R>> for String {
+    fn extend<I: IntoIterator<Item = CatMany<L, R>>>(&mut self, iter: I) {
+        extend_cats(self, iter);
+    }
+}
+
+impl<'a, L: Cat, R: Cat> Extend<&'a CatMany<L, R>> for String {
+    fn extend<I: IntoIterator<Item = &'a CatMany<L, R>>>(&mut self, iter: I) {
+        extend_cats(self, iter);
     }
 }
 
 impl<L: Cat, R: Cat> From<CatMany<L, R>> for String {
     fn from(src: CatMany<L, R>) -> String {
-        let capacity = src.size_hint();
-        src.into_string(capacity)
+        cat_into_string(src)
     }
 }
 
+// The alternate form `{:#?}` shows the tree of the concatenation
+// with the type of every piece.
 impl<L: Cat + Debug, R: Cat + Debug> Debug for CatMany<L, R> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if f.alternate() {
+            return f
+                .debug_struct("CatMany")
+                .field("lhs", &TreeNode(&self.lhs))
+                .field("rhs", &TreeNode(&self.rhs))
+                .finish();
+        }
         Debug::fmt(&self.lhs, f)?;
         Display::fmt(" + ", f)?;
         Debug::fmt(&self.rhs, f)
     }
 }
 
+// Padding and truncation apply to the whole concatenation, not to
+// each piece.
 impl<L: Cat + Display, R: Cat + Display> Display for CatMany<L, R> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if f.width().is_some() || f.precision().is_some() {
+            return pad_segments(self, f);
+        }
         Display::fmt(&self.lhs, f)?;
         Display::fmt(&self.rhs, f)
     }
 }
 
+/// A concatenation of one piece.
+///
+/// This is created by adding a piece to [`CAT`](constant.CAT.html).
+/// Unlike [`CatMany`](struct.CatMany.html), it does not implement
+/// [`Cat`](trait.Cat.html) itself; the piece can be obtained with
+/// [`into_inner`](#method.into_inner).
 #[derive(Clone)]
 pub struct CatOne<T: Cat> {
-    inner: T,
+    pub(crate) inner: T,
 }
 
 impl<T: Cat + Copy> Copy for CatOne<T> {}
 
+impl<T: Cat> CatOne<T> {
+    /// Returns a reference to the only piece.
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    /// Returns the only piece.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+
+    /// Returns the size hint of the concatenation in bytes, which is
+    /// the capacity reserved when it is materialized.
+    pub fn size_hint(&self) -> usize {
+        self.inner.size_hint()
+    }
+
+    /// Returns the exact length of the concatenation in bytes.
+    pub fn len(&self) -> usize {
+        segments_len(&self.inner)
+    }
+
+    /// Returns `true` if the concatenation has a length of zero
+    /// bytes.
+    pub fn is_empty(&self) -> bool {
+        segments_empty(&self.inner)
+    }
+
+    /// Appends the concatenation to `s`, reserving its size hint
+    /// first.
+    ///
+    /// This is the same as `*s += self` but borrows `self`.
+    pub fn append_to(&self, s: &mut String) {
+        append_cat(s, &self.inner);
+    }
+
+    /// Writes the concatenation to a `fmt::Write` sink without
+    /// materializing it.
+    pub fn write_to<W: Write>(&self, w: &mut W) -> fmt::Result {
+        self.inner.write_to(w)
+    }
+}
+
 impl<T: Cat> Add<CatStart> for CatOne<T> {
     type Output = CatOne<T>;
     fn add(self, _rhs: CatStart) -> CatOne<T> {
@@ -177,56 +1261,78 @@ impl<T: Cat> Add<CatStart> for CatOne<T> {
 impl<L: Cat, R: Cat> Add<CatOne<R>> for CatOne<L> {
     type Output = CatMany<L, R>;
     fn add(self, rhs: CatOne<R>) -> CatMany<L, R> {
-        CatMany {
-            lhs: self.inner,
-            rhs: rhs.inner,
-        }
+        CatMany::new(self.inner, rhs.inner)
     }
 }
 
 impl<L: Cat, R: Cat> Add<R> for CatOne<L> {
     type Output = CatMany<L, R>;
     fn add(self, rhs: R) -> CatMany<L, R> {
-        CatMany {
-            lhs: self.inner,
-            rhs: rhs,
-        }
+        CatMany::new(self.inner, rhs)
     }
 }
 
 impl<T: Cat> AddAssign<CatOne<T>> for String {
     fn add_assign(&mut self, rhs: CatOne<T>) {
-        self.reserve(rhs.inner.size_hint());
-        rhs.inner.append_to(self);
+        append_cat(self, &rhs.inner);
     }
 }
 
-impl<'a, T: Cat> AddAssign<&'a CatOne<T>> for String {
+impl<T: Cat> AddAssign<&CatOne<T>> for String {
     fn add_assign(&mut self, rhs: &CatOne<T>) {
-        self.reserve(rhs.inner.size_hint());
-        rhs.inner.append_to(self);
+        append_cat(self, &rhs.inner);
+    }
+}
+
+impl<'a, T: Cat> Add<CatOne<T>> for &'a str {
+    type Output = CatMany<&'a str, T>;
+    fn add(self, rhs: CatOne<T>) -> CatMany<&'a str, T> {
+        CatMany::new(self, rhs.inner)
+    }
+}
+
+impl<T: Cat> Extend<CatOne<T>> for String {
+    fn extend<I: IntoIterator<Item = CatOne<T>>>(&mut self, iter: I) {
+        extend_cats(self, iter.into_iter().map(|one| one.inner));
+    }
+}
+
+impl<'a, T: Cat> Extend<&'a CatOne<T>> for String {
+    fn extend<I: IntoIterator<Item = &'a CatOne<T>>>(&mut self, iter: I) {
+        extend_cats(self, iter.into_iter().map(|one| &one.inner));
     }
 }
 
 impl<T: Cat> From<CatOne<T>> for String {
     fn from(src: CatOne<T>) -> String {
-        let capacity = src.inner.size_hint();
-        src.inner.into_string(capacity)
+        cat_into_string(src.inner)
     }
 }
 
 impl<T: Cat + Debug> Debug for CatOne<T> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if f.alternate() {
+            return f
+                .debug_struct("CatOne")
+                .field("inner", &TreeNode(&self.inner))
+                .finish();
+        }
         Debug::fmt(&self.inner, f)
     }
 }
 
 impl<T: Cat + Display> Display for CatOne<T> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if f.width().is_some() || f.precision().is_some() {
+            return pad_segments(&self.inner, f);
+        }
         Display::fmt(&self.inner, f)
     }
 }
 
+/// An empty concatenation.
+///
+/// The only value of this type is [`CAT`](constant.CAT.html).
 #[derive(Clone, Copy)]
 pub struct CatStart;
 
@@ -249,6 +1355,61 @@ pub struct CatStart;
 /// ```
 pub const CAT: CatStart = CatStart;
 
+/// Creates a concatenation of the listed pieces.
+///
+/// `cat!(a, b, c)` expands to `CAT + a + b + c`, so it produces the
+/// same value as the operator syntax and has the same allocation
+/// behaviour.
+///
+/// # Examples
+///
+/// ```rust
+/// #[macro_use]
+/// extern crate sconcat;
+///
+/// # fn main() {
+/// let name = String::from("world");
+/// let cat = cat!("Hello, ", name, '!');
+/// assert_eq!(String::from(cat), "Hello, world!");
+///
+/// let mut s = String::from("a");
+/// s += cat!('b', "c",);
+/// s += cat!();
+/// assert_eq!(s, "abc");
+/// # }
+/// ```
+#[macro_export]
+macro_rules! cat {
+    ($($piece:expr),* $(,)*) => {
+        $crate::CAT $(+ $piece)*
+    };
+}
+
+impl CatStart {
+    /// Returns zero, as the empty concatenation needs no capacity.
+    pub fn size_hint(&self) -> usize {
+        0
+    }
+
+    /// Returns zero.
+    pub fn len(&self) -> usize {
+        0
+    }
+
+    /// Returns `true`.
+    pub fn is_empty(&self) -> bool {
+        true
+    }
+
+    /// Appends nothing to `s`.
+    pub fn append_to(&self, _s: &mut String) {}
+
+    /// Writes nothing to a `fmt::Write` sink.
+    pub fn write_to<W: Write>(&self, _w: &mut W) -> fmt::Result {
+        Ok(())
+    }
+}
+
 impl Add<CatStart> for CatStart {
     type Output = CatStart;
     fn add(self, _rhs: CatStart) -> CatStart {
@@ -274,10 +1435,17 @@ impl AddAssign<CatStart> for String {
     fn add_assign(&mut self, _rhs: CatStart) {}
 }
 
-impl<'a> AddAssign<&'a CatStart> for String {
+impl AddAssign<&CatStart> for String {
     fn add_assign(&mut self, _rhs: &CatStart) {}
 }
 
+impl<'a> Add<CatStart> for &'a str {
+    type Output = CatOne<&'a str>;
+    fn add(self, _rhs: CatStart) -> CatOne<&'a str> {
+        CatOne { inner: self }
+    }
+}
+
 impl From<CatStart> for String {
     fn from(_src: CatStart) -> String {
         String::new()
@@ -286,19 +1454,27 @@ impl From<CatStart> for String {
 
 impl Debug for CatStart {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if f.alternate() {
+            return f.write_str("CatStart");
+        }
         Display::fmt("\"\"", f)
     }
 }
 
 impl Display for CatStart {
-    fn fmt(&self, _f: &mut fmt::Formatter) -> fmt::Result {
-        Ok(())
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("")
     }
 }
 
 #[cfg(test)]
 mod tests {
     use CAT;
+    use std::borrow::Cow;
+    use std::rc::Rc;
+    use std::sync::Arc;
+    #[cfg(all(feature = "alloc-check", debug_assertions))]
+    use super::Cat;
 
     #[test]
     fn it_works() {
@@ -314,6 +1490,132 @@ mod tests {
         assert_eq!(s.as_ptr(), ptr);
     }
 
+    #[test]
+    fn macro_pieces() {
+        let cat = cat!("Hello, ", String::from("world"), '!');
+        assert_eq!(format!("{:?}", cat), "\"Hello, \" + \"world\" + '!'");
+        assert_eq!(String::from(cat), "Hello, world!");
+        let flags = [true, false];
+        let cat = cat!(if flags[1] { 'y' } else { 'n' }, "o");
+        assert_eq!(String::from(cat), "no");
+        let mut s = String::from("a");
+        s += cat!('b',);
+        s += cat!();
+        assert_eq!(s, "ab");
+    }
+
+    #[test]
+    fn extend() {
+        let mut s = String::from("list:");
+        s.extend((1..4).map(|i| CAT + ' ' + (b'0' + i) as char));
+        assert_eq!(s, "list: 1 2 3");
+        let ones = vec![CAT + "alpha", CAT + "bravo"];
+        let mut s = String::with_capacity(20);
+        s.extend(&ones);
+        s.extend(ones);
+        assert_eq!(s, "alphabravoalphabravo");
+        assert_eq!(s.capacity(), 20);
+        let manys = [CAT + "xyzzy" + '!', CAT + "plugh" + '?'];
+        let mut s = String::new();
+        s.extend(manys.iter());
+        assert_eq!(s, "xyzzy!plugh?");
+        let mut s = String::new();
+        s.extend((0..1000).map(|_| CAT + "ab" + 'c'));
+        assert_eq!(s.len(), 3000);
+        assert!(s.capacity() < 6000);
+    }
+
+    #[test]
+    fn add_to_strings() {
+        // A second `Add` impl for `String` would break coercing
+        // `&String` to `&str` in `String + &String`.
+        let (a, b) = (String::from("a"), String::from("b"));
+        assert_eq!(a + &b, "ab");
+        let cat = "x" + (CAT + 'y' + "z");
+        assert_eq!(format!("{:?}", cat), "\"x\" + 'y' + \"z\"");
+        let s = String::from(cat + '!');
+        assert_eq!(s, "xyz!");
+        assert_eq!(s.len(), s.capacity());
+        assert_eq!(String::from("x" + (CAT + 'y')), "xy");
+        assert_eq!(String::from("x" + CAT), "x");
+    }
+
+    #[test]
+    fn append_to() {
+        let mut s = String::from("a");
+        CAT.append_to(&mut s);
+        (CAT + 'b').append_to(&mut s);
+        let cat = CAT + "cd" + 'e';
+        cat.append_to(&mut s);
+        assert_eq!(s, "abcde");
+    }
+
+    #[test]
+    fn write_to() {
+        use super::Cat;
+        use std::fmt::{self, Write};
+
+        // Accepts at most `limit` bytes.
+        struct Limited {
+            s: String,
+            limit: usize,
+        }
+
+        impl Write for Limited {
+            fn write_str(&mut self, s: &str) -> fmt::Result {
+                if self.s.len() + s.len() > self.limit {
+                    return Err(fmt::Error);
+                }
+                self.s.push_str(s);
+                Ok(())
+            }
+        }
+
+        let cat = CAT + "ab" + 'é' + String::from("cd");
+        let mut w = Limited {
+            s: String::new(),
+            limit: 10,
+        };
+        cat.write_to(&mut w).unwrap();
+        (CAT + "!").write_to(&mut w).unwrap();
+        CAT.write_to(&mut w).unwrap();
+        "xyz".write_to(&mut w).unwrap();
+        assert_eq!(w.s, "abécd!xyz");
+        assert!(cat.write_to(&mut w).is_err());
+        assert_eq!(w.s, "abécd!xyz");
+    }
+
+    #[test]
+    fn write_coalesced() {
+        use super::Cat;
+        use std::fmt::{self, Write};
+
+        // Records every string written.
+        struct Calls(Vec<String>);
+
+        impl Write for Calls {
+            fn write_str(&mut self, s: &str) -> fmt::Result {
+                self.0.push(String::from(s));
+                Ok(())
+            }
+        }
+
+        let long = "x".repeat(40);
+        let cat = CAT + '[' + "a" + 'é' + ']' + &*long + ',' + ' ' + "end";
+        let mut w = Calls(Vec::new());
+        cat.write_to(&mut w).unwrap();
+        assert_eq!(w.0, ["[aé]", long.as_str(), ", end"]);
+        let many = CAT + "0123456789abcdef".repeat(20) + ' ';
+        let mut w = Calls(Vec::new());
+        many.write_to(&mut w).unwrap();
+        assert_eq!(w.0.len(), 2);
+        let mut w = Calls(Vec::new());
+        let chars = ['a'; 300];
+        (CAT + &chars[..] + "").write_to(&mut w).unwrap();
+        assert_eq!(w.0.len(), 2);
+        assert_eq!(w.0.concat(), "a".repeat(300));
+    }
+
     #[test]
     fn formatting() {
         let cat0 = CAT;
@@ -329,81 +1631,270 @@ mod tests {
         assert_eq!(format!("{}", cat3), "Hello, world! ☺");
         assert_eq!(format!("{:?}", cat3), "\"Hello, \" + \"world! \" + '☺'");
     }
-}
 
-// fast_fmt impls here
-#[cfg(feature = "fast_fmt")]
-use ::fast_fmt::{Fmt, Write};
-use ::fast_fmt::Display as FFDisplay;
-use ::fast_fmt::Debug as FFDebug;
+    #[test]
+    fn shared_strings() {
+        let mut buf = String::with_capacity(64);
+        buf.push_str("unique");
+        let ptr = buf.as_ptr();
+        let s = String::from(CAT + Rc::new(buf) + '!');
+        assert_eq!(s, "unique!");
+        assert_eq!(s.as_ptr(), ptr);
 
-#[cfg(feature = "fast_fmt")]
-impl<L: Cat + Fmt, R: Cat + Fmt> Fmt for CatMany<L, R> {
-    fn fmt<W: Write>(&self, writer: &mut W, strategy: &FFDisplay) -> Result<(), W::Error> {
-        self.lhs.fmt(writer, strategy)?;
-        self.rhs.fmt(writer, strategy)
+        let shared = Arc::new(String::from("shared"));
+        let s = String::from(CAT + Arc::clone(&shared) + '!');
+        assert_eq!(s, "shared!");
+        assert_eq!(s.len(), s.capacity());
+        let mut s = String::from("a ");
+        s += CAT + shared + ' ' + Rc::new(String::from("b"));
+        assert_eq!(s, "a shared b");
     }
 
-    fn size_hint(&self, strategy: &FFDisplay) -> usize {
-        Fmt::size_hint(&self.lhs, strategy) + Fmt::size_hint(&self.rhs, strategy)
+    #[test]
+    fn str_pointers() {
+        let boxed: Box<str> = Box::from("boxed");
+        let ptr = boxed.as_ptr();
+        let s = String::from(CAT + boxed);
+        assert_eq!(s, "boxed");
+        assert_eq!(s.as_ptr(), ptr);
+        let s = String::from(CAT + s.into_boxed_str() + '!');
+        assert_eq!(s, "boxed!");
+
+        let interned: Arc<str> = Arc::from("interned");
+        let s = String::from(CAT + Arc::clone(&interned) + '!');
+        assert_eq!(s, "interned!");
+        assert_eq!(s.len(), s.capacity());
+        let mut s = String::from("a ");
+        s += CAT + interned + ' ' + Rc::<str>::from("b");
+        assert_eq!(s, "a interned b");
     }
-}
 
-#[cfg(feature = "fast_fmt")]
-impl<T: Cat + Fmt> Fmt for CatOne<T> {
-    fn fmt<W: Write>(&self, writer: &mut W, strategy: &FFDisplay) -> Result<(), W::Error> {
-        self.inner.fmt(writer, strategy)
+    #[test]
+    fn cow_strings() {
+        let mut buf = String::with_capacity(64);
+        buf.push_str("owned");
+        let ptr = buf.as_ptr();
+        let owned: Cow<str> = Cow::Owned(buf);
+        let s = String::from(CAT + owned + '!');
+        assert_eq!(s, "owned!");
+        assert_eq!(s.as_ptr(), ptr);
+
+        let borrowed: Cow<str> = Cow::Borrowed("borrowed");
+        let s = String::from(CAT + borrowed + '!');
+        assert_eq!(s, "borrowed!");
+        assert_eq!(s.len(), s.capacity());
+        let mut s = String::from("a ");
+        s += CAT + Cow::Borrowed("b") + ' ' + Cow::Owned::<str>("c".into());
+        assert_eq!(s, "a b c");
     }
 
-    fn size_hint(&self, strategy: &FFDisplay) -> usize {
-        Fmt::size_hint(&self.inner, strategy)
+    #[test]
+    fn borrowed_items() {
+        let owned = String::from("owned");
+        let chr = &'!';
+        let slice = &"slice";
+        let inner = CAT + "x" + 'y';
+        let (owned_ref, inner_ref) = (&owned, &inner);
+        let cat = CAT + owned_ref + chr + slice + ' ' + inner_ref;
+        let debug = "\"owned\" + '!' + \"slice\" + ' ' + \"x\" + 'y'";
+        assert_eq!(format!("{:?}", cat), debug);
+        let s = String::from(cat);
+        assert_eq!(s, "owned!slice xy");
+        assert_eq!(s.len(), s.capacity());
+        let s = String::from(CAT + &owned);
+        assert_eq!(s, "owned");
+        assert_ne!(s.as_ptr(), owned.as_ptr());
+        let mut s = String::from("a ");
+        s += CAT + owned_ref + ' ' + inner_ref;
+        assert_eq!(s, "a owned xy");
     }
-}
 
-#[cfg(feature = "fast_fmt")]
-impl Fmt for CatStart {
-    // Prints nothing
-    fn fmt<W: Write>(&self, _writer: &mut W, _strategy: &FFDisplay) -> Result<(), W::Error> {
-        Ok(())
+    #[test]
+    fn optional_items() {
+        let suffix: Option<&str> = None;
+        let cat = CAT + "user " + Some(String::from("bob")) + suffix + '!';
+        let s = String::from(cat);
+        assert_eq!(s, "user bob!");
+        assert_eq!(s.len(), s.capacity());
+        let s = String::from(CAT + None::<char> + Some('x'));
+        assert_eq!(s, "x");
+        let mut s = String::from("a");
+        s += CAT + Some("b") + None::<String>;
+        assert_eq!(s, "ab");
     }
 
-    fn size_hint(&self, _strategy: &FFDisplay) -> usize {
-        0
+    #[test]
+    fn parts() {
+        let one = CAT + "a";
+        assert_eq!(*one.inner(), "a");
+        assert_eq!(one.into_inner(), "a");
+        let many = CAT + "a" + String::from("b") + 'c';
+        assert_eq!(*many.rhs(), 'c');
+        assert_eq!(*many.lhs().rhs(), "b");
+        let (lhs, rhs) = many.into_parts();
+        let (a, b) = lhs.into_parts();
+        assert_eq!(String::from(CAT + rhs + b + a), "cba");
     }
-}
 
-#[cfg(feature = "fast_fmt")]
-impl<L: Cat + Fmt<FFDebug>, R: Cat + Fmt<FFDebug>> Fmt<FFDebug> for CatMany<L, R> {
-    fn fmt<W: Write>(&self, writer: &mut W, strategy: &FFDebug) -> Result<(), W::Error> {
-        self.lhs.fmt(writer, strategy)?;
-        Fmt::fmt(" + ", writer, &FFDisplay)?;
-        self.rhs.fmt(writer, strategy)
+    #[cfg(all(feature = "alloc-check", debug_assertions))]
+    #[derive(Clone, Copy)]
+    struct Underestimate;
+
+    #[cfg(all(feature = "alloc-check", debug_assertions))]
+    impl Cat for Underestimate {
+        fn size_hint(&self) -> usize {
+            0
+        }
+
+        fn append_to(&self, s: &mut String) {
+            s.push_str("more than nothing");
+        }
+
+        fn for_each_segment(&self, f: &mut dyn FnMut(&str)) {
+            f("more than nothing");
+        }
     }
 
-    fn size_hint(&self, strategy: &FFDebug) -> usize {
-        Fmt::<FFDebug>::size_hint(&self.lhs, strategy) + 3 + Fmt::<FFDebug>::size_hint(&self.rhs, strategy)
+    #[cfg(all(feature = "alloc-check", debug_assertions))]
+    #[test]
+    #[should_panic(expected = "concatenation reallocated")]
+    fn check_into_string() {
+        let _ = String::from(CAT + "x" + Underestimate);
     }
-}
 
-#[cfg(feature = "fast_fmt")]
-impl<T: Cat + Fmt<FFDebug>> Fmt<FFDebug> for CatOne<T> {
-    fn fmt<W: Write>(&self, writer: &mut W, strategy: &FFDebug) -> Result<(), W::Error> {
-        self.inner.fmt(writer, strategy)
+    #[cfg(all(feature = "alloc-check", debug_assertions))]
+    #[test]
+    #[should_panic(expected = "concatenation reallocated")]
+    fn check_append() {
+        let mut s = String::new();
+        s += CAT + Underestimate;
     }
 
-    fn size_hint(&self, strategy: &FFDebug) -> usize {
-        Fmt::<FFDebug>::size_hint(&self.inner, strategy)
+    #[test]
+    fn lengths() {
+        let cat = CAT + "ab" + 'é' + String::new() + Some("xyz");
+        assert_eq!((cat.size_hint(), cat.len(), cat.is_empty()), (7, 7, false));
+        let empty = CAT + "" + String::new() + None::<&str>;
+        assert_eq!((empty.len(), empty.is_empty()), (0, true));
+        let one = CAT + ::Dbg(123);
+        assert_eq!((one.len(), one.is_empty()), (3, false));
+        assert!((CAT + "").is_empty());
+        assert_eq!((CAT.size_hint(), CAT.len(), CAT.is_empty()), (0, 0, true));
     }
-}
 
-#[cfg(feature = "fast_fmt")]
-impl Fmt<FFDebug> for CatStart {
-    // Prints nothing
-    fn fmt<W: Write>(&self, writer: &mut W, _strategy: &FFDebug) -> Result<(), W::Error> {
-        Fmt::fmt("\"\"", writer, &FFDisplay)
+    #[test]
+    fn padding() {
+        let cat = CAT + "ab" + 'é' + String::from("cd");
+        assert_eq!(format!("{:>8}|", cat), "   abécd|");
+        assert_eq!(format!("{:8}|", cat), "abécd   |");
+        assert_eq!(format!("{:*^9}|", cat), "**abécd**|");
+        assert_eq!(format!("{:*^8}|", cat), "*abécd**|");
+        assert_eq!(format!("{:.3}|", cat), "abé|");
+        assert_eq!(format!("{:-<6.4}|", cat), "abéc--|");
+        assert_eq!(format!("{:3}|", cat), "abécd|");
+        assert_eq!(format!("{:.0}|", cat), "|");
+        assert_eq!(format!("{:>4}|", CAT + 'x'), "   x|");
+        assert_eq!(format!("{:.1}|", CAT + "xyz"), "x|");
+        assert_eq!(format!("{:>3}|", CAT), "   |");
+        assert_eq!(format!("{}|", cat), "abécd|");
+    }
+
+    #[test]
+    fn debug_tree() {
+        let inner = CAT + String::from("c") + Some('\u{1}');
+        let cat = CAT + "a" + 'b' + inner;
+        let flat = "\"a\" + 'b' + \"c\" + Some('\\u{1}')";
+        assert_eq!(format!("{:?}", cat), flat);
+        let expected = r#"CatMany {
+    lhs: CatMany {
+        lhs: &str "a",
+        rhs: char 'b',
+    },
+    rhs: CatMany {
+        lhs: String "c",
+        rhs: Option<char> Some(
+            '\u{1}',
+        ),
+    },
+}"#;
+        assert_eq!(format!("{:#?}", cat), expected);
+        let one = CAT + Rc::<str>::from("x");
+        let expected = "CatOne {\n    inner: Rc<str> \"x\",\n}";
+        assert_eq!(format!("{:#?}", one), expected);
+        assert_eq!(format!("{:#?}", CAT), "CatStart");
+        assert_eq!(format!("{:?}", CAT), "\"\"");
+    }
+
+    #[test]
+    fn cached_size_hint() {
+        use std::cell::Cell;
+        use super::Cat;
+
+        struct Counted<'a>(&'a Cell<usize>);
+
+        impl<'a> Cat for Counted<'a> {
+            fn size_hint(&self) -> usize {
+                self.0.set(self.0.get() + 1);
+                1
+            }
+
+            fn append_to(&self, s: &mut String) {
+                s.push('x');
+            }
+
+            fn for_each_segment(&self, f: &mut dyn FnMut(&str)) {
+                f("x");
+            }
+        }
+
+        let cat = CAT + "ab" + 'c' + String::from("de") + Some('f');
+        assert_eq!(cat.cheap_size_hint(), Some(6));
+        let calls = Cell::new(0);
+        let cat = CAT + "ab" + Counted(&calls) + 'c' + (CAT + "d" + 'e');
+        assert_eq!(cat.cheap_size_hint(), None);
+        assert_eq!(calls.get(), 0);
+        assert_eq!(cat.size_hint(), 6);
+        assert_eq!(calls.get(), 1);
+        let s = String::from(CAT + '[' + cat + ']');
+        assert_eq!(s, "[abxcde]");
+        assert_eq!(s.len(), s.capacity());
+        assert_eq!(calls.get(), 2);
+        let huge = CAT + Counted(&calls) + ::repeat::repeat("y", usize::MAX);
+        assert_eq!(huge.checked_size_hint(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "length of concatenation overflows usize")]
+    fn overflow_panics_when_built() {
+        use super::Cat;
+
+        struct Huge;
+
+        impl Cat for Huge {
+            fn size_hint(&self) -> usize {
+                usize::MAX
+            }
+
+            fn append_to(&self, _s: &mut String) {
+                unreachable!()
+            }
+
+            fn for_each_segment(&self, _f: &mut dyn FnMut(&str)) {
+                unreachable!()
+            }
+
+            fn cheap_size_hint(&self) -> Option<usize> {
+                Some(usize::MAX)
+            }
+        }
+
+        let _ = CAT + "x" + Huge;
     }
 
-    fn size_hint(&self, _strategy: &FFDebug) -> usize {
-        2
+    #[test]
+    #[should_panic(expected = "capacity overflow")]
+    fn overflow_panics_when_sized() {
+        let huge = CAT + "x" + ::repeat::repeat("y", usize::MAX);
+        huge.size_hint();
     }
 }
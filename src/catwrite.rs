@@ -0,0 +1,156 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Support for `cat_write!`. Like `write!`, which calls the
+// `write_fmt` method that both `fmt::Write` and `io::Write` provide,
+// the macro brings two traits with a `__cat_write` method into scope,
+// one implemented for every `fmt::Write` target and one for every
+// `io::Write` target, and method resolution picks whichever applies.
+
+use cat::{Cat, CatMany, CatOne, CatStart};
+use std::fmt;
+use std::io;
+use write;
+
+/// Writes a concatenation to a `fmt::Write` or `io::Write` target.
+///
+/// `cat_write!(w, a, b, c)` builds `CAT + a + b + c` and writes it to
+/// `w` in one call without materializing it, returning a
+/// `fmt::Result` or an `io::Result<()>` like `write!`. Pieces are
+/// written as they are, with no format string to parse. An
+/// `io::Write` target receives the concatenation through a stack
+/// buffer, so one of up to 1024 bytes is written with a single
+/// `write_all`; a `fmt::Write` target receives it segment by segment.
+///
+/// # Examples
+///
+/// ```rust
+/// #[macro_use]
+/// extern crate sconcat;
+///
+/// # fn main() {
+/// let (x, y) = ("1", String::from("2"));
+/// let mut s = String::new();
+/// cat_write!(s, "x=", x, ", y=", &*y).unwrap();
+/// assert_eq!(s, "x=1, y=2");
+///
+/// let mut log = Vec::new();
+/// cat_write!(&mut log, "x=", x, ", y=", y, '\n').unwrap();
+/// assert_eq!(log, b"x=1, y=2\n");
+/// # }
+/// ```
+#[macro_export]
+macro_rules! cat_write {
+    ($dst:expr $(, $piece:expr)* $(,)*) => {{
+        #[allow(unused_imports)]
+        use $crate::{__CatWriteFmt, __CatWriteIo};
+        ($dst).__cat_write(&($crate::CAT $(+ $piece)*))
+    }};
+}
+
+#[doc(hidden)]
+pub trait __WriteCat {
+    fn write_fmt_to<W: fmt::Write>(&self, w: &mut W) -> fmt::Result;
+    fn write_io_to<W: io::Write>(&self, w: &mut W) -> io::Result<()>;
+}
+
+impl<L: Cat, R: Cat> __WriteCat for CatMany<L, R> {
+    fn write_fmt_to<W: fmt::Write>(&self, w: &mut W) -> fmt::Result {
+        Cat::write_to(self, w)
+    }
+
+    fn write_io_to<W: io::Write>(&self, w: &mut W) -> io::Result<()> {
+        write::write_staged(self, w).map(|_| ())
+    }
+}
+
+impl<T: Cat> __WriteCat for CatOne<T> {
+    fn write_fmt_to<W: fmt::Write>(&self, w: &mut W) -> fmt::Result {
+        self.inner.write_to(w)
+    }
+
+    fn write_io_to<W: io::Write>(&self, w: &mut W) -> io::Result<()> {
+        write::write_staged(&self.inner, w).map(|_| ())
+    }
+}
+
+impl __WriteCat for CatStart {
+    fn write_fmt_to<W: fmt::Write>(&self, _w: &mut W) -> fmt::Result {
+        Ok(())
+    }
+
+    fn write_io_to<W: io::Write>(&self, _w: &mut W) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[doc(hidden)]
+pub trait __CatWriteFmt {
+    fn __cat_write<C: __WriteCat>(&mut self, cat: &C) -> fmt::Result;
+}
+
+impl<W: fmt::Write> __CatWriteFmt for W {
+    fn __cat_write<C: __WriteCat>(&mut self, cat: &C) -> fmt::Result {
+        cat.write_fmt_to(self)
+    }
+}
+
+#[doc(hidden)]
+pub trait __CatWriteIo {
+    fn __cat_write<C: __WriteCat>(&mut self, cat: &C) -> io::Result<()>;
+}
+
+impl<W: io::Write> __CatWriteIo for W {
+    fn __cat_write<C: __WriteCat>(&mut self, cat: &C) -> io::Result<()> {
+        cat.write_io_to(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt::{self, Write};
+    use std::io;
+
+    struct Failing;
+
+    impl io::Write for Failing {
+        fn write(&mut self, _buf: &[u8]) -> io::Result<usize> {
+            Err(io::Error::other("full"))
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn fmt_targets() {
+        let mut s = String::from("> ");
+        cat_write!(s, "a", 'b', String::from("c")).unwrap();
+        cat_write!(&mut s, "d").unwrap();
+        cat_write!(s).unwrap();
+        assert_eq!(s, "> abcd");
+        let mut f = String::new();
+        write!(f, "{}", 1).unwrap();
+        let r: fmt::Result = cat_write!(f, ::Dbg(2), ',');
+        r.unwrap();
+        assert_eq!(f, "12,");
+    }
+
+    #[test]
+    fn io_targets() {
+        let mut v = Vec::new();
+        cat_write!(v, "x", '=', "1").unwrap();
+        cat_write!(&mut v, "\n",).unwrap();
+        cat_write!(v).unwrap();
+        assert_eq!(v, b"x=1\n");
+        let err = cat_write!(Failing, "x").unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::Other);
+        cat_write!(Failing).unwrap();
+    }
+}
@@ -0,0 +1,130 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use cat::{Cat, SegmentWriter};
+use count::CountingWriter;
+use std::error::Error;
+use std::fmt::{self, Display, Write};
+
+const SEPARATOR: &str = ": ";
+
+/// A piece that formats an error and its sources.
+///
+/// This is created by the [`error_chain`](fn.error_chain.html)
+/// function.
+#[derive(Clone, Copy, Debug)]
+pub struct ErrorChain<'a> {
+    error: &'a dyn Error,
+}
+
+/// Creates a piece that formats `error` followed by the errors
+/// returned by successive calls to `Error::source`, separated by
+/// `": "`.
+///
+/// The errors are formatted twice, once with a counting writer to
+/// compute an exact size hint and once to append them, so that no
+/// intermediate `String` is allocated.
+///
+/// # Examples
+///
+/// ```rust
+/// use sconcat::{CAT, error_chain};
+/// use std::error::Error;
+/// use std::fmt;
+///
+/// #[derive(Debug)]
+/// struct ConfigError(std::num::ParseIntError);
+///
+/// impl fmt::Display for ConfigError {
+///     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+///         f.write_str("invalid port")
+///     }
+/// }
+///
+/// impl Error for ConfigError {
+///     fn source(&self) -> Option<&(dyn Error + 'static)> {
+///         Some(&self.0)
+///     }
+/// }
+///
+/// let error = ConfigError("80a".parse::<u16>().unwrap_err());
+/// let line = String::from(CAT + "error: " + error_chain(&error));
+/// assert_eq!(line, "error: invalid port: invalid digit found in string");
+/// ```
+pub fn error_chain(error: &dyn Error) -> ErrorChain<'_> {
+    ErrorChain { error }
+}
+
+impl<'a> ErrorChain<'a> {
+    fn write<W: Write>(&self, w: &mut W) -> fmt::Result {
+        write!(w, "{}", self.error)?;
+        let mut source = self.error.source();
+        while let Some(error) = source {
+            write!(w, "{}{}", SEPARATOR, error)?;
+            source = error.source();
+        }
+        Ok(())
+    }
+}
+
+impl<'a> Cat for ErrorChain<'a> {
+    fn size_hint(&self) -> usize {
+        let mut w = CountingWriter::new();
+        self.write(&mut w).expect("capacity overflow");
+        w.count()
+    }
+
+    fn append_to(&self, s: &mut String) {
+        self.write(s).unwrap();
+    }
+
+    fn for_each_segment(&self, f: &mut dyn FnMut(&str)) {
+        self.write(&mut SegmentWriter::new(f)).unwrap();
+    }
+}
+
+impl<'a> Display for ErrorChain<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.write(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use CAT;
+    use std::error::Error;
+    use std::fmt;
+    use std::io;
+    use super::error_chain;
+
+    #[derive(Debug)]
+    struct Wrapped(&'static str, io::Error);
+
+    impl fmt::Display for Wrapped {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str(self.0)
+        }
+    }
+
+    impl Error for Wrapped {
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            Some(&self.1)
+        }
+    }
+
+    #[test]
+    fn chain() {
+        let inner = io::Error::other("disk full");
+        let outer = Wrapped("cannot save", inner);
+        let s = String::from(CAT + error_chain(&outer) + '.');
+        assert_eq!(s, "cannot save: disk full.");
+        assert_eq!(s.len(), s.capacity());
+        let single = io::Error::other("alone");
+        assert_eq!(error_chain(&single).to_string(), "alone");
+    }
+}
@@ -0,0 +1,424 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use cat::{Cat, CatMany, CatOne, CatStart};
+use segments::Cursor;
+
+// Walks the segments from one end. Segments borrowed from the
+// concatenation are kept as they are, and other segments, such as
+// encoded characters, are read from the cursor.
+struct End<'a> {
+    cursor: Cursor,
+    borrowed: Option<&'a str>,
+    loaded: bool,
+    consumed: usize,
+    back: bool,
+}
+
+impl<'a> End<'a> {
+    fn new(back: bool) -> End<'a> {
+        End {
+            cursor: Cursor::new(back),
+            borrowed: None,
+            loaded: false,
+            consumed: 0,
+            back,
+        }
+    }
+
+    // Returns the current segment and the number of bytes consumed
+    // from this end, moving to the next segment if the current
+    // segment has been consumed, or `None` at the end.
+    fn segment<'s>(
+        &'s mut self,
+        root: &'a dyn Cat,
+    ) -> Option<(&'s str, usize)> {
+        loop {
+            if self.loaded {
+                let len = match self.borrowed {
+                    Some(segment) => segment.len(),
+                    None => self.cursor.peek(root).map_or(0, str::len),
+                };
+                if self.consumed < len {
+                    break;
+                }
+                self.cursor.advance();
+                self.loaded = false;
+            }
+            self.cursor.peek(root)?;
+            self.loaded = true;
+            self.consumed = 0;
+            self.borrowed = self.cursor.borrowed(root);
+        }
+        let segment = match self.borrowed {
+            Some(segment) => segment,
+            None => self.cursor.peek(root)?,
+        };
+        Some((segment, self.consumed))
+    }
+
+    fn char(&mut self, root: &'a dyn Cat) -> Option<char> {
+        let back = self.back;
+        let c = {
+            let (segment, consumed) = self.segment(root)?;
+            if back {
+                segment[..segment.len() - consumed].chars().next_back()
+            } else {
+                segment[consumed..].chars().next()
+            }
+        }?;
+        self.consumed += c.len_utf8();
+        Some(c)
+    }
+
+    fn byte(&mut self, root: &'a dyn Cat) -> Option<u8> {
+        let back = self.back;
+        let b = {
+            let (segment, consumed) = self.segment(root)?;
+            let bytes = segment.as_bytes();
+            if back {
+                bytes[bytes.len() - 1 - consumed]
+            } else {
+                bytes[consumed]
+            }
+        };
+        self.consumed += 1;
+        Some(b)
+    }
+}
+
+fn total_len(cat: &dyn Cat) -> usize {
+    let mut len = 0;
+    cat.for_each_segment(&mut |segment| len += segment.len());
+    len
+}
+
+/// A double-ended iterator over the characters of a concatenation.
+///
+/// This is created by the `chars` methods of the concatenation
+/// types. The iterator reads segments borrowed from the concatenation
+/// in place, so the concatenation is never materialized as a whole
+/// and nothing is copied except characters and pieces formatted on
+/// the fly.
+pub struct Chars<'a> {
+    root: &'a dyn Cat,
+    front: End<'a>,
+    back: End<'a>,
+    remaining: usize,
+}
+
+impl<'a> Chars<'a> {
+    fn new(root: &'a dyn Cat) -> Chars<'a> {
+        Chars {
+            root,
+            front: End::new(false),
+            back: End::new(true),
+            remaining: total_len(root),
+        }
+    }
+}
+
+impl<'a> Iterator for Chars<'a> {
+    type Item = char;
+
+    fn next(&mut self) -> Option<char> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let c = self.front.char(self.root)?;
+        self.remaining -= c.len_utf8();
+        Some(c)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining.div_ceil(4), Some(self.remaining))
+    }
+}
+
+impl<'a> DoubleEndedIterator for Chars<'a> {
+    fn next_back(&mut self) -> Option<char> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let c = self.back.char(self.root)?;
+        self.remaining -= c.len_utf8();
+        Some(c)
+    }
+}
+
+/// A double-ended iterator over the characters of a concatenation
+/// and their byte positions.
+///
+/// This is created by the `char_indices` methods of the
+/// concatenation types. Like `str::CharIndices`, the positions are
+/// byte offsets into the string the concatenation would be
+/// materialized into.
+pub struct CharIndices<'a> {
+    chars: Chars<'a>,
+    front: usize,
+}
+
+impl<'a> Iterator for CharIndices<'a> {
+    type Item = (usize, char);
+
+    fn next(&mut self) -> Option<(usize, char)> {
+        let c = self.chars.next()?;
+        let index = self.front;
+        self.front += c.len_utf8();
+        Some((index, c))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.chars.size_hint()
+    }
+}
+
+impl<'a> DoubleEndedIterator for CharIndices<'a> {
+    fn next_back(&mut self) -> Option<(usize, char)> {
+        let c = self.chars.next_back()?;
+        Some((self.front + self.chars.remaining, c))
+    }
+}
+
+/// A double-ended iterator over the bytes of a concatenation.
+///
+/// This is created by the `bytes` methods of the concatenation
+/// types. Like [`Chars`](struct.Chars.html), the iterator reads
+/// segments borrowed from the concatenation in place.
+pub struct Bytes<'a> {
+    root: &'a dyn Cat,
+    front: End<'a>,
+    back: End<'a>,
+    remaining: usize,
+}
+
+impl<'a> Bytes<'a> {
+    fn new(root: &'a dyn Cat) -> Bytes<'a> {
+        Bytes {
+            root,
+            front: End::new(false),
+            back: End::new(true),
+            remaining: total_len(root),
+        }
+    }
+}
+
+impl<'a> Iterator for Bytes<'a> {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<u8> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let b = self.front.byte(self.root)?;
+        self.remaining -= 1;
+        Some(b)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a> DoubleEndedIterator for Bytes<'a> {
+    fn next_back(&mut self) -> Option<u8> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let b = self.back.byte(self.root)?;
+        self.remaining -= 1;
+        Some(b)
+    }
+}
+
+impl<'a> ExactSizeIterator for Bytes<'a> {}
+
+impl<L: Cat, R: Cat> CatMany<L, R> {
+    /// Returns a double-ended iterator over the characters of the
+    /// concatenation.
+    ///
+    /// Iterating from the back walks the pieces from the tail, so
+    /// suffixes can be inspected without materializing the
+    /// concatenation.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use sconcat::CAT;
+    ///
+    /// let dir = String::from("/tmp/");
+    /// let path = CAT + dir + "report" + '.' + "txt";
+    /// let mut ext: Vec<char> =
+    ///     path.chars().rev().take_while(|&c| c != '.').collect();
+    /// ext.reverse();
+    /// assert_eq!(ext, ['t', 'x', 't']);
+    /// assert_eq!(path.chars().next(), Some('/'));
+    /// ```
+    pub fn chars(&self) -> Chars<'_> {
+        Chars::new(self)
+    }
+
+    /// Returns a double-ended iterator over the characters of the
+    /// concatenation and their byte positions.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use sconcat::CAT;
+    ///
+    /// let entry = CAT + "naïve" + '=' + String::from("yes");
+    /// let eq = entry.char_indices().find(|&(_, c)| c == '=');
+    /// assert_eq!(eq, Some((6, '=')));
+    /// ```
+    pub fn char_indices(&self) -> CharIndices<'_> {
+        CharIndices {
+            chars: self.chars(),
+            front: 0,
+        }
+    }
+
+    /// Returns a double-ended iterator over the bytes of the
+    /// concatenation.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use sconcat::CAT;
+    ///
+    /// let line = CAT + "status: " + String::from("ok") + '\n';
+    /// assert_eq!(line.bytes().next_back(), Some(b'\n'));
+    /// assert_eq!(line.bytes().len(), 11);
+    /// ```
+    pub fn bytes(&self) -> Bytes<'_> {
+        Bytes::new(self)
+    }
+}
+
+impl<T: Cat> CatOne<T> {
+    /// Returns a double-ended iterator over the characters of the
+    /// concatenation.
+    pub fn chars(&self) -> Chars<'_> {
+        Chars::new(&self.inner)
+    }
+
+    /// Returns a double-ended iterator over the characters of the
+    /// concatenation and their byte positions.
+    pub fn char_indices(&self) -> CharIndices<'_> {
+        CharIndices {
+            chars: self.chars(),
+            front: 0,
+        }
+    }
+
+    /// Returns a double-ended iterator over the bytes of the
+    /// concatenation.
+    pub fn bytes(&self) -> Bytes<'_> {
+        Bytes::new(&self.inner)
+    }
+}
+
+impl CatStart {
+    /// Returns an iterator over the characters of the empty
+    /// concatenation, which yields nothing.
+    pub fn chars(&self) -> Chars<'static> {
+        Chars::new(&None::<char>)
+    }
+
+    /// Returns an iterator over the characters of the empty
+    /// concatenation and their positions, which yields nothing.
+    pub fn char_indices(&self) -> CharIndices<'static> {
+        CharIndices {
+            chars: self.chars(),
+            front: 0,
+        }
+    }
+
+    /// Returns an iterator over the bytes of the empty
+    /// concatenation, which yields nothing.
+    pub fn bytes(&self) -> Bytes<'static> {
+        Bytes::new(&None::<char>)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use CAT;
+    use Dbg;
+
+    #[test]
+    fn both_ends() {
+        let inner = CAT + 'é' + "" + "fg";
+        let cat = CAT + "ab" + inner + Dbg(12) + String::from("☺z");
+        let s = cat.to_string();
+        assert!(cat.chars().eq(s.chars()));
+        assert!(cat.chars().rev().eq(s.chars().rev()));
+        assert!(cat.bytes().eq(s.bytes()));
+        assert!(cat.bytes().rev().eq(s.bytes().rev()));
+
+        let mut chars = cat.chars();
+        let mut expected = s.chars();
+        for i in 0..s.len() {
+            let (c, e) = if i % 3 == 0 {
+                (chars.next_back(), expected.next_back())
+            } else {
+                (chars.next(), expected.next())
+            };
+            assert_eq!(c, e);
+        }
+        assert!(cat.char_indices().eq(s.char_indices()));
+        assert!(cat.char_indices().rev().eq(s.char_indices().rev()));
+        let mut indices = cat.char_indices();
+        let mut expected = s.char_indices();
+        for i in 0..s.len() {
+            if i % 2 == 0 {
+                assert_eq!(indices.next_back(), expected.next_back());
+            } else {
+                assert_eq!(indices.next(), expected.next());
+            }
+        }
+        let mut bytes = cat.bytes();
+        assert_eq!(bytes.len(), s.len());
+        bytes.next();
+        bytes.next_back();
+        assert_eq!(bytes.len(), s.len() - 2);
+    }
+
+    #[test]
+    fn long_list() {
+        let items: Vec<String> = (0..40_000).map(|i| i.to_string()).collect();
+        let expected = format!("[{}é[1]]", items.concat());
+        let cat = CAT + '[' + &items[..] + 'é' + Dbg([1]) + "]";
+        assert!(cat.chars().eq(expected.chars()));
+        assert!(cat.chars().rev().eq(expected.chars().rev()));
+        assert!(cat.bytes().rev().eq(expected.bytes().rev()));
+    }
+
+    #[cfg(feature = "test-support")]
+    #[test]
+    fn no_allocations() {
+        use testing::count_allocations;
+        let owned = String::from("cd");
+        let cat = CAT + "ab" + 'é' + &owned[..] + (CAT + owned.clone() + 'z');
+        let (len, count) = count_allocations(|| {
+            let mut chars = cat.chars();
+            chars.next_back();
+            chars.count() + cat.bytes().rev().count()
+        });
+        assert_eq!((len, count), (7 + 9, 0));
+    }
+
+    #[test]
+    fn small() {
+        assert_eq!((CAT + "x").chars().rev().collect::<String>(), "x");
+        assert_eq!((CAT + "x").bytes().collect::<Vec<_>>(), b"x");
+        assert_eq!(CAT.chars().next_back(), None);
+        assert_eq!(CAT.bytes().next(), None);
+        assert_eq!(CAT.char_indices().next(), None);
+        assert_eq!((CAT + 'é').char_indices().next_back(), Some((0, 'é')));
+    }
+}
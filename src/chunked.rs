@@ -0,0 +1,107 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use cat::{Cat, CatMany, CatOne, CatStart};
+use std::io::{self, Write};
+
+const LAST_CHUNK: &[u8] = b"0\r\n\r\n";
+
+// Writes each non-empty segment as one chunk, skipping the rest
+// after an error, then writes the last chunk.
+fn write_chunks<C: Cat, W: Write>(cat: &C, w: &mut W) -> io::Result<()> {
+    let mut result = Ok(());
+    cat.for_each_segment(&mut |segment| {
+        if result.is_ok() && !segment.is_empty() {
+            result = write!(w, "{:X}\r\n", segment.len())
+                .and_then(|()| w.write_all(segment.as_bytes()))
+                .and_then(|()| w.write_all(b"\r\n"));
+        }
+    });
+    result?;
+    w.write_all(LAST_CHUNK)
+}
+
+impl<L: Cat, R: Cat> CatMany<L, R> {
+    /// Writes the concatenation to `w` as an HTTP/1.1 message body
+    /// with chunked transfer encoding.
+    ///
+    /// Each non-empty piece is written as one chunk, followed by the
+    /// terminating zero-length chunk with no trailer fields. The
+    /// concatenation is never materialized. Writing stops at the
+    /// first error.
+    ///
+    /// The head written before the body should contain a
+    /// `Transfer-Encoding: chunked` header field, see the
+    /// [`http`](http/index.html) module.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use sconcat::CAT;
+    ///
+    /// let mut out = Vec::new();
+    /// let user = String::from("alice");
+    /// (CAT + "Hello, " + user + '!')
+    ///     .write_chunked_to(&mut out)
+    ///     .unwrap();
+    /// let expected = "7\r\nHello, \r\n\
+    ///                 5\r\nalice\r\n\
+    ///                 1\r\n!\r\n\
+    ///                 0\r\n\r\n";
+    /// assert_eq!(out, expected.as_bytes());
+    /// ```
+    pub fn write_chunked_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        write_chunks(self, w)
+    }
+}
+
+impl<T: Cat> CatOne<T> {
+    /// Writes the concatenation to `w` as an HTTP/1.1 message body
+    /// with chunked transfer encoding.
+    pub fn write_chunked_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        write_chunks(&self.inner, w)
+    }
+}
+
+impl CatStart {
+    /// Writes an empty HTTP/1.1 message body with chunked transfer
+    /// encoding, which is only the terminating zero-length chunk.
+    pub fn write_chunked_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        w.write_all(LAST_CHUNK)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use CAT;
+    use http::{Head, header, status_line};
+
+    #[test]
+    fn chunked() {
+        let mut head = Head::response(status_line(200, "OK").unwrap());
+        head.push_header(header("Transfer-Encoding", "chunked").unwrap());
+        let mut out = head.to_string().into_bytes();
+        let body = "x".repeat(26);
+        (CAT + body.as_str() + "" + 'é').write_chunked_to(&mut out).unwrap();
+        let expected = format!(
+            "HTTP/1.1 200 OK\r\n\
+             Transfer-Encoding: chunked\r\n\
+             \r\n\
+             1A\r\n{}\r\n\
+             2\r\né\r\n\
+             0\r\n\r\n",
+            body
+        );
+        assert_eq!(out, expected.as_bytes());
+
+        let mut out = Vec::new();
+        (CAT + "").write_chunked_to(&mut out).unwrap();
+        CAT.write_chunked_to(&mut out).unwrap();
+        assert_eq!(out, b"0\r\n\r\n0\r\n\r\n");
+    }
+}
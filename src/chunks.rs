@@ -0,0 +1,247 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use cat::{Cat, CatMany, CatOne, CatStart};
+use segments::Segments;
+use std::str;
+
+// Splits the segments of a concatenation into chunks.
+struct Chunker<'a> {
+    segments: Segments<'a>,
+    offset: usize,
+    size: usize,
+}
+
+impl<'a> Chunker<'a> {
+    // Fills `buf` with the next chunk, splitting segments at
+    // character boundaries if `chars` is true.
+    fn fill(&mut self, buf: &mut Vec<u8>, chars: bool) {
+        buf.clear();
+        while buf.len() < self.size {
+            let room = self.size - buf.len();
+            let (taken, whole) = {
+                let segment = match self.segments.peek() {
+                    Some(segment) => segment,
+                    None => break,
+                };
+                let rest = &segment.as_bytes()[self.offset..];
+                if rest.len() <= room {
+                    buf.extend_from_slice(rest);
+                    (rest.len(), true)
+                } else {
+                    let mut end = room;
+                    while chars && !segment.is_char_boundary(self.offset + end)
+                    {
+                        end -= 1;
+                    }
+                    buf.extend_from_slice(&rest[..end]);
+                    (end, false)
+                }
+            };
+            if whole {
+                self.segments.advance();
+                self.offset = 0;
+            } else {
+                self.offset += taken;
+                break;
+            }
+        }
+    }
+}
+
+/// A lending iterator over string chunks of a concatenation.
+///
+/// This is created by the `chunks` methods of the concatenation
+/// types.
+pub struct Chunks<'a> {
+    chunker: Chunker<'a>,
+    buf: Vec<u8>,
+}
+
+impl<'a> Chunks<'a> {
+    fn new(segments: Segments<'a>, size: usize) -> Chunks<'a> {
+        assert!(size >= 4, "chunk size must be at least 4");
+        Chunks {
+            chunker: Chunker {
+                segments,
+                offset: 0,
+                size,
+            },
+            buf: Vec::with_capacity(size),
+        }
+    }
+
+    /// Returns the next chunk, or `None` when the whole
+    /// concatenation has been returned.
+    pub fn next_chunk(&mut self) -> Option<&str> {
+        self.chunker.fill(&mut self.buf, true);
+        if self.buf.is_empty() {
+            None
+        } else {
+            Some(str::from_utf8(&self.buf).expect("split at char boundary"))
+        }
+    }
+}
+
+/// A lending iterator over byte chunks of a concatenation.
+///
+/// This is created by the `byte_chunks` methods of the
+/// concatenation types.
+pub struct ByteChunks<'a> {
+    chunker: Chunker<'a>,
+    buf: Vec<u8>,
+}
+
+impl<'a> ByteChunks<'a> {
+    fn new(segments: Segments<'a>, size: usize) -> ByteChunks<'a> {
+        assert!(size > 0, "chunk size must be non-zero");
+        ByteChunks {
+            chunker: Chunker {
+                segments,
+                offset: 0,
+                size,
+            },
+            buf: Vec::with_capacity(size),
+        }
+    }
+
+    /// Returns the next chunk, or `None` when the whole
+    /// concatenation has been returned.
+    pub fn next_chunk(&mut self) -> Option<&[u8]> {
+        self.chunker.fill(&mut self.buf, false);
+        if self.buf.is_empty() {
+            None
+        } else {
+            Some(&self.buf)
+        }
+    }
+}
+
+impl<L: Cat, R: Cat> CatMany<L, R> {
+    /// Returns a lending iterator over string chunks of at most
+    /// `size` bytes.
+    ///
+    /// Chunks are split at character boundaries, and all chunks
+    /// except the last are as long as possible. The chunks are
+    /// assembled in a buffer of `size` bytes, so the concatenation
+    /// is never materialized as a whole.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is less than 4, which is the maximum length
+    /// of a character.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use sconcat::CAT;
+    ///
+    /// let cat = CAT + "abc" + 'é' + String::from("defgh");
+    /// let mut chunks = cat.chunks(4);
+    /// let mut lines = Vec::new();
+    /// while let Some(chunk) = chunks.next_chunk() {
+    ///     lines.push(String::from(chunk));
+    /// }
+    /// assert_eq!(lines, ["abc", "éde", "fgh"]);
+    /// ```
+    pub fn chunks(&self, size: usize) -> Chunks<'_> {
+        Chunks::new(self.segments(), size)
+    }
+
+    /// Returns a lending iterator over byte chunks of `size` bytes,
+    /// except for the last chunk which may be shorter.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is zero.
+    pub fn byte_chunks(&self, size: usize) -> ByteChunks<'_> {
+        ByteChunks::new(self.segments(), size)
+    }
+}
+
+impl<T: Cat> CatOne<T> {
+    /// Returns a lending iterator over string chunks of at most
+    /// `size` bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is less than 4.
+    pub fn chunks(&self, size: usize) -> Chunks<'_> {
+        Chunks::new(self.segments(), size)
+    }
+
+    /// Returns a lending iterator over byte chunks of `size` bytes,
+    /// except for the last chunk which may be shorter.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is zero.
+    pub fn byte_chunks(&self, size: usize) -> ByteChunks<'_> {
+        ByteChunks::new(self.segments(), size)
+    }
+}
+
+impl CatStart {
+    /// Returns a lending iterator over string chunks of the empty
+    /// concatenation, which yields nothing.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is less than 4.
+    pub fn chunks(&self, size: usize) -> Chunks<'static> {
+        Chunks::new(self.segments(), size)
+    }
+
+    /// Returns a lending iterator over byte chunks of the empty
+    /// concatenation, which yields nothing.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is zero.
+    pub fn byte_chunks(&self, size: usize) -> ByteChunks<'static> {
+        ByteChunks::new(self.segments(), size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use CAT;
+
+    #[test]
+    fn str_chunks() {
+        let cat = CAT + "☺☺" + "" + 'a' + String::from("bcdefg");
+        let mut chunks = cat.chunks(5);
+        let mut all = Vec::new();
+        while let Some(chunk) = chunks.next_chunk() {
+            all.push(String::from(chunk));
+        }
+        assert_eq!(all, ["☺", "☺ab", "cdefg"]);
+        assert!(CAT.chunks(4).next_chunk().is_none());
+    }
+
+    #[test]
+    fn byte_chunks() {
+        let cat = CAT + "☺" + "ab";
+        let mut chunks = cat.byte_chunks(2);
+        let mut all = Vec::new();
+        while let Some(chunk) = chunks.next_chunk() {
+            all.push(chunk.to_vec());
+        }
+        assert_eq!(all, [&b"\xe2\x98"[..], b"\xbaa", b"b"]);
+        let one = CAT + "xyz";
+        let mut chunks = one.byte_chunks(8);
+        assert_eq!(chunks.next_chunk(), Some(&b"xyz"[..]));
+        assert_eq!(chunks.next_chunk(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "at least 4")]
+    fn small_str_chunks() {
+        (CAT + "a").chunks(3);
+    }
+}
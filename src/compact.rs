@@ -0,0 +1,65 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Integration with the compact_str crate. The size hint is used as
+// the initial capacity, so short concatenations are stored inline
+// and never touch the heap.
+
+use cat::{Cat, CatMany, CatOne, CatStart};
+use compact_str::CompactString;
+
+fn compact_string<C: Cat>(cat: &C) -> CompactString {
+    let mut s = CompactString::with_capacity(cat.size_hint());
+    cat.for_each_segment(&mut |segment| s.push_str(segment));
+    s
+}
+
+impl<L: Cat, R: Cat> From<CatMany<L, R>> for CompactString {
+    fn from(src: CatMany<L, R>) -> CompactString {
+        compact_string(&src)
+    }
+}
+
+impl<T: Cat> From<CatOne<T>> for CompactString {
+    fn from(src: CatOne<T>) -> CompactString {
+        compact_string(&src.inner)
+    }
+}
+
+impl From<CatStart> for CompactString {
+    fn from(_src: CatStart) -> CompactString {
+        CompactString::new("")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use CAT;
+    use Dbg;
+    use compact_str::CompactString;
+
+    #[test]
+    fn inline() {
+        let s = CompactString::from(CAT + "id=" + Dbg(42) + ';');
+        assert_eq!(s, "id=42;");
+        assert!(!s.is_heap_allocated());
+        let s = CompactString::from(CAT + "x");
+        assert_eq!(s, "x");
+        assert!(!s.is_heap_allocated());
+        assert_eq!(CompactString::from(CAT), "");
+    }
+
+    #[test]
+    fn heap() {
+        let long = "a".repeat(30);
+        let s = CompactString::from(CAT + long.as_str() + 'é');
+        assert_eq!(s.len(), 32);
+        assert!(s.ends_with("aé"));
+        assert!(s.is_heap_allocated());
+    }
+}
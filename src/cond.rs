@@ -0,0 +1,371 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use cat::Cat;
+use std::fmt::{self, Display};
+use std::marker::PhantomData;
+
+// An excluded piece, which is empty and zero-sized.
+#[derive(Clone, Copy, Debug)]
+struct Excluded<T> {
+    phantom: PhantomData<T>,
+}
+
+impl<T> Excluded<T> {
+    fn new(_piece: T) -> Excluded<T> {
+        Excluded {
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<T> Cat for Excluded<T> {
+    fn size_hint(&self) -> usize {
+        0
+    }
+
+    fn append_to(&self, _s: &mut String) {}
+
+    fn for_each_segment(&self, _f: &mut dyn FnMut(&str)) {}
+}
+
+impl<T> Display for Excluded<T> {
+    fn fmt(&self, _f: &mut fmt::Formatter) -> fmt::Result {
+        Ok(())
+    }
+}
+
+#[cfg(debug_assertions)]
+type DebugPiece<T> = T;
+#[cfg(not(debug_assertions))]
+type DebugPiece<T> = Excluded<T>;
+#[cfg(debug_assertions)]
+type ReleasePiece<T> = Excluded<T>;
+#[cfg(not(debug_assertions))]
+type ReleasePiece<T> = T;
+
+#[cfg(debug_assertions)]
+fn debug_piece<T>(piece: T) -> DebugPiece<T> {
+    piece
+}
+
+#[cfg(not(debug_assertions))]
+fn debug_piece<T>(piece: T) -> DebugPiece<T> {
+    Excluded::new(piece)
+}
+
+#[cfg(debug_assertions)]
+fn release_piece<T>(piece: T) -> ReleasePiece<T> {
+    Excluded::new(piece)
+}
+
+#[cfg(not(debug_assertions))]
+fn release_piece<T>(piece: T) -> ReleasePiece<T> {
+    piece
+}
+
+/// A piece that is only present when debug assertions are enabled.
+///
+/// This is created by the [`debug_only`](fn.debug_only.html)
+/// function.
+#[derive(Clone, Copy, Debug)]
+pub struct DebugOnly<T: Cat> {
+    piece: DebugPiece<T>,
+}
+
+/// A piece that is only present when debug assertions are disabled.
+///
+/// This is created by the [`release_only`](fn.release_only.html)
+/// function.
+#[derive(Clone, Copy, Debug)]
+pub struct ReleaseOnly<T: Cat> {
+    piece: ReleasePiece<T>,
+}
+
+/// Creates a piece that is only present when debug assertions are
+/// enabled.
+///
+/// Otherwise the piece is dropped immediately, and the returned
+/// piece is empty and zero-sized. Note that `piece` itself is still
+/// evaluated. Debug assertions are checked for this crate, which is
+/// normally built with the same profile as the crate using it.
+///
+/// # Examples
+///
+/// ```rust
+/// use sconcat::{CAT, debug_only};
+///
+/// let cat = CAT + "request failed" + debug_only(" (see trace log)");
+/// // "request failed (see trace log)" in debug builds
+/// // "request failed" in release builds
+/// let s = String::from(cat);
+/// assert!(s.starts_with("request failed"));
+/// ```
+pub fn debug_only<T: Cat>(piece: T) -> DebugOnly<T> {
+    DebugOnly {
+        piece: debug_piece(piece),
+    }
+}
+
+/// Creates a piece that is only present when debug assertions are
+/// disabled.
+///
+/// Otherwise the piece is dropped immediately, and the returned
+/// piece is empty and zero-sized. Note that `piece` itself is still
+/// evaluated.
+pub fn release_only<T: Cat>(piece: T) -> ReleaseOnly<T> {
+    ReleaseOnly {
+        piece: release_piece(piece),
+    }
+}
+
+impl<T: Cat> Cat for DebugOnly<T> {
+    fn size_hint(&self) -> usize {
+        self.piece.size_hint()
+    }
+
+    fn append_to(&self, s: &mut String) {
+        self.piece.append_to(s);
+    }
+
+    fn into_string(self, capacity: usize) -> String {
+        self.piece.into_string(capacity)
+    }
+
+    fn for_each_segment(&self, f: &mut dyn FnMut(&str)) {
+        self.piece.for_each_segment(f);
+    }
+
+    fn pieces(&self) -> usize {
+        self.piece.pieces()
+    }
+}
+
+impl<T: Cat + Display> Display for DebugOnly<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        Display::fmt(&self.piece, f)
+    }
+}
+
+impl<T: Cat> Cat for ReleaseOnly<T> {
+    fn size_hint(&self) -> usize {
+        self.piece.size_hint()
+    }
+
+    fn append_to(&self, s: &mut String) {
+        self.piece.append_to(s);
+    }
+
+    fn into_string(self, capacity: usize) -> String {
+        self.piece.into_string(capacity)
+    }
+
+    fn for_each_segment(&self, f: &mut dyn FnMut(&str)) {
+        self.piece.for_each_segment(f);
+    }
+
+    fn pieces(&self) -> usize {
+        self.piece.pieces()
+    }
+}
+
+impl<T: Cat + Display> Display for ReleaseOnly<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        Display::fmt(&self.piece, f)
+    }
+}
+
+/// A piece that is only present if a condition holds.
+///
+/// This is created by the [`when`](fn.when.html) function.
+#[derive(Clone, Copy, Debug)]
+pub struct When<T: Cat> {
+    piece: Option<T>,
+}
+
+/// Creates a piece that is only present if `cond` is `true`.
+///
+/// Otherwise the piece is dropped immediately and the returned piece
+/// is empty. This keeps optional prefixes and suffixes inline in a
+/// concatenation.
+///
+/// # Examples
+///
+/// ```rust
+/// use sconcat::{CAT, when};
+///
+/// fn files(n: usize, count: &str) -> String {
+///     String::from(CAT + count + " file" + when(n != 1, 's'))
+/// }
+///
+/// assert_eq!(files(1, "1"), "1 file");
+/// assert_eq!(files(3, "3"), "3 files");
+/// ```
+pub fn when<T: Cat>(cond: bool, piece: T) -> When<T> {
+    When {
+        piece: if cond { Some(piece) } else { None },
+    }
+}
+
+impl<T: Cat> Cat for When<T> {
+    fn size_hint(&self) -> usize {
+        self.piece.size_hint()
+    }
+
+    fn append_to(&self, s: &mut String) {
+        self.piece.append_to(s);
+    }
+
+    fn into_string(self, capacity: usize) -> String {
+        self.piece.into_string(capacity)
+    }
+
+    fn for_each_segment(&self, f: &mut dyn FnMut(&str)) {
+        self.piece.for_each_segment(f);
+    }
+
+    fn pieces(&self) -> usize {
+        self.piece.pieces()
+    }
+}
+
+impl<T: Cat + Display> Display for When<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.piece {
+            Some(ref piece) => Display::fmt(piece, f),
+            None => Ok(()),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Choice<A, B> {
+    First(A),
+    Second(B),
+}
+
+/// One of two pieces, chosen by a condition.
+///
+/// This is created by the [`either`](fn.either.html) function.
+#[derive(Clone, Copy, Debug)]
+pub struct Either<A: Cat, B: Cat> {
+    choice: Choice<A, B>,
+}
+
+/// Creates a piece that is `a` if `cond` is `true` and `b` otherwise.
+///
+/// The other piece is dropped immediately. The two pieces can have
+/// different types.
+///
+/// # Examples
+///
+/// ```rust
+/// use sconcat::{CAT, either};
+///
+/// let verbose = true;
+/// let err = String::from("disk full");
+/// let cat = CAT + "error" + either(verbose, CAT + ": " + err, '!');
+/// assert_eq!(String::from(cat), "error: disk full");
+/// ```
+pub fn either<A: Cat, B: Cat>(cond: bool, a: A, b: B) -> Either<A, B> {
+    Either {
+        choice: if cond {
+            Choice::First(a)
+        } else {
+            Choice::Second(b)
+        },
+    }
+}
+
+impl<A: Cat, B: Cat> Cat for Either<A, B> {
+    fn size_hint(&self) -> usize {
+        match self.choice {
+            Choice::First(ref a) => a.size_hint(),
+            Choice::Second(ref b) => b.size_hint(),
+        }
+    }
+
+    fn append_to(&self, s: &mut String) {
+        match self.choice {
+            Choice::First(ref a) => a.append_to(s),
+            Choice::Second(ref b) => b.append_to(s),
+        }
+    }
+
+    fn into_string(self, capacity: usize) -> String {
+        match self.choice {
+            Choice::First(a) => a.into_string(capacity),
+            Choice::Second(b) => b.into_string(capacity),
+        }
+    }
+
+    fn for_each_segment(&self, f: &mut dyn FnMut(&str)) {
+        match self.choice {
+            Choice::First(ref a) => a.for_each_segment(f),
+            Choice::Second(ref b) => b.for_each_segment(f),
+        }
+    }
+
+    fn checked_size_hint(&self) -> Option<usize> {
+        match self.choice {
+            Choice::First(ref a) => a.checked_size_hint(),
+            Choice::Second(ref b) => b.checked_size_hint(),
+        }
+    }
+
+    fn pieces(&self) -> usize {
+        match self.choice {
+            Choice::First(ref a) => a.pieces(),
+            Choice::Second(ref b) => b.pieces(),
+        }
+    }
+}
+
+impl<A: Cat + Display, B: Cat + Display> Display for Either<A, B> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.choice {
+            Choice::First(ref a) => Display::fmt(a, f),
+            Choice::Second(ref b) => Display::fmt(b, f),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use CAT;
+    use std::mem;
+    use super::{debug_only, either, release_only, when};
+
+    #[test]
+    fn conditional() {
+        let state = String::from("state");
+        let cat = CAT + "a" + debug_only(state) + release_only('r') + "b";
+        let s = String::from(cat);
+        if cfg!(debug_assertions) {
+            assert_eq!(s, "astateb");
+            assert_eq!(mem::size_of_val(&release_only("x")), 0);
+        } else {
+            assert_eq!(s, "arb");
+            assert_eq!(mem::size_of_val(&debug_only(String::new())), 0);
+        }
+        assert_eq!(s.len(), s.capacity());
+    }
+
+    #[test]
+    fn chosen() {
+        let n = 2;
+        let cat = CAT + "item" + when(n != 1, 's') + when(false, "!");
+        assert_eq!(cat.size_hint(), 5);
+        assert_eq!(cat.to_string(), "items");
+        let cat = CAT + either(n > 1, "many", String::from("one")) + '.';
+        assert_eq!(cat.to_string(), "many.");
+        let s = String::from(CAT + either(false, "x", CAT + "y" + 'z'));
+        assert_eq!(s, "yz");
+        assert_eq!(s.len(), s.capacity());
+    }
+}
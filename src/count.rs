@@ -0,0 +1,67 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::fmt::{self, Write};
+
+/// A `fmt::Write` that counts the bytes written and discards them.
+///
+/// This can be used to compute an exact size hint for a formatted
+/// item without allocating.
+///
+/// # Examples
+///
+/// ```rust
+/// use sconcat::CountingWriter;
+/// use std::fmt::Write;
+///
+/// let mut w = CountingWriter::new();
+/// write!(w, "{}-{:04}", "id", 7).unwrap();
+/// assert_eq!(w.count(), 7);
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CountingWriter {
+    count: usize,
+}
+
+impl CountingWriter {
+    /// Creates a writer with a count of zero.
+    pub fn new() -> CountingWriter {
+        CountingWriter { count: 0 }
+    }
+
+    /// Returns the number of bytes written so far.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+}
+
+impl Write for CountingWriter {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.count = self.count.checked_add(s.len()).ok_or(fmt::Error)?;
+        Ok(())
+    }
+
+    fn write_char(&mut self, c: char) -> fmt::Result {
+        self.count = self.count.checked_add(c.len_utf8()).ok_or(fmt::Error)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt::Write;
+    use super::CountingWriter;
+
+    #[test]
+    fn counts() {
+        let mut w = CountingWriter::default();
+        w.write_char('☺').unwrap();
+        write!(w, "{:>5}{:?}", 1, "a").unwrap();
+        assert_eq!(w.count(), 3 + 5 + 3);
+    }
+}
@@ -0,0 +1,196 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use cat::{self, Cat, CatMany, CatOne, CatStart};
+use std::borrow::Cow;
+use std::ops::AddAssign;
+
+fn to_cow<C: Cat>(src: &C) -> Cow<'_, str> {
+    match src.single_str() {
+        Some(s) => Cow::Borrowed(s),
+        None => {
+            let mut s = String::new();
+            cat::append_cat(&mut s, src);
+            Cow::Owned(s)
+        }
+    }
+}
+
+// A borrowed target is converted to an owned string only if the
+// concatenation is not empty, and the owned string is created with
+// room for both the borrowed text and the concatenation, so that at
+// most one allocation happens.
+fn append_to_cow<C: Cat>(cow: &mut Cow<'_, str>, src: &C) {
+    match *cow {
+        Cow::Owned(ref mut s) => cat::append_cat(s, src),
+        Cow::Borrowed(b) => {
+            if cat::segments_empty(src) {
+                return;
+            }
+            let capacity = src
+                .checked_size_hint()
+                .and_then(|len| len.checked_add(b.len()))
+                .expect("capacity overflow");
+            let mut s = String::with_capacity(capacity);
+            s.push_str(b);
+            cat::append_cat(&mut s, src);
+            *cow = Cow::Owned(s);
+        }
+    }
+}
+
+impl<L: Cat, R: Cat> CatMany<L, R> {
+    /// Returns the concatenation as a `Cow`, borrowing it when only
+    /// one piece contributes bytes.
+    ///
+    /// If every piece except one string slice or string is empty,
+    /// that piece is borrowed and nothing is allocated. Otherwise the
+    /// concatenation is materialized into an owned `String`. Pieces
+    /// other than strings, such as characters or formatted values,
+    /// always lead to an owned result.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use sconcat::CAT;
+    /// use std::borrow::Cow;
+    ///
+    /// fn label(prefix: &str, body: &str) -> String {
+    ///     let cat = CAT + prefix + body;
+    ///     match cat.to_cow() {
+    ///         Cow::Borrowed(s) => format!("borrowed {}", s),
+    ///         Cow::Owned(s) => format!("owned {}", s),
+    ///     }
+    /// }
+    ///
+    /// assert_eq!(label("", "body"), "borrowed body");
+    /// assert_eq!(label("pre-", "body"), "owned pre-body");
+    /// ```
+    pub fn to_cow(&self) -> Cow<'_, str> {
+        to_cow(self)
+    }
+}
+
+impl<T: Cat> CatOne<T> {
+    /// Returns the concatenation as a `Cow`, borrowing it when it is
+    /// a string slice or string.
+    pub fn to_cow(&self) -> Cow<'_, str> {
+        to_cow(&self.inner)
+    }
+}
+
+impl CatStart {
+    /// Returns an empty borrowed `Cow`.
+    pub fn to_cow(&self) -> Cow<'static, str> {
+        Cow::Borrowed("")
+    }
+}
+
+impl<'a> From<CatOne<&'a str>> for Cow<'a, str> {
+    fn from(src: CatOne<&'a str>) -> Cow<'a, str> {
+        Cow::Borrowed(src.inner)
+    }
+}
+
+impl<'a> From<CatStart> for Cow<'a, str> {
+    fn from(_src: CatStart) -> Cow<'a, str> {
+        Cow::Borrowed("")
+    }
+}
+
+impl<'a, L: Cat, R: Cat> AddAssign<CatMany<L, R>> for Cow<'a, str> {
+    fn add_assign(&mut self, rhs: CatMany<L, R>) {
+        append_to_cow(self, &rhs);
+    }
+}
+
+impl<'a, L: Cat, R: Cat> AddAssign<&CatMany<L, R>> for Cow<'a, str> {
+    fn add_assign(&mut self, rhs: &CatMany<L, R>) {
+        append_to_cow(self, rhs);
+    }
+}
+
+impl<'a, T: Cat> AddAssign<CatOne<T>> for Cow<'a, str> {
+    fn add_assign(&mut self, rhs: CatOne<T>) {
+        append_to_cow(self, &rhs.inner);
+    }
+}
+
+impl<'a, T: Cat> AddAssign<&CatOne<T>> for Cow<'a, str> {
+    fn add_assign(&mut self, rhs: &CatOne<T>) {
+        append_to_cow(self, &rhs.inner);
+    }
+}
+
+impl<'a> AddAssign<CatStart> for Cow<'a, str> {
+    fn add_assign(&mut self, _rhs: CatStart) {}
+}
+
+impl<'a> AddAssign<&CatStart> for Cow<'a, str> {
+    fn add_assign(&mut self, _rhs: &CatStart) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use CAT;
+    use Dbg;
+    use std::borrow::Cow;
+
+    #[test]
+    fn borrowed() {
+        let body = String::from("body");
+        let prefix: Option<&str> = None;
+        let cat = CAT + "" + prefix + &*body + String::new();
+        let cow = cat.to_cow();
+        assert_eq!(cow, "body");
+        assert!(matches!(cow, Cow::Borrowed(_)));
+        assert!(matches!((CAT + body.clone()).to_cow(), Cow::Borrowed(_)));
+        assert!(matches!((CAT + "" + "").to_cow(), Cow::Borrowed(_)));
+        assert!(matches!(CAT.to_cow(), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn owned() {
+        let cat = CAT + "a" + "b";
+        let cow = cat.to_cow();
+        assert_eq!(cow, "ab");
+        assert!(!matches!(cow, Cow::Borrowed(_)));
+        let cat = CAT + "" + 'c';
+        let cow = cat.to_cow();
+        assert_eq!(cow, "c");
+        assert!(!matches!(cow, Cow::Borrowed(_)));
+        assert!(!matches!((CAT + Dbg(1)).to_cow(), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn from() {
+        let s = String::from("x");
+        let cow = Cow::from(CAT + s.as_str());
+        assert!(matches!(cow, Cow::Borrowed(_)));
+        assert_eq!(cow, "x");
+        assert_eq!(Cow::from(CAT), "");
+    }
+
+    #[test]
+    fn add_assign() {
+        let mut cow = Cow::Borrowed("base");
+        cow += CAT;
+        cow += CAT + "" + String::new();
+        cow += &(CAT + "");
+        assert!(matches!(cow, Cow::Borrowed("base")));
+        cow += CAT + '/' + "path";
+        assert_eq!(cow, "base/path");
+        assert_eq!(cow.len(), cow.to_mut().capacity());
+        cow += &(CAT + "!");
+        assert_eq!(cow, "base/path!");
+        let mut cow = Cow::Borrowed("");
+        cow += CAT + Dbg(7);
+        assert!(matches!(cow, Cow::Owned(_)));
+        assert_eq!(cow, "7");
+    }
+}
@@ -0,0 +1,88 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use cat::{Cat, CatMany, CatOne, CatStart};
+use std::convert::TryFrom;
+use std::ffi::{CString, NulError};
+
+// Room for the terminator is reserved up front, so `CString::new`
+// neither grows nor shrinks the buffer when the size hint is exact.
+fn cat_into_c_string<C: Cat>(cat: &C) -> Result<CString, NulError> {
+    let capacity = cat.size_hint().checked_add(1).expect("capacity overflow");
+    let mut v = Vec::with_capacity(capacity);
+    cat.for_each_segment(&mut |segment| {
+        v.extend_from_slice(segment.as_bytes())
+    });
+    CString::new(v)
+}
+
+/// Converts a concatenation to a NUL-terminated `CString`.
+///
+/// The buffer is allocated once with room for the terminator. If the
+/// concatenation contains a NUL byte, the error gives its position
+/// and the bytes can be recovered with `NulError::into_vec`.
+///
+/// # Examples
+///
+/// ```rust
+/// use sconcat::CAT;
+/// use std::convert::TryFrom;
+/// use std::ffi::CString;
+///
+/// let dir = "/tmp";
+/// let path = CString::try_from(CAT + dir + "/app.sock").unwrap();
+/// assert_eq!(path.as_bytes_with_nul(), b"/tmp/app.sock\0");
+/// let err = CString::try_from(CAT + dir + "\0x").unwrap_err();
+/// assert_eq!(err.nul_position(), 4);
+/// ```
+impl<L: Cat, R: Cat> TryFrom<CatMany<L, R>> for CString {
+    type Error = NulError;
+    fn try_from(src: CatMany<L, R>) -> Result<CString, NulError> {
+        cat_into_c_string(&src)
+    }
+}
+
+impl<T: Cat> TryFrom<CatOne<T>> for CString {
+    type Error = NulError;
+    fn try_from(src: CatOne<T>) -> Result<CString, NulError> {
+        cat_into_c_string(&src.inner)
+    }
+}
+
+impl TryFrom<CatStart> for CString {
+    type Error = NulError;
+    fn try_from(_src: CatStart) -> Result<CString, NulError> {
+        CString::new("")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use CAT;
+    use Dbg;
+    use std::convert::TryFrom;
+    use std::ffi::CString;
+
+    #[test]
+    fn c_string() {
+        let cat = CAT + "name=" + 'é' + Dbg(1) + String::from("x");
+        let c = CString::try_from(cat).unwrap();
+        assert_eq!(c.as_bytes_with_nul(), "name=é1x\0".as_bytes());
+        let c = CString::try_from(CAT + "one").unwrap();
+        assert_eq!(c.as_bytes(), b"one");
+        assert_eq!(CString::try_from(CAT).unwrap().as_bytes(), b"");
+    }
+
+    #[test]
+    fn interior_nul() {
+        let err = CString::try_from(CAT + "ab" + '\0' + "c").unwrap_err();
+        assert_eq!(err.nul_position(), 2);
+        assert_eq!(err.into_vec(), b"ab\0c");
+        assert!(CString::try_from(CAT + "\0").is_err());
+    }
+}
@@ -0,0 +1,94 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use cat::{Cat, CatMany, CatOne, CatStart};
+use subtle::{Choice, ConstantTimeEq};
+
+// Compares the segments against `expected` without branching on the
+// contents. Only the lengths may affect the timing.
+fn ct_eq_segments<C: Cat>(cat: &C, expected: &str) -> Choice {
+    let expected = expected.as_bytes();
+    let mut pos = 0;
+    let mut eq = Choice::from(1);
+    cat.for_each_segment(&mut |segment| {
+        for &b in segment.as_bytes() {
+            if pos < expected.len() {
+                eq &= b.ct_eq(&expected[pos]);
+            }
+            pos += 1;
+        }
+    });
+    eq & (pos as u64).ct_eq(&(expected.len() as u64))
+}
+
+impl<L: Cat, R: Cat> CatMany<L, R> {
+    /// Compares the concatenation with `expected` in constant time.
+    ///
+    /// The concatenation is not materialized. The time taken depends
+    /// on the lengths, but not on the contents of the strings.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use sconcat::CAT;
+    ///
+    /// let token = "s3cr3t";
+    /// let cat = CAT + "Bearer " + token;
+    /// assert!(bool::from(cat.ct_eq("Bearer s3cr3t")));
+    /// assert!(!bool::from(cat.ct_eq("Bearer guess!")));
+    /// ```
+    pub fn ct_eq(&self, expected: &str) -> Choice {
+        ct_eq_segments(self, expected)
+    }
+}
+
+impl<T: Cat> CatOne<T> {
+    /// Compares the concatenation with `expected` in constant time.
+    ///
+    /// The concatenation is not materialized. The time taken depends
+    /// on the lengths, but not on the contents of the strings.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use sconcat::CAT;
+    ///
+    /// let cat = CAT + "s3cr3t";
+    /// assert!(bool::from(cat.ct_eq("s3cr3t")));
+    /// assert!(!bool::from(cat.ct_eq("s3cr3")));
+    /// ```
+    pub fn ct_eq(&self, expected: &str) -> Choice {
+        ct_eq_segments(&self.inner, expected)
+    }
+}
+
+impl CatStart {
+    /// Compares the empty concatenation with `expected` in constant
+    /// time.
+    pub fn ct_eq(&self, expected: &str) -> Choice {
+        (expected.len() as u64).ct_eq(&0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use CAT;
+
+    #[test]
+    fn ct_eq() {
+        let cat = CAT + "abc" + 'd' + String::from("ef");
+        assert_eq!(cat.ct_eq("abcdef").unwrap_u8(), 1);
+        assert_eq!(cat.ct_eq("abcdeg").unwrap_u8(), 0);
+        assert_eq!(cat.ct_eq("abcde").unwrap_u8(), 0);
+        assert_eq!(cat.ct_eq("abcdefg").unwrap_u8(), 0);
+        assert_eq!(cat.ct_eq("").unwrap_u8(), 0);
+        assert_eq!((CAT + "").ct_eq("").unwrap_u8(), 1);
+        assert_eq!(CAT.ct_eq("").unwrap_u8(), 1);
+        assert_eq!(CAT.ct_eq("a").unwrap_u8(), 0);
+    }
+}
@@ -0,0 +1,443 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use cat::{Cat, SegmentWriter};
+#[cfg(feature = "chrono")]
+use chrono::{DateTime, Datelike, Offset, TimeZone, Timelike};
+use count::CountingWriter;
+use std::fmt::{self, Display, Write};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+#[cfg(feature = "time")]
+use time::OffsetDateTime;
+
+// 0000-01-01T00:00:00Z and 9999-12-31T23:59:59Z
+const MIN_SECS: i64 = -62_167_219_200;
+const MAX_SECS: i64 = 253_402_300_799;
+const HTTP_DATE_LEN: usize = 29;
+const RFC3339_LEN: usize = 20;
+
+const WEEKDAYS: [&str; 7] = ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"];
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct",
+    "Nov", "Dec",
+];
+
+/// A piece that formats a `SystemTime` as an HTTP date.
+///
+/// This is created by the [`http_date`](fn.http_date.html) function.
+#[derive(Clone, Copy, Debug)]
+pub struct HttpDate {
+    secs: i64,
+}
+
+/// A piece that formats a `SystemTime` as an RFC 3339 timestamp.
+///
+/// This is created by the [`rfc3339`](fn.rfc3339.html) function.
+#[derive(Clone, Copy, Debug)]
+pub struct Rfc3339 {
+    secs: i64,
+}
+
+/// Creates a piece that formats `time` in the IMF-fixdate format
+/// used in HTTP headers, for example
+/// `Sun, 06 Nov 1994 08:49:37 GMT`.
+///
+/// Fractions of a second are truncated, and times outside the years
+/// 0 to 9999 are clamped to that range. The size hint is exact.
+///
+/// # Examples
+///
+/// ```rust
+/// use sconcat::{CAT, http_date};
+/// use std::time::{Duration, UNIX_EPOCH};
+///
+/// let time = UNIX_EPOCH + Duration::from_secs(784_111_777);
+/// let cat = CAT + "Date: " + http_date(time) + "\r\n";
+/// assert_eq!(String::from(cat), "Date: Sun, 06 Nov 1994 08:49:37 GMT\r\n");
+/// ```
+pub fn http_date(time: SystemTime) -> HttpDate {
+    HttpDate {
+        secs: unix_secs(time),
+    }
+}
+
+/// Creates a piece that formats `time` as an RFC 3339 timestamp in
+/// UTC, for example `1994-11-06T08:49:37Z`.
+///
+/// Fractions of a second are truncated, and times outside the years
+/// 0 to 9999 are clamped to that range. The size hint is exact.
+///
+/// # Examples
+///
+/// ```rust
+/// use sconcat::{CAT, rfc3339};
+/// use std::time::{Duration, UNIX_EPOCH};
+///
+/// let time = UNIX_EPOCH + Duration::from_secs(784_111_777);
+/// let cat = CAT + '[' + rfc3339(time) + "] started";
+/// assert_eq!(String::from(cat), "[1994-11-06T08:49:37Z] started");
+/// ```
+pub fn rfc3339(time: SystemTime) -> Rfc3339 {
+    Rfc3339 {
+        secs: unix_secs(time),
+    }
+}
+
+// Seconds since the Unix epoch, rounded down and clamped.
+fn unix_secs(time: SystemTime) -> i64 {
+    let secs = match time.duration_since(UNIX_EPOCH) {
+        Ok(d) => d.as_secs().min(MAX_SECS as u64) as i64,
+        Err(e) => {
+            let d = e.duration();
+            let secs = d.as_secs().min(-MIN_SECS as u64) as i64;
+            if d.subsec_nanos() > 0 {
+                -secs - 1
+            } else {
+                -secs
+            }
+        }
+    };
+    secs.clamp(MIN_SECS, MAX_SECS)
+}
+
+struct Civil {
+    year: i64,
+    month: usize,
+    day: i64,
+    weekday: usize,
+    hour: i64,
+    minute: i64,
+    second: i64,
+}
+
+// Converts seconds since the Unix epoch to a proleptic Gregorian
+// date and time.
+fn civil(secs: i64) -> Civil {
+    let days = secs.div_euclid(86_400);
+    let secs_of_day = secs.rem_euclid(86_400);
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    Civil {
+        year: yoe + era * 400 + if month <= 2 { 1 } else { 0 },
+        month: month as usize,
+        day: doy - (153 * mp + 2) / 5 + 1,
+        // 1970-01-01 was a Thursday.
+        weekday: (days + 4).rem_euclid(7) as usize,
+        hour: secs_of_day / 3600,
+        minute: secs_of_day / 60 % 60,
+        second: secs_of_day % 60,
+    }
+}
+
+impl HttpDate {
+    fn write<W: Write>(&self, w: &mut W) -> fmt::Result {
+        let c = civil(self.secs);
+        write!(
+            w,
+            "{}, {:02} {} {:04} {:02}:{:02}:{:02} GMT",
+            WEEKDAYS[c.weekday],
+            c.day,
+            MONTHS[c.month - 1],
+            c.year,
+            c.hour,
+            c.minute,
+            c.second
+        )
+    }
+}
+
+impl Cat for HttpDate {
+    fn size_hint(&self) -> usize {
+        HTTP_DATE_LEN
+    }
+
+    fn append_to(&self, s: &mut String) {
+        self.write(s).unwrap();
+    }
+
+    fn for_each_segment(&self, f: &mut dyn FnMut(&str)) {
+        self.write(&mut SegmentWriter::new(f)).unwrap();
+    }
+}
+
+impl Display for HttpDate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.write(f)
+    }
+}
+
+impl Rfc3339 {
+    fn write<W: Write>(&self, w: &mut W) -> fmt::Result {
+        let c = civil(self.secs);
+        write!(
+            w,
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+            c.year,
+            c.month,
+            c.day,
+            c.hour,
+            c.minute,
+            c.second
+        )
+    }
+}
+
+impl Cat for Rfc3339 {
+    fn size_hint(&self) -> usize {
+        RFC3339_LEN
+    }
+
+    fn append_to(&self, s: &mut String) {
+        self.write(s).unwrap();
+    }
+
+    fn for_each_segment(&self, f: &mut dyn FnMut(&str)) {
+        self.write(&mut SegmentWriter::new(f)).unwrap();
+    }
+}
+
+impl Display for Rfc3339 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.write(f)
+    }
+}
+
+// Durations are written in the same form as their `Debug` output,
+// such as `1.5s`, `250ms` or `100ns`, which picks the largest unit
+// that keeps the integer part non-zero.
+impl Cat for Duration {
+    fn size_hint(&self) -> usize {
+        let mut w = CountingWriter::new();
+        write!(w, "{:?}", self).expect("capacity overflow");
+        w.count()
+    }
+
+    fn append_to(&self, s: &mut String) {
+        write!(s, "{:?}", self).unwrap();
+    }
+
+    fn for_each_segment(&self, f: &mut dyn FnMut(&str)) {
+        write!(SegmentWriter::new(f), "{:?}", self).unwrap();
+    }
+}
+
+// A timestamp with an offset from UTC, broken down into the fields
+// of an RFC 3339 timestamp.
+#[cfg(any(feature = "chrono", feature = "time"))]
+struct Timestamp {
+    year: i32,
+    month: u32,
+    day: u32,
+    hour: u32,
+    minute: u32,
+    second: u32,
+    nanos: u32,
+    offset_secs: i32,
+}
+
+#[cfg(any(feature = "chrono", feature = "time"))]
+impl Timestamp {
+    // Writes the fraction of a second with 3, 6 or 9 digits, or not
+    // at all if it is zero, and writes a zero offset as `Z`. Years
+    // outside 0 to 9999 do not fit in four digits and are written in
+    // full.
+    fn write<W: Write>(&self, w: &mut W) -> fmt::Result {
+        write!(
+            w,
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+            self.year,
+            self.month,
+            self.day,
+            self.hour,
+            self.minute,
+            self.second
+        )?;
+        if self.nanos != 0 {
+            let (fraction, digits) = if self.nanos.is_multiple_of(1_000_000) {
+                (self.nanos / 1_000_000, 3)
+            } else if self.nanos.is_multiple_of(1_000) {
+                (self.nanos / 1_000, 6)
+            } else {
+                (self.nanos, 9)
+            };
+            write!(w, ".{:0width$}", fraction, width = digits)?;
+        }
+        if self.offset_secs == 0 {
+            return w.write_char('Z');
+        }
+        let sign = if self.offset_secs < 0 { '-' } else { '+' };
+        let minutes = self.offset_secs.unsigned_abs() / 60;
+        write!(w, "{}{:02}:{:02}", sign, minutes / 60, minutes % 60)
+    }
+
+    fn len(&self) -> usize {
+        let mut w = CountingWriter::new();
+        self.write(&mut w).expect("capacity overflow");
+        w.count()
+    }
+}
+
+// A leap second is represented by chrono as a fraction of at least one
+// second, and is written as second 60.
+#[cfg(feature = "chrono")]
+fn chrono_timestamp<Tz: TimeZone>(time: &DateTime<Tz>) -> Timestamp {
+    let nanos = time.nanosecond();
+    let leap = nanos >= 1_000_000_000;
+    Timestamp {
+        year: time.year(),
+        month: time.month(),
+        day: time.day(),
+        hour: time.hour(),
+        minute: time.minute(),
+        second: time.second() + if leap { 1 } else { 0 },
+        nanos: if leap { nanos - 1_000_000_000 } else { nanos },
+        offset_secs: time.offset().fix().local_minus_utc(),
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl<Tz: TimeZone> Cat for DateTime<Tz> {
+    fn size_hint(&self) -> usize {
+        chrono_timestamp(self).len()
+    }
+
+    fn append_to(&self, s: &mut String) {
+        chrono_timestamp(self).write(s).unwrap();
+    }
+
+    fn for_each_segment(&self, f: &mut dyn FnMut(&str)) {
+        chrono_timestamp(self)
+            .write(&mut SegmentWriter::new(f))
+            .unwrap();
+    }
+}
+
+#[cfg(feature = "time")]
+fn time_timestamp(time: &OffsetDateTime) -> Timestamp {
+    Timestamp {
+        year: time.year(),
+        month: u32::from(u8::from(time.month())),
+        day: u32::from(time.day()),
+        hour: u32::from(time.hour()),
+        minute: u32::from(time.minute()),
+        second: u32::from(time.second()),
+        nanos: time.nanosecond(),
+        offset_secs: time.offset().whole_seconds(),
+    }
+}
+
+#[cfg(feature = "time")]
+impl Cat for OffsetDateTime {
+    fn size_hint(&self) -> usize {
+        time_timestamp(self).len()
+    }
+
+    fn append_to(&self, s: &mut String) {
+        time_timestamp(self).write(s).unwrap();
+    }
+
+    fn for_each_segment(&self, f: &mut dyn FnMut(&str)) {
+        time_timestamp(self)
+            .write(&mut SegmentWriter::new(f))
+            .unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use CAT;
+    use cat::Cat;
+    use std::time::{Duration, UNIX_EPOCH};
+    use super::{http_date, rfc3339};
+
+    #[test]
+    fn known_dates() {
+        let epoch = CAT + http_date(UNIX_EPOCH) + ' ' + rfc3339(UNIX_EPOCH);
+        let s = String::from(epoch);
+        assert_eq!(s, "Thu, 01 Jan 1970 00:00:00 GMT 1970-01-01T00:00:00Z");
+        assert_eq!(s.len(), s.capacity());
+
+        let leap = UNIX_EPOCH + Duration::new(951_782_400, 999_999_999);
+        assert_eq!(
+            http_date(leap).to_string(),
+            "Tue, 29 Feb 2000 00:00:00 GMT"
+        );
+        assert_eq!(rfc3339(leap).to_string(), "2000-02-29T00:00:00Z");
+    }
+
+    #[test]
+    fn before_epoch() {
+        let time = UNIX_EPOCH - Duration::new(0, 1);
+        assert_eq!(rfc3339(time).to_string(), "1969-12-31T23:59:59Z");
+        let time = UNIX_EPOCH - Duration::from_secs(86_400 * 365);
+        assert_eq!(
+            http_date(time).to_string(),
+            "Wed, 01 Jan 1969 00:00:00 GMT"
+        );
+    }
+
+    #[test]
+    fn clamped() {
+        let far = UNIX_EPOCH + Duration::from_secs(1 << 40);
+        assert_eq!(rfc3339(far).to_string(), "9999-12-31T23:59:59Z");
+        assert_eq!(http_date(far).to_string(), "Fri, 31 Dec 9999 23:59:59 GMT");
+    }
+
+    #[test]
+    fn durations() {
+        let elapsed = Duration::from_millis(1234);
+        let waited = Duration::from_nanos(250);
+        let s = String::from(CAT + "took " + elapsed + ", waited " + waited);
+        assert_eq!(s, "took 1.234s, waited 250ns");
+        assert_eq!(s.len(), s.capacity());
+        assert_eq!(Duration::from_micros(1500).size_hint(), 5);
+        assert_eq!(String::from(CAT + Duration::ZERO + ""), "0ns");
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn chrono_timestamps() {
+        use chrono::{FixedOffset, TimeZone, Utc};
+
+        let utc = Utc.with_ymd_and_hms(1994, 11, 6, 8, 49, 37).unwrap();
+        let s = String::from(CAT + '[' + utc + "] up");
+        assert_eq!(s, "[1994-11-06T08:49:37Z] up");
+        assert_eq!(s.len(), s.capacity());
+        let offset = FixedOffset::west_opt(5 * 3600 + 30 * 60).unwrap();
+        let local = utc.with_timezone(&offset);
+        let millis = local + chrono::Duration::milliseconds(120);
+        assert_eq!(
+            String::from(CAT + millis + ""),
+            "1994-11-06T03:19:37.120-05:30"
+        );
+        let nanos = utc + chrono::Duration::nanoseconds(7);
+        assert_eq!(nanos.size_hint(), 30);
+    }
+
+    #[cfg(feature = "time")]
+    #[test]
+    fn time_timestamps() {
+        use time::{OffsetDateTime, UtcOffset};
+
+        let utc = OffsetDateTime::from_unix_timestamp(784_111_777).unwrap();
+        let s = String::from(CAT + utc + ' ' + "up");
+        assert_eq!(s, "1994-11-06T08:49:37Z up");
+        assert_eq!(s.len(), s.capacity());
+        let offset = UtcOffset::from_hms(9, 0, 0).unwrap();
+        let local = utc.to_offset(offset).replace_microsecond(5).unwrap();
+        assert_eq!(
+            String::from(CAT + local + ""),
+            "1994-11-06T17:49:37.000005+09:00"
+        );
+    }
+}
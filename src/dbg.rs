@@ -0,0 +1,80 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use cat::{Cat, SegmentWriter};
+use count::CountingWriter;
+use std::fmt::{self, Debug, Display, Write};
+
+/// A piece that formats a value with its `Debug` implementation.
+///
+/// The value is formatted twice, once with a counting writer to
+/// compute an exact size hint and once to append it, so that no
+/// intermediate `String` is allocated. When concatenated, the value
+/// is always formatted in the compact form, not the alternate `{:#?}`
+/// form.
+///
+/// # Examples
+///
+/// ```rust
+/// use sconcat::{CAT, Dbg};
+///
+/// #[derive(Debug)]
+/// enum State {
+///     Idle,
+///     Busy { jobs: u32 },
+/// }
+///
+/// let state = State::Busy { jobs: 3 };
+/// let cat = CAT + "state=" + Dbg(&state) + ", next=" + Dbg(State::Idle);
+/// assert_eq!(String::from(cat), "state=Busy { jobs: 3 }, next=Idle");
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct Dbg<T: Debug>(pub T);
+
+impl<T: Debug> Dbg<T> {
+    fn write<W: Write>(&self, w: &mut W) -> fmt::Result {
+        write!(w, "{:?}", self.0)
+    }
+}
+
+impl<T: Debug> Cat for Dbg<T> {
+    fn size_hint(&self) -> usize {
+        let mut w = CountingWriter::new();
+        self.write(&mut w).expect("capacity overflow");
+        w.count()
+    }
+
+    fn append_to(&self, s: &mut String) {
+        self.write(s).unwrap();
+    }
+
+    fn for_each_segment(&self, f: &mut dyn FnMut(&str)) {
+        self.write(&mut SegmentWriter::new(f)).unwrap();
+    }
+}
+
+impl<T: Debug> Display for Dbg<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        Debug::fmt(&self.0, f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use CAT;
+    use super::Dbg;
+
+    #[test]
+    fn debug_pieces() {
+        let v = vec![Some("a"), None];
+        let s = String::from(CAT + "v=" + Dbg(&v) + ' ' + Dbg('\n'));
+        assert_eq!(s, "v=[Some(\"a\"), None] '\\n'");
+        assert_eq!(s.len(), s.capacity());
+        assert_eq!(format!("{:>4}", Dbg(1)), "   1");
+    }
+}
@@ -0,0 +1,60 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Integration with the defmt crate. Concatenations are logged as
+// strings through `Display2Format`, which streams the segments to
+// the logger, so they do not need to be materialized first.
+
+use cat::{Cat, CatMany, CatOne, CatStart};
+use defmt;
+use defmt::{Display2Format, Format, Formatter};
+use std::fmt::{self, Display};
+
+// Displays the segments of any concatenation, even if its pieces
+// do not implement `Display` themselves.
+struct Segments<'a, C: 'a>(&'a C);
+
+impl<'a, C: Cat> Display for Segments<'a, C> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.write_to(f)
+    }
+}
+
+impl<L: Cat, R: Cat> Format for CatMany<L, R> {
+    fn format(&self, fmt: Formatter) {
+        defmt::write!(fmt, "{}", Display2Format(&Segments(self)));
+    }
+}
+
+impl<T: Cat> Format for CatOne<T> {
+    fn format(&self, fmt: Formatter) {
+        defmt::write!(fmt, "{}", Display2Format(&Segments(&self.inner)));
+    }
+}
+
+impl Format for CatStart {
+    fn format(&self, fmt: Formatter) {
+        defmt::write!(fmt, "");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use CAT;
+    use defmt::Format;
+
+    // Logging needs a global logger, so only the bounds are checked.
+    fn assert_format<T: Format>(_value: &T) {}
+
+    #[test]
+    fn implements_format() {
+        assert_format(&CAT);
+        assert_format(&(CAT + "id="));
+        assert_format(&(CAT + "id=" + String::from("7") + '\n'));
+    }
+}
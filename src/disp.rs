@@ -0,0 +1,158 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use cat::{Cat, SegmentWriter};
+use count::CountingWriter;
+use std::fmt::{self, Display, Write};
+
+/// A piece that formats a value with its `Display` implementation.
+///
+/// This is created by the [`disp`](fn.disp.html) and
+/// [`disp_hint`](fn.disp_hint.html) functions.
+#[derive(Clone, Copy, Debug)]
+pub struct Disp<T: Display> {
+    value: T,
+    size_hint: Option<usize>,
+}
+
+/// Creates a piece that formats `value` with its `Display`
+/// implementation when the concatenation is materialized.
+///
+/// Unless a size hint is set with
+/// [`with_size_hint`](struct.Disp.html#method.with_size_hint), the
+/// value is formatted twice like for [`Dbg`](struct.Dbg.html), once
+/// with a counting writer to compute an exact size hint and once to
+/// append it, so that no intermediate `String` is allocated. With a
+/// size hint, the value is formatted only once.
+///
+/// # Examples
+///
+/// ```rust
+/// use sconcat::{CAT, disp};
+/// use std::net::Ipv4Addr;
+///
+/// let addr = Ipv4Addr::new(127, 0, 0, 1);
+/// let port = 8080;
+/// let cat = CAT + "listening on " + disp(addr).with_size_hint(15)
+///     + ':' + disp(port).with_size_hint(5);
+/// assert_eq!(String::from(cat), "listening on 127.0.0.1:8080");
+/// ```
+pub fn disp<T: Display>(value: T) -> Disp<T> {
+    Disp {
+        value,
+        size_hint: None,
+    }
+}
+
+/// Creates a piece that formats `value` with its `Display`
+/// implementation, reserving `size_hint` bytes for it.
+///
+/// This is the same as `disp(value).with_size_hint(size_hint)`. When
+/// the formatted length is known, such as for fixed-width IDs or
+/// bounded numbers, this keeps the concatenation to a single
+/// allocation.
+///
+/// The hint does not need to be exact. If the value is longer, the
+/// `String` grows as it would without a hint, and if it is shorter,
+/// the unused bytes are left as spare capacity; the output is the
+/// same in both cases. With the `alloc-check` feature, a hint that is
+/// too small is reported like any other.
+///
+/// # Examples
+///
+/// ```rust
+/// use sconcat::{CAT, disp_hint};
+///
+/// let id = 0x2a_u32;
+/// let hex = format_args!("{:08x}", id);
+/// let s = String::from(CAT + "id-" + disp_hint(hex, 8));
+/// assert_eq!(s, "id-0000002a");
+/// assert_eq!(s.capacity(), 11);
+/// ```
+pub fn disp_hint<T: Display>(value: T, size_hint: usize) -> Disp<T> {
+    Disp {
+        value,
+        size_hint: Some(size_hint),
+    }
+}
+
+impl<T: Display> Disp<T> {
+    /// Sets the number of bytes reserved for the piece.
+    pub fn with_size_hint(self, size_hint: usize) -> Disp<T> {
+        Disp {
+            size_hint: Some(size_hint),
+            ..self
+        }
+    }
+
+    /// Returns the wrapped value.
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T: Display> Cat for Disp<T> {
+    fn size_hint(&self) -> usize {
+        match self.size_hint {
+            Some(size_hint) => size_hint,
+            None => {
+                let mut w = CountingWriter::new();
+                write!(w, "{}", self.value).expect("capacity overflow");
+                w.count()
+            }
+        }
+    }
+
+    fn append_to(&self, s: &mut String) {
+        write!(s, "{}", self.value).unwrap();
+    }
+
+    fn for_each_segment(&self, f: &mut dyn FnMut(&str)) {
+        write!(SegmentWriter::new(f), "{}", self.value).unwrap();
+    }
+}
+
+impl<T: Display> Display for Disp<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        Display::fmt(&self.value, f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use CAT;
+    use super::{disp, disp_hint};
+
+    #[test]
+    fn display_pieces() {
+        let s = String::from(CAT + "port " + disp(443) + ' ' + disp(1.5));
+        assert_eq!(s, "port 443 1.5");
+        assert_eq!(s.len(), s.capacity());
+        let s = String::from(CAT + '[' + disp("x").with_size_hint(1) + ']');
+        assert_eq!(s, "[x]");
+        assert_eq!(s.len(), s.capacity());
+        assert_eq!(format!("{:>4}", disp(7)), "   7");
+        assert_eq!(disp(7).into_inner(), 7);
+    }
+
+    #[test]
+    fn wrong_hints() {
+        // With alloc-check, a hint that is too small panics.
+        #[cfg(not(feature = "alloc-check"))]
+        {
+            let s = String::from(CAT + "n=" + disp_hint(12345, 2) + ';');
+            assert_eq!(s, "n=12345;");
+        }
+        let s = String::from(CAT + "n=" + disp_hint(1, 10) + ';');
+        assert_eq!(s, "n=1;");
+        assert!(s.capacity() >= 13);
+        let mut buf = String::new();
+        (CAT + disp_hint('é', 0)).write_to(&mut buf).unwrap();
+        assert_eq!(buf, "é");
+    }
+}
@@ -0,0 +1,162 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use cat::{self, Cat};
+use std::fmt::{self, Debug, Display};
+use std::ops::AddAssign;
+
+/// A concatenation that can grow at run time.
+///
+/// Unlike a concatenation started with [`CAT`](constant.CAT.html),
+/// whose type records every piece, this stores its pieces as boxed
+/// trait objects, so it can be built in a loop or in branches that
+/// add different pieces. The pieces are still not materialized until
+/// the concatenation is converted to or appended to a `String`, at
+/// which point one allocation or reallocation takes place.
+///
+/// # Examples
+///
+/// ```rust
+/// use sconcat::{CAT, DynCat};
+///
+/// let mut cat = DynCat::new();
+/// for (i, name) in ["a", "b", "c"].iter().enumerate() {
+///     if i > 0 {
+///         cat += ", ";
+///     }
+///     cat.push(CAT + '"' + *name + '"');
+/// }
+/// let s = String::from(cat);
+/// assert_eq!(s, "\"a\", \"b\", \"c\"");
+/// assert_eq!(s.len(), s.capacity());
+/// ```
+#[derive(Default)]
+pub struct DynCat<'a> {
+    pieces: Vec<Box<dyn Cat + 'a>>,
+}
+
+impl<'a> DynCat<'a> {
+    /// Creates an empty concatenation.
+    pub fn new() -> DynCat<'a> {
+        DynCat { pieces: Vec::new() }
+    }
+
+    /// Appends a piece to the concatenation.
+    pub fn push<T: Cat + 'a>(&mut self, piece: T) {
+        self.pieces.push(Box::new(piece));
+    }
+
+    /// Returns the number of pieces that were pushed.
+    pub fn len(&self) -> usize {
+        self.pieces.len()
+    }
+
+    /// Returns `true` if no pieces were pushed.
+    pub fn is_empty(&self) -> bool {
+        self.pieces.is_empty()
+    }
+}
+
+impl<'a> Cat for DynCat<'a> {
+    fn size_hint(&self) -> usize {
+        let mut len = Some(0usize);
+        for piece in &self.pieces {
+            len = len.and_then(|len| len.checked_add(piece.size_hint()));
+        }
+        len.expect("capacity overflow")
+    }
+
+    fn append_to(&self, s: &mut String) {
+        for piece in &self.pieces {
+            piece.append_to(s);
+        }
+    }
+
+    fn for_each_segment(&self, f: &mut dyn FnMut(&str)) {
+        for piece in &self.pieces {
+            piece.for_each_segment(f);
+        }
+    }
+
+    fn pieces(&self) -> usize {
+        self.pieces.iter().map(|piece| piece.pieces()).sum()
+    }
+}
+
+impl<'a, T: Cat + 'a> AddAssign<T> for DynCat<'a> {
+    fn add_assign(&mut self, rhs: T) {
+        self.push(rhs);
+    }
+}
+
+impl<'a> AddAssign<DynCat<'a>> for String {
+    fn add_assign(&mut self, rhs: DynCat<'a>) {
+        cat::append_cat(self, &rhs);
+    }
+}
+
+impl<'a> AddAssign<&DynCat<'a>> for String {
+    fn add_assign(&mut self, rhs: &DynCat<'a>) {
+        cat::append_cat(self, rhs);
+    }
+}
+
+impl<'a> From<DynCat<'a>> for String {
+    fn from(src: DynCat<'a>) -> String {
+        cat::cat_into_string(src)
+    }
+}
+
+impl<'a> Debug for DynCat<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("DynCat")
+            .field("pieces", &self.pieces.len())
+            .finish()
+    }
+}
+
+impl<'a> Display for DynCat<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut result = Ok(());
+        self.for_each_segment(&mut |segment| {
+            if result.is_ok() {
+                result = f.write_str(segment);
+            }
+        });
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use CAT;
+    use super::DynCat;
+
+    #[test]
+    fn dynamic() {
+        let owned = String::from("owned");
+        let mut cat = DynCat::new();
+        assert!(cat.is_empty());
+        for i in 0..3 {
+            if i % 2 == 0 {
+                cat += 'x';
+            } else {
+                cat.push(CAT + "[" + owned.as_str() + "]");
+            }
+        }
+        assert_eq!(cat.len(), 3);
+        assert_eq!(format!("{:?}", cat), "DynCat { pieces: 3 }");
+        assert_eq!(cat.to_string(), "x[owned]x");
+        let mut s = String::from("> ");
+        s += &cat;
+        assert_eq!(s, "> x[owned]x");
+        let s = String::from(CAT + "<" + cat + ">");
+        assert_eq!(s, "<x[owned]x>");
+        assert_eq!(s.len(), s.capacity());
+    }
+}
@@ -0,0 +1,95 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use cat::{self, Cat, CatMany, CatOne, CatStart};
+
+impl<L: Cat, R: Cat> CatMany<L, R> {
+    /// Converts the concatenation to a `String`, passing it to `edit`
+    /// before returning it.
+    ///
+    /// The string is allocated once with the size hint as its
+    /// capacity, and `edit` can modify it in place. Edits that do not
+    /// make the string longer, such as changing the case of ASCII
+    /// characters or truncating, do not reallocate.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use sconcat::CAT;
+    ///
+    /// let cat = CAT + "hello, " + "world";
+    /// let s = cat.into_string_with_edit(|s| s[..1].make_ascii_uppercase());
+    /// assert_eq!(s, "Hello, world");
+    /// ```
+    pub fn into_string_with_edit<F>(self, edit: F) -> String
+    where
+        F: FnOnce(&mut String),
+    {
+        let mut s = cat::cat_into_string(self);
+        edit(&mut s);
+        s
+    }
+}
+
+impl<T: Cat> CatOne<T> {
+    /// Converts the concatenation to a `String`, passing it to `edit`
+    /// before returning it.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use sconcat::CAT;
+    ///
+    /// let cat = CAT + "trailing  ";
+    /// let s = cat.into_string_with_edit(|s| {
+    ///     let len = s.trim_end().len();
+    ///     s.truncate(len);
+    /// });
+    /// assert_eq!(s, "trailing");
+    /// ```
+    pub fn into_string_with_edit<F>(self, edit: F) -> String
+    where
+        F: FnOnce(&mut String),
+    {
+        let mut s = cat::cat_into_string(self.inner);
+        edit(&mut s);
+        s
+    }
+}
+
+impl CatStart {
+    /// Passes an empty `String` to `edit` and returns it.
+    pub fn into_string_with_edit<F>(self, edit: F) -> String
+    where
+        F: FnOnce(&mut String),
+    {
+        let mut s = String::new();
+        edit(&mut s);
+        s
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use CAT;
+
+    #[test]
+    fn edits() {
+        let cat = CAT + "abc" + 'd' + String::from("ef");
+        let s = cat.into_string_with_edit(|s| {
+            assert_eq!(s, "abcdef");
+            s.make_ascii_uppercase();
+        });
+        assert_eq!(s, "ABCDEF");
+        assert!(s.capacity() >= 6);
+        let s = (CAT + "x").into_string_with_edit(|s| s.push('y'));
+        assert_eq!(s, "xy");
+        let s = CAT.into_string_with_edit(|s| s.push_str("empty"));
+        assert_eq!(s, "empty");
+    }
+}
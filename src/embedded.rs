@@ -0,0 +1,112 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use cat::{Cat, CatMany, CatOne, CatStart};
+use embedded_io::Write;
+
+// Writes the segments one by one, skipping the rest after an error.
+fn write_segments<C: Cat, W: Write>(
+    cat: &C,
+    w: &mut W,
+) -> Result<(), W::Error> {
+    let mut result = Ok(());
+    cat.for_each_segment(&mut |segment| {
+        if result.is_ok() {
+            result = w.write_all(segment.as_bytes());
+        }
+    });
+    result
+}
+
+impl<L: Cat, R: Cat> CatMany<L, R> {
+    /// Writes the concatenation to an `embedded_io::Write` target
+    /// piece by piece.
+    ///
+    /// The concatenation is never materialized, so no allocations
+    /// take place. Writing stops at the first error.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// use sconcat::CAT;
+    ///
+    /// let mut uart = board.uart();
+    /// (CAT + "temp=" + reading + "\r\n").write_to_embedded(&mut uart)?;
+    /// ```
+    pub fn write_to_embedded<W: Write>(
+        &self,
+        w: &mut W,
+    ) -> Result<(), W::Error> {
+        write_segments(self, w)
+    }
+}
+
+impl<T: Cat> CatOne<T> {
+    /// Writes the concatenation to an `embedded_io::Write` target.
+    ///
+    /// The concatenation is never materialized, so no allocations
+    /// take place.
+    pub fn write_to_embedded<W: Write>(
+        &self,
+        w: &mut W,
+    ) -> Result<(), W::Error> {
+        write_segments(&self.inner, w)
+    }
+}
+
+impl CatStart {
+    /// Writes nothing to an `embedded_io::Write` target.
+    pub fn write_to_embedded<W: Write>(
+        &self,
+        _w: &mut W,
+    ) -> Result<(), W::Error> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use CAT;
+    use embedded_io::{ErrorType, Write};
+    use std::convert::Infallible;
+
+    struct Sink {
+        bytes: Vec<u8>,
+        writes: usize,
+    }
+
+    impl ErrorType for Sink {
+        type Error = Infallible;
+    }
+
+    impl Write for Sink {
+        fn write(&mut self, buf: &[u8]) -> Result<usize, Infallible> {
+            self.bytes.extend_from_slice(buf);
+            self.writes += 1;
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> Result<(), Infallible> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn writes_pieces() {
+        let mut sink = Sink {
+            bytes: Vec::new(),
+            writes: 0,
+        };
+        let cat = CAT + "temp=" + String::from("21.5") + '\r' + '\n';
+        cat.write_to_embedded(&mut sink).unwrap();
+        (CAT + "ok").write_to_embedded(&mut sink).unwrap();
+        CAT.write_to_embedded(&mut sink).unwrap();
+        assert_eq!(sink.bytes, b"temp=21.5\r\nok");
+        assert_eq!(sink.writes, 5);
+    }
+}
@@ -0,0 +1,279 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Integration with the encoding_rs crate. The segments are streamed
+// through a single encoder, so stateful encodings such as ISO-2022-JP
+// see the concatenation as one text. The output buffer is sized for
+// the size hint up front and only grows if the hint is too small or
+// unmappable characters are replaced.
+
+use cat::{Cat, CatMany, CatOne, CatStart};
+use encoding_rs::{CoderResult, Encoder, EncoderResult, Encoding};
+use std::error::Error;
+use std::fmt::{self, Display};
+
+/// An error returned when a concatenation contains a character that
+/// cannot be encoded in the target encoding.
+///
+/// This is returned by the `try_encode_to` methods.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UnmappableError {
+    unmappable: char,
+    position: usize,
+    encoding: &'static Encoding,
+}
+
+impl UnmappableError {
+    /// Returns the character that cannot be encoded.
+    pub fn unmappable(&self) -> char {
+        self.unmappable
+    }
+
+    /// Returns the byte position of the character in the
+    /// concatenation.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Returns the encoding that cannot represent the character.
+    pub fn encoding(&self) -> &'static Encoding {
+        self.encoding
+    }
+}
+
+impl Display for UnmappableError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "character {:?} at byte {} cannot be encoded in {}",
+            self.unmappable,
+            self.position,
+            self.encoding.name()
+        )
+    }
+}
+
+impl Error for UnmappableError {}
+
+// Encodes `src`, which starts at byte `pos` of the concatenation,
+// growing `dst` whenever the encoder runs out of space.
+fn encode_segment(
+    encoder: &mut Encoder,
+    mut src: &str,
+    mut pos: usize,
+    dst: &mut Vec<u8>,
+    last: bool,
+    replace: bool,
+) -> Result<(), UnmappableError> {
+    loop {
+        let (result, read) = if replace {
+            let (result, read, _) =
+                encoder.encode_from_utf8_to_vec(src, dst, last);
+            let result = match result {
+                CoderResult::InputEmpty => EncoderResult::InputEmpty,
+                CoderResult::OutputFull => EncoderResult::OutputFull,
+            };
+            (result, read)
+        } else {
+            encoder.encode_from_utf8_to_vec_without_replacement(src, dst, last)
+        };
+        src = &src[read..];
+        pos += read;
+        match result {
+            EncoderResult::InputEmpty => return Ok(()),
+            EncoderResult::OutputFull => {
+                let more = encoder
+                    .max_buffer_length_from_utf8_if_no_unmappables(src.len())
+                    .expect("capacity overflow");
+                dst.reserve(more);
+            }
+            // The unmappable character has already been read.
+            EncoderResult::Unmappable(unmappable) => {
+                return Err(UnmappableError {
+                    unmappable,
+                    position: pos - unmappable.len_utf8(),
+                    encoding: encoder.encoding(),
+                });
+            }
+        }
+    }
+}
+
+fn encode<C: Cat>(
+    cat: &C,
+    encoding: &'static Encoding,
+    replace: bool,
+) -> Result<Vec<u8>, UnmappableError> {
+    let mut encoder = encoding.new_encoder();
+    let hint = cat.size_hint();
+    let capacity = encoder
+        .max_buffer_length_from_utf8_if_no_unmappables(hint)
+        .unwrap_or(hint);
+    let mut dst = Vec::with_capacity(capacity);
+    let mut pos = 0;
+    let mut result = Ok(());
+    cat.for_each_segment(&mut |segment| {
+        if result.is_ok() {
+            result = encode_segment(
+                &mut encoder,
+                segment,
+                pos,
+                &mut dst,
+                false,
+                replace,
+            );
+            pos += segment.len();
+        }
+    });
+    result?;
+    encode_segment(&mut encoder, "", pos, &mut dst, true, replace)?;
+    Ok(dst)
+}
+
+impl<L: Cat, R: Cat> CatMany<L, R> {
+    /// Encodes the concatenation in `encoding`, replacing unmappable
+    /// characters with HTML decimal character references such as
+    /// `&#8364;`.
+    ///
+    /// As with the encoders of the `encoding_rs` crate, UTF-16
+    /// encodings and the replacement encoding produce UTF-8.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # extern crate encoding_rs;
+    /// # extern crate sconcat;
+    /// use encoding_rs::{SHIFT_JIS, WINDOWS_1252};
+    /// use sconcat::CAT;
+    ///
+    /// # fn main() {
+    /// let name = "Zoë";
+    /// let cat = CAT + "name=" + name + ';';
+    /// assert_eq!(cat.encode_to(WINDOWS_1252), b"name=Zo\xeb;");
+    /// let cat = CAT + "価格: " + "5€";
+    /// assert_eq!(cat.encode_to(SHIFT_JIS), b"\x89\xbf\x8ai: 5&#8364;");
+    /// # }
+    /// ```
+    pub fn encode_to(&self, encoding: &'static Encoding) -> Vec<u8> {
+        match encode(self, encoding, true) {
+            Ok(v) => v,
+            Err(_) => unreachable!(),
+        }
+    }
+
+    /// Encodes the concatenation in `encoding`, failing if it
+    /// contains a character that cannot be encoded.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # extern crate encoding_rs;
+    /// # extern crate sconcat;
+    /// use encoding_rs::WINDOWS_1252;
+    /// use sconcat::CAT;
+    ///
+    /// # fn main() {
+    /// let cat = CAT + "price: " + "5€";
+    /// assert_eq!(cat.try_encode_to(WINDOWS_1252).unwrap(), b"price: 5\x80");
+    /// let cat = CAT + "price: " + "5₿";
+    /// let err = cat.try_encode_to(WINDOWS_1252).unwrap_err();
+    /// assert_eq!(err.unmappable(), '₿');
+    /// assert_eq!(err.position(), 8);
+    /// # }
+    /// ```
+    pub fn try_encode_to(
+        &self,
+        encoding: &'static Encoding,
+    ) -> Result<Vec<u8>, UnmappableError> {
+        encode(self, encoding, false)
+    }
+}
+
+impl<T: Cat> CatOne<T> {
+    /// Encodes the concatenation in `encoding`, replacing unmappable
+    /// characters with HTML decimal character references.
+    pub fn encode_to(&self, encoding: &'static Encoding) -> Vec<u8> {
+        match encode(&self.inner, encoding, true) {
+            Ok(v) => v,
+            Err(_) => unreachable!(),
+        }
+    }
+
+    /// Encodes the concatenation in `encoding`, failing if it
+    /// contains a character that cannot be encoded.
+    pub fn try_encode_to(
+        &self,
+        encoding: &'static Encoding,
+    ) -> Result<Vec<u8>, UnmappableError> {
+        encode(&self.inner, encoding, false)
+    }
+}
+
+impl CatStart {
+    /// Returns an empty vector, as the concatenation is empty.
+    pub fn encode_to(&self, _encoding: &'static Encoding) -> Vec<u8> {
+        Vec::new()
+    }
+
+    /// Returns an empty vector, as the concatenation is empty.
+    pub fn try_encode_to(
+        &self,
+        _encoding: &'static Encoding,
+    ) -> Result<Vec<u8>, UnmappableError> {
+        Ok(Vec::new())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use CAT;
+    use encoding_rs::{ISO_2022_JP, SHIFT_JIS, UTF_16LE, WINDOWS_1252};
+
+    #[test]
+    fn replace() {
+        let cat = CAT + "a" + 'é' + "€" + "₿" + 'z';
+        assert_eq!(cat.encode_to(WINDOWS_1252), b"a\xe9\x80&#8383;z");
+        assert_eq!(cat.encode_to(UTF_16LE), "aé€₿z".as_bytes());
+        assert_eq!((CAT + "x").encode_to(WINDOWS_1252), b"x");
+        assert_eq!(CAT.encode_to(SHIFT_JIS), b"");
+    }
+
+    #[test]
+    fn error() {
+        let cat = CAT + "ab" + "cé" + "d₿e";
+        let err = cat.try_encode_to(WINDOWS_1252).unwrap_err();
+        assert_eq!(err.unmappable(), '₿');
+        assert_eq!(err.position(), 6);
+        assert_eq!(err.encoding(), WINDOWS_1252);
+        assert_eq!(
+            err.to_string(),
+            "character '₿' at byte 6 cannot be encoded in windows-1252"
+        );
+        assert_eq!(cat.try_encode_to(UTF_16LE).unwrap(), "abcéd₿e".as_bytes());
+        assert_eq!(CAT.try_encode_to(WINDOWS_1252), Ok(Vec::new()));
+    }
+
+    #[test]
+    fn stateful() {
+        // The escape back to ASCII is only written at the end.
+        let cat = CAT + "a" + "日" + "本" + 'b';
+        let expected = b"a\x1b$BF|K\\\x1b(Bb";
+        assert_eq!(cat.try_encode_to(ISO_2022_JP).unwrap(), expected);
+        let cat = CAT + "日" + "本";
+        assert_eq!(cat.encode_to(ISO_2022_JP), b"\x1b$BF|K\\\x1b(B");
+    }
+
+    #[test]
+    fn grow() {
+        // Every character needs a long replacement.
+        let s = "₿".repeat(100);
+        let out = (CAT + "[" + &*s + "]").encode_to(SHIFT_JIS);
+        assert_eq!(out.len(), 2 + 100 * "&#8383;".len());
+        assert!(out.starts_with(b"[&#8383;&#8383;"));
+    }
+}
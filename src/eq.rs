@@ -0,0 +1,179 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use cat::{Cat, CatMany, CatOne, CatStart};
+use std::cmp::Ordering;
+
+// Compares the segments against `expected` without materializing
+// them. Segments after the first difference are skipped.
+fn eq_segments<C: Cat>(cat: &C, expected: &str) -> bool {
+    let mut rest = Some(expected.as_bytes());
+    cat.for_each_segment(&mut |segment| {
+        let bytes = segment.as_bytes();
+        rest = match rest {
+            Some(r) if r.starts_with(bytes) => Some(&r[bytes.len()..]),
+            _ => None,
+        };
+    });
+    match rest {
+        Some(r) => r.is_empty(),
+        None => false,
+    }
+}
+
+// Compares the segments with `other` lexicographically without
+// materializing them. Segments after the first difference are
+// skipped.
+fn cmp_segments<C: Cat>(cat: &C, other: &str) -> Ordering {
+    let mut rest = other.as_bytes();
+    let mut ord = Ordering::Equal;
+    cat.for_each_segment(&mut |segment| {
+        if ord != Ordering::Equal {
+            return;
+        }
+        let bytes = segment.as_bytes();
+        let n = bytes.len().min(rest.len());
+        ord = bytes[..n].cmp(&rest[..n]);
+        if ord == Ordering::Equal && n < bytes.len() {
+            ord = Ordering::Greater;
+        }
+        rest = &rest[n..];
+    });
+    if ord == Ordering::Equal && !rest.is_empty() {
+        ord = Ordering::Less;
+    }
+    ord
+}
+
+impl<L: Cat, R: Cat> CatMany<L, R> {
+    /// Compares the concatenation with `other` lexicographically,
+    /// like `str::cmp`, without materializing it.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use sconcat::CAT;
+    ///
+    /// let table = ["apple", "banana", "cherry"];
+    /// let key = CAT + "ban" + "ana";
+    /// assert_eq!(table.binary_search_by(|s| key.cmp_str(s).reverse()), Ok(1));
+    /// assert!(key > "apricot" && key < "blueberry");
+    /// ```
+    pub fn cmp_str(&self, other: &str) -> Ordering {
+        cmp_segments(self, other)
+    }
+}
+
+impl<T: Cat> CatOne<T> {
+    /// Compares the concatenation with `other` lexicographically,
+    /// like `str::cmp`, without materializing it.
+    pub fn cmp_str(&self, other: &str) -> Ordering {
+        cmp_segments(&self.inner, other)
+    }
+}
+
+impl CatStart {
+    /// Compares the empty concatenation with `other`, which is
+    /// `Equal` if `other` is empty and `Less` otherwise.
+    pub fn cmp_str(&self, other: &str) -> Ordering {
+        "".cmp(other)
+    }
+}
+
+// Implements `PartialEq` and `PartialOrd` for `str`, `&str` and
+// `String`.
+macro_rules! impl_eq {
+    ($([$($gen:tt)*] $Cat:ty, $self:ident => $inner:expr;)*) => { $(
+        impl<$($gen)*> PartialEq<str> for $Cat {
+            fn eq(&$self, other: &str) -> bool {
+                eq_segments($inner, other)
+            }
+        }
+
+        impl<'b, $($gen)*> PartialEq<&'b str> for $Cat {
+            fn eq(&$self, other: &&'b str) -> bool {
+                eq_segments($inner, other)
+            }
+        }
+
+        impl<$($gen)*> PartialEq<String> for $Cat {
+            fn eq(&$self, other: &String) -> bool {
+                eq_segments($inner, other)
+            }
+        }
+
+        impl<$($gen)*> PartialOrd<str> for $Cat {
+            fn partial_cmp(&$self, other: &str) -> Option<Ordering> {
+                Some(cmp_segments($inner, other))
+            }
+        }
+
+        impl<'b, $($gen)*> PartialOrd<&'b str> for $Cat {
+            fn partial_cmp(&$self, other: &&'b str) -> Option<Ordering> {
+                Some(cmp_segments($inner, other))
+            }
+        }
+
+        impl<$($gen)*> PartialOrd<String> for $Cat {
+            fn partial_cmp(&$self, other: &String) -> Option<Ordering> {
+                Some(cmp_segments($inner, other))
+            }
+        }
+    )* };
+}
+
+impl_eq! {
+    [L: Cat, R: Cat] CatMany<L, R>, self => self;
+    [T: Cat] CatOne<T>, self => &self.inner;
+    [] CatStart, self => &"";
+}
+
+#[cfg(test)]
+mod tests {
+    use CAT;
+    use std::cmp::Ordering;
+
+    #[test]
+    fn equal() {
+        let cat = CAT + "abc" + 'd' + String::from("ef");
+        assert!(cat == "abcdef");
+        assert!(cat == *"abcdef");
+        let expected = String::from("abcdef");
+        assert!(cat == expected);
+        assert!(cat != "abcdeg");
+        assert!(cat != "abcde");
+        assert!(cat != "abcdefg");
+        let empty = String::new();
+        assert!(cat != empty);
+        assert!(CAT + "" == empty);
+        assert!(CAT + 'é' != "e");
+        assert!(CAT == empty);
+        assert!(CAT != expected);
+    }
+
+    #[test]
+    fn ordered() {
+        let cat = CAT + "ab" + 'c' + String::from("de");
+        for &(other, ord) in &[
+            ("abcde", Ordering::Equal),
+            ("abcd", Ordering::Greater),
+            ("abcdef", Ordering::Less),
+            ("abd", Ordering::Less),
+            ("abb", Ordering::Greater),
+            ("", Ordering::Greater),
+        ] {
+            assert_eq!(cat.cmp_str(other), ord, "{}", other);
+            assert_eq!(cat.cmp_str(other), "abcde".cmp(other));
+        }
+        let max = String::from("abcde");
+        assert!(cat < "b" && cat > *"a" && cat <= max);
+        assert_eq!((CAT + "x").cmp_str("y"), Ordering::Less);
+        assert_eq!(CAT.cmp_str(""), Ordering::Equal);
+        assert!(CAT < "a");
+    }
+}
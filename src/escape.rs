@@ -0,0 +1,444 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Pieces that escape other pieces while they are appended.
+//!
+//! Every adapter wraps a piece and escapes its segments on the fly,
+//! so the escaped text is never materialized separately. The size
+//! hint of an adapter is exact; it is computed by walking the
+//! segments of the wrapped piece once.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use sconcat::CAT;
+//! use sconcat::escape::html;
+//!
+//! let user_input = "<b>Tom & Jerry</b>";
+//! let cell = CAT + "<td>" + html(user_input) + "</td>";
+//! let expected = "<td>&lt;b&gt;Tom &amp; Jerry&lt;/b&gt;</td>";
+//! assert_eq!(String::from(cell), expected);
+//! ```
+
+use cat::Cat;
+use std::fmt::{self, Display};
+use url::encode;
+
+// Passes `s` to `f` in runs of bytes that need no escaping and the
+// escapes of the other bytes. Only ASCII bytes may be escaped, so
+// the runs are always on character boundaries.
+fn escape_ascii(
+    s: &str,
+    escape: fn(u8) -> Option<&'static str>,
+    f: &mut dyn FnMut(&str),
+) {
+    let mut start = 0;
+    for (i, &b) in s.as_bytes().iter().enumerate() {
+        if let Some(escaped) = escape(b) {
+            if start < i {
+                f(&s[start..i]);
+            }
+            f(escaped);
+            start = i + 1;
+        }
+    }
+    if start < s.len() {
+        f(&s[start..]);
+    }
+}
+
+macro_rules! escape_piece {
+    ($Piece:ident, $escape_segments:expr) => {
+        impl<T: Cat> Cat for $Piece<T> {
+            fn size_hint(&self) -> usize {
+                let mut len = 0usize;
+                self.for_each_segment(&mut |segment| len += segment.len());
+                len
+            }
+
+            fn append_to(&self, s: &mut String) {
+                self.for_each_segment(&mut |segment| s.push_str(segment));
+            }
+
+            fn for_each_segment(&self, f: &mut dyn FnMut(&str)) {
+                let escape_segments: fn(&Self, &mut dyn FnMut(&str)) =
+                    $escape_segments;
+                escape_segments(self, f);
+            }
+        }
+
+        impl<T: Cat> Display for $Piece<T> {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                let mut result = Ok(());
+                self.for_each_segment(&mut |segment| {
+                    if result.is_ok() {
+                        result = f.write_str(segment);
+                    }
+                });
+                result
+            }
+        }
+    };
+}
+
+/// A piece that escapes another piece for HTML.
+///
+/// This is created by the [`html`](fn.html.html) function.
+#[derive(Clone, Copy, Debug)]
+pub struct Html<T: Cat> {
+    inner: T,
+}
+
+/// Creates a piece that escapes `value` for HTML text and quoted
+/// attribute values.
+///
+/// The characters `&`, `<`, `>`, `"` and `'` are replaced by
+/// `&amp;`, `&lt;`, `&gt;`, `&quot;` and `&#39;`.
+///
+/// # Examples
+///
+/// ```rust
+/// use sconcat::CAT;
+/// use sconcat::escape::html;
+///
+/// let title = String::from("\"Q&A\"");
+/// let attr = CAT + "<a title=\"" + html(title) + "\">";
+/// assert_eq!(String::from(attr), "<a title=\"&quot;Q&amp;A&quot;\">");
+/// ```
+pub fn html<T: Cat>(value: T) -> Html<T> {
+    Html { inner: value }
+}
+
+fn html_escape(b: u8) -> Option<&'static str> {
+    match b {
+        b'&' => Some("&amp;"),
+        b'<' => Some("&lt;"),
+        b'>' => Some("&gt;"),
+        b'"' => Some("&quot;"),
+        b'\'' => Some("&#39;"),
+        _ => None,
+    }
+}
+
+escape_piece!(Html, |html, f| {
+    html.inner
+        .for_each_segment(&mut |segment| escape_ascii(segment, html_escape, f))
+});
+
+/// A piece that escapes another piece for a JSON string.
+///
+/// This is created by the [`json`](fn.json.html) function.
+#[derive(Clone, Copy, Debug)]
+pub struct Json<T: Cat> {
+    inner: T,
+}
+
+/// Creates a piece that escapes `value` for use inside a JSON string
+/// literal.
+///
+/// As specified by RFC 8259, `"` and `\` are escaped with a
+/// backslash, and control characters U+0000 to U+001F are escaped
+/// with their short forms `\b`, `\t`, `\n`, `\f` and `\r` where
+/// available, or as `\u00XX` otherwise. The surrounding quotes are
+/// not added.
+///
+/// # Examples
+///
+/// ```rust
+/// use sconcat::CAT;
+/// use sconcat::escape::json;
+///
+/// let msg = "say \"hi\"\n";
+/// let payload = CAT + "{\"msg\":\"" + json(msg) + "\"}";
+/// assert_eq!(String::from(payload), r#"{"msg":"say \"hi\"\n"}"#);
+/// ```
+pub fn json<T: Cat>(value: T) -> Json<T> {
+    Json { inner: value }
+}
+
+const JSON_CONTROL: [&str; 32] = [
+    "\\u0000", "\\u0001", "\\u0002", "\\u0003", "\\u0004", "\\u0005",
+    "\\u0006", "\\u0007", "\\b", "\\t", "\\n", "\\u000b", "\\f", "\\r",
+    "\\u000e", "\\u000f", "\\u0010", "\\u0011", "\\u0012", "\\u0013",
+    "\\u0014", "\\u0015", "\\u0016", "\\u0017", "\\u0018", "\\u0019",
+    "\\u001a", "\\u001b", "\\u001c", "\\u001d", "\\u001e", "\\u001f",
+];
+
+fn json_escape(b: u8) -> Option<&'static str> {
+    match b {
+        b'"' => Some("\\\""),
+        b'\\' => Some("\\\\"),
+        0..=0x1f => Some(JSON_CONTROL[usize::from(b)]),
+        _ => None,
+    }
+}
+
+escape_piece!(Json, |json, f| {
+    json.inner
+        .for_each_segment(&mut |segment| escape_ascii(segment, json_escape, f))
+});
+
+/// The set of characters left unencoded by a [`Url`](struct.Url.html)
+/// piece.
+///
+/// ASCII alphanumerics and `-._~` are never encoded, and non-ASCII
+/// characters are always encoded as the percent-encoded bytes of
+/// their UTF-8 encoding.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EncodeSet {
+    /// Everything else is encoded, so that the result can be used as
+    /// any single component of a URL.
+    Component,
+    /// `!$&'()*+,;=:@/` are also left unencoded, as in a path.
+    Path,
+    /// `!$'()*,;:@/?` are also left unencoded, as in a query; `&`, `=`
+    /// and `+` are encoded as they separate or encode parameters.
+    Query,
+    /// `!$&'()*+,;=:@/?` are also left unencoded, as in a fragment.
+    Fragment,
+}
+
+impl EncodeSet {
+    fn keep(self) -> fn(u8) -> bool {
+        fn keep_with(b: u8, extra: &[u8]) -> bool {
+            b.is_ascii_alphanumeric()
+                || b"-._~".contains(&b)
+                || extra.contains(&b)
+        }
+        match self {
+            EncodeSet::Component => |b| keep_with(b, b""),
+            EncodeSet::Path => |b| keep_with(b, b"!$&'()*+,;=:@/"),
+            EncodeSet::Query => |b| keep_with(b, b"!$'()*,;:@/?"),
+            EncodeSet::Fragment => |b| keep_with(b, b"!$&'()*+,;=:@/?"),
+        }
+    }
+}
+
+/// A piece that percent-encodes another piece.
+///
+/// This is created by the [`url`](fn.url.html) and
+/// [`url_with`](fn.url_with.html) functions.
+#[derive(Clone, Copy, Debug)]
+pub struct Url<T: Cat> {
+    inner: T,
+    set: EncodeSet,
+}
+
+/// Creates a piece that percent-encodes `value` as a URL component.
+///
+/// This is the same as [`url_with`](fn.url_with.html) with
+/// [`EncodeSet::Component`](enum.EncodeSet.html#variant.Component).
+///
+/// # Examples
+///
+/// ```rust
+/// use sconcat::CAT;
+/// use sconcat::escape::url;
+///
+/// let query = "fish & chips";
+/// let cat = CAT + "https://example.com/search?q=" + url(query);
+/// let expected = "https://example.com/search?q=fish%20%26%20chips";
+/// assert_eq!(String::from(cat), expected);
+/// ```
+pub fn url<T: Cat>(value: T) -> Url<T> {
+    url_with(value, EncodeSet::Component)
+}
+
+/// Creates a piece that percent-encodes `value`, leaving the
+/// characters in `set` unencoded.
+///
+/// # Examples
+///
+/// ```rust
+/// use sconcat::CAT;
+/// use sconcat::escape::{EncodeSet, url_with};
+///
+/// let path = "docs/café menu";
+/// let cat = CAT + "https://example.com/" + url_with(path, EncodeSet::Path);
+/// let expected = "https://example.com/docs/caf%C3%A9%20menu";
+/// assert_eq!(String::from(cat), expected);
+/// ```
+pub fn url_with<T: Cat>(value: T, set: EncodeSet) -> Url<T> {
+    Url { inner: value, set }
+}
+
+escape_piece!(Url, |url, f| {
+    let keep = url.set.keep();
+    url.inner
+        .for_each_segment(&mut |segment| encode(segment, keep, f))
+});
+
+/// A piece that quotes another piece for a POSIX shell.
+///
+/// This is created by the [`sh`](fn.sh.html) function.
+#[derive(Clone, Copy, Debug)]
+pub struct Sh<T: Cat> {
+    inner: T,
+}
+
+/// Creates a piece that quotes `arg` as a single POSIX shell word.
+///
+/// The argument is enclosed in single quotes, and every single quote
+/// inside it is replaced by `'\''`, which closes the quoted string,
+/// adds an escaped quote and opens a new quoted string. An empty
+/// argument becomes `''`.
+///
+/// # Examples
+///
+/// ```rust
+/// use sconcat::CAT;
+/// use sconcat::escape::sh;
+///
+/// let file = "it's here.txt";
+/// let cmd = CAT + "cat " + sh(file);
+/// assert_eq!(String::from(cmd), r"cat 'it'\''s here.txt'");
+/// ```
+pub fn sh<T: Cat>(arg: T) -> Sh<T> {
+    Sh { inner: arg }
+}
+
+fn sh_escape(b: u8) -> Option<&'static str> {
+    match b {
+        b'\'' => Some("'\\''"),
+        _ => None,
+    }
+}
+
+escape_piece!(Sh, |sh, f| {
+    f("'");
+    sh.inner
+        .for_each_segment(&mut |segment| escape_ascii(segment, sh_escape, f));
+    f("'");
+});
+
+/// A piece that quotes another piece as a CSV field.
+///
+/// This is created by the [`csv`](fn.csv.html) function.
+#[derive(Clone, Copy, Debug)]
+pub struct Csv<T: Cat> {
+    inner: T,
+}
+
+/// Creates a piece that quotes `field` as a CSV field when needed.
+///
+/// As in RFC 4180, a field that contains a comma, a double quote, a
+/// carriage return or a line feed is enclosed in double quotes, and
+/// every double quote inside it is doubled. Other fields are left
+/// unchanged.
+///
+/// # Examples
+///
+/// ```rust
+/// use sconcat::CAT;
+/// use sconcat::escape::csv;
+///
+/// let row = CAT + csv("plain") + ',' + csv("say \"hi\", then go") + '\n';
+/// let expected = "plain,\"say \"\"hi\"\", then go\"\n";
+/// assert_eq!(String::from(row), expected);
+/// ```
+pub fn csv<T: Cat>(field: T) -> Csv<T> {
+    Csv { inner: field }
+}
+
+fn csv_escape(b: u8) -> Option<&'static str> {
+    match b {
+        b'"' => Some("\"\""),
+        _ => None,
+    }
+}
+
+escape_piece!(Csv, |csv, f| {
+    let mut quote = false;
+    csv.inner.for_each_segment(&mut |segment| {
+        quote = quote
+            || segment
+                .bytes()
+                .any(|b| matches!(b, b',' | b'"' | b'\r' | b'\n'));
+    });
+    if quote {
+        f("\"");
+        csv.inner.for_each_segment(&mut |segment| {
+            escape_ascii(segment, csv_escape, f)
+        });
+        f("\"");
+    } else {
+        csv.inner.for_each_segment(f);
+    }
+});
+
+#[cfg(test)]
+mod tests {
+    use CAT;
+    use Dbg;
+    use cat::Cat;
+    use super::{csv, html, json, sh, url, url_with, EncodeSet};
+
+    #[test]
+    fn html_escapes() {
+        let value = CAT + "<a href='x'>" + '&' + Dbg("é\"") + String::new();
+        let cat = CAT + "<p>" + html(value) + "</p>";
+        let s = String::from(cat);
+        let expected = concat!(
+            "<p>&lt;a href=&#39;x&#39;&gt;&amp;",
+            "&quot;é\\&quot;&quot;</p>"
+        );
+        assert_eq!(s, expected);
+        assert_eq!(s.len(), s.capacity());
+        assert_eq!(html("plain").to_string(), "plain");
+        assert_eq!(html("").size_hint(), 0);
+        assert_eq!(html("<>").size_hint(), 8);
+    }
+
+    #[test]
+    fn json_escapes() {
+        let value = CAT + "a\"b\\c" + '\n' + "\u{1}\u{1f}\u{7f}é";
+        let cat = CAT + '"' + json(value) + '"';
+        let s = String::from(cat);
+        assert_eq!(s, "\"a\\\"b\\\\c\\n\\u0001\\u001f\u{7f}é\"");
+        assert_eq!(s.len(), s.capacity());
+        assert_eq!(json("\t\r\u{8}\u{c}").to_string(), "\\t\\r\\b\\f");
+    }
+
+    #[test]
+    fn url_encodes() {
+        let value = CAT + "a/b?c" + '&' + "d=e+f#g h" + 'é';
+        let s = String::from(CAT + '?' + url(value));
+        assert_eq!(s, "?a%2Fb%3Fc%26d%3De%2Bf%23g%20h%C3%A9");
+        assert_eq!(s.len(), s.capacity());
+        let unreserved = "AZaz09-._~";
+        assert_eq!(url(unreserved).to_string(), unreserved);
+        let all = "!$&'()*+,;=:@/?#";
+        let path = url_with(all, EncodeSet::Path).to_string();
+        assert_eq!(path, "!$&'()*+,;=:@/%3F%23");
+        let query = url_with(all, EncodeSet::Query).to_string();
+        assert_eq!(query, "!$%26'()*%2B,;%3D:@/?%23");
+        let fragment = url_with(all, EncodeSet::Fragment).to_string();
+        assert_eq!(fragment, "!$&'()*+,;=:@/?%23");
+    }
+
+    #[test]
+    fn sh_quotes() {
+        let arg = CAT + "don't" + '\'' + "$HOME; ls";
+        let s = String::from(CAT + "echo " + sh(arg));
+        assert_eq!(s, r"echo 'don'\''t'\''$HOME; ls'");
+        assert_eq!(s.len(), s.capacity());
+        assert_eq!(sh("").to_string(), "''");
+        assert_eq!(sh("'").size_hint(), 6);
+    }
+
+    #[test]
+    fn csv_quotes() {
+        let field = CAT + "a \"b\"" + '\n' + "c";
+        let s = String::from(CAT + csv("x") + ',' + csv(field) + '\n');
+        assert_eq!(s, "x,\"a \"\"b\"\"\nc\"\n");
+        assert_eq!(s.len(), s.capacity());
+        assert_eq!(csv("").to_string(), "");
+        assert_eq!(csv("1,2").to_string(), "\"1,2\"");
+        assert_eq!(csv("a\rb").size_hint(), 5);
+    }
+}
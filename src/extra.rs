@@ -0,0 +1,89 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use cat::{Cat, CatOne, CatStart};
+use std::fmt::{self, Display};
+
+/// An empty piece that reserves extra capacity.
+///
+/// This is created by the
+/// [`with_extra`](struct.CatStart.html#method.with_extra) method of
+/// `CAT`. It has no text, but its size hint is the number of extra
+/// bytes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Extra {
+    bytes: usize,
+}
+
+impl CatStart {
+    /// Starts a concatenation that reserves `bytes` more bytes than
+    /// it needs.
+    ///
+    /// The extra bytes are added to the size hint, so the `String`
+    /// the concatenation is converted into has room for pushes that
+    /// follow without reallocating. As the concatenation no longer
+    /// starts with the first real piece, a leading `String` piece is
+    /// copied rather than reused.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use sconcat::CAT;
+    ///
+    /// let mut s = String::from(CAT.with_extra(16) + "GET " + "/index");
+    /// assert!(s.capacity() >= 26);
+    /// let cap = s.capacity();
+    /// s.push_str(" HTTP/1.1\r\n");
+    /// assert_eq!(s.capacity(), cap);
+    /// assert_eq!(s, "GET /index HTTP/1.1\r\n");
+    /// ```
+    pub fn with_extra(self, bytes: usize) -> CatOne<Extra> {
+        CatOne {
+            inner: Extra { bytes },
+        }
+    }
+}
+
+impl Cat for Extra {
+    fn size_hint(&self) -> usize {
+        self.bytes
+    }
+
+    fn append_to(&self, _s: &mut String) {}
+
+    fn for_each_segment(&self, _f: &mut dyn FnMut(&str)) {}
+
+    fn segment_count(&self) -> usize {
+        0
+    }
+}
+
+impl Display for Extra {
+    fn fmt(&self, _f: &mut fmt::Formatter) -> fmt::Result {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use CAT;
+
+    #[test]
+    fn extra() {
+        let cat = CAT.with_extra(5) + "ab" + 'c';
+        assert_eq!((cat.size_hint(), cat.len()), (8, 3));
+        assert_eq!(cat.to_string(), "abc");
+        let s = String::from(cat);
+        assert_eq!((s.as_str(), s.capacity()), ("abc", 8));
+        let s = String::from(CAT.with_extra(4));
+        assert_eq!((s.as_str(), s.capacity()), ("", 4));
+        let mut s = String::new();
+        s += CAT.with_extra(10) + "x";
+        assert!(s.capacity() >= 11);
+    }
+}
@@ -0,0 +1,185 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use cat::{self, Cat, CatMany, CatOne, CatStart};
+use std::collections::TryReserveError;
+use std::error::Error;
+use std::fmt::{self, Display};
+
+/// An error returned when a concatenation cannot be materialized.
+///
+/// This is returned by the `try_into_string` and `try_append_to`
+/// methods.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CatError {
+    /// The total size of the concatenation overflows `usize`.
+    Overflow,
+    /// The buffer for the concatenation could not be allocated.
+    Alloc(TryReserveError),
+}
+
+impl Display for CatError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            CatError::Overflow => {
+                f.write_str("concatenation size overflows usize")
+            }
+            CatError::Alloc(ref e) => {
+                write!(f, "cannot allocate concatenation: {}", e)
+            }
+        }
+    }
+}
+
+impl Error for CatError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match *self {
+            CatError::Overflow => None,
+            CatError::Alloc(ref e) => Some(e),
+        }
+    }
+}
+
+// The size hint of a concatenation panics on overflow.
+fn checked_size_hint<C: Cat>(src: &C) -> Result<usize, CatError> {
+    src.checked_size_hint().ok_or(CatError::Overflow)
+}
+
+fn try_append<C: Cat>(s: &mut String, src: &C) -> Result<(), CatError> {
+    let additional = checked_size_hint(src)?;
+    s.try_reserve(additional).map_err(CatError::Alloc)?;
+    cat::append_cat(s, src);
+    Ok(())
+}
+
+fn try_into_string<C: Cat>(src: &C) -> Result<String, CatError> {
+    let mut s = String::new();
+    s.try_reserve_exact(checked_size_hint(src)?)
+        .map_err(CatError::Alloc)?;
+    cat::append_cat(&mut s, src);
+    Ok(s)
+}
+
+impl<L: Cat, R: Cat> CatMany<L, R> {
+    /// Converts the concatenation to a `String`, returning an error
+    /// instead of panicking or aborting if the size overflows or the
+    /// buffer cannot be allocated.
+    ///
+    /// The buffer is reserved with `String::try_reserve_exact`
+    /// before anything is appended. Only the size hints are
+    /// checked, so a piece whose size hint is too small can still
+    /// make the buffer grow infallibly.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use sconcat::{CAT, CatError};
+    ///
+    /// let s = (CAT + "id=" + "7").try_into_string().unwrap();
+    /// assert_eq!(s, "id=7");
+    ///
+    /// let huge = sconcat::repeat("x", usize::MAX);
+    /// let err = (CAT + "a" + huge).try_into_string().unwrap_err();
+    /// assert_eq!(err, CatError::Overflow);
+    /// ```
+    pub fn try_into_string(self) -> Result<String, CatError> {
+        try_into_string(&self)
+    }
+
+    /// Appends the concatenation to `s`, returning an error instead
+    /// of panicking or aborting if the size overflows or the buffer
+    /// cannot be grown.
+    ///
+    /// If an error is returned, `s` is left unchanged.
+    pub fn try_append_to(&self, s: &mut String) -> Result<(), CatError> {
+        try_append(s, self)
+    }
+}
+
+impl<T: Cat> CatOne<T> {
+    /// Converts the concatenation to a `String`, returning an error
+    /// instead of aborting if the buffer cannot be allocated.
+    pub fn try_into_string(self) -> Result<String, CatError> {
+        try_into_string(&self.inner)
+    }
+
+    /// Appends the concatenation to `s`, returning an error instead
+    /// of aborting if the buffer cannot be grown.
+    ///
+    /// If an error is returned, `s` is left unchanged.
+    pub fn try_append_to(&self, s: &mut String) -> Result<(), CatError> {
+        try_append(s, &self.inner)
+    }
+}
+
+impl CatStart {
+    /// Converts the empty concatenation to a `String`.
+    ///
+    /// This never fails.
+    pub fn try_into_string(self) -> Result<String, CatError> {
+        Ok(String::new())
+    }
+
+    /// Appends nothing to `s`.
+    ///
+    /// This never fails.
+    pub fn try_append_to(&self, _s: &mut String) -> Result<(), CatError> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use CAT;
+    use CatError;
+    use repeat;
+    use std::error::Error;
+
+    #[test]
+    fn success() {
+        let cat = CAT + "ab" + 'c' + String::from("de");
+        let mut s = String::from(">");
+        cat.try_append_to(&mut s).unwrap();
+        CAT.try_append_to(&mut s).unwrap();
+        (CAT + '<').try_append_to(&mut s).unwrap();
+        assert_eq!(s, ">abcde<");
+        let s = cat.try_into_string().unwrap();
+        assert_eq!((s.as_str(), s.capacity()), ("abcde", 5));
+        assert_eq!((CAT + "x").try_into_string().unwrap(), "x");
+        assert_eq!(CAT.try_into_string().unwrap(), "");
+    }
+
+    #[test]
+    fn overflow() {
+        let cat = CAT + "a" + repeat("x", usize::MAX);
+        assert_eq!(cat.try_into_string(), Err(CatError::Overflow));
+        let mut s = String::from("keep");
+        assert_eq!(cat.try_append_to(&mut s), Err(CatError::Overflow));
+        assert_eq!(s, "keep");
+        let err = CatError::Overflow;
+        assert_eq!(err.to_string(), "concatenation size overflows usize");
+        assert!(err.source().is_none());
+    }
+
+    #[test]
+    fn alloc() {
+        let cat = CAT + repeat("x", usize::MAX / 2);
+        let mut s = String::from("keep");
+        match cat.try_append_to(&mut s) {
+            Err(CatError::Alloc(_)) => {}
+            other => panic!("unexpected {:?}", other),
+        }
+        assert_eq!(s, "keep");
+        match cat.try_into_string() {
+            Err(ref err @ CatError::Alloc(_)) => {
+                assert!(err.source().is_some())
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,139 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// A thin bridge to fast_fmt. Displaying a concatenation goes through
+// the crate's own sink path, so the pieces only need to implement
+// `Cat`; only the debug output relies on fast_fmt for the pieces.
+
+use cat::{Cat, CatMany, CatOne, CatStart};
+use fast_fmt::{Debug, Display, Fmt, Write};
+use sink::{self, write_cat};
+
+// Passes the segments of a concatenation to a fast_fmt writer.
+struct Bridge<'a, W: 'a>(&'a mut W);
+
+impl<'a, W: Write> sink::Write for Bridge<'a, W> {
+    type Error = W::Error;
+    fn write_str(&mut self, s: &str) -> Result<(), W::Error> {
+        Fmt::fmt(s, self.0, &Display)
+    }
+}
+
+impl<L: Cat, R: Cat> Fmt for CatMany<L, R> {
+    fn fmt<W: Write>(
+        &self,
+        writer: &mut W,
+        _strategy: &Display,
+    ) -> Result<(), W::Error> {
+        write_cat(self, &mut Bridge(writer))
+    }
+
+    fn size_hint(&self, _strategy: &Display) -> usize {
+        Cat::size_hint(self)
+    }
+}
+
+impl<T: Cat> Fmt for CatOne<T> {
+    fn fmt<W: Write>(
+        &self,
+        writer: &mut W,
+        _strategy: &Display,
+    ) -> Result<(), W::Error> {
+        write_cat(&self.inner, &mut Bridge(writer))
+    }
+
+    fn size_hint(&self, _strategy: &Display) -> usize {
+        Cat::size_hint(&self.inner)
+    }
+}
+
+impl Fmt for CatStart {
+    // Prints nothing
+    fn fmt<W: Write>(
+        &self,
+        _writer: &mut W,
+        _strategy: &Display,
+    ) -> Result<(), W::Error> {
+        Ok(())
+    }
+
+    fn size_hint(&self, _strategy: &Display) -> usize {
+        0
+    }
+}
+
+impl<L, R> Fmt<Debug> for CatMany<L, R>
+where
+    L: Cat + Fmt<Debug>,
+    R: Cat + Fmt<Debug>,
+{
+    fn fmt<W: Write>(
+        &self,
+        writer: &mut W,
+        strategy: &Debug,
+    ) -> Result<(), W::Error> {
+        self.lhs.fmt(writer, strategy)?;
+        Fmt::fmt(" + ", writer, &Display)?;
+        self.rhs.fmt(writer, strategy)
+    }
+
+    fn size_hint(&self, strategy: &Debug) -> usize {
+        Fmt::<Debug>::size_hint(&self.lhs, strategy)
+            + 3
+            + Fmt::<Debug>::size_hint(&self.rhs, strategy)
+    }
+}
+
+impl<T: Cat + Fmt<Debug>> Fmt<Debug> for CatOne<T> {
+    fn fmt<W: Write>(
+        &self,
+        writer: &mut W,
+        strategy: &Debug,
+    ) -> Result<(), W::Error> {
+        self.inner.fmt(writer, strategy)
+    }
+
+    fn size_hint(&self, strategy: &Debug) -> usize {
+        Fmt::<Debug>::size_hint(&self.inner, strategy)
+    }
+}
+
+impl Fmt<Debug> for CatStart {
+    fn fmt<W: Write>(
+        &self,
+        writer: &mut W,
+        _strategy: &Debug,
+    ) -> Result<(), W::Error> {
+        Fmt::fmt("\"\"", writer, &Display)
+    }
+
+    fn size_hint(&self, _strategy: &Debug) -> usize {
+        2
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use CAT;
+    use fast_fmt::{Debug, Display, Fmt};
+
+    #[test]
+    fn bridge() {
+        let cat = CAT + "a" + 'é' + String::from("bc");
+        let mut s = String::new();
+        Fmt::fmt(&cat, &mut s, &Display).unwrap();
+        Fmt::fmt(&(CAT + 'd'), &mut s, &Display).unwrap();
+        Fmt::fmt(&CAT, &mut s, &Display).unwrap();
+        assert_eq!(s, "aébcd");
+        assert_eq!(Fmt::size_hint(&cat, &Display), 5);
+        assert_eq!(Fmt::size_hint(&(CAT + 'd'), &Display), 1);
+        let mut s = String::new();
+        Fmt::fmt(&CAT, &mut s, &Debug).unwrap();
+        assert_eq!(s, "\"\"");
+    }
+}
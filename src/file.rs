@@ -0,0 +1,135 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use cat::{Cat, CatMany, CatOne, CatStart};
+use std::cmp;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+// Largest buffer used when writing a concatenation to a file, so
+// that a huge concatenation is written in chunks rather than copied
+// into a buffer of its own size.
+const MAX_BUF: usize = 64 * 1024;
+
+// Writes the segments through a buffer sized from the size hint, so
+// a concatenation of up to `MAX_BUF` bytes is written with a single
+// write. Segments at least as large as the buffer bypass it.
+fn write_file<C: Cat>(cat: &C, file: File) -> io::Result<()> {
+    let capacity = cmp::min(cat.size_hint(), MAX_BUF);
+    let mut w = BufWriter::with_capacity(cmp::max(capacity, 1), file);
+    let mut result = Ok(());
+    cat.for_each_segment(&mut |segment| {
+        if result.is_ok() {
+            result = w.write_all(segment.as_bytes());
+        }
+    });
+    result?;
+    w.flush()
+}
+
+fn write_path<C: Cat>(cat: &C, path: &Path) -> io::Result<()> {
+    write_file(cat, File::create(path)?)
+}
+
+fn append_path<C: Cat>(cat: &C, path: &Path) -> io::Result<()> {
+    let file = OpenOptions::new().append(true).create(true).open(path)?;
+    write_file(cat, file)
+}
+
+impl<L: Cat, R: Cat> CatMany<L, R> {
+    /// Writes the concatenation to a file, creating it if it does
+    /// not exist and truncating it if it does.
+    ///
+    /// This is like `fs::write(path, String::from(cat))` without
+    /// materializing the concatenation first. The segments are
+    /// written through a buffer sized from the size hint, of at most
+    /// 64 KiB.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use sconcat::CAT;
+    /// use std::{env, fs};
+    ///
+    /// let path = env::temp_dir().join("sconcat-doc-write.conf");
+    /// let port = String::from("8080");
+    /// (CAT + "[server]\n" + "port=" + port + '\n').write_to_path(&path)?;
+    /// (CAT + "debug=" + "true\n").append_to_path(&path)?;
+    /// let contents = fs::read_to_string(&path)?;
+    /// assert_eq!(contents, "[server]\nport=8080\ndebug=true\n");
+    /// # fs::remove_file(&path)?;
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn write_to_path<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        write_path(self, path.as_ref())
+    }
+
+    /// Appends the concatenation to a file, creating it if it does
+    /// not exist.
+    pub fn append_to_path<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        append_path(self, path.as_ref())
+    }
+}
+
+impl<T: Cat> CatOne<T> {
+    /// Writes the concatenation to a file, creating it if it does
+    /// not exist and truncating it if it does.
+    pub fn write_to_path<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        write_path(&self.inner, path.as_ref())
+    }
+
+    /// Appends the concatenation to a file, creating it if it does
+    /// not exist.
+    pub fn append_to_path<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        append_path(&self.inner, path.as_ref())
+    }
+}
+
+impl CatStart {
+    /// Creates an empty file, or truncates it if it exists.
+    pub fn write_to_path<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        File::create(path).map(|_| ())
+    }
+
+    /// Creates an empty file if it does not exist, leaving an
+    /// existing file unchanged.
+    pub fn append_to_path<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        OpenOptions::new()
+            .append(true)
+            .create(true)
+            .open(path)
+            .map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use CAT;
+    use std::{env, fs, process};
+
+    #[test]
+    fn files() {
+        let name = format!("sconcat-test-{}.txt", process::id());
+        let path = env::temp_dir().join(name);
+        let big = "x".repeat(100_000);
+        (CAT + "a" + 'é' + big.as_str())
+            .write_to_path(&path)
+            .unwrap();
+        (CAT + "!").append_to_path(&path).unwrap();
+        CAT.append_to_path(&path).unwrap();
+        let contents = fs::read_to_string(&path).unwrap();
+        assert_eq!(contents.len(), 100_004);
+        assert!(contents.starts_with("aéxx") && contents.ends_with("x!"));
+        (CAT + "new" + '\n').write_to_path(&path).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "new\n");
+        CAT.write_to_path(&path).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "");
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,283 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Fixed-capacity strings. The size hint of a piece is not required
+// to be exact, so the segments are pushed one by one and the target
+// is restored to its original length if they do not all fit.
+
+#[cfg(feature = "arrayvec")]
+use arrayvec::ArrayString;
+use cat::Cat;
+#[cfg(any(feature = "arrayvec", feature = "heapless"))]
+use cat::{CatMany, CatOne, CatStart};
+#[cfg(feature = "heapless")]
+use heapless::String as HeaplessString;
+use std::error::Error;
+use std::fmt::{self, Display};
+
+/// An error returned when a concatenation does not fit in a
+/// fixed-capacity string.
+///
+/// This is returned by the `try_into_stack_string`,
+/// `write_to_heapless` and `try_append_to_array` methods. The target
+/// string is left unchanged when this error is returned.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CapacityError {
+    pub(crate) size: usize,
+    pub(crate) available: usize,
+}
+
+impl CapacityError {
+    /// Returns the size of the concatenation in bytes.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Returns the number of bytes that were available in the
+    /// target.
+    pub fn available(&self) -> usize {
+        self.available
+    }
+}
+
+impl Display for CapacityError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "concatenation of {} bytes does not fit in {} available bytes",
+            self.size,
+            self.available
+        )
+    }
+}
+
+impl Error for CapacityError {}
+
+// Pushes the segments with `push` while they fit, and returns the
+// total size of the segments, including the ones that did not fit.
+pub(crate) fn push_segments<C, F>(
+    cat: &C,
+    available: usize,
+    mut push: F,
+) -> usize
+where
+    C: Cat,
+    F: FnMut(&str),
+{
+    let mut size = 0;
+    cat.for_each_segment(&mut |segment| {
+        size += segment.len();
+        if size <= available {
+            push(segment);
+        }
+    });
+    size
+}
+
+#[cfg(feature = "heapless")]
+fn write_heapless<C: Cat, const N: usize>(
+    cat: &C,
+    s: &mut HeaplessString<N>,
+) -> Result<(), CapacityError> {
+    let len = s.len();
+    let available = N - len;
+    let size = push_segments(cat, available, |segment| {
+        s.push_str(segment).expect("segment should fit");
+    });
+    if size > available {
+        s.truncate(len);
+        return Err(CapacityError { size, available });
+    }
+    Ok(())
+}
+
+#[cfg(feature = "arrayvec")]
+fn append_array<C: Cat, const CAP: usize>(
+    cat: &C,
+    s: &mut ArrayString<CAP>,
+) -> Result<(), CapacityError> {
+    let len = s.len();
+    let available = CAP - len;
+    let size = push_segments(cat, available, |segment| s.push_str(segment));
+    if size > available {
+        s.truncate(len);
+        return Err(CapacityError { size, available });
+    }
+    Ok(())
+}
+
+#[cfg(feature = "heapless")]
+impl<L: Cat, R: Cat> CatMany<L, R> {
+    /// Appends the concatenation to a `heapless::String`.
+    ///
+    /// No allocations take place. If the concatenation does not fit
+    /// in the remaining capacity, an error is returned and the
+    /// string is left unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # extern crate heapless;
+    /// # extern crate sconcat;
+    /// use heapless::String;
+    /// use sconcat::CAT;
+    ///
+    /// # fn main() {
+    /// let mut s = String::<8>::new();
+    /// (CAT + "t=" + "21C").write_to_heapless(&mut s).unwrap();
+    /// assert_eq!(s, "t=21C");
+    /// let err = (CAT + ", " + "ok").write_to_heapless(&mut s).unwrap_err();
+    /// assert_eq!((err.size(), err.available()), (4, 3));
+    /// assert_eq!(s, "t=21C");
+    /// # }
+    /// ```
+    pub fn write_to_heapless<const N: usize>(
+        &self,
+        s: &mut HeaplessString<N>,
+    ) -> Result<(), CapacityError> {
+        write_heapless(self, s)
+    }
+}
+
+#[cfg(feature = "heapless")]
+impl<T: Cat> CatOne<T> {
+    /// Appends the concatenation to a `heapless::String`.
+    ///
+    /// No allocations take place. If the concatenation does not fit
+    /// in the remaining capacity, an error is returned and the
+    /// string is left unchanged.
+    pub fn write_to_heapless<const N: usize>(
+        &self,
+        s: &mut HeaplessString<N>,
+    ) -> Result<(), CapacityError> {
+        write_heapless(&self.inner, s)
+    }
+}
+
+#[cfg(feature = "heapless")]
+impl CatStart {
+    /// Appends nothing to a `heapless::String`.
+    ///
+    /// This never fails.
+    pub fn write_to_heapless<const N: usize>(
+        &self,
+        _s: &mut HeaplessString<N>,
+    ) -> Result<(), CapacityError> {
+        Ok(())
+    }
+}
+
+#[cfg(feature = "arrayvec")]
+impl<L: Cat, R: Cat> CatMany<L, R> {
+    /// Appends the concatenation to an `arrayvec::ArrayString`.
+    ///
+    /// No allocations take place, so this can be used where heap
+    /// allocation is not permitted. If the concatenation does not
+    /// fit in the remaining capacity, an error is returned and the
+    /// string is left unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # extern crate arrayvec;
+    /// # extern crate sconcat;
+    /// use arrayvec::ArrayString;
+    /// use sconcat::CAT;
+    ///
+    /// # fn main() {
+    /// let mut s = ArrayString::<16>::new();
+    /// (CAT + "signal " + "15").try_append_to_array(&mut s).unwrap();
+    /// assert_eq!(s.as_str(), "signal 15");
+    /// let cat = CAT + " received " + "SIGTERM";
+    /// let err = cat.try_append_to_array(&mut s).unwrap_err();
+    /// assert_eq!((err.size(), err.available()), (17, 7));
+    /// assert_eq!(s.as_str(), "signal 15");
+    /// # }
+    /// ```
+    pub fn try_append_to_array<const CAP: usize>(
+        &self,
+        s: &mut ArrayString<CAP>,
+    ) -> Result<(), CapacityError> {
+        append_array(self, s)
+    }
+}
+
+#[cfg(feature = "arrayvec")]
+impl<T: Cat> CatOne<T> {
+    /// Appends the concatenation to an `arrayvec::ArrayString`.
+    ///
+    /// No allocations take place. If the concatenation does not fit
+    /// in the remaining capacity, an error is returned and the
+    /// string is left unchanged.
+    pub fn try_append_to_array<const CAP: usize>(
+        &self,
+        s: &mut ArrayString<CAP>,
+    ) -> Result<(), CapacityError> {
+        append_array(&self.inner, s)
+    }
+}
+
+#[cfg(feature = "arrayvec")]
+impl CatStart {
+    /// Appends nothing to an `arrayvec::ArrayString`.
+    ///
+    /// This never fails.
+    pub fn try_append_to_array<const CAP: usize>(
+        &self,
+        _s: &mut ArrayString<CAP>,
+    ) -> Result<(), CapacityError> {
+        Ok(())
+    }
+}
+
+#[cfg(all(test, any(feature = "arrayvec", feature = "heapless")))]
+mod tests {
+    use CAT;
+    use Dbg;
+    #[cfg(feature = "arrayvec")]
+    use arrayvec::ArrayString;
+    #[cfg(feature = "heapless")]
+    use heapless::String as HeaplessString;
+
+    #[cfg(feature = "heapless")]
+    #[test]
+    fn heapless() {
+        let mut s = HeaplessString::<10>::new();
+        let cat = CAT + "ab" + 'é' + Dbg(7);
+        cat.write_to_heapless(&mut s).unwrap();
+        assert_eq!(s, "abé7");
+        (CAT + "cdef").write_to_heapless(&mut s).unwrap();
+        assert_eq!(s, "abé7cdef");
+        let err = cat.write_to_heapless(&mut s).unwrap_err();
+        assert_eq!((err.size(), err.available()), (5, 1));
+        assert_eq!(
+            err.to_string(),
+            "concatenation of 5 bytes does not fit in 1 available bytes"
+        );
+        assert_eq!(s, "abé7cdef");
+        CAT.write_to_heapless(&mut s).unwrap();
+        (CAT + "g").write_to_heapless(&mut s).unwrap();
+        assert_eq!(s, "abé7cdefg");
+    }
+
+    #[cfg(feature = "arrayvec")]
+    #[test]
+    fn arrayvec() {
+        let mut s = ArrayString::<10>::new();
+        let cat = CAT + "ab" + 'é' + Dbg(7);
+        cat.try_append_to_array(&mut s).unwrap();
+        (CAT + "cdef").try_append_to_array(&mut s).unwrap();
+        assert_eq!(s.as_str(), "abé7cdef");
+        let err = cat.try_append_to_array(&mut s).unwrap_err();
+        assert_eq!((err.size(), err.available()), (5, 1));
+        assert_eq!(s.as_str(), "abé7cdef");
+        CAT.try_append_to_array(&mut s).unwrap();
+        (CAT + "g").try_append_to_array(&mut s).unwrap();
+        assert_eq!(s.as_str(), "abé7cdefg");
+    }
+}
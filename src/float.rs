@@ -0,0 +1,54 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use cat::Cat;
+use ryu::Buffer;
+
+// Floats are formatted into a stack buffer with the shortest
+// representation that round-trips, and the size hint formats the
+// float to find its exact length.
+macro_rules! float_cat {
+    ($($T:ty)*) => { $(
+        impl Cat for $T {
+            fn size_hint(&self) -> usize {
+                Buffer::new().format(*self).len()
+            }
+
+            fn append_to(&self, s: &mut String) {
+                s.push_str(Buffer::new().format(*self));
+            }
+
+            fn for_each_segment(&self, f: &mut dyn FnMut(&str)) {
+                f(Buffer::new().format(*self));
+            }
+
+            fn segment_count(&self) -> usize {
+                1
+            }
+        }
+    )* };
+}
+
+float_cat! { f32 f64 }
+
+#[cfg(test)]
+mod tests {
+    use CAT;
+    use std::f64;
+
+    #[test]
+    fn floats() {
+        let cat = CAT + "x=" + 1.5f64 + ", y=" + 0.1f32 + ", z=" + -1e300;
+        let s = String::from(cat);
+        assert_eq!(s, "x=1.5, y=0.1, z=-1e300");
+        assert_eq!(s.len(), s.capacity());
+        let mut s = String::from("[");
+        s += CAT + 2.0 + ' ' + f64::NAN + ' ' + f64::NEG_INFINITY + ']';
+        assert_eq!(s, "[2.0 NaN -inf]");
+    }
+}
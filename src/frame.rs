@@ -0,0 +1,183 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Length-prefixed frames. The size hint is not required to be exact,
+// so the header is reserved first and filled in with the actual
+// length once the payload has been appended, which keeps the frame
+// to a single pass over the pieces.
+
+use cat::{Cat, CatMany, CatOne, CatStart};
+use limit::LimitError;
+use std::cmp;
+
+/// The byte order of the length header of a frame.
+///
+/// This is used by the `into_frame` methods.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Endian {
+    /// Most significant byte first, as in network byte order.
+    Big,
+    /// Least significant byte first.
+    Little,
+}
+
+fn frame<C: Cat>(
+    cat: &C,
+    endian: Endian,
+    width: usize,
+) -> Result<Vec<u8>, LimitError> {
+    assert!(
+        (1..=8).contains(&width),
+        "frame length width must be from 1 to 8 bytes"
+    );
+    let max = u64::MAX >> (64 - 8 * width);
+    let limit = cmp::min(max, usize::MAX as u64) as usize;
+    let payload = match cat.checked_size_hint() {
+        Some(hint) => cmp::min(hint, limit),
+        None => {
+            let size = usize::MAX;
+            return Err(LimitError { size, limit });
+        }
+    };
+    let mut v = Vec::with_capacity(width.saturating_add(payload));
+    v.resize(width, 0);
+    let mut size = 0usize;
+    cat.for_each_segment(&mut |segment| {
+        size = size.saturating_add(segment.len());
+        if size <= limit {
+            v.extend_from_slice(segment.as_bytes());
+        }
+    });
+    if size > limit {
+        return Err(LimitError { size, limit });
+    }
+    match endian {
+        Endian::Big => {
+            let bytes = (size as u64).to_be_bytes();
+            v[..width].copy_from_slice(&bytes[8 - width..]);
+        }
+        Endian::Little => {
+            let bytes = (size as u64).to_le_bytes();
+            v[..width].copy_from_slice(&bytes[..width]);
+        }
+    }
+    Ok(v)
+}
+
+impl<L: Cat, R: Cat> CatMany<L, R> {
+    /// Converts the concatenation into a frame consisting of its
+    /// length in bytes followed by its bytes.
+    ///
+    /// The length is written as an unsigned integer of `width` bytes
+    /// in the byte order `endian`. The frame is built in a single
+    /// buffer with a capacity for the header and the size hint of
+    /// the payload, up to the largest length that `width` bytes can
+    /// represent. If the payload is longer than that, or if its
+    /// length overflows `usize`, an error is returned, and the
+    /// payload is not appended past that length.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `width` is not from 1 to 8.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use sconcat::{CAT, Endian};
+    ///
+    /// let cat = CAT + "PING " + "42";
+    /// let frame = cat.into_frame(Endian::Big, 2).unwrap();
+    /// assert_eq!(frame, b"\0\x07PING 42");
+    /// assert_eq!(frame.len(), frame.capacity());
+    ///
+    /// let cat = CAT + "x".repeat(300) + '!';
+    /// assert!(cat.into_frame(Endian::Little, 1).is_err());
+    /// ```
+    pub fn into_frame(
+        self,
+        endian: Endian,
+        width: usize,
+    ) -> Result<Vec<u8>, LimitError> {
+        frame(&self, endian, width)
+    }
+}
+
+impl<T: Cat> CatOne<T> {
+    /// Converts the concatenation into a frame consisting of its
+    /// length in bytes followed by its bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `width` is not from 1 to 8.
+    pub fn into_frame(
+        self,
+        endian: Endian,
+        width: usize,
+    ) -> Result<Vec<u8>, LimitError> {
+        frame(&self.inner, endian, width)
+    }
+}
+
+impl CatStart {
+    /// Converts the empty concatenation into a frame consisting of a
+    /// zero length.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `width` is not from 1 to 8.
+    pub fn into_frame(
+        self,
+        endian: Endian,
+        width: usize,
+    ) -> Result<Vec<u8>, LimitError> {
+        frame(&"", endian, width)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use CAT;
+    use Dbg;
+    use repeat::repeat;
+    use super::Endian;
+
+    #[test]
+    fn frames() {
+        let cat = CAT + "ab" + 'é' + Dbg(7);
+        let frame = cat.into_frame(Endian::Big, 4).unwrap();
+        assert_eq!(frame, b"\0\0\0\x05ab\xc3\xa97");
+        let frame = cat.into_frame(Endian::Little, 3).unwrap();
+        assert_eq!(frame, b"\x05\0\0ab\xc3\xa97");
+        let frame = (CAT + "xyz").into_frame(Endian::Big, 8).unwrap();
+        assert_eq!(frame, b"\0\0\0\0\0\0\0\x03xyz");
+        assert_eq!(CAT.into_frame(Endian::Little, 2).unwrap(), b"\0\0");
+    }
+
+    #[test]
+    fn too_long() {
+        let long = "x".repeat(256);
+        let err = (CAT + &*long).into_frame(Endian::Big, 1).unwrap_err();
+        assert_eq!((err.size(), err.limit()), (256, 255));
+        let frame = (CAT + &long[1..]).into_frame(Endian::Big, 1).unwrap();
+        assert_eq!(frame.len(), 256);
+        assert_eq!(frame[0], 255);
+    }
+
+    #[test]
+    fn overflow() {
+        let cat = CAT + "x" + repeat("y", usize::MAX);
+        let err = cat.into_frame(Endian::Big, 8).unwrap_err();
+        assert_eq!((err.size(), err.limit()), (usize::MAX, usize::MAX));
+    }
+
+    #[test]
+    #[should_panic(expected = "frame length width")]
+    fn bad_width() {
+        let _ = (CAT + "x").into_frame(Endian::Big, 0);
+    }
+}
@@ -0,0 +1,186 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use cat::{Cat, SegmentWriter};
+use std::fmt::{self, Display, Write};
+use std::str;
+
+// The number of decimal digits in `u128::MAX`.
+const MAX_DIGITS: usize = 39;
+
+mod sealed {
+    pub trait Int: Copy {
+        // Returns whether the integer is negative, and its magnitude.
+        fn sign_magnitude(self) -> (bool, u128);
+    }
+
+    macro_rules! signed {
+        ($($T:ty)*) => { $(
+            impl Int for $T {
+                fn sign_magnitude(self) -> (bool, u128) {
+                    (self < 0, (self as i128).unsigned_abs())
+                }
+            }
+        )* };
+    }
+
+    macro_rules! unsigned {
+        ($($T:ty)*) => { $(
+            impl Int for $T {
+                fn sign_magnitude(self) -> (bool, u128) {
+                    (false, self as u128)
+                }
+            }
+        )* };
+    }
+
+    signed! { i8 i16 i32 i64 i128 isize }
+    unsigned! { u8 u16 u32 u64 u128 usize }
+}
+
+/// A piece that formats an integer with separators between groups of
+/// thousands.
+///
+/// This is created by the [`group`](fn.group.html) function.
+#[derive(Clone, Copy, Debug)]
+pub struct Group {
+    negative: bool,
+    magnitude: u128,
+    sep: char,
+}
+
+/// Creates a piece that formats the integer `value` in decimal with
+/// `sep` between groups of three digits.
+///
+/// Any primitive integer type can be used. The digits are written
+/// directly to the destination without formatting machinery, and the
+/// size hint is exact, including the separators.
+///
+/// # Examples
+///
+/// ```rust
+/// use sconcat::{CAT, group};
+///
+/// let cat = CAT + "rows: " + group(1234567u32, ',');
+/// assert_eq!(String::from(cat), "rows: 1,234,567");
+/// let cat = CAT + group(-98765i64, '.') + " | " + group(999u16, ',');
+/// assert_eq!(String::from(cat), "-98.765 | 999");
+/// assert_eq!(group(1_000_000u64, ' ').to_string(), "1 000 000");
+/// ```
+pub fn group<T: sealed::Int>(value: T, sep: char) -> Group {
+    let (negative, magnitude) = value.sign_magnitude();
+    Group {
+        negative,
+        magnitude,
+        sep,
+    }
+}
+
+// Writes the decimal digits of `n` to the end of `buf` and returns
+// them.
+fn digits(n: u128, buf: &mut [u8; MAX_DIGITS]) -> &str {
+    let mut n = n;
+    let mut start = buf.len();
+    loop {
+        start -= 1;
+        buf[start] = b'0' + (n % 10) as u8;
+        n /= 10;
+        if n == 0 {
+            break;
+        }
+    }
+    str::from_utf8(&buf[start..]).unwrap()
+}
+
+fn digit_count(n: u128) -> usize {
+    let mut n = n;
+    let mut count = 1;
+    while n >= 10 {
+        n /= 10;
+        count += 1;
+    }
+    count
+}
+
+// Writes `digits` with `sep` between groups of three digits counted
+// from the right.
+pub(crate) fn write_grouped<W: Write>(
+    w: &mut W,
+    digits: &str,
+    sep: char,
+) -> fmt::Result {
+    let first = match digits.len() % 3 {
+        0 => 3,
+        rem => rem,
+    };
+    w.write_str(&digits[..first])?;
+    let mut rest = &digits[first..];
+    while !rest.is_empty() {
+        w.write_char(sep)?;
+        w.write_str(&rest[..3])?;
+        rest = &rest[3..];
+    }
+    Ok(())
+}
+
+impl Group {
+    fn write<W: Write>(&self, w: &mut W) -> fmt::Result {
+        if self.negative {
+            w.write_char('-')?;
+        }
+        let mut buf = [0; MAX_DIGITS];
+        write_grouped(w, digits(self.magnitude, &mut buf), self.sep)
+    }
+}
+
+impl Cat for Group {
+    fn size_hint(&self) -> usize {
+        let count = digit_count(self.magnitude);
+        let seps = (count - 1) / 3;
+        self.negative as usize + count + seps * self.sep.len_utf8()
+    }
+
+    fn append_to(&self, s: &mut String) {
+        self.write(s).unwrap();
+    }
+
+    fn for_each_segment(&self, f: &mut dyn FnMut(&str)) {
+        self.write(&mut SegmentWriter::new(f)).unwrap();
+    }
+}
+
+impl Display for Group {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.write(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use CAT;
+    use cat::Cat;
+    use super::group;
+
+    #[test]
+    fn groups() {
+        assert_eq!(group(0u8, ',').to_string(), "0");
+        assert_eq!(group(999u16, ',').to_string(), "999");
+        assert_eq!(group(1000u32, ',').to_string(), "1,000");
+        assert_eq!(group(-100_000i32, ',').to_string(), "-100,000");
+        assert_eq!(group(i8::MIN, ',').to_string(), "-128");
+        assert_eq!(
+            group(i64::MIN, ',').to_string(),
+            "-9,223,372,036,854,775,808"
+        );
+        let s = String::from(CAT + '[' + group(u128::MAX, '\u{202f}') + ']');
+        assert!(s.starts_with("[340\u{202f}282\u{202f}366\u{202f}"));
+        assert_eq!(s.len(), s.capacity());
+        assert_eq!(group(i128::MIN, '_').size_hint(), 52);
+        assert_eq!(group(-1234isize, '.').size_hint(), 6);
+    }
+}
@@ -0,0 +1,197 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use cat::{Cat, CatMany, CatOne, CatStart};
+#[cfg(feature = "digest")]
+use digest::Update;
+use std::hash::{Hash, Hasher};
+
+// Writes the segments to `state` followed by the terminator that
+// `str` writes, so that streaming hashers such as `DefaultHasher`
+// produce the same hash as for the materialized string.
+fn hash_segments<C: Cat, H: Hasher>(cat: &C, state: &mut H) {
+    cat.for_each_segment(&mut |segment| state.write(segment.as_bytes()));
+    state.write_u8(0xff);
+}
+
+/// Hashes the concatenation like the `str` it would be materialized
+/// into.
+///
+/// The segments are passed to the hasher one by one, so this is only
+/// consistent with the `str` hash for hashers whose result depends
+/// only on the sequence of bytes written, not on how they are split
+/// into calls. The `DefaultHasher` of the standard library is such a
+/// hasher.
+///
+/// # Examples
+///
+/// ```rust
+/// use sconcat::CAT;
+/// use std::collections::hash_map::DefaultHasher;
+/// use std::hash::{Hash, Hasher};
+///
+/// fn hash<T: Hash + ?Sized>(t: &T) -> u64 {
+///     let mut h = DefaultHasher::new();
+///     t.hash(&mut h);
+///     h.finish()
+/// }
+///
+/// let cat = CAT + "user:" + String::from("42");
+/// assert_eq!(hash(&cat), hash("user:42"));
+/// ```
+impl<L: Cat, R: Cat> Hash for CatMany<L, R> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        hash_segments(self, state);
+    }
+}
+
+/// Hashes the concatenation like the `str` it would be materialized
+/// into.
+impl<T: Cat> Hash for CatOne<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        hash_segments(&self.inner, state);
+    }
+}
+
+/// Hashes the empty concatenation like the empty `str`.
+impl Hash for CatStart {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        "".hash(state);
+    }
+}
+
+impl<L: Cat, R: Cat> CatMany<L, R> {
+    /// Passes the bytes of the concatenation to `f` segment by
+    /// segment, without materializing it.
+    ///
+    /// This can feed any checksum or digest that accepts its input in
+    /// chunks, for example to compute a cache key or an ETag for
+    /// composed text.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use sconcat::CAT;
+    ///
+    /// // FNV-1a
+    /// let mut hash = 0xcbf2_9ce4_8422_2325_u64;
+    /// let cat = CAT + "GET " + "/index.html";
+    /// cat.feed(|bytes| {
+    ///     for &b in bytes {
+    ///         hash = (hash ^ u64::from(b)).wrapping_mul(0x100_0000_01b3);
+    ///     }
+    /// });
+    /// assert_eq!(hash, 0x0f7e_9e01_0e97_4661);
+    /// ```
+    pub fn feed<F: FnMut(&[u8])>(&self, mut f: F) {
+        self.visit_segments(|segment| f(segment.as_bytes()));
+    }
+
+    /// Updates the digest `d` with the bytes of the concatenation,
+    /// without materializing it.
+    ///
+    /// This is available with the `digest` feature, and works with
+    /// any hash function implementing the traits of the
+    /// [`digest`](https://crates.io/crates/digest) crate, such as the
+    /// SHA-2 family from the `sha2` crate.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// use sconcat::CAT;
+    /// use sha2::{Digest, Sha256};
+    ///
+    /// let mut hasher = Sha256::new();
+    /// (CAT + "v1:" + body.as_str()).hash_into(&mut hasher);
+    /// let etag = hasher.finalize();
+    /// ```
+    #[cfg(feature = "digest")]
+    pub fn hash_into<D: Update>(&self, d: &mut D) {
+        self.visit_segments(|segment| d.update(segment.as_bytes()));
+    }
+}
+
+impl<T: Cat> CatOne<T> {
+    /// Passes the bytes of the concatenation to `f` segment by
+    /// segment, without materializing it.
+    pub fn feed<F: FnMut(&[u8])>(&self, mut f: F) {
+        self.visit_segments(|segment| f(segment.as_bytes()));
+    }
+
+    /// Updates the digest `d` with the bytes of the concatenation,
+    /// without materializing it.
+    #[cfg(feature = "digest")]
+    pub fn hash_into<D: Update>(&self, d: &mut D) {
+        self.visit_segments(|segment| d.update(segment.as_bytes()));
+    }
+}
+
+impl CatStart {
+    /// Does nothing, as the concatenation is empty.
+    pub fn feed<F: FnMut(&[u8])>(&self, _f: F) {}
+
+    /// Does nothing, as the concatenation is empty.
+    #[cfg(feature = "digest")]
+    pub fn hash_into<D: Update>(&self, _d: &mut D) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use CAT;
+    use Dbg;
+    #[cfg(feature = "digest")]
+    use digest::Update;
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{Hash, Hasher};
+
+    fn hash<T: Hash + ?Sized>(t: &T) -> u64 {
+        let mut h = DefaultHasher::new();
+        t.hash(&mut h);
+        h.finish()
+    }
+
+    #[test]
+    fn same_as_str() {
+        let cat = CAT + "a" + 'é' + Dbg([1, 2]) + String::from("xyz");
+        let s = String::from(cat.clone());
+        assert_eq!(hash(&cat), hash(&s));
+        assert_eq!(hash(&cat), hash(s.as_str()));
+        assert_eq!(hash(&(CAT + "one")), hash("one"));
+        assert_eq!(hash(&CAT), hash(""));
+        assert_ne!(hash(&(CAT + "ab" + "c")), hash("abc\u{0}"));
+        assert_eq!(hash(&(CAT + "ab" + "c")), hash(&(CAT + 'a' + "bc")));
+    }
+
+    #[test]
+    fn feed() {
+        let cat = CAT + "ab" + 'é' + Dbg(3);
+        let mut bytes = Vec::new();
+        cat.feed(|b| bytes.extend_from_slice(b));
+        (CAT + "!").feed(|b| bytes.extend_from_slice(b));
+        CAT.feed(|_| panic!("fed empty concatenation"));
+        assert_eq!(bytes, "abé3!".as_bytes());
+    }
+
+    #[cfg(feature = "digest")]
+    #[test]
+    fn hash_into() {
+        struct Collect(Vec<u8>);
+
+        impl Update for Collect {
+            fn update(&mut self, data: &[u8]) {
+                self.0.extend_from_slice(data);
+            }
+        }
+
+        let mut d = Collect(Vec::new());
+        (CAT + "ab" + 'é' + Dbg(3)).hash_into(&mut d);
+        (CAT + "!").hash_into(&mut d);
+        CAT.hash_into(&mut d);
+        assert_eq!(d.0, "abé3!".as_bytes());
+    }
+}
@@ -0,0 +1,123 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use cat::Cat;
+use std::fmt::{self, Display};
+use std::str;
+
+const LOWER: &[u8; 16] = b"0123456789abcdef";
+const UPPER: &[u8; 16] = b"0123456789ABCDEF";
+// Bytes encoded at a time by `for_each_segment`.
+const CHUNK: usize = 32;
+
+/// A piece that encodes bytes in hexadecimal.
+///
+/// This is created by the [`hex`](fn.hex.html) and
+/// [`hex_upper`](fn.hex_upper.html) functions.
+#[derive(Clone, Copy, Debug)]
+pub struct Hex<'a> {
+    bytes: &'a [u8],
+    digits: &'static [u8; 16],
+}
+
+/// Creates a piece that encodes `bytes` in lowercase hexadecimal.
+///
+/// Every byte is encoded as two digits, so the size hint is exactly
+/// twice the number of bytes.
+///
+/// # Examples
+///
+/// ```rust
+/// use sconcat::{CAT, hex};
+///
+/// let id = [0xde, 0xad, 0xbe, 0xef];
+/// let cat = CAT + "id=" + hex(&id);
+/// assert_eq!(String::from(cat), "id=deadbeef");
+/// ```
+pub fn hex(bytes: &[u8]) -> Hex<'_> {
+    Hex {
+        bytes,
+        digits: LOWER,
+    }
+}
+
+/// Creates a piece that encodes `bytes` in uppercase hexadecimal.
+///
+/// # Examples
+///
+/// ```rust
+/// use sconcat::{CAT, hex_upper};
+///
+/// let cat = CAT + "0x" + hex_upper(&[0x0a, 0xf0]);
+/// assert_eq!(String::from(cat), "0x0AF0");
+/// ```
+pub fn hex_upper(bytes: &[u8]) -> Hex<'_> {
+    Hex {
+        bytes,
+        digits: UPPER,
+    }
+}
+
+impl<'a> Cat for Hex<'a> {
+    fn size_hint(&self) -> usize {
+        self.bytes.len().checked_mul(2).expect("capacity overflow")
+    }
+
+    fn append_to(&self, s: &mut String) {
+        for &b in self.bytes {
+            s.push(char::from(self.digits[usize::from(b >> 4)]));
+            s.push(char::from(self.digits[usize::from(b & 0xf)]));
+        }
+    }
+
+    fn for_each_segment(&self, f: &mut dyn FnMut(&str)) {
+        let mut buf = [0u8; 2 * CHUNK];
+        for chunk in self.bytes.chunks(CHUNK) {
+            for (&b, pair) in chunk.iter().zip(buf.chunks_mut(2)) {
+                pair[0] = self.digits[usize::from(b >> 4)];
+                pair[1] = self.digits[usize::from(b & 0xf)];
+            }
+            // The digits are all ASCII characters.
+            f(str::from_utf8(&buf[..2 * chunk.len()]).unwrap());
+        }
+    }
+}
+
+impl<'a> Display for Hex<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut result = Ok(());
+        self.for_each_segment(&mut |segment| {
+            if result.is_ok() {
+                result = f.write_str(segment);
+            }
+        });
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use CAT;
+    use cat::Cat;
+    use super::{hex, hex_upper};
+
+    #[test]
+    fn encodes() {
+        let bytes = (0u8..=255).collect::<Vec<_>>();
+        let s = String::from(CAT + '[' + hex(&bytes) + ']');
+        assert_eq!(s.len(), 2 + 512);
+        assert_eq!(s.len(), s.capacity());
+        assert_eq!(&s[..9], "[00010203");
+        assert_eq!(&s[s.len() - 9..], "fcfdfeff]");
+        let mut segments = String::new();
+        hex(&bytes).for_each_segment(&mut |segment| segments.push_str(segment));
+        assert_eq!(segments, s[1..s.len() - 1]);
+        assert_eq!(hex_upper(&[0xab, 0x1c]).to_string(), "AB1C");
+        assert_eq!(hex(&[]).size_hint(), 0);
+    }
+}
@@ -0,0 +1,169 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use cat::{Cat, SegmentWriter};
+use std::fmt::{self, Display, Write};
+use std::mem;
+
+const BYTES_PER_LINE: usize = 16;
+const OFFSET_WIDTH: usize = 8;
+// Two spaces after the offset, three columns per byte, one extra
+// space in the middle, one space and two bars around the gutter, and
+// the newline.
+const LINE_OVERHEAD: usize = OFFSET_WIDTH + 2 + 3 * BYTES_PER_LINE + 1 + 4;
+
+/// A piece that renders bytes as a classic hex dump.
+///
+/// This is created by the [`hexdump`](fn.hexdump.html) function.
+#[derive(Clone, Copy, Debug)]
+pub struct HexDump<'a> {
+    bytes: &'a [u8],
+}
+
+/// Creates a piece that renders `bytes` as a hex dump.
+///
+/// Every line has the offset in hexadecimal, up to sixteen bytes in
+/// hexadecimal and an ASCII gutter, where non-printable bytes are
+/// shown as `.`. A final line contains the total length. This is the
+/// layout of `hexdump -C`, except that repeated lines are not
+/// collapsed. The size hint is exact.
+///
+/// # Examples
+///
+/// ```rust
+/// use sconcat::{CAT, hexdump};
+///
+/// let cat = CAT + "packet:\n" + hexdump(b"Hello, world!\n");
+/// let expected = "packet:\n\
+///     00000000  48 65 6c 6c 6f 2c 20 77  6f 72 6c 64 21 0a        \
+///     |Hello, world!.|\n\
+///     0000000e\n";
+/// assert_eq!(String::from(cat), expected);
+/// ```
+pub fn hexdump(bytes: &[u8]) -> HexDump<'_> {
+    HexDump { bytes }
+}
+
+fn offset_width(offset: usize) -> usize {
+    let max_width = 2 * mem::size_of::<usize>();
+    let mut width = OFFSET_WIDTH;
+    while width < max_width && offset >> (4 * width) != 0 {
+        width += 1;
+    }
+    width
+}
+
+impl<'a> HexDump<'a> {
+    fn write<W: Write>(&self, w: &mut W) -> fmt::Result {
+        if self.bytes.is_empty() {
+            return Ok(());
+        }
+        for (line, chunk) in self.bytes.chunks(BYTES_PER_LINE).enumerate() {
+            write!(w, "{:08x}  ", line * BYTES_PER_LINE)?;
+            for i in 0..BYTES_PER_LINE {
+                if i == BYTES_PER_LINE / 2 {
+                    w.write_char(' ')?;
+                }
+                match chunk.get(i) {
+                    Some(b) => write!(w, "{:02x} ", b)?,
+                    None => w.write_str("   ")?,
+                }
+            }
+            w.write_str(" |")?;
+            for &b in chunk {
+                let c = if b == b' ' || b.is_ascii_graphic() {
+                    char::from(b)
+                } else {
+                    '.'
+                };
+                w.write_char(c)?;
+            }
+            w.write_str("|\n")?;
+        }
+        writeln!(w, "{:08x}", self.bytes.len())
+    }
+}
+
+impl<'a> Cat for HexDump<'a> {
+    fn size_hint(&self) -> usize {
+        let len = self.bytes.len();
+        if len == 0 {
+            return 0;
+        }
+        let lines = len.div_ceil(BYTES_PER_LINE);
+        // Offsets wider than the minimum need extra columns.
+        let mut extra = offset_width(len) - OFFSET_WIDTH;
+        let mut width = OFFSET_WIDTH;
+        while width < 2 * mem::size_of::<usize>() {
+            let threshold = 1usize << (4 * width);
+            if threshold >= len {
+                break;
+            }
+            extra += lines - threshold.div_ceil(BYTES_PER_LINE);
+            width += 1;
+        }
+        lines
+            .checked_mul(LINE_OVERHEAD)
+            .and_then(|total| total.checked_add(len))
+            .and_then(|total| total.checked_add(extra + OFFSET_WIDTH + 1))
+            .expect("capacity overflow")
+    }
+
+    fn append_to(&self, s: &mut String) {
+        self.write(s).unwrap();
+    }
+
+    fn for_each_segment(&self, f: &mut dyn FnMut(&str)) {
+        self.write(&mut SegmentWriter::new(f)).unwrap();
+    }
+}
+
+impl<'a> Display for HexDump<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.write(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use CAT;
+    use super::{hexdump, offset_width};
+
+    #[test]
+    fn layout() {
+        assert_eq!(String::from(CAT + hexdump(b"")), "");
+        let bytes = (0u8..40).collect::<Vec<_>>();
+        let cat = CAT + hexdump(&bytes);
+        let s = String::from(cat);
+        let expected = concat!(
+            "00000000  00 01 02 03 04 05 06 07  08 09 0a 0b 0c 0d 0e 0f  ",
+            "|................|\n",
+            "00000010  10 11 12 13 14 15 16 17  18 19 1a 1b 1c 1d 1e 1f  ",
+            "|................|\n",
+            "00000020  20 21 22 23 24 25 26 27                           ",
+            "| !\"#$%&'|\n",
+            "00000028\n"
+        );
+        assert_eq!(s, expected);
+        assert_eq!(s.len(), s.capacity());
+        assert_eq!(cat.to_string(), s);
+    }
+
+    #[test]
+    fn wide_offsets() {
+        assert_eq!(offset_width(0), 8);
+        assert_eq!(offset_width(0xffff_ffff), 8);
+    }
+
+    #[cfg(target_pointer_width = "64")]
+    #[test]
+    fn wide_offsets_64() {
+        assert_eq!(offset_width(0x1_0000_0000), 9);
+        assert_eq!(offset_width(usize::MAX), 16);
+    }
+}
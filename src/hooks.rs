@@ -0,0 +1,142 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::sync::{Arc, RwLock};
+
+type Hook = Arc<dyn Fn(&Materialization) + Send + Sync>;
+
+static HOOK: RwLock<Option<Hook>> = RwLock::new(None);
+
+/// Information about a concatenation that was converted or appended
+/// to a `String`.
+///
+/// This is passed to the hook installed with
+/// [`set_materialize_hook`](fn.set_materialize_hook.html).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Materialization {
+    pieces: usize,
+    capacity: usize,
+    reallocated: bool,
+}
+
+impl Materialization {
+    /// Returns the number of pieces in the concatenation.
+    pub fn pieces(&self) -> usize {
+        self.pieces
+    }
+
+    /// Returns the number of bytes that were reserved for the
+    /// concatenation.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns `true` if the reserved capacity was not enough and the
+    /// string had to be reallocated while the pieces were appended.
+    ///
+    /// This is an estimate when a concatenation is converted into a
+    /// new `String`. It is then `true` if the result is longer than
+    /// the reserved capacity, which misses a reallocation that grew
+    /// the buffer of a leading `String` piece only as far as needed,
+    /// and reports one when such a buffer was already large enough.
+    /// When a concatenation is appended to an existing `String`, the
+    /// capacity of the string is checked before and after appending.
+    pub fn reallocated(&self) -> bool {
+        self.reallocated
+    }
+}
+
+/// Installs a hook that is called every time a concatenation is
+/// converted or appended to a `String`, replacing any previous hook.
+///
+/// The hook is called on the thread that materialized the
+/// concatenation. Empty concatenations started with `CAT` and not
+/// extended do not call the hook. No lock is held while the hook
+/// runs, so the hook can materialize concatenations and install or
+/// remove hooks itself; a hook that is replaced while it runs still
+/// finishes that call.
+///
+/// # Examples
+///
+/// ```rust
+/// use sconcat::{CAT, set_materialize_hook, remove_materialize_hook};
+///
+/// set_materialize_hook(|m| {
+///     if m.reallocated() {
+///         eprintln!("badly sized concatenation of {} pieces", m.pieces());
+///     }
+/// });
+/// let s = String::from(CAT + "Hello, " + "world!");
+/// assert_eq!(s, "Hello, world!");
+/// remove_materialize_hook();
+/// ```
+pub fn set_materialize_hook<F>(hook: F)
+where
+    F: Fn(&Materialization) + Send + Sync + 'static,
+{
+    let mut guard = HOOK.write().unwrap_or_else(|e| e.into_inner());
+    *guard = Some(Arc::new(hook));
+}
+
+/// Removes the hook installed with
+/// [`set_materialize_hook`](fn.set_materialize_hook.html), if any.
+pub fn remove_materialize_hook() {
+    let mut guard = HOOK.write().unwrap_or_else(|e| e.into_inner());
+    *guard = None;
+}
+
+pub fn record(pieces: usize, capacity: usize, reallocated: bool) {
+    // The lock is released before the hook is called, as the hook
+    // could take it again.
+    let hook = HOOK.read().unwrap_or_else(|e| e.into_inner()).clone();
+    if let Some(hook) = hook {
+        hook(&Materialization {
+            pieces,
+            capacity,
+            reallocated,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use CAT;
+    use std::cell::RefCell;
+    use super::{Materialization, remove_materialize_hook, set_materialize_hook};
+
+    thread_local! {
+        static SEEN: RefCell<Vec<Materialization>> =
+            const { RefCell::new(Vec::new()) };
+    }
+
+    #[test]
+    fn hook_sees_materializations() {
+        set_materialize_hook(|m| SEEN.with(|seen| seen.borrow_mut().push(*m)));
+        let s = String::from(CAT + "ab" + 'c' + String::from("de"));
+        let mut t = String::new();
+        t += CAT + "xyz";
+        remove_materialize_hook();
+        let _ = String::from(CAT + "unseen");
+        assert_eq!(s, "abcde");
+        let seen = SEEN.with(|seen| seen.borrow().clone());
+        assert_eq!(seen.len(), 2);
+        assert_eq!((seen[0].pieces(), seen[0].capacity()), (3, 5));
+        assert!(!seen[0].reallocated());
+        assert_eq!((seen[1].pieces(), seen[1].capacity()), (1, 3));
+        assert!(!seen[1].reallocated());
+
+        // The hook runs without the lock, so it can take it again.
+        set_materialize_hook(|m| {
+            if m.pieces() == 2 {
+                let _ = String::from(CAT + "inner");
+                remove_materialize_hook();
+            }
+        });
+        assert_eq!(String::from(CAT + "a" + 'b'), "ab");
+    }
+}
@@ -0,0 +1,421 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! HTTP/1.1 message heads.
+//!
+//! The pieces in this module validate their parts when they are
+//! created, so that a message head assembled from them is
+//! well-formed. A [`Head`](struct.Head.html) combines a start line,
+//! header fields and the terminating blank line, and can either be
+//! concatenated like any other piece or written with a single
+//! vectored write.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use sconcat::CAT;
+//! use sconcat::http::{Head, header, status_line};
+//!
+//! # fn main() -> Result<(), sconcat::http::HeadError> {
+//! let body = "hello";
+//! let len = body.len().to_string();
+//! let mut head = Head::response(status_line(200, "OK")?);
+//! head.push_header(header("Content-Type", "text/plain")?)
+//!     .push_header(header("Content-Length", &len)?);
+//! let expected = "HTTP/1.1 200 OK\r\n\
+//!                 Content-Type: text/plain\r\n\
+//!                 Content-Length: 5\r\n\
+//!                 \r\n\
+//!                 hello";
+//! assert_eq!(String::from(CAT + head + body), expected);
+//! # Ok(())
+//! # }
+//! ```
+
+use cat::Cat;
+use std::error::Error;
+use std::fmt::{self, Display};
+use std::io::{self, IoSlice, Write};
+use std::str;
+
+/// The blank line that terminates a message head.
+pub const END_OF_HEAD: &str = "\r\n";
+
+const VERSION: &str = "HTTP/1.1";
+const TCHARS: &[u8] = b"!#$%&'*+-.^_`|~";
+
+/// An error returned when a part of a message head is invalid.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HeadError {
+    /// The status code does not have three digits.
+    StatusCode,
+    /// The reason phrase contains control characters.
+    Reason,
+    /// The method is not a token.
+    Method,
+    /// The request target is empty or contains spaces or control
+    /// characters.
+    Target,
+    /// The header field name is not a token.
+    HeaderName,
+    /// The header field value contains control characters.
+    HeaderValue,
+}
+
+impl Display for HeadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let part = match *self {
+            HeadError::StatusCode => "status code",
+            HeadError::Reason => "reason phrase",
+            HeadError::Method => "method",
+            HeadError::Target => "request target",
+            HeadError::HeaderName => "header field name",
+            HeadError::HeaderValue => "header field value",
+        };
+        write!(f, "invalid HTTP {}", part)
+    }
+}
+
+impl Error for HeadError {}
+
+fn is_token(s: &str) -> bool {
+    !s.is_empty()
+        && s.bytes()
+            .all(|b| b.is_ascii_alphanumeric() || TCHARS.contains(&b))
+}
+
+// Checks for visible characters, spaces and tabs.
+fn is_text(s: &str) -> bool {
+    s.bytes().all(|b| b == b'\t' || (b >= b' ' && b != 0x7f))
+}
+
+fn is_target(s: &str) -> bool {
+    !s.is_empty() && s.bytes().all(|b| b.is_ascii_graphic())
+}
+
+/// A status line, for example `HTTP/1.1 404 Not Found\r\n`.
+///
+/// This is created by the [`status_line`](fn.status_line.html)
+/// function.
+#[derive(Clone, Copy, Debug)]
+pub struct StatusLine<'a> {
+    code: [u8; 3],
+    reason: &'a str,
+}
+
+/// Creates a status line piece.
+///
+/// # Errors
+///
+/// An error is returned if `code` does not have three digits or if
+/// `reason` contains control characters other than tabs.
+pub fn status_line(
+    code: u16,
+    reason: &str,
+) -> Result<StatusLine<'_>, HeadError> {
+    if !(100..=999).contains(&code) {
+        return Err(HeadError::StatusCode);
+    }
+    if !is_text(reason) {
+        return Err(HeadError::Reason);
+    }
+    let digit = |place: u16| b'0' + (code / place % 10) as u8;
+    Ok(StatusLine {
+        code: [digit(100), digit(10), digit(1)],
+        reason,
+    })
+}
+
+impl<'a> StatusLine<'a> {
+    fn parts<'s>(&'s self, f: &mut dyn FnMut(&'s str)) {
+        f(VERSION);
+        f(" ");
+        f(str::from_utf8(&self.code).unwrap());
+        f(" ");
+        f(self.reason);
+        f("\r\n");
+    }
+}
+
+/// A request line, for example `GET /index.html HTTP/1.1\r\n`.
+///
+/// This is created by the [`request_line`](fn.request_line.html)
+/// function.
+#[derive(Clone, Copy, Debug)]
+pub struct RequestLine<'a> {
+    method: &'a str,
+    target: &'a str,
+}
+
+/// Creates a request line piece.
+///
+/// # Errors
+///
+/// An error is returned if `method` is not a token, or if `target`
+/// is empty or contains spaces or control characters.
+pub fn request_line<'a>(
+    method: &'a str,
+    target: &'a str,
+) -> Result<RequestLine<'a>, HeadError> {
+    if !is_token(method) {
+        return Err(HeadError::Method);
+    }
+    if !is_target(target) {
+        return Err(HeadError::Target);
+    }
+    Ok(RequestLine { method, target })
+}
+
+impl<'a> RequestLine<'a> {
+    fn parts<'s>(&'s self, f: &mut dyn FnMut(&'s str)) {
+        f(self.method);
+        f(" ");
+        f(self.target);
+        f(" ");
+        f(VERSION);
+        f("\r\n");
+    }
+}
+
+/// A header field line, for example `Content-Length: 5\r\n`.
+///
+/// This is created by the [`header`](fn.header.html) function.
+#[derive(Clone, Copy, Debug)]
+pub struct Header<'a> {
+    name: &'a str,
+    value: &'a str,
+}
+
+/// Creates a header field piece.
+///
+/// # Errors
+///
+/// An error is returned if `name` is not a token, or if `value`
+/// contains control characters other than tabs. In particular, line
+/// breaks cannot be used to inject further header fields.
+///
+/// # Examples
+///
+/// ```rust
+/// use sconcat::http::{HeadError, header};
+///
+/// let user_agent = "evil\r\nX-Admin: 1";
+/// let err = header("User-Agent", user_agent).unwrap_err();
+/// assert_eq!(err, HeadError::HeaderValue);
+/// ```
+pub fn header<'a>(
+    name: &'a str,
+    value: &'a str,
+) -> Result<Header<'a>, HeadError> {
+    if !is_token(name) {
+        return Err(HeadError::HeaderName);
+    }
+    if !is_text(value) {
+        return Err(HeadError::HeaderValue);
+    }
+    Ok(Header { name, value })
+}
+
+impl<'a> Header<'a> {
+    fn parts<'s>(&'s self, f: &mut dyn FnMut(&'s str)) {
+        f(self.name);
+        f(": ");
+        f(self.value);
+        f("\r\n");
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum StartLine<'a> {
+    Request(RequestLine<'a>),
+    Status(StatusLine<'a>),
+}
+
+/// A complete message head, including the terminating blank line.
+#[derive(Clone, Debug)]
+pub struct Head<'a> {
+    start: StartLine<'a>,
+    headers: Vec<Header<'a>>,
+}
+
+impl<'a> Head<'a> {
+    /// Creates a request head with no header fields.
+    pub fn request(line: RequestLine<'a>) -> Head<'a> {
+        Head {
+            start: StartLine::Request(line),
+            headers: Vec::new(),
+        }
+    }
+
+    /// Creates a response head with no header fields.
+    pub fn response(line: StatusLine<'a>) -> Head<'a> {
+        Head {
+            start: StartLine::Status(line),
+            headers: Vec::new(),
+        }
+    }
+
+    /// Appends a header field.
+    pub fn push_header(&mut self, header: Header<'a>) -> &mut Head<'a> {
+        self.headers.push(header);
+        self
+    }
+
+    /// Writes the head to `w` with vectored writes, without
+    /// materializing it.
+    ///
+    /// Writing is retried until the whole head has been written or
+    /// an error other than `ErrorKind::Interrupted` occurs.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use sconcat::http::{Head, request_line};
+    ///
+    /// let head = Head::request(request_line("GET", "/").unwrap());
+    /// let mut out = Vec::new();
+    /// head.write_vectored_to(&mut out).unwrap();
+    /// assert_eq!(out, b"GET / HTTP/1.1\r\n\r\n");
+    /// ```
+    pub fn write_vectored_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        let mut slices = Vec::with_capacity(7 + 4 * self.headers.len());
+        self.parts(&mut |part| slices.push(IoSlice::new(part.as_bytes())));
+        let mut slices = &mut slices[..];
+        while !slices.is_empty() {
+            match w.write_vectored(slices) {
+                Ok(0) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::WriteZero,
+                        "failed to write whole head",
+                    ));
+                }
+                Ok(n) => IoSlice::advance_slices(&mut slices, n),
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+
+    fn parts<'s>(&'s self, f: &mut dyn FnMut(&'s str)) {
+        match self.start {
+            StartLine::Request(ref line) => line.parts(f),
+            StartLine::Status(ref line) => line.parts(f),
+        }
+        for header in &self.headers {
+            header.parts(f);
+        }
+        f(END_OF_HEAD);
+    }
+}
+
+// Implements `Cat` and `Display` in terms of the `parts` method.
+macro_rules! impl_piece {
+    ($($Piece:ident)*) => { $(
+        impl<'a> Cat for $Piece<'a> {
+            fn size_hint(&self) -> usize {
+                let mut len = 0;
+                self.parts(&mut |part| len += part.len());
+                len
+            }
+
+            fn append_to(&self, s: &mut String) {
+                self.parts(&mut |part| s.push_str(part));
+            }
+
+            fn for_each_segment(&self, f: &mut dyn FnMut(&str)) {
+                self.parts(&mut |part| f(part));
+            }
+        }
+
+        impl<'a> Display for $Piece<'a> {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                let mut result = Ok(());
+                self.parts(&mut |part| {
+                    if result.is_ok() {
+                        result = f.write_str(part);
+                    }
+                });
+                result
+            }
+        }
+    )* };
+}
+
+impl_piece! { StatusLine RequestLine Header Head }
+
+#[cfg(test)]
+mod tests {
+    use CAT;
+    use std::io::{self, IoSlice, Write};
+    use super::{Head, HeadError, header, request_line, status_line};
+
+    // Accepts at most three bytes per write.
+    struct Trickle {
+        bytes: Vec<u8>,
+    }
+
+    impl Write for Trickle {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            let n = buf.len().min(3);
+            self.bytes.extend_from_slice(&buf[..n]);
+            Ok(n)
+        }
+
+        fn write_vectored(&mut self, bufs: &[IoSlice]) -> io::Result<usize> {
+            match bufs.iter().find(|buf| !buf.is_empty()) {
+                Some(buf) => self.write(buf),
+                None => Ok(0),
+            }
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn lines() {
+        let status = status_line(404, "Not Found").unwrap();
+        let request = request_line("GET", "/a?b=c").unwrap();
+        let host = header("Host", "example.com").unwrap();
+        let s = String::from(CAT + status + request + host);
+        let expected = "HTTP/1.1 404 Not Found\r\n\
+                        GET /a?b=c HTTP/1.1\r\n\
+                        Host: example.com\r\n";
+        assert_eq!(s, expected);
+        assert_eq!(s.len(), s.capacity());
+        let empty = status_line(204, "").unwrap();
+        assert_eq!(empty.to_string(), "HTTP/1.1 204 \r\n");
+    }
+
+    #[test]
+    fn invalid() {
+        assert_eq!(status_line(99, "").unwrap_err(), HeadError::StatusCode);
+        assert_eq!(status_line(1000, "").unwrap_err(), HeadError::StatusCode);
+        assert_eq!(status_line(200, "O\nK").unwrap_err(), HeadError::Reason);
+        assert_eq!(request_line("G T", "/").unwrap_err(), HeadError::Method);
+        assert_eq!(request_line("GET", "/ x").unwrap_err(), HeadError::Target);
+        assert_eq!(request_line("GET", "").unwrap_err(), HeadError::Target);
+        assert_eq!(header("", "x").unwrap_err(), HeadError::HeaderName);
+        assert_eq!(header("A:B", "x").unwrap_err(), HeadError::HeaderName);
+        assert_eq!(header("A", "x\0").unwrap_err(), HeadError::HeaderValue);
+        assert!(header("A", "\tx y é").is_ok());
+    }
+
+    #[test]
+    fn vectored() {
+        let mut head = Head::response(status_line(301, "Moved").unwrap());
+        head.push_header(header("Location", "/new").unwrap());
+        let mut out = Trickle { bytes: Vec::new() };
+        head.write_vectored_to(&mut out).unwrap();
+        let expected = "HTTP/1.1 301 Moved\r\nLocation: /new\r\n\r\n";
+        assert_eq!(out.bytes, expected.as_bytes());
+        assert_eq!(String::from(CAT + head), expected);
+    }
+}
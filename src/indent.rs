@@ -0,0 +1,112 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use cat::Cat;
+use std::fmt::{self, Display};
+
+/// A piece that prefixes every line of another piece.
+///
+/// This is created by the [`indent`](fn.indent.html) function.
+#[derive(Clone, Copy, Debug)]
+pub struct Indent<'a, T: Cat> {
+    inner: T,
+    prefix: &'a str,
+}
+
+/// Creates a piece that inserts `prefix` at the start of every line
+/// of `value`.
+///
+/// Empty lines are not prefixed, so that indenting does not add
+/// trailing whitespace. The size hint is the size hint of `value`
+/// plus the length of the prefixes, which are counted by walking the
+/// segments of `value` once.
+///
+/// # Examples
+///
+/// ```rust
+/// use sconcat::{CAT, indent};
+///
+/// let report = "disk: ok\n\nnet: down\n";
+/// let cat = CAT + "status:\n" + indent(report, "    ");
+/// let expected = "status:\n    disk: ok\n\n    net: down\n";
+/// assert_eq!(String::from(cat), expected);
+/// ```
+pub fn indent<T: Cat>(value: T, prefix: &str) -> Indent<'_, T> {
+    Indent {
+        inner: value,
+        prefix,
+    }
+}
+
+impl<'a, T: Cat> Indent<'a, T> {
+    // Passes every line of the inner piece to `line`, together with
+    // whether the line starts there, split at segment boundaries.
+    fn for_each_part(&self, line: &mut dyn FnMut(&str, bool)) {
+        let mut at_start = true;
+        self.inner.for_each_segment(&mut |segment| {
+            for part in segment.split_inclusive('\n') {
+                line(part, at_start && part != "\n");
+                at_start = part.ends_with('\n');
+            }
+        });
+    }
+}
+
+impl<'a, T: Cat> Cat for Indent<'a, T> {
+    fn size_hint(&self) -> usize {
+        let mut lines = 0usize;
+        self.for_each_part(&mut |_, starts| lines += usize::from(starts));
+        lines
+            .checked_mul(self.prefix.len())
+            .and_then(|len| len.checked_add(self.inner.size_hint()))
+            .expect("capacity overflow")
+    }
+
+    fn append_to(&self, s: &mut String) {
+        self.for_each_segment(&mut |segment| s.push_str(segment));
+    }
+
+    fn for_each_segment(&self, f: &mut dyn FnMut(&str)) {
+        self.for_each_part(&mut |part, starts| {
+            if starts && !self.prefix.is_empty() {
+                f(self.prefix);
+            }
+            f(part);
+        });
+    }
+}
+
+impl<'a, T: Cat> Display for Indent<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut result = Ok(());
+        self.for_each_segment(&mut |segment| {
+            if result.is_ok() {
+                result = f.write_str(segment);
+            }
+        });
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use CAT;
+    use cat::Cat;
+    use super::indent;
+
+    #[test]
+    fn indents_lines() {
+        let value = CAT + "a\nb" + "c\n" + "\n" + 'd' + "\ne\n";
+        let s = String::from(CAT + '{' + '\n' + indent(value, "  ") + '}');
+        assert_eq!(s, "{\n  a\n  bc\n\n  d\n  e\n}");
+        assert_eq!(s.len(), s.capacity());
+        assert_eq!(indent("", "> ").size_hint(), 0);
+        assert_eq!(indent("x", "> ").to_string(), "> x");
+        assert_eq!(indent("\n\nx\n", "> ").to_string(), "\n\n> x\n");
+    }
+}
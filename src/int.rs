@@ -0,0 +1,55 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use cat::Cat;
+use itoa::Buffer;
+
+// Integers are formatted into a stack buffer, which is cheap enough
+// that the size hint formats the integer to find its exact length.
+macro_rules! int_cat {
+    ($($T:ty)*) => { $(
+        impl Cat for $T {
+            fn size_hint(&self) -> usize {
+                Buffer::new().format(*self).len()
+            }
+
+            fn append_to(&self, s: &mut String) {
+                s.push_str(Buffer::new().format(*self));
+            }
+
+            fn for_each_segment(&self, f: &mut dyn FnMut(&str)) {
+                f(Buffer::new().format(*self));
+            }
+
+            fn segment_count(&self) -> usize {
+                1
+            }
+        }
+    )* };
+}
+
+int_cat! { i8 i16 i32 i64 i128 isize u8 u16 u32 u64 u128 usize }
+
+#[cfg(test)]
+mod tests {
+    use CAT;
+    use cat::Cat;
+
+    #[test]
+    fn integers() {
+        let cat = CAT + "id=" + 42u32 + ", delta=" + -7i8 + ", max=" + u64::MAX;
+        let s = String::from(cat);
+        assert_eq!(s, "id=42, delta=-7, max=18446744073709551615");
+        assert_eq!(s.len(), s.capacity());
+        assert_eq!(i64::MIN.size_hint(), 20);
+        assert_eq!(0usize.size_hint(), 1);
+        let mut s = String::from("n");
+        s += CAT + 1 + 2;
+        assert_eq!(s, "n12");
+    }
+}
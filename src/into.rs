@@ -0,0 +1,109 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use cat::{Cat, CatOne, CatStart};
+
+mod sealed {
+    use cat::{Cat, CatOne, CatStart};
+
+    pub trait Sealed {}
+
+    impl<T: Cat> Sealed for T {}
+    impl<T: Cat> Sealed for CatOne<T> {}
+    impl Sealed for CatStart {}
+}
+
+/// Trait for values that can be used as a piece of a concatenation.
+///
+/// This is implemented for every piece, that is every type that
+/// implements the concatenation traits, such as `char`, `&str`,
+/// `String` and concatenations started with `CAT` and extended with
+/// at least two pieces. It is also implemented for `CAT` itself and
+/// for concatenations with one piece, which are converted to an empty
+/// string slice and to their piece respectively.
+///
+/// A function can take an `impl IntoCat` parameter to accept any of
+/// these without naming the concatenation types. The parameter is
+/// converted with [`into_cat`](#tymethod.into_cat) and can then be
+/// added to a concatenation like any other piece, so nothing is
+/// copied until the result is materialized. The trait is sealed and
+/// cannot be implemented outside this crate; implementing the
+/// concatenation traits for a type makes it implement this trait.
+///
+/// # Examples
+///
+/// ```rust
+/// use sconcat::{CAT, IntoCat};
+///
+/// fn banner(title: impl IntoCat) -> String {
+///     String::from(CAT + "== " + title.into_cat() + " ==")
+/// }
+///
+/// let name = String::from("world");
+/// assert_eq!(banner("Intro"), "== Intro ==");
+/// assert_eq!(banner('x'), "== x ==");
+/// let hello = CAT + "Hello, " + name.as_str();
+/// assert_eq!(banner(hello), "== Hello, world ==");
+/// assert_eq!(banner(CAT + name), "== world ==");
+/// assert_eq!(banner(CAT), "==  ==");
+/// ```
+pub trait IntoCat: sealed::Sealed {
+    /// The piece that the value is converted into.
+    type Piece: Cat;
+
+    /// Converts the value into a piece.
+    fn into_cat(self) -> Self::Piece;
+}
+
+impl<T: Cat> IntoCat for T {
+    type Piece = T;
+
+    fn into_cat(self) -> T {
+        self
+    }
+}
+
+impl<T: Cat> IntoCat for CatOne<T> {
+    type Piece = T;
+
+    fn into_cat(self) -> T {
+        self.inner
+    }
+}
+
+impl IntoCat for CatStart {
+    type Piece = &'static str;
+
+    fn into_cat(self) -> &'static str {
+        ""
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use CAT;
+    use Dbg;
+    use super::IntoCat;
+
+    fn wrap<T: IntoCat>(value: T) -> String {
+        String::from(CAT + '<' + value.into_cat() + '>')
+    }
+
+    #[test]
+    fn into_cat() {
+        let owned = String::from("s");
+        assert_eq!(wrap("a"), "<a>");
+        assert_eq!(wrap('b'), "<b>");
+        assert_eq!(wrap(owned.clone()), "<s>");
+        assert_eq!(wrap(Dbg(1)), "<1>");
+        assert_eq!(wrap(Some("c")), "<c>");
+        assert_eq!(wrap(CAT), "<>");
+        assert_eq!(wrap(CAT + owned.as_str()), "<s>");
+        assert_eq!(wrap(CAT + "x" + owned + 'y'), "<xsy>");
+    }
+}
@@ -0,0 +1,112 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use cat::Cat;
+use std::fmt::{self, Display};
+
+/// A piece that concatenates the pieces of an iterator.
+///
+/// This is created by the [`iter`](fn.iter.html) function.
+#[derive(Clone, Copy, Debug)]
+pub struct CatIter<I> {
+    iter: I,
+}
+
+/// Creates a piece that concatenates the pieces of `iter`.
+///
+/// The pieces are not collected. Instead, `iter` is cloned and
+/// traversed once to compute the size hint and once more to append
+/// the pieces, so the concatenation is still materialized with one
+/// allocation. Iterators over borrowed items, such as `slice::Iter`,
+/// are cheap to clone. To collect the pieces instead, use
+/// [`join`](fn.join.html) with an empty separator.
+///
+/// # Examples
+///
+/// ```rust
+/// use sconcat::{CAT, iter};
+///
+/// let components = vec!["usr", "local", "bin"];
+/// let dirs = components.iter().map(|c| CAT + '/' + *c);
+/// let s = String::from(CAT + iter(dirs) + '/');
+/// assert_eq!(s, "/usr/local/bin/");
+/// assert_eq!(s.len(), s.capacity());
+/// ```
+pub fn iter<I>(iter: I) -> CatIter<I::IntoIter>
+where
+    I: IntoIterator,
+    I::IntoIter: Clone,
+    I::Item: Cat,
+{
+    CatIter {
+        iter: iter.into_iter(),
+    }
+}
+
+impl<I> Cat for CatIter<I>
+where
+    I: Iterator + Clone,
+    I::Item: Cat,
+{
+    fn size_hint(&self) -> usize {
+        let mut len = Some(0usize);
+        for item in self.iter.clone() {
+            len = len.and_then(|len| len.checked_add(item.size_hint()));
+        }
+        len.expect("capacity overflow")
+    }
+
+    fn append_to(&self, s: &mut String) {
+        for item in self.iter.clone() {
+            item.append_to(s);
+        }
+    }
+
+    fn for_each_segment(&self, f: &mut dyn FnMut(&str)) {
+        for item in self.iter.clone() {
+            item.for_each_segment(f);
+        }
+    }
+
+    fn pieces(&self) -> usize {
+        self.iter.clone().map(|item| item.pieces()).sum()
+    }
+}
+
+impl<I> Display for CatIter<I>
+where
+    I: Iterator + Clone,
+    I::Item: Cat + Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for item in self.iter.clone() {
+            Display::fmt(&item, f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use CAT;
+    use super::iter;
+
+    #[test]
+    fn iterated() {
+        let words = ["a", "bc", "def"];
+        let cat = CAT + '[' + iter(&words) + ']';
+        assert_eq!(cat.to_string(), "[abcdef]");
+        let s = String::from(cat);
+        assert_eq!(s, "[abcdef]");
+        assert_eq!(s.len(), s.capacity());
+        let mut s = String::from("x");
+        s += CAT + iter(words.iter().rev().map(|w| CAT + *w + ' '));
+        assert_eq!(s, "xdef bc a ");
+        assert_eq!(String::from(CAT + iter(Vec::<char>::new())), "");
+    }
+}
@@ -0,0 +1,246 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use cat::Cat;
+use std::error::Error;
+use std::fmt::{self, Display};
+
+#[cfg(windows)]
+const PATH_SEPARATOR: &str = ";";
+#[cfg(not(windows))]
+const PATH_SEPARATOR: &str = ":";
+
+/// A piece that joins pieces with a separator.
+///
+/// This is created by the [`join`](fn.join.html) and
+/// [`try_join`](fn.try_join.html) functions.
+#[derive(Clone, Debug)]
+pub struct Join<'a, T: Cat> {
+    items: Vec<T>,
+    sep: &'a str,
+}
+
+/// Creates a piece that joins the pieces of `iter` with `sep`.
+///
+/// The pieces are collected, but not materialized. The size hint is
+/// the sum of the size hints of the pieces and separators.
+///
+/// # Examples
+///
+/// ```rust
+/// use sconcat::{CAT, join};
+///
+/// let cat = CAT + '[' + join(vec!["a", "b", "c"], ", ") + ']';
+/// assert_eq!(String::from(cat), "[a, b, c]");
+/// ```
+pub fn join<I>(iter: I, sep: &str) -> Join<'_, I::Item>
+where
+    I: IntoIterator,
+    I::Item: Cat,
+{
+    Join {
+        items: iter.into_iter().collect(),
+        sep,
+    }
+}
+
+/// Creates a piece that joins the pieces of `iter` with `sep`, or
+/// returns the first error.
+///
+/// No items are taken from `iter` after the first error.
+///
+/// # Examples
+///
+/// ```rust
+/// use sconcat::{CAT, try_join};
+///
+/// fn check(name: &str) -> Result<&str, String> {
+///     if name.chars().all(char::is_alphanumeric) {
+///         Ok(name)
+///     } else {
+///         Err(String::from(CAT + "invalid name: " + name))
+///     }
+/// }
+///
+/// let names = ["alice", "bob"];
+/// let users = try_join(names.iter().map(|n| check(n)), ",")?;
+/// let cat = CAT + "users=" + users;
+/// assert_eq!(String::from(cat), "users=alice,bob");
+///
+/// let names = ["alice", "b;b", "c;c"];
+/// let err = try_join(names.iter().map(|n| check(n)), ",").unwrap_err();
+/// assert_eq!(err, "invalid name: b;b");
+/// # Ok::<(), String>(())
+/// ```
+pub fn try_join<I, T, E>(iter: I, sep: &str) -> Result<Join<'_, T>, E>
+where
+    I: IntoIterator<Item = Result<T, E>>,
+    T: Cat,
+{
+    Ok(Join {
+        items: iter.into_iter().collect::<Result<_, _>>()?,
+        sep,
+    })
+}
+
+/// An error returned when a path contains the separator of path
+/// lists.
+///
+/// This is returned by the [`join_paths`](fn.join_paths.html)
+/// function.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct JoinPathsError {
+    index: usize,
+}
+
+impl JoinPathsError {
+    /// Returns the index of the first path that contains the
+    /// separator.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+}
+
+impl Display for JoinPathsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "path {} contains the path list separator {:?}",
+            self.index,
+            PATH_SEPARATOR
+        )
+    }
+}
+
+impl Error for JoinPathsError {}
+
+/// Creates a piece that joins the paths of `iter` with the separator
+/// of the platform's `PATH` environment variable, that is `;` on
+/// Windows and `:` elsewhere.
+///
+/// This returns an error if a path contains the separator, as the
+/// path could not be split again. Outside Windows, this is the rule
+/// of `std::env::join_paths`. On Windows, `std::env::join_paths`
+/// instead encloses a path containing `;` in double quotes and only
+/// rejects paths containing `"`, while this function rejects any
+/// path containing `;` and accepts `"`. The paths are checked
+/// segment by segment, but not materialized.
+///
+/// # Examples
+///
+/// ```rust
+/// use sconcat::{CAT, join_paths};
+/// use std::env;
+///
+/// let home = String::from("/home/user");
+/// let dirs = vec![CAT + home.as_str() + "/bin", CAT + "/usr" + "/bin"];
+/// let path = String::from(CAT + join_paths(dirs).unwrap());
+/// let expected = env::join_paths(&["/home/user/bin", "/usr/bin"]).unwrap();
+/// assert_eq!(path, expected.to_str().unwrap());
+/// ```
+pub fn join_paths<I>(iter: I) -> Result<Join<'static, I::Item>, JoinPathsError>
+where
+    I: IntoIterator,
+    I::Item: Cat,
+{
+    let items: Vec<I::Item> = iter.into_iter().collect();
+    for (index, item) in items.iter().enumerate() {
+        let mut found = false;
+        item.for_each_segment(&mut |segment| {
+            found = found || segment.contains(PATH_SEPARATOR);
+        });
+        if found {
+            return Err(JoinPathsError { index });
+        }
+    }
+    Ok(Join {
+        items,
+        sep: PATH_SEPARATOR,
+    })
+}
+
+impl<'a, T: Cat> Cat for Join<'a, T> {
+    fn size_hint(&self) -> usize {
+        let seps = self.items.len().saturating_sub(1);
+        let mut len = self.sep.len().checked_mul(seps);
+        for item in &self.items {
+            len = len.and_then(|len| len.checked_add(item.size_hint()));
+        }
+        len.expect("capacity overflow")
+    }
+
+    fn append_to(&self, s: &mut String) {
+        for (i, item) in self.items.iter().enumerate() {
+            if i > 0 {
+                s.push_str(self.sep);
+            }
+            item.append_to(s);
+        }
+    }
+
+    fn for_each_segment(&self, f: &mut dyn FnMut(&str)) {
+        for (i, item) in self.items.iter().enumerate() {
+            if i > 0 {
+                f(self.sep);
+            }
+            item.for_each_segment(f);
+        }
+    }
+
+    fn pieces(&self) -> usize {
+        self.items.iter().map(Cat::pieces).sum()
+    }
+}
+
+impl<'a, T: Cat + Display> Display for Join<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, item) in self.items.iter().enumerate() {
+            if i > 0 {
+                f.write_str(self.sep)?;
+            }
+            Display::fmt(item, f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use CAT;
+    use super::{PATH_SEPARATOR, join, join_paths, try_join};
+
+    #[test]
+    fn joins() {
+        let parts = vec![String::from("a"), String::from("bc")];
+        let empty = join(None::<&str>, ",");
+        let s = String::from(CAT + join(parts, " + ") + " = " + empty);
+        assert_eq!(s, "a + bc = ");
+        assert_eq!(s.len(), s.capacity());
+        assert_eq!(join(vec![CAT + 'x' + 'y'], ",").to_string(), "xy");
+    }
+
+    #[test]
+    fn short_circuits() {
+        let mut taken = 0;
+        let items = vec![Ok("a"), Err(1), Err(2), Ok("b")];
+        let result = try_join(items.into_iter().inspect(|_| taken += 1), ",");
+        assert_eq!(result.unwrap_err(), 1);
+        assert_eq!(taken, 2);
+        let ok: Result<_, ()> = try_join(vec![Ok('a'), Ok('b')], "-");
+        assert_eq!(String::from(CAT + ok.unwrap()), "a-b");
+    }
+
+    #[test]
+    fn paths() {
+        let s = String::from(CAT + join_paths(vec!["a", "b", "c"]).unwrap());
+        assert_eq!(s, ["a", "b", "c"].join(PATH_SEPARATOR));
+        let bad = String::from(CAT + "x" + PATH_SEPARATOR);
+        let err = join_paths(vec![String::from("ok"), bad]).unwrap_err();
+        assert_eq!(err.index(), 1);
+    }
+}
@@ -0,0 +1,59 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Integration with the js-sys crate. Every segment passed to
+// JavaScript is a separate call across the wasm boundary, so short
+// segments are coalesced as when writing to a formatter, and the
+// resulting strings are joined with `String.prototype.concat`. No
+// Rust `String` holding the whole concatenation is allocated.
+//
+// JavaScript strings are only available on wasm targets, so there
+// are no tests here; calling these functions elsewhere panics.
+
+use cat::{self, Cat, CatMany, CatOne, CatStart};
+use js_sys::JsString;
+use std::fmt::{Result, Write};
+
+struct JsWriter {
+    acc: Option<JsString>,
+}
+
+impl Write for JsWriter {
+    fn write_str(&mut self, s: &str) -> Result {
+        let piece = JsString::from(s);
+        self.acc = Some(match self.acc.take() {
+            Some(acc) => acc.concat(&piece),
+            None => piece,
+        });
+        Ok(())
+    }
+}
+
+fn js_string<C: Cat>(src: &C) -> JsString {
+    let mut w = JsWriter { acc: None };
+    cat::write_coalesced(src, &mut w).unwrap();
+    w.acc.unwrap_or_else(|| JsString::from(""))
+}
+
+impl<L: Cat, R: Cat> From<CatMany<L, R>> for JsString {
+    fn from(src: CatMany<L, R>) -> JsString {
+        js_string(&src)
+    }
+}
+
+impl<T: Cat> From<CatOne<T>> for JsString {
+    fn from(src: CatOne<T>) -> JsString {
+        js_string(&src.inner)
+    }
+}
+
+impl From<CatStart> for JsString {
+    fn from(_src: CatStart) -> JsString {
+        JsString::from("")
+    }
+}
@@ -0,0 +1,377 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use cat::{Cat, SegmentWriter};
+use count::CountingWriter;
+use std::fmt::{self, Debug, Display, Write};
+
+/// Creates a piece from `format!`-style arguments without formatting
+/// them.
+///
+/// Formatting is deferred until the concatenation is materialized,
+/// so that no intermediate `String` is allocated. The arguments are
+/// evaluated and formatted twice, once to compute an exact size hint
+/// and once to append them. Variables used in the arguments are
+/// borrowed, so the piece cannot outlive them.
+///
+/// # Examples
+///
+/// ```rust
+/// #[macro_use]
+/// extern crate sconcat;
+/// use sconcat::CAT;
+///
+/// # fn main() {
+/// let (x, y) = (1.5, -2);
+/// let cat = CAT + "point " + fmt_piece!("({:.2}, {})", x, y) + '!';
+/// assert_eq!(String::from(cat), "point (1.50, -2)!");
+/// # }
+/// ```
+#[macro_export]
+macro_rules! fmt_piece {
+    ($($arg:tt)*) => {
+        $crate::FmtPiece::new(
+            |f: &mut ::std::fmt::Formatter| write!(f, $($arg)*)
+        )
+    };
+}
+
+/// Creates a concatenation from a `format!`-style template.
+///
+/// The template is parsed at compile time by `format_args!`,
+/// including variables captured by name, but nothing is formatted
+/// until the concatenation is materialized. As the size hint of the
+/// piece is exact, the result is still built with one allocation,
+/// and it can be extended with more pieces.
+///
+/// Unlike for [`fmt_piece!`](macro.fmt_piece.html), the arguments
+/// after the template are evaluated once, when the macro is invoked,
+/// and are moved into the piece like into a `move` closure, so pass
+/// a reference to keep using a value that is not `Copy`. Variables
+/// captured by name in the template are borrowed. At most 16
+/// arguments can follow the template.
+///
+/// # Examples
+///
+/// ```rust
+/// #[macro_use]
+/// extern crate sconcat;
+/// use sconcat::CAT;
+///
+/// # fn main() {
+/// let (name, n) = ("Ann", 3);
+/// let cat = catf!("Hello, {name}! You have {n} messages") + '.';
+/// assert_eq!(String::from(cat), "Hello, Ann! You have 3 messages.");
+/// let items = vec![1, 2, 3];
+/// let cat = CAT + "> " + catf!("{:>3}|{sum}", items.len(), sum = 6);
+/// assert_eq!(String::from(cat), ">   3|6");
+/// # }
+/// ```
+#[macro_export]
+macro_rules! catf {
+    ($fmt:literal $(,)*) => {
+        $crate::CAT + $crate::fmt_piece!($fmt)
+    };
+    ($fmt:literal, $($args:tt)+) => {
+        $crate::__catf!(
+            $fmt; []; []; [];
+            [a0 a1 a2 a3 a4 a5 a6 a7 a8 a9 a10 a11 a12 a13 a14 a15];
+            $($args)+
+        )
+    };
+}
+
+// Binds every argument to a name from the pool, collecting the
+// values, the names and the arguments for `write!`.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __catf {
+    (
+        $fmt:literal; [$($val:expr,)*]; [$($used:ident)*]; [$($arg:tt)*];
+        [$($pool:ident)*];
+    ) => {
+        $crate::CAT + $crate::__fmt_bind(
+            ($($val,)*),
+            |&($(ref $used,)*), f: &mut ::std::fmt::Formatter| {
+                write!(f, $fmt, $($arg)*)
+            },
+        )
+    };
+    (
+        $fmt:literal; [$($val:expr,)*]; [$($used:ident)*]; [$($arg:tt)*];
+        [];
+        $($rest:tt)+
+    ) => {
+        compile_error!("too many arguments for catf!")
+    };
+    (
+        $fmt:literal; [$($val:expr,)*]; [$($used:ident)*]; [$($arg:tt)*];
+        [$next:ident $($pool:ident)*];
+        $name:ident = $value:expr $(, $($rest:tt)*)?
+    ) => {
+        $crate::__catf!(
+            $fmt; [$($val,)* $value,]; [$($used)* $next];
+            [$($arg)* $name = $next,]; [$($pool)*];
+            $($($rest)*)?
+        )
+    };
+    (
+        $fmt:literal; [$($val:expr,)*]; [$($used:ident)*]; [$($arg:tt)*];
+        [$next:ident $($pool:ident)*];
+        $value:expr $(, $($rest:tt)*)?
+    ) => {
+        $crate::__catf!(
+            $fmt; [$($val,)* $value,]; [$($used)* $next];
+            [$($arg)* $next,]; [$($pool)*];
+            $($($rest)*)?
+        )
+    };
+}
+
+// Creates a piece that formats arguments evaluated by `catf!`.
+#[doc(hidden)]
+pub fn __fmt_bind<A, G>(
+    args: A,
+    fmt: G,
+) -> FmtPiece<impl Fn(&mut fmt::Formatter) -> fmt::Result>
+where
+    G: Fn(&A, &mut fmt::Formatter) -> fmt::Result,
+{
+    FmtPiece::new(move |f: &mut fmt::Formatter| fmt(&args, f))
+}
+
+/// A piece that formats its contents when it is materialized.
+///
+/// This is created by the [`fmt_piece!`](macro.fmt_piece.html)
+/// macro.
+#[derive(Clone, Copy)]
+pub struct FmtPiece<F> {
+    fmt: F,
+}
+
+impl<F> FmtPiece<F>
+where
+    F: Fn(&mut fmt::Formatter) -> fmt::Result,
+{
+    /// Creates a piece from a formatting closure.
+    pub fn new(fmt: F) -> FmtPiece<F> {
+        FmtPiece { fmt }
+    }
+}
+
+impl<F> Cat for FmtPiece<F>
+where
+    F: Fn(&mut fmt::Formatter) -> fmt::Result,
+{
+    fn size_hint(&self) -> usize {
+        let mut w = CountingWriter::new();
+        write!(w, "{}", self).expect("capacity overflow");
+        w.count()
+    }
+
+    fn append_to(&self, s: &mut String) {
+        write!(s, "{}", self).unwrap();
+    }
+
+    fn for_each_segment(&self, f: &mut dyn FnMut(&str)) {
+        write!(SegmentWriter::new(f), "{}", self).unwrap();
+    }
+}
+
+impl<F> Display for FmtPiece<F>
+where
+    F: Fn(&mut fmt::Formatter) -> fmt::Result,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        (self.fmt)(f)
+    }
+}
+
+impl<F> Debug for FmtPiece<F>
+where
+    F: Fn(&mut fmt::Formatter) -> fmt::Result,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("FmtPiece")
+            .field(&format_args!("{}", self))
+            .finish()
+    }
+}
+
+/// A piece that is produced by a closure when it is materialized.
+///
+/// This is created by the [`lazy`](fn.lazy.html) function.
+#[derive(Clone, Copy)]
+pub struct Lazy<F> {
+    append: F,
+    size_hint: usize,
+}
+
+/// Creates a piece that is appended by a closure when the
+/// concatenation is materialized.
+///
+/// The closure is called with the `String` being built, and must
+/// only append to it. The size hint is zero unless set with
+/// [`with_size_hint`](struct.Lazy.html#method.with_size_hint), in
+/// which case no reallocation takes place if the closure appends at
+/// most that many bytes.
+///
+/// # Examples
+///
+/// ```rust
+/// use sconcat::{CAT, lazy};
+///
+/// let items = [3, 1, 2];
+/// let sorted = lazy(|s: &mut String| {
+///     let mut v = items;
+///     v.sort();
+///     for i in &v {
+///         s.push(char::from(b'0' + *i));
+///     }
+/// });
+/// let cat = CAT + "sorted: " + sorted.with_size_hint(3);
+/// let s = String::from(cat);
+/// assert_eq!(s, "sorted: 123");
+/// assert_eq!(s.len(), s.capacity());
+/// ```
+pub fn lazy<F>(append: F) -> Lazy<F>
+where
+    F: Fn(&mut String),
+{
+    Lazy {
+        append,
+        size_hint: 0,
+    }
+}
+
+impl<F> Lazy<F>
+where
+    F: Fn(&mut String),
+{
+    /// Sets the number of bytes reserved for the piece.
+    pub fn with_size_hint(self, size_hint: usize) -> Lazy<F> {
+        Lazy { size_hint, ..self }
+    }
+}
+
+impl<F> Cat for Lazy<F>
+where
+    F: Fn(&mut String),
+{
+    fn size_hint(&self) -> usize {
+        self.size_hint
+    }
+
+    fn append_to(&self, s: &mut String) {
+        (self.append)(s);
+    }
+
+    fn for_each_segment(&self, f: &mut dyn FnMut(&str)) {
+        let mut s = String::with_capacity(self.size_hint);
+        self.append_to(&mut s);
+        f(&s);
+    }
+}
+
+impl<F> Display for Lazy<F>
+where
+    F: Fn(&mut String),
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut s = String::with_capacity(self.size_hint);
+        self.append_to(&mut s);
+        f.write_str(&s)
+    }
+}
+
+impl<F> Debug for Lazy<F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Lazy")
+            .field("size_hint", &self.size_hint)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use CAT;
+    use std::cell::Cell;
+    use super::lazy;
+
+    #[test]
+    fn deferred() {
+        let calls = Cell::new(0);
+        let count = |n: u32| {
+            calls.set(calls.get() + 1);
+            n
+        };
+        let piece = fmt_piece!("{:>4}|{:x}", "ab", count(255));
+        let cat = CAT + "[" + piece + "]";
+        assert_eq!(calls.get(), 0);
+        let s = String::from(cat);
+        assert_eq!(s, "[  ab|ff]");
+        assert_eq!(calls.get(), 2);
+        assert_eq!(s.len(), s.capacity());
+    }
+
+    #[test]
+    fn borrowed() {
+        let name = String::from("world");
+        let piece = fmt_piece!("hello, {}", name);
+        assert_eq!(format!("{:?}", piece), "FmtPiece(hello, world)");
+        assert_eq!(piece.to_string(), "hello, world");
+        assert_eq!(name, "world");
+    }
+
+    #[test]
+    fn closure() {
+        let calls = Cell::new(0);
+        let piece = lazy(|s: &mut String| {
+            calls.set(calls.get() + 1);
+            s.push_str("late");
+        });
+        assert_eq!(format!("{:?}", piece), "Lazy { size_hint: 0 }");
+        assert_eq!(calls.get(), 0);
+        let s = String::from(CAT + "[" + piece.with_size_hint(4) + "]");
+        assert_eq!(s, "[late]");
+        assert_eq!(s.len(), s.capacity());
+        assert_eq!(calls.get(), 1);
+        assert_eq!((CAT + piece).to_string(), "late");
+        assert_eq!(calls.get(), 2);
+    }
+
+    #[test]
+    fn template() {
+        let (user, count) = ("bob", 12);
+        let cat = catf!("{user} has {count:>4} items") + '!';
+        let s = String::from(cat);
+        assert_eq!(s, "bob has   12 items!");
+        assert_eq!(s.len(), s.capacity());
+        assert_eq!(String::from(catf!("plain")), "plain");
+    }
+
+    #[test]
+    fn template_arguments() {
+        let calls = Cell::new(0);
+        let count = |n: u32| {
+            calls.set(calls.get() + 1);
+            n
+        };
+        let name = String::from("ann");
+        let cat = catf!("{name}: {:>4}|{x:x}", count(7), x = count(255),);
+        assert_eq!(calls.get(), 2);
+        let s = String::from(cat + '!');
+        assert_eq!(s, "ann:    7|ff!");
+        assert_eq!(s.len(), s.capacity());
+        assert_eq!(calls.get(), 2);
+        assert_eq!(name, "ann");
+        let owned = String::from("moved");
+        let cat = catf!("<{}{}>", owned, &name);
+        assert_eq!(cat.to_string(), "<movedann>");
+    }
+}
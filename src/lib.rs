@@ -98,18 +98,481 @@
 //! sconcat = "0.1"
 //! ```
 //!
+//! ## Optional features
+//!
+//! The crate has the following optional features:
+//!
+//!  1. `bech32`, disabled by default. This provides the
+//!     [`bech32`][bech32], [`bech32m`][bech32m] and
+//!     [`segwit`][segwit] pieces.
+//!  2. `secrecy`, disabled by default. This implements the
+//!     concatenation traits for `Secret<String>` from the
+//!     [`secrecy`][secrecy] crate, and a concatenation can be
+//!     converted into a `Secret<String>`. Secret pieces are only
+//!     exposed by that conversion; everywhere else, including
+//!     `String::from`, formatting and serializing, they are replaced
+//!     by `[REDACTED]`.
+//!  3. `subtle`, disabled by default. This provides a `ct_eq`
+//!     method to compare a concatenation with a string in constant
+//!     time using the [`subtle`][subtle] crate.
+//!  4. `punycode`, disabled by default. This provides the
+//!     [`punycode`][punycode] piece for internationalized host
+//!     names.
+//!  5. `hooks`, disabled by default. This provides
+//!     [`set_materialize_hook`][hook] to observe every conversion of a
+//!     concatenation to a `String`, for example to collect metrics.
+//!  6. `embedded-io`, disabled by default. This provides a
+//!     `write_to_embedded` method to write a concatenation piece by
+//!     piece to an [`embedded_io::Write`][embedded-io] target.
+//!  7. `alloc-check`, disabled by default. With this feature and
+//!     debug assertions, converting or appending a concatenation to
+//!     a `String` panics if the buffer had to be reallocated because
+//!     a size hint was too small. This is meant to catch regressions
+//!     in tests.
+//!  8. `tracing`, disabled by default. This provides a `field`
+//!     method to record a concatenation lazily as a field of a
+//!     [`tracing`][tracing] span or event.
+//!  9. `rayon`, disabled by default. This provides
+//!     [`ParString`][ParString] to collect the pieces of a
+//!     [`rayon`][rayon] parallel iterator into an exactly sized
+//!     `String`, and [`par_concat`][par_concat] to concatenate a
+//!     slice of many pieces using multiple threads.
+//! 10. `itoa`, disabled by default. This implements the
+//!     concatenation traits for the primitive integer types, which
+//!     are formatted directly into the destination using the
+//!     [`itoa`][itoa] crate.
+//! 11. `ryu`, disabled by default. This implements the concatenation
+//!     traits for `f32` and `f64`, which are formatted directly into
+//!     the destination in their shortest round-trip form using the
+//!     [`ryu`][ryu] crate.
+//! 12. `futures-io` and `tokio`, disabled by default. These provide
+//!     the `write_to_async` and `write_to_tokio` methods to write a
+//!     concatenation segment by segment to a
+//!     [`futures_io::AsyncWrite`][futures-io] or
+//!     [`tokio::io::AsyncWrite`][tokio] writer.
+//! 13. `serde`, disabled by default. This implements `Serialize`
+//!     from the [`serde`][serde] crate for concatenations, which are
+//!     serialized as strings without being materialized first when
+//!     the serializer supports it.
+//! 14. `heapless`, disabled by default. This provides a
+//!     `write_to_heapless` method to append a concatenation to a
+//!     fixed-capacity [`heapless::String`][heapless] without any
+//!     allocations.
+//! 15. `arrayvec`, disabled by default. This provides a
+//!     `try_append_to_array` method to append a concatenation to an
+//!     [`arrayvec::ArrayString`][arrayvec] without any allocations.
+//! 16. `compact_str`, disabled by default. This implements
+//!     conversion of a concatenation into a
+//!     [`CompactString`][compact_str], which stores short results
+//!     inline without allocating.
+//! 17. `bytes`, disabled by default. This provides an
+//!     `append_to_bytes` method to append a concatenation to a
+//!     `BytesMut` buffer, and an `into_buf` method to convert a
+//!     concatenation into a [`bytes::Buf`][bytes].
+//! 18. `base64`, disabled by default. This provides the
+//!     [`base64`][base64] piece to encode bytes in base64 with the
+//!     standard or URL-safe alphabet.
+//! 19. `unicode-normalization`, disabled by default. This provides
+//!     the [`nfc`][nfc] piece and a `normalize_nfc` method to
+//!     normalize text to Unicode Normalization Form C with the
+//!     [`unicode-normalization`][unicode-normalization] crate.
+//! 20. `ufmt`, disabled by default. This implements `uDisplay` and
+//!     `uDebug` from the [`ufmt`][ufmt] crate for concatenations,
+//!     provides a `write_to_ufmt` method to write a concatenation
+//!     segment by segment to a `uWrite` sink without using
+//!     `core::fmt`, and provides the [`UDisp`][UDisp] piece for
+//!     `uDisplay` values.
+//! 21. `defmt`, disabled by default. This implements `Format` from
+//!     the [`defmt`][defmt] crate for concatenations, which are
+//!     streamed to the logger without being materialized first.
+//! 22. `test-support`, disabled by default. This provides the
+//!     [`testing`][testing] module and the
+//!     `debug_assert_single_alloc!` macro to count the allocations
+//!     made when a concatenation is converted to a `String`.
+//! 23. `rope`, disabled by default. This provides [`Rope`][Rope], a
+//!     chunked string that a concatenation can be converted into
+//!     without copying its large owned pieces.
+//! 24. `chrono` and `time`, disabled by default. These implement the
+//!     concatenation traits for `DateTime` from the
+//!     [`chrono`][chrono] crate and for `OffsetDateTime` from the
+//!     [`time`][time] crate, which are written as RFC 3339
+//!     timestamps.
+//! 25. `uuid`, disabled by default. This implements the
+//!     concatenation traits for `Uuid` and its formatter types from
+//!     the [`uuid`][uuid] crate, which are written with an exact size
+//!     hint.
+//! 26. `wasm`, disabled by default. This implements conversion of a
+//!     concatenation into a [`js_sys::JsString`][js-sys], which is
+//!     built from the segments without an intermediate `String`.
+//! 27. `encoding_rs`, disabled by default. This provides
+//!     `encode_to` and `try_encode_to` methods to encode a
+//!     concatenation in a legacy encoding such as Shift_JIS or
+//!     windows-1252 with the [`encoding_rs`][encoding_rs] crate.
+//! 28. `unicode-width`, disabled by default. This provides the
+//!     [`pad_display_width`][pad_display_width] and
+//!     [`truncate_display_width`][truncate_display_width] pieces,
+//!     which measure text in terminal columns with the
+//!     [`unicode-width`][unicode-width] crate.
+//! 29. `log`, disabled by default. This provides the `cat_log!`
+//!     macro to log a concatenation with the [`log`][log] crate
+//!     without building it if the level is disabled.
+//! 30. `digest`, disabled by default. This provides a `hash_into`
+//!     method to update a hash function from the [`digest`][digest]
+//!     crate with a concatenation without materializing it.
+//! 31. `fast_fmt`, disabled by default. This implements `Fmt` from
+//!     the [`fast_fmt`][fast_fmt] crate for concatenations. It is a
+//!     thin bridge over the crate's own [`Write`][Write] sink, which
+//!     does not need any optional feature.
+//! 32. `hashbrown`, disabled by default. This provides
+//!     [`hashbrown_get_or_insert_with`][hashbrown_get], which looks up
+//!     a concatenated key in a [`hashbrown`][hashbrown] map without
+//!     materializing it unless it is inserted.
+//!
 //! [CAT]:     constant.CAT.html
 //! [Debug]:   https://doc.rust-lang.org/std/fmt/trait.Debug.html
 //! [Display]: https://doc.rust-lang.org/std/fmt/trait.Display.html
+//! [ParString]: struct.ParString.html
+//! [Rope]:    struct.Rope.html
 //! [String]:  https://doc.rust-lang.org/std/string/struct.String.html
+//! [UDisp]:   struct.UDisp.html
+//! [Write]:   trait.Write.html
+//! [arrayvec]: https://docs.rs/arrayvec/0.7/arrayvec/struct.ArrayString.html
+//! [base64]:  fn.base64.html
+//! [bech32]:  fn.bech32.html
+//! [bech32m]: fn.bech32m.html
+//! [bytes]:   https://docs.rs/bytes/1/bytes/trait.Buf.html
 //! [char]:    https://doc.rust-lang.org/std/primitive.char.html
+//! [chrono]:  https://crates.io/crates/chrono
+//! [compact_str]: https://docs.rs/compact_str/0.9/compact_str/struct.CompactString.html
+//! [defmt]:   https://docs.rs/defmt/1/defmt/trait.Format.html
+//! [digest]:  https://crates.io/crates/digest
+//! [embedded-io]: https://docs.rs/embedded-io/0.6/embedded_io/trait.Write.html
+//! [encoding_rs]: https://crates.io/crates/encoding_rs
+//! [fast_fmt]: https://crates.io/crates/fast_fmt
+//! [futures-io]: https://docs.rs/futures-io/0.3/futures_io/trait.AsyncWrite.html
+//! [hashbrown]: https://crates.io/crates/hashbrown
+//! [hashbrown_get]: fn.hashbrown_get_or_insert_with.html
+//! [heapless]: https://docs.rs/heapless/0.8/heapless/struct.String.html
+//! [hook]:    fn.set_materialize_hook.html
+//! [itoa]:    https://crates.io/crates/itoa
+//! [js-sys]:  https://docs.rs/js-sys/0.3/js_sys/struct.JsString.html
+//! [log]:     https://crates.io/crates/log
+//! [nfc]:     fn.nfc.html
+//! [pad_display_width]: fn.pad_display_width.html
+//! [par_concat]: fn.par_concat.html
+//! [punycode]: fn.punycode.html
+//! [rayon]:   https://crates.io/crates/rayon
+//! [ryu]:     https://crates.io/crates/ryu
+//! [secrecy]: https://crates.io/crates/secrecy
+//! [segwit]:  fn.segwit.html
+//! [serde]:   https://crates.io/crates/serde
 //! [str]:     https://doc.rust-lang.org/std/primitive.str.html
+//! [subtle]:  https://crates.io/crates/subtle
+//! [testing]: testing/index.html
+//! [time]:    https://crates.io/crates/time
+//! [tokio]:   https://docs.rs/tokio/1/tokio/io/trait.AsyncWrite.html
+//! [tracing]: https://crates.io/crates/tracing
+//! [truncate_display_width]: fn.truncate_display_width.html
+//! [ufmt]:    https://crates.io/crates/ufmt
+//! [unicode-normalization]: https://crates.io/crates/unicode-normalization
+//! [unicode-width]: https://crates.io/crates/unicode-width
+//! [uuid]:    https://crates.io/crates/uuid
 
+#[cfg(feature = "arrayvec")]
+extern crate arrayvec;
+#[cfg(feature = "bytes")]
+extern crate bytes;
+#[cfg(feature = "chrono")]
+extern crate chrono;
+#[cfg(feature = "compact_str")]
+extern crate compact_str;
+#[cfg(feature = "defmt")]
+extern crate defmt;
+#[cfg(feature = "digest")]
+extern crate digest;
+#[cfg(feature = "embedded-io")]
+extern crate embedded_io;
+#[cfg(feature = "encoding_rs")]
+extern crate encoding_rs;
 #[cfg(feature = "fast_fmt")]
 extern crate fast_fmt;
+#[cfg(feature = "futures-io")]
+extern crate futures_io;
+#[cfg(feature = "hashbrown")]
+extern crate hashbrown;
+#[cfg(feature = "heapless")]
+extern crate heapless;
+#[cfg(feature = "itoa")]
+extern crate itoa;
+#[cfg(feature = "wasm")]
+extern crate js_sys;
+#[cfg(feature = "log")]
+extern crate log;
+#[cfg(feature = "rayon")]
+extern crate rayon;
+#[cfg(feature = "ryu")]
+extern crate ryu;
+#[cfg(feature = "secrecy")]
+extern crate secrecy;
+#[cfg(feature = "serde")]
+extern crate serde;
+#[cfg(all(test, feature = "serde"))]
+extern crate serde_json;
+#[cfg(feature = "subtle")]
+extern crate subtle;
+#[cfg(feature = "time")]
+extern crate time;
+#[cfg(feature = "tokio")]
+extern crate tokio;
+#[cfg(feature = "tracing")]
+extern crate tracing;
+#[cfg(feature = "ufmt")]
+extern crate ufmt;
+#[cfg(feature = "unicode-normalization")]
+extern crate unicode_normalization;
+#[cfg(feature = "unicode-width")]
+extern crate unicode_width;
+#[cfg(feature = "uuid")]
+extern crate uuid;
 
+mod amount;
+mod args;
+#[cfg(any(feature = "futures-io", feature = "tokio"))]
+mod asyncio;
+#[cfg(feature = "base64")]
+mod base64;
+#[cfg(feature = "bech32")]
+mod bech32;
+mod boxed;
+#[cfg(feature = "bytes")]
+mod buf;
+mod buffer;
+mod case;
 mod cat;
-pub use cat::CAT;
+mod catwrite;
+mod chain;
+mod chars;
+mod chunked;
+mod chunks;
+#[cfg(feature = "compact_str")]
+mod compact;
+mod cond;
+mod count;
+mod cow;
+mod cstring;
+#[cfg(feature = "subtle")]
+mod ct;
+mod date;
+mod dbg;
+#[cfg(feature = "defmt")]
+mod dfmt;
+mod disp;
+mod dyncat;
+mod edit;
+#[cfg(feature = "embedded-io")]
+mod embedded;
+#[cfg(feature = "encoding_rs")]
+mod encoding;
+mod eq;
+pub mod escape;
+mod extra;
+mod fallible;
+#[cfg(feature = "fast_fmt")]
+mod fastfmt;
+mod file;
+mod fixed;
+mod frame;
+#[cfg(feature = "ryu")]
+mod float;
+mod group;
+mod hash;
+mod hex;
+mod hexdump;
+#[cfg(feature = "hooks")]
+mod hooks;
+mod indent;
+#[cfg(feature = "itoa")]
+mod int;
+mod into;
+pub mod http;
+mod iter;
+mod join;
+#[cfg(feature = "wasm")]
+mod js;
+mod lazy;
+mod limit;
+mod list;
+mod line;
+#[cfg(feature = "log")]
+mod logging;
+mod lossy;
+mod map;
+mod mapstr;
+pub mod md;
+mod net;
+#[cfg(feature = "unicode-normalization")]
+mod nfc;
+mod oscat;
+mod owned;
+mod pad;
+mod pathcat;
+#[cfg(feature = "rayon")]
+mod par;
+pub mod pretty;
+#[cfg(feature = "punycode")]
+mod punycode;
+mod radix;
+mod reader;
+mod recycle;
+mod repeat;
+#[cfg(feature = "rope")]
+mod rope;
+mod search;
+#[cfg(feature = "secrecy")]
+mod secret;
+mod segments;
+mod sink;
+mod sep;
+#[cfg(feature = "serde")]
+mod ser;
+mod slice;
+mod stack;
+mod static_cat;
+mod table;
+mod tee;
+#[cfg(feature = "test-support")]
+pub mod testing;
+#[cfg(feature = "tracing")]
+mod trace;
+mod truncate;
+mod tuple;
+#[cfg(feature = "uuid")]
+mod uid;
+mod url;
+mod utf16;
+#[cfg(feature = "ufmt")]
+mod uwrite;
+mod vcat;
+#[cfg(feature = "unicode-width")]
+mod width;
+mod wrap;
+mod write;
+pub use amount::{Btc, Sats, btc, sats};
+#[cfg(feature = "futures-io")]
+pub use asyncio::WriteAsync;
+#[cfg(feature = "tokio")]
+pub use asyncio::WriteTokio;
+#[cfg(feature = "base64")]
+pub use base64::{Base64, Base64Alphabet, base64, base64_with};
+#[cfg(feature = "bech32")]
+pub use bech32::{Bech32, bech32, bech32m, segwit};
+#[cfg(feature = "bytes")]
+pub use buf::CatBuf;
+pub use buffer::BufferTooSmall;
+pub use case::{CaseMap, lower, upper};
+pub use cat::{
+    CAT,
+    Cat,
+    CatMany,
+    CatOne,
+    CatStart,
+    SegmentBuf,
+    SegmentWriter,
+};
+#[doc(hidden)]
+pub use catwrite::{__CatWriteFmt, __CatWriteIo, __WriteCat};
+pub use chain::{ErrorChain, error_chain};
+pub use chars::{Bytes, CharIndices, Chars};
+pub use chunks::{ByteChunks, Chunks};
+pub use cond::{
+    DebugOnly,
+    Either,
+    ReleaseOnly,
+    When,
+    debug_only,
+    either,
+    release_only,
+    when,
+};
+pub use count::CountingWriter;
+pub use date::{HttpDate, Rfc3339, http_date, rfc3339};
+pub use dbg::Dbg;
+pub use disp::{Disp, disp, disp_hint};
+pub use dyncat::DynCat;
+#[cfg(feature = "encoding_rs")]
+pub use encoding::UnmappableError;
+pub use extra::Extra;
+pub use fallible::CatError;
+pub use fixed::CapacityError;
+pub use frame::Endian;
+pub use group::{Group, group};
+pub use hex::{Hex, hex, hex_upper};
+pub use hexdump::{HexDump, hexdump};
+#[cfg(feature = "hooks")]
+pub use hooks::{
+    Materialization,
+    remove_materialize_hook,
+    set_materialize_hook,
+};
+pub use indent::{Indent, indent};
+pub use into::IntoCat;
+pub use iter::{CatIter, iter};
+pub use join::{Join, JoinPathsError, join, join_paths, try_join};
+#[doc(hidden)]
+pub use lazy::__fmt_bind;
+pub use lazy::{FmtPiece, Lazy, lazy};
+pub use limit::LimitError;
+#[cfg(feature = "log")]
+#[doc(hidden)]
+pub use logging::{__LogCat, __log, __log_enabled};
+pub use lossy::{LossyOs, LossyUtf8, lossy, lossy_utf8};
+pub use map::get_or_insert_with;
+#[cfg(feature = "hashbrown")]
+pub use map::hashbrown_get_or_insert_with;
+pub use mapstr::{MapStr, map_str};
+#[cfg(feature = "unicode-normalization")]
+pub use nfc::{Nfc, nfc};
+pub use oscat::OSCAT;
+pub use pad::{Pad, pad, zero_pad};
+pub use pathcat::PCAT;
+#[cfg(feature = "rayon")]
+pub use par::{ParString, par_concat};
+#[cfg(feature = "punycode")]
+pub use punycode::{Punycode, punycode};
+pub use radix::{Radix, bin, hex_int, oct};
+pub use reader::CatReader;
+pub use repeat::{Repeat, RepeatChar, repeat, repeat_char, spaces};
+#[cfg(feature = "rope")]
+pub use rope::Rope;
+pub use segments::Segments;
+pub use sep::Sep;
+pub use sink::{FmtSink, Write};
+pub use slice::Slice;
+pub use stack::StackString;
+#[doc(hidden)]
+pub use static_cat::{
+    __static_cat_bytes,
+    __static_cat_len,
+    __static_cat_str,
+};
+pub use table::{Align, Table};
+pub use tee::Tee;
+#[cfg(feature = "tracing")]
+pub use trace::CatField;
+pub use truncate::{Truncate, truncate};
+pub use url::{DotSegmentError, Query, UrlPath, query, url_path};
+#[cfg(feature = "ufmt")]
+pub use uwrite::UDisp;
+pub use vcat::{BCAT, VCAT};
+#[cfg(feature = "unicode-width")]
+pub use width::{
+    PadWidth,
+    TruncateWidth,
+    pad_display_width,
+    truncate_display_width,
+};
+pub use wrap::wrap;
+pub use write::IoSliceStorage;
 
 #[cfg(test)]
 mod tests {
@@ -0,0 +1,187 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use cat::{Cat, CatMany, CatOne, CatStart};
+use std::error::Error;
+use std::fmt::{self, Display};
+
+/// An error returned when a concatenation is larger than the
+/// permitted limit.
+///
+/// This is returned by the `try_into_string_limited` and `into_frame`
+/// methods.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LimitError {
+    pub(crate) size: usize,
+    pub(crate) limit: usize,
+}
+
+impl LimitError {
+    /// Returns the size of the concatenation in bytes.
+    ///
+    /// This is `usize::MAX` if the size overflows `usize`.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Returns the limit that was exceeded in bytes.
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+}
+
+impl Display for LimitError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "concatenation of {} bytes exceeds limit of {} bytes",
+            self.size,
+            self.limit
+        )
+    }
+}
+
+impl Error for LimitError {}
+
+fn check_limit(size: usize, limit: usize) -> Result<(), LimitError> {
+    if size > limit {
+        Err(LimitError { size, limit })
+    } else {
+        Ok(())
+    }
+}
+
+// Checks the size hint before allocating, and then the actual length
+// of the segments while appending them, as a piece can report a size
+// hint that is too small. Once the limit is exceeded, nothing more is
+// appended, but the rest of the segments are still measured for the
+// error.
+fn into_string_limited<C: Cat>(
+    cat: &C,
+    limit: usize,
+) -> Result<String, LimitError> {
+    let capacity = cat.checked_size_hint().unwrap_or(usize::MAX);
+    check_limit(capacity, limit)?;
+    let mut s = String::with_capacity(capacity);
+    let mut size = 0usize;
+    cat.for_each_segment(&mut |segment| {
+        size = size.saturating_add(segment.len());
+        if size <= limit {
+            s.push_str(segment);
+        }
+    });
+    check_limit(size, limit)?;
+    Ok(s)
+}
+
+impl<L: Cat, R: Cat> CatMany<L, R> {
+    /// Converts the concatenation to a `String` if it is not longer
+    /// than `limit` bytes.
+    ///
+    /// If the size hint exceeds the limit, this fails without
+    /// allocating. Otherwise, the string is allocated with the size
+    /// hint as its capacity, and the limit is also checked against
+    /// the actual length of the segments while they are appended, so
+    /// the string never grows past the limit even if a piece reports
+    /// a size hint that is too small.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use sconcat::CAT;
+    ///
+    /// let user_input = "x".repeat(100);
+    /// let cat = CAT + "name=" + user_input.as_str();
+    /// let err = cat.try_into_string_limited(64).unwrap_err();
+    /// assert_eq!(err.size(), 105);
+    /// let cat = CAT + "name=" + "alice";
+    /// assert_eq!(cat.try_into_string_limited(64).unwrap(), "name=alice");
+    /// ```
+    pub fn try_into_string_limited(
+        self,
+        limit: usize,
+    ) -> Result<String, LimitError> {
+        into_string_limited(&self, limit)
+    }
+}
+
+impl<T: Cat> CatOne<T> {
+    /// Converts the concatenation to a `String` if it is not longer
+    /// than `limit` bytes.
+    ///
+    /// The limit is checked like for
+    /// [`CatMany::try_into_string_limited`][CatMany].
+    ///
+    /// [CatMany]: struct.CatMany.html#method.try_into_string_limited
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use sconcat::CAT;
+    ///
+    /// let cat = CAT + "hello";
+    /// assert!(cat.try_into_string_limited(4).is_err());
+    /// assert_eq!(cat.try_into_string_limited(5).unwrap(), "hello");
+    /// ```
+    pub fn try_into_string_limited(
+        self,
+        limit: usize,
+    ) -> Result<String, LimitError> {
+        into_string_limited(&self.inner, limit)
+    }
+}
+
+impl CatStart {
+    /// Converts the empty concatenation to a `String`.
+    ///
+    /// This never fails, as the empty concatenation is within any
+    /// limit.
+    pub fn try_into_string_limited(
+        self,
+        _limit: usize,
+    ) -> Result<String, LimitError> {
+        Ok(String::new())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use CAT;
+    use disp::disp_hint;
+    use repeat::repeat;
+
+    #[test]
+    fn limits() {
+        let cat = CAT + "abc" + 'd' + String::from("ef");
+        let err = cat.clone().try_into_string_limited(5).unwrap_err();
+        assert_eq!((err.size(), err.limit()), (6, 5));
+        assert_eq!(
+            err.to_string(),
+            "concatenation of 6 bytes exceeds limit of 5 bytes"
+        );
+        assert_eq!(cat.try_into_string_limited(6).unwrap(), "abcdef");
+        assert_eq!(CAT.try_into_string_limited(0).unwrap(), "");
+    }
+
+    #[test]
+    fn overflowing_hint() {
+        let cat = CAT + "a" + repeat("x", usize::MAX);
+        let err = cat.try_into_string_limited(10).unwrap_err();
+        assert_eq!((err.size(), err.limit()), (usize::MAX, 10));
+    }
+
+    #[test]
+    fn short_hint() {
+        let big = "y".repeat(1000);
+        let cat = CAT + "a" + disp_hint(&big, 0);
+        let err = cat.try_into_string_limited(10).unwrap_err();
+        assert_eq!((err.size(), err.limit()), (1001, 10));
+        let cat = CAT + "a" + disp_hint("bc", 0);
+        assert_eq!(cat.try_into_string_limited(3).unwrap(), "abc");
+    }
+}
@@ -0,0 +1,88 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use cat::{Cat, CatMany, CatOne, CatStart};
+
+impl CatStart {
+    /// Starts a concatenation with `item` followed by a newline.
+    ///
+    /// This is the same as `CAT + item + '\n'`. As the newline is
+    /// just another piece, a concatenation built line by line is
+    /// still sized in one pass and materialized with one allocation.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// #[macro_use]
+    /// extern crate sconcat;
+    /// use sconcat::CAT;
+    ///
+    /// # fn main() {
+    /// let port = String::from("8080");
+    /// let cat = CAT.line("[server]").line(cat!("port=", port));
+    /// assert_eq!(String::from(cat), "[server]\nport=8080\n");
+    /// # }
+    /// ```
+    pub fn line<T: Cat>(self, item: T) -> CatMany<T, char> {
+        CatOne { inner: item } + '\n'
+    }
+}
+
+impl<L: Cat> CatOne<L> {
+    /// Appends `item` followed by a newline.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use sconcat::CAT;
+    ///
+    /// let cat = (CAT + "# header\n").line("body");
+    /// assert_eq!(String::from(cat), "# header\nbody\n");
+    /// ```
+    pub fn line<T: Cat>(self, item: T) -> CatMany<CatMany<L, T>, char> {
+        self + item + '\n'
+    }
+}
+
+impl<L: Cat, R: Cat> CatMany<L, R> {
+    /// Appends `item` followed by a newline.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use sconcat::CAT;
+    ///
+    /// let cat = CAT.line("SELECT *").line("FROM t") + ';';
+    /// assert_eq!(String::from(cat), "SELECT *\nFROM t\n;");
+    /// ```
+    pub fn line<T: Cat>(
+        self,
+        item: T,
+    ) -> CatMany<CatMany<CatMany<L, R>, T>, char> {
+        self + item + '\n'
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use CAT;
+
+    #[test]
+    fn lines() {
+        let key = "name";
+        let cat = CAT
+            .line("[user]")
+            .line(CAT + key + '=' + "x")
+            .line("")
+            .line(String::from("end"));
+        let s = String::from(cat);
+        assert_eq!(s, "[user]\nname=x\n\nend\n");
+        assert_eq!(s.len(), s.capacity());
+        assert_eq!((CAT + 'a').line('b').to_string(), "ab\n");
+    }
+}
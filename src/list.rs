@@ -0,0 +1,133 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Slices, arrays and vectors of pieces are pieces themselves, so
+// that a list of parts whose length is only known at run time can be
+// concatenated directly. The elements are appended in order with no
+// separator; use `join` to insert one. Like tuples, these are foreign
+// types and do not implement `Display`. Since `char` is a piece, this
+// also covers `&[char]` and `[char; N]`, whose size hint is the exact
+// UTF-8 length and whose chars are pushed one by one. The elements
+// are exposed as child pieces, so that walking the segments of a
+// long list one at a time does not start from the first element for
+// every segment.
+
+use cat::Cat;
+#[cfg(feature = "rope")]
+use rope::Rope;
+
+macro_rules! list_cat {
+    ($([$($gen:tt)*] $L:ty;)*) => { $(
+        impl<$($gen)*> Cat for $L {
+            fn size_hint(&self) -> usize {
+                self.checked_size_hint().expect("capacity overflow")
+            }
+
+            fn append_to(&self, s: &mut String) {
+                for item in self.iter() {
+                    item.append_to(s);
+                }
+            }
+
+            fn for_each_segment(&self, f: &mut dyn FnMut(&str)) {
+                for item in self.iter() {
+                    item.for_each_segment(f);
+                }
+            }
+
+            fn checked_size_hint(&self) -> Option<usize> {
+                self.iter().try_fold(0usize, |len, item| {
+                    len.checked_add(item.checked_size_hint()?)
+                })
+            }
+
+            fn pieces(&self) -> usize {
+                self.iter().map(Cat::pieces).sum()
+            }
+
+            fn segment_count(&self) -> usize {
+                self.iter().map(Cat::segment_count).sum()
+            }
+
+            fn child_count(&self) -> usize {
+                self.len()
+            }
+
+            fn child_at(&self, index: usize) -> &dyn Cat {
+                &self[index]
+            }
+
+            // Elements of arrays and vectors are moved into the rope,
+            // while elements of slices are borrowed and copied.
+            #[cfg(feature = "rope")]
+            fn append_to_rope(self, rope: &mut Rope) {
+                for item in IntoIterator::into_iter(self) {
+                    item.append_to_rope(rope);
+                }
+            }
+
+            #[cfg(feature = "secrecy")]
+            fn for_each_exposed_segment(&self, f: &mut dyn FnMut(&str)) {
+                for item in self.iter() {
+                    item.for_each_exposed_segment(f);
+                }
+            }
+        }
+    )* };
+}
+
+list_cat! {
+    ['a, T: Cat] &'a [T];
+    [T: Cat, const N: usize] [T; N];
+    [T: Cat] Vec<T>;
+}
+
+#[cfg(test)]
+mod tests {
+    use CAT;
+    use cat::Cat;
+
+    #[test]
+    fn lists() {
+        let components = vec!["usr", "/", "local", "/", "bin"];
+        let s = String::from(CAT + '/' + &components[..] + "/sh");
+        assert_eq!(s, "/usr/local/bin/sh");
+        assert_eq!(s.len(), s.capacity());
+        assert_eq!(components.size_hint(), 13);
+        assert_eq!(components.pieces(), 5);
+        let tokens = [String::from("a"), String::from("bc")];
+        assert_eq!(String::from(CAT + "<" + tokens + ">"), "<abc>");
+        let nested = vec![CAT + "x" + 'y', CAT + "z" + 'w'];
+        assert_eq!(nested.size_hint(), 4);
+        assert_eq!(nested.pieces(), 4);
+        assert_eq!(String::from(CAT + nested + "!"), "xyzw!");
+        let empty: Vec<&str> = Vec::new();
+        assert_eq!(String::from(CAT + "a" + empty + "b"), "ab");
+    }
+
+    #[test]
+    fn children() {
+        let parts = vec![String::from("a"), String::from("bc")];
+        assert_eq!(parts.child_count(), 2);
+        assert_eq!(parts.child_at(1).single_str(), Some("bc"));
+        let tokens = ["x", "y", "z"];
+        assert_eq!((&tokens[1..]).child_at(0).single_str(), Some("y"));
+        assert_eq!(Some(tokens).child_count(), 3);
+    }
+
+    #[test]
+    fn chars() {
+        let run = ['─'; 3];
+        assert_eq!(run.size_hint(), 9);
+        let s = String::from(CAT + '┌' + run + &['┐', '\n'][..]);
+        assert_eq!(s, "┌───┐\n");
+        assert_eq!(s.len(), s.capacity());
+        let wild: &[char] = &['*', 'é'];
+        assert_eq!(String::from(CAT + wild + wild), "*é*é");
+    }
+}
@@ -0,0 +1,133 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Support for `cat_log!`. The macros of the log crate are re-exported
+// so that the expansion does not require the caller to name the log
+// crate, and the concatenation is passed to them through a `Display`
+// wrapper that writes the segments, so pieces do not need to
+// implement `Display` themselves.
+
+use cat::Cat;
+use std::fmt::{self, Display};
+
+#[doc(hidden)]
+pub use log::{log as __log, log_enabled as __log_enabled};
+
+/// Logs a concatenation with the `log` crate if the level is enabled.
+///
+/// `cat_log!(level, a, b, c)` builds `CAT + a + b + c` and logs it at
+/// `level`, and `cat_log!(target: t, level, a, b, c)` logs it with
+/// the target `t`. The logger is asked first whether the record is
+/// enabled, and if it is not, the pieces are not even evaluated, so a
+/// disabled level costs neither allocations nor the work to build the
+/// pieces. If it is enabled, the concatenation is written to the
+/// logger segment by segment without being materialized.
+///
+/// # Examples
+///
+/// ```rust
+/// #[macro_use]
+/// extern crate sconcat;
+/// extern crate log;
+///
+/// use log::Level;
+///
+/// # fn main() {
+/// let user = String::from("alice");
+/// cat_log!(Level::Warn, "login failed for ", &*user, ", attempt ", '3');
+/// cat_log!(target: "audit", Level::Info, "user ", user, " locked out");
+/// # }
+/// ```
+#[macro_export]
+macro_rules! cat_log {
+    (target: $target:expr, $lvl:expr $(, $piece:expr)* $(,)*) => {{
+        let lvl = $lvl;
+        if $crate::__log_enabled!(target: $target, lvl) {
+            $crate::__log!(
+                target: $target,
+                lvl,
+                "{}",
+                $crate::__LogCat(&$crate::IntoCat::into_cat(
+                    $crate::CAT $(+ $piece)*
+                ))
+            );
+        }
+    }};
+    ($lvl:expr $(, $piece:expr)* $(,)*) => {
+        $crate::cat_log!(target: module_path!(), $lvl $(, $piece)*)
+    };
+}
+
+#[doc(hidden)]
+pub struct __LogCat<'a, C: 'a>(pub &'a C);
+
+impl<'a, C: Cat> Display for __LogCat<'a, C> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut result = Ok(());
+        self.0.for_each_segment(&mut |segment| {
+            if result.is_ok() {
+                result = f.write_str(segment);
+            }
+        });
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use log::{self, Level, LevelFilter, Log, Metadata, Record};
+    use std::sync::Mutex;
+
+    // Records the messages of enabled records; only `Info` and more
+    // severe levels are enabled.
+    struct Capture(Mutex<Vec<(String, String)>>);
+
+    impl Log for Capture {
+        fn enabled(&self, metadata: &Metadata) -> bool {
+            metadata.level() <= Level::Info
+        }
+
+        fn log(&self, record: &Record) {
+            let target = String::from(record.target());
+            let msg = record.args().to_string();
+            self.0.lock().unwrap().push((target, msg));
+        }
+
+        fn flush(&self) {}
+    }
+
+    static CAPTURE: Capture = Capture(Mutex::new(Vec::new()));
+
+    #[test]
+    fn cat_log() {
+        log::set_logger(&CAPTURE).unwrap();
+        log::set_max_level(LevelFilter::Trace);
+        let name = String::from("disk");
+        let mut evaluated = false;
+        cat_log!(Level::Info, "checking ", &*name, '…');
+        cat_log!(target: "t", Level::Error, name.as_str(), " failed",);
+        cat_log!(Level::Debug, "skipped ", {
+            evaluated = true;
+            "piece"
+        });
+        cat_log!(Level::Warn);
+        cat_log!(Level::Warn, name);
+        assert!(!evaluated);
+        let records = CAPTURE.0.lock().unwrap();
+        let module = String::from(module_path!());
+        assert_eq!(
+            *records,
+            [
+                (module.clone(), String::from("checking disk…")),
+                (String::from("t"), String::from("disk failed")),
+                (module.clone(), String::new()),
+                (module, String::from("disk")),
+            ]
+        );
+    }
+}
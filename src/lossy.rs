@@ -0,0 +1,202 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use cat::Cat;
+use std::ffi::OsStr;
+use std::fmt::{self, Display};
+#[cfg(unix)]
+use std::os::unix::ffi::OsStrExt;
+use std::str;
+
+const REPLACEMENT: &str = "\u{FFFD}";
+
+/// A piece that decodes bytes as UTF-8, replacing invalid sequences.
+///
+/// This is created by the [`lossy_utf8`](fn.lossy_utf8.html)
+/// function.
+#[derive(Clone, Copy, Debug)]
+pub struct LossyUtf8<'a> {
+    bytes: &'a [u8],
+}
+
+/// Creates a piece that decodes `bytes` as UTF-8 while appending.
+///
+/// Invalid sequences are replaced with U+FFFD REPLACEMENT CHARACTER
+/// in the same way as `String::from_utf8_lossy`, but no intermediate
+/// `String` is allocated. Valid runs are borrowed from `bytes`. The
+/// size hint is exact, accounting for the three bytes of each
+/// replacement character.
+///
+/// # Examples
+///
+/// ```rust
+/// use sconcat::{CAT, lossy_utf8};
+///
+/// let data = b"user=\xffbob\xe2\x98";
+/// let line = String::from(CAT + "recv " + lossy_utf8(data) + '\n');
+/// assert_eq!(line, "recv user=\u{FFFD}bob\u{FFFD}\n");
+/// ```
+pub fn lossy_utf8(bytes: &[u8]) -> LossyUtf8<'_> {
+    LossyUtf8 { bytes }
+}
+
+impl<'a> Cat for LossyUtf8<'a> {
+    fn size_hint(&self) -> usize {
+        let mut len = 0usize;
+        self.for_each_segment(&mut |segment| len += segment.len());
+        len
+    }
+
+    fn append_to(&self, s: &mut String) {
+        self.for_each_segment(&mut |segment| s.push_str(segment));
+    }
+
+    fn for_each_segment(&self, f: &mut dyn FnMut(&str)) {
+        let mut rest = self.bytes;
+        while !rest.is_empty() {
+            let error = match str::from_utf8(rest) {
+                Ok(valid) => {
+                    f(valid);
+                    return;
+                }
+                Err(error) => error,
+            };
+            let valid = error.valid_up_to();
+            if valid > 0 {
+                f(str::from_utf8(&rest[..valid]).unwrap());
+            }
+            f(REPLACEMENT);
+            // A sequence truncated at the end is replaced as a whole.
+            let invalid = error.error_len().unwrap_or(rest.len() - valid);
+            rest = &rest[valid + invalid..];
+        }
+    }
+}
+
+impl<'a> Display for LossyUtf8<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut result = Ok(());
+        self.for_each_segment(&mut |segment| {
+            if result.is_ok() {
+                result = f.write_str(segment);
+            }
+        });
+        result
+    }
+}
+
+/// A piece that converts an OS string to UTF-8, replacing invalid
+/// sequences.
+///
+/// This is created by the [`lossy`](fn.lossy.html) function.
+#[derive(Clone, Copy, Debug)]
+pub struct LossyOs<'a> {
+    os: &'a OsStr,
+}
+
+/// Creates a piece that converts `os` to UTF-8 while appending.
+///
+/// The output is the same as that of `OsStr::to_string_lossy`, but
+/// it is appended directly, so paths and environment variables can be
+/// mentioned in diagnostics without an intermediate `Cow<str>`. On
+/// Unix-like platforms nothing is allocated even if `os` is not valid
+/// UTF-8; elsewhere, only invalid strings are converted through
+/// `to_string_lossy`. The size hint is exact.
+///
+/// # Examples
+///
+/// ```rust
+/// use sconcat::{CAT, lossy};
+/// use std::path::Path;
+///
+/// let path = Path::new("/etc/app.conf");
+/// let msg = String::from(CAT + "cannot read " + lossy(path));
+/// assert_eq!(msg, "cannot read /etc/app.conf");
+/// ```
+pub fn lossy<S: AsRef<OsStr> + ?Sized>(os: &S) -> LossyOs<'_> {
+    LossyOs { os: os.as_ref() }
+}
+
+impl<'a> Cat for LossyOs<'a> {
+    fn size_hint(&self) -> usize {
+        let mut len = 0usize;
+        self.for_each_segment(&mut |segment| len += segment.len());
+        len
+    }
+
+    fn append_to(&self, s: &mut String) {
+        self.for_each_segment(&mut |segment| s.push_str(segment));
+    }
+
+    #[cfg(unix)]
+    fn for_each_segment(&self, f: &mut dyn FnMut(&str)) {
+        lossy_utf8(self.os.as_bytes()).for_each_segment(f);
+    }
+
+    #[cfg(not(unix))]
+    fn for_each_segment(&self, f: &mut dyn FnMut(&str)) {
+        match self.os.to_str() {
+            Some(valid) => f(valid),
+            None => f(&self.os.to_string_lossy()),
+        }
+    }
+}
+
+impl<'a> Display for LossyOs<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut result = Ok(());
+        self.for_each_segment(&mut |segment| {
+            if result.is_ok() {
+                result = f.write_str(segment);
+            }
+        });
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use CAT;
+    use std::ffi::OsStr;
+    #[cfg(unix)]
+    use std::os::unix::ffi::OsStrExt;
+    use super::{lossy, lossy_utf8};
+
+    #[test]
+    fn matches_std() {
+        let inputs: &[&[u8]] = &[
+            b"",
+            b"plain",
+            b"\xff",
+            b"a\xc3",
+            b"\xf0\x9f\x98\x80\xf0\x9f\x98",
+            b"\xed\xa0\x80x\xc0\xaf\xe2\x98\xba",
+        ];
+        for &bytes in inputs {
+            let s = String::from(CAT + lossy_utf8(bytes) + "");
+            assert_eq!(s, String::from_utf8_lossy(bytes));
+            assert_eq!(s.len(), s.capacity());
+            assert_eq!(lossy_utf8(bytes).to_string(), s);
+        }
+    }
+
+    #[test]
+    fn os_strings() {
+        let mut inputs = vec![OsStr::new(""), OsStr::new("plain/é")];
+        #[cfg(unix)]
+        inputs.push(OsStr::from_bytes(b"dir/\xff\xfe.txt"));
+        for os in inputs {
+            let s = String::from(CAT + '<' + lossy(os) + '>');
+            assert_eq!(s, format!("<{}>", os.to_string_lossy()));
+            assert_eq!(s.len(), s.capacity());
+            assert_eq!(lossy(os).to_string(), os.to_string_lossy());
+        }
+        let owned = String::from("x");
+        assert_eq!(lossy(&owned).to_string(), "x");
+    }
+}
@@ -0,0 +1,203 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use CAT;
+use cat::Cat;
+use std::collections::HashMap;
+use std::hash::BuildHasher;
+use std::str;
+
+// Keys up to this length are looked up from a buffer on the stack.
+const STACK_KEY_LEN: usize = 128;
+
+/// Returns the value for a concatenated key in a map, inserting the
+/// value returned by `default` if the key is not present.
+///
+/// Keys of up to 128 bytes are assembled in a buffer on the stack and
+/// looked up as a `&str`, so the key is only materialized as a
+/// `String` when an entry is inserted. As the entry API of `HashMap`
+/// needs an owned key, a key that is present is looked up twice, and
+/// a key that is inserted three times. Longer keys are always
+/// materialized. With the `hashbrown` feature,
+/// [`hashbrown_get_or_insert_with`](fn.hashbrown_get_or_insert_with.html)
+/// looks up short keys only once.
+///
+/// # Examples
+///
+/// ```rust
+/// use sconcat::{CAT, get_or_insert_with};
+/// use std::collections::HashMap;
+///
+/// let mut cache = HashMap::new();
+/// let (user, page) = ("alice", 3);
+/// let page = page.to_string();
+/// *get_or_insert_with(&mut cache, CAT + user + ':' + &*page, || 0) += 1;
+/// *get_or_insert_with(&mut cache, CAT + user + ':' + &*page, || 0) += 1;
+/// assert_eq!(cache["alice:3"], 2);
+/// ```
+pub fn get_or_insert_with<C, V, S, F>(
+    map: &mut HashMap<String, V, S>,
+    key: C,
+    default: F,
+) -> &mut V
+where
+    C: Cat,
+    S: BuildHasher,
+    F: FnOnce() -> V,
+{
+    if key.size_hint() <= STACK_KEY_LEN {
+        let mut buf = [0u8; STACK_KEY_LEN];
+        if let Some(borrowed) = stack_key(&key, &mut buf) {
+            // Returning the result of `get_mut` directly would keep
+            // the map borrowed on the insert path as well.
+            if map.contains_key(borrowed) {
+                return map.get_mut(borrowed).unwrap();
+            }
+            return map.entry(String::from(borrowed)).or_insert_with(default);
+        }
+    }
+    map.entry(String::from(CAT + key)).or_insert_with(default)
+}
+
+/// Returns the value for a concatenated key in a `hashbrown` map,
+/// inserting the value returned by `default` if the key is not
+/// present.
+///
+/// Keys of up to 128 bytes are assembled in a buffer on the stack
+/// and looked up once with `entry_ref`, so the key is only
+/// materialized as a `String` when an entry is inserted. Longer keys
+/// are always materialized.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use sconcat::{CAT, hashbrown_get_or_insert_with};
+///
+/// let mut cache = hashbrown::HashMap::with_hasher(RandomState::new());
+/// let user = "alice";
+/// *hashbrown_get_or_insert_with(&mut cache, CAT + user + ":3", || 0) += 1;
+/// assert_eq!(cache["alice:3"], 1);
+/// ```
+#[cfg(feature = "hashbrown")]
+pub fn hashbrown_get_or_insert_with<C, V, S, F>(
+    map: &mut hashbrown::HashMap<String, V, S>,
+    key: C,
+    default: F,
+) -> &mut V
+where
+    C: Cat,
+    S: BuildHasher,
+    F: FnOnce() -> V,
+{
+    if key.size_hint() <= STACK_KEY_LEN {
+        let mut buf = [0u8; STACK_KEY_LEN];
+        if let Some(borrowed) = stack_key(&key, &mut buf) {
+            return map.entry_ref(borrowed).or_insert_with(default);
+        }
+    }
+    map.entry(String::from(CAT + key)).or_insert_with(default)
+}
+
+// Assembles the key in `buf`, or returns `None` if it does not fit.
+fn stack_key<'b, C: Cat>(
+    key: &C,
+    buf: &'b mut [u8; STACK_KEY_LEN],
+) -> Option<&'b str> {
+    let mut len = 0;
+    let mut fits = true;
+    key.for_each_segment(&mut |segment| {
+        let end = len + segment.len();
+        if fits && end <= STACK_KEY_LEN {
+            buf[len..end].copy_from_slice(segment.as_bytes());
+            len = end;
+        } else {
+            fits = false;
+        }
+    });
+    if fits {
+        // The buffer holds whole segments, so it is valid UTF-8.
+        Some(str::from_utf8(&buf[..len]).unwrap())
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use CAT;
+    #[cfg(feature = "hashbrown")]
+    use std::collections::hash_map::RandomState;
+    use std::collections::HashMap;
+    use super::get_or_insert_with;
+
+    #[test]
+    fn inserts_once() {
+        let mut map = HashMap::new();
+        let mut calls = 0;
+        for _ in 0..3 {
+            let v = get_or_insert_with(&mut map, CAT + "k" + '☺', || {
+                calls += 1;
+                10
+            });
+            *v += 1;
+        }
+        assert_eq!(calls, 1);
+        assert_eq!(map["k☺"], 13);
+    }
+
+    #[test]
+    fn long_keys() {
+        let long = "x".repeat(200);
+        let mut map = HashMap::new();
+        *get_or_insert_with(&mut map, CAT + &*long + "!", || 0) += 1;
+        *get_or_insert_with(&mut map, CAT + &*long + "!", || 0) += 1;
+        assert_eq!(map.len(), 1);
+        assert_eq!(map[&(long + "!")], 2);
+    }
+
+    #[cfg(feature = "test-support")]
+    #[test]
+    fn hits_do_not_allocate() {
+        use testing::count_allocations;
+        let mut map = HashMap::new();
+        let user = String::from("alice");
+        let key = || CAT + &*user + ':' + '3';
+        let (_, count) = count_allocations(|| {
+            *get_or_insert_with(&mut map, key(), || 0) += 1;
+        });
+        assert!(count > 0);
+        let (_, count) = count_allocations(|| {
+            *get_or_insert_with(&mut map, key(), || 0) += 1;
+        });
+        assert_eq!(count, 0);
+        assert_eq!(map["alice:3"], 2);
+    }
+
+    #[cfg(feature = "hashbrown")]
+    #[test]
+    fn hashbrown_keys() {
+        use hashbrown_get_or_insert_with;
+        let mut map = hashbrown::HashMap::with_hasher(RandomState::new());
+        let mut calls = 0;
+        let key = || CAT + "k" + '☺';
+        for _ in 0..3 {
+            let v = hashbrown_get_or_insert_with(&mut map, key(), || {
+                calls += 1;
+                10
+            });
+            *v += 1;
+        }
+        assert_eq!(calls, 1);
+        assert_eq!(map["k☺"], 13);
+        let long = "x".repeat(200);
+        let key = || CAT + &*long + "!";
+        *hashbrown_get_or_insert_with(&mut map, key(), || 0) += 1;
+        *hashbrown_get_or_insert_with(&mut map, key(), || 0) += 1;
+        assert_eq!(map[&(long + "!")], 2);
+    }
+}
@@ -0,0 +1,143 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use cat::Cat;
+use std::borrow::Cow;
+use std::fmt::{self, Debug, Display};
+
+/// A piece that transforms the text of another piece with a
+/// function.
+///
+/// This is created by the [`map_str`](fn.map_str.html) function.
+#[derive(Clone, Copy)]
+pub struct MapStr<T: Cat, F> {
+    inner: T,
+    map: F,
+}
+
+/// Creates a piece that passes the text of `value` through `map`.
+///
+/// The text of `value` is borrowed if it is a single string slice or
+/// string, and is otherwise buffered in a temporary `String`, which
+/// only holds that piece and not the whole concatenation. The
+/// function can return the text unchanged as `Cow::Borrowed`. It is
+/// called twice, once to compute an exact size hint and once to
+/// append its result.
+///
+/// # Examples
+///
+/// ```rust
+/// use sconcat::{CAT, map_str};
+/// use std::borrow::Cow;
+///
+/// fn strip_newlines(s: &str) -> Cow<'_, str> {
+///     if s.contains('\n') {
+///         Cow::Owned(s.replace('\n', " "))
+///     } else {
+///         Cow::Borrowed(s)
+///     }
+/// }
+///
+/// let user = "evil\nFAKE LOG LINE";
+/// let cat = CAT + "user=" + map_str(CAT + user + '\n', strip_newlines);
+/// assert_eq!(String::from(cat), "user=evil FAKE LOG LINE ");
+/// ```
+pub fn map_str<T, F>(value: T, map: F) -> MapStr<T, F>
+where
+    T: Cat,
+    F: Fn(&str) -> Cow<str>,
+{
+    MapStr { inner: value, map }
+}
+
+impl<T, F> MapStr<T, F>
+where
+    T: Cat,
+    F: Fn(&str) -> Cow<str>,
+{
+    // Passes the transformed text to `f`.
+    fn with_mapped<R>(&self, f: &mut dyn FnMut(&str) -> R) -> R {
+        match self.inner.single_str() {
+            Some(s) => f(&(self.map)(s)),
+            None => {
+                let mut buf = String::with_capacity(self.inner.size_hint());
+                self.inner.append_to(&mut buf);
+                f(&(self.map)(&buf))
+            }
+        }
+    }
+}
+
+impl<T, F> Cat for MapStr<T, F>
+where
+    T: Cat,
+    F: Fn(&str) -> Cow<str>,
+{
+    fn size_hint(&self) -> usize {
+        self.with_mapped(&mut |s| s.len())
+    }
+
+    fn append_to(&self, s: &mut String) {
+        self.with_mapped(&mut |mapped| s.push_str(mapped));
+    }
+
+    fn for_each_segment(&self, f: &mut dyn FnMut(&str)) {
+        self.with_mapped(&mut |s| f(s));
+    }
+
+    fn segment_count(&self) -> usize {
+        1
+    }
+}
+
+impl<T, F> Display for MapStr<T, F>
+where
+    T: Cat,
+    F: Fn(&str) -> Cow<str>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.with_mapped(&mut |s| f.write_str(s))
+    }
+}
+
+impl<T: Cat + Debug, F> Debug for MapStr<T, F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("MapStr")
+            .field("inner", &self.inner)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use CAT;
+    use cat::Cat;
+    use std::borrow::Cow;
+    use std::cell::Cell;
+    use super::map_str;
+
+    #[test]
+    fn mapped() {
+        let calls = Cell::new(0);
+        let pin = map_str(CAT + "12" + '3' + "4", |s| {
+            calls.set(calls.get() + 1);
+            Cow::Owned(s.chars().map(|_| '*').collect::<String>())
+        });
+        let cat = CAT + "pin=" + pin + ';';
+        let s = String::from(cat);
+        assert_eq!(s, "pin=****;");
+        assert_eq!(s.len(), s.capacity());
+        assert_eq!(calls.get(), 2);
+        let upper = map_str("é!", |s: &str| Cow::Owned(s.to_uppercase()));
+        assert_eq!(upper.size_hint(), 3);
+        assert_eq!(upper.to_string(), "É!");
+        let same = map_str(String::from("x"), |s| Cow::Borrowed(s));
+        assert_eq!(format!("{:?}", same), "MapStr { inner: \"x\" }");
+        assert_eq!(String::from(CAT + same + ""), "x");
+    }
+}
@@ -0,0 +1,416 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Markdown pieces.
+//!
+//! The pieces in this module wrap other pieces in Markdown syntax.
+//! They can be concatenated like any other piece, so that a whole
+//! document can be built with at most one allocation.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use sconcat::CAT;
+//! use sconcat::md::{bullet, code, code_block, heading};
+//!
+//! let cat = CAT
+//!     + heading(2, "Build report")
+//!     + bullet(CAT + "target: " + code("x86_64"))
+//!     + bullet("status: ok")
+//!     + code_block("sh", "cargo build\n");
+//! let expected = "## Build report\n\
+//!                 - target: `x86_64`\n\
+//!                 - status: ok\n\
+//!                 ```sh\ncargo build\n```\n";
+//! assert_eq!(String::from(cat), expected);
+//! ```
+
+use cat::{Cat, SegmentWriter};
+use std::fmt::{self, Display, Write};
+
+const MIN_BLOCK_FENCE: usize = 3;
+
+/// A Markdown heading.
+///
+/// This is created by the [`heading`](fn.heading.html) function.
+#[derive(Clone, Copy, Debug)]
+pub struct Heading<T: Cat> {
+    level: usize,
+    text: T,
+}
+
+/// A Markdown bullet item.
+///
+/// This is created by the [`bullet`](fn.bullet.html) function.
+#[derive(Clone, Copy, Debug)]
+pub struct Bullet<T: Cat> {
+    item: T,
+}
+
+/// A Markdown code span.
+///
+/// This is created by the [`code`](fn.code.html) function.
+#[derive(Clone, Copy, Debug)]
+pub struct Code<T: Cat> {
+    text: T,
+}
+
+/// A fenced Markdown code block.
+///
+/// This is created by the [`code_block`](fn.code_block.html)
+/// function.
+#[derive(Clone, Copy, Debug)]
+pub struct CodeBlock<'a, T: Cat> {
+    info: &'a str,
+    body: T,
+}
+
+/// Creates an ATX heading of the given level followed by a newline.
+///
+/// # Panics
+///
+/// Panics if `level` is not in the range 1 to 6.
+///
+/// # Examples
+///
+/// ```rust
+/// use sconcat::CAT;
+/// use sconcat::md::heading;
+///
+/// assert_eq!(String::from(CAT + heading(1, "Title")), "# Title\n");
+/// ```
+pub fn heading<T: Cat>(level: usize, text: T) -> Heading<T> {
+    assert!((1..=6).contains(&level), "invalid heading level");
+    Heading { level, text }
+}
+
+/// Creates a bullet item followed by a newline.
+///
+/// # Examples
+///
+/// ```rust
+/// use sconcat::CAT;
+/// use sconcat::md::bullet;
+///
+/// let cat = CAT + bullet("first") + bullet("second");
+/// assert_eq!(String::from(cat), "- first\n- second\n");
+/// ```
+pub fn bullet<T: Cat>(item: T) -> Bullet<T> {
+    Bullet { item }
+}
+
+/// Creates a code span.
+///
+/// The span is delimited by a backtick string longer than any run of
+/// backticks inside `text`, and padded with spaces where required,
+/// so that the text is always rendered verbatim. As Markdown has no
+/// empty code span, empty text produces nothing.
+///
+/// # Examples
+///
+/// ```rust
+/// use sconcat::CAT;
+/// use sconcat::md::code;
+///
+/// assert_eq!(String::from(CAT + code("x + 1")), "`x + 1`");
+/// assert_eq!(String::from(CAT + code("a`b")), "``a`b``");
+/// assert_eq!(String::from(CAT + code("`")), "`` ` ``");
+/// assert_eq!(String::from(CAT + code("")), "");
+/// ```
+pub fn code<T: Cat>(text: T) -> Code<T> {
+    Code { text }
+}
+
+/// Creates a fenced code block with the info string `info`, which is
+/// usually the language, followed by a newline.
+///
+/// The fence is longer than any run of backticks inside `body`, and a
+/// newline is added after `body` if it does not end with one.
+///
+/// # Examples
+///
+/// ```rust
+/// use sconcat::CAT;
+/// use sconcat::md::code_block;
+///
+/// let cat = CAT + code_block("rust", "let x = 1;");
+/// assert_eq!(String::from(cat), "```rust\nlet x = 1;\n```\n");
+/// ```
+pub fn code_block<T: Cat>(info: &str, body: T) -> CodeBlock<'_, T> {
+    CodeBlock { info, body }
+}
+
+// Properties of the text inside code that affect the delimiters.
+struct Scan {
+    longest_run: usize,
+    first: Option<u8>,
+    last: Option<u8>,
+}
+
+fn scan<T: Cat>(text: &T) -> Scan {
+    let mut scan = Scan {
+        longest_run: 0,
+        first: None,
+        last: None,
+    };
+    let mut run = 0;
+    text.for_each_segment(&mut |segment| {
+        for &b in segment.as_bytes() {
+            if scan.first.is_none() {
+                scan.first = Some(b);
+            }
+            scan.last = Some(b);
+            if b == b'`' {
+                run += 1;
+                scan.longest_run = scan.longest_run.max(run);
+            } else {
+                run = 0;
+            }
+        }
+    });
+    scan
+}
+
+fn repeat<W: Write>(w: &mut W, c: char, count: usize) -> fmt::Result {
+    for _ in 0..count {
+        w.write_char(c)?;
+    }
+    Ok(())
+}
+
+impl<T: Cat> Heading<T> {
+    fn prefix<W: Write>(&self, w: &mut W) -> fmt::Result {
+        repeat(w, '#', self.level)?;
+        w.write_char(' ')
+    }
+}
+
+impl<T: Cat> Cat for Heading<T> {
+    fn size_hint(&self) -> usize {
+        self.text
+            .size_hint()
+            .checked_add(self.level + 2)
+            .expect("capacity overflow")
+    }
+
+    fn append_to(&self, s: &mut String) {
+        self.prefix(s).unwrap();
+        self.text.append_to(s);
+        s.push('\n');
+    }
+
+    fn for_each_segment(&self, f: &mut dyn FnMut(&str)) {
+        f(&"###### "[6 - self.level..]);
+        self.text.for_each_segment(f);
+        f("\n");
+    }
+}
+
+impl<T: Cat + Display> Display for Heading<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.prefix(f)?;
+        Display::fmt(&self.text, f)?;
+        f.write_char('\n')
+    }
+}
+
+impl<T: Cat> Cat for Bullet<T> {
+    fn size_hint(&self) -> usize {
+        self.item
+            .size_hint()
+            .checked_add(3)
+            .expect("capacity overflow")
+    }
+
+    fn append_to(&self, s: &mut String) {
+        s.push_str("- ");
+        self.item.append_to(s);
+        s.push('\n');
+    }
+
+    fn for_each_segment(&self, f: &mut dyn FnMut(&str)) {
+        f("- ");
+        self.item.for_each_segment(f);
+        f("\n");
+    }
+}
+
+impl<T: Cat + Display> Display for Bullet<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("- ")?;
+        Display::fmt(&self.item, f)?;
+        f.write_char('\n')
+    }
+}
+
+impl<T: Cat> Code<T> {
+    // Returns the number of backticks in the delimiters and whether
+    // the text needs to be padded with spaces. Empty text has no
+    // delimiters, as two backticks would not be parsed as a span.
+    fn delimiters(&self) -> (usize, bool) {
+        let scan = scan(&self.text);
+        if scan.first.is_none() {
+            return (0, false);
+        }
+        let pad = matches!(
+            (scan.first, scan.last),
+            (Some(b'`'), _) | (_, Some(b'`')) | (Some(b' '), Some(b' '))
+        );
+        (scan.longest_run + 1, pad)
+    }
+
+    fn open<W: Write>(w: &mut W, ticks: usize, pad: bool) -> fmt::Result {
+        repeat(w, '`', ticks)?;
+        if pad {
+            w.write_char(' ')?;
+        }
+        Ok(())
+    }
+
+    fn close<W: Write>(w: &mut W, ticks: usize, pad: bool) -> fmt::Result {
+        if pad {
+            w.write_char(' ')?;
+        }
+        repeat(w, '`', ticks)
+    }
+}
+
+impl<T: Cat> Cat for Code<T> {
+    fn size_hint(&self) -> usize {
+        let (ticks, pad) = self.delimiters();
+        let delimiters = 2 * (ticks + if pad { 1 } else { 0 });
+        self.text
+            .size_hint()
+            .checked_add(delimiters)
+            .expect("capacity overflow")
+    }
+
+    fn append_to(&self, s: &mut String) {
+        let (ticks, pad) = self.delimiters();
+        Code::<T>::open(s, ticks, pad).unwrap();
+        self.text.append_to(s);
+        Code::<T>::close(s, ticks, pad).unwrap();
+    }
+
+    fn for_each_segment(&self, f: &mut dyn FnMut(&str)) {
+        let (ticks, pad) = self.delimiters();
+        Code::<T>::open(&mut SegmentWriter::new(f), ticks, pad).unwrap();
+        self.text.for_each_segment(f);
+        Code::<T>::close(&mut SegmentWriter::new(f), ticks, pad).unwrap();
+    }
+}
+
+impl<T: Cat + Display> Display for Code<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (ticks, pad) = self.delimiters();
+        Code::<T>::open(f, ticks, pad)?;
+        Display::fmt(&self.text, f)?;
+        Code::<T>::close(f, ticks, pad)
+    }
+}
+
+impl<'a, T: Cat> CodeBlock<'a, T> {
+    // Returns the length of the fence and whether a newline is needed
+    // after the body.
+    fn layout(&self) -> (usize, bool) {
+        let scan = scan(&self.body);
+        let fence = (scan.longest_run + 1).max(MIN_BLOCK_FENCE);
+        let newline = match scan.last {
+            None | Some(b'\n') => false,
+            Some(_) => true,
+        };
+        (fence, newline)
+    }
+
+    fn open<W: Write>(&self, w: &mut W, fence: usize) -> fmt::Result {
+        repeat(w, '`', fence)?;
+        w.write_str(self.info)?;
+        w.write_char('\n')
+    }
+
+    fn close<W: Write>(w: &mut W, fence: usize, newline: bool) -> fmt::Result {
+        if newline {
+            w.write_char('\n')?;
+        }
+        repeat(w, '`', fence)?;
+        w.write_char('\n')
+    }
+}
+
+impl<'a, T: Cat> Cat for CodeBlock<'a, T> {
+    fn size_hint(&self) -> usize {
+        let (fence, newline) = self.layout();
+        let overhead = 2 * (fence + 1) + self.info.len();
+        let overhead = overhead + if newline { 1 } else { 0 };
+        self.body
+            .size_hint()
+            .checked_add(overhead)
+            .expect("capacity overflow")
+    }
+
+    fn append_to(&self, s: &mut String) {
+        let (fence, newline) = self.layout();
+        self.open(s, fence).unwrap();
+        self.body.append_to(s);
+        CodeBlock::<T>::close(s, fence, newline).unwrap();
+    }
+
+    fn for_each_segment(&self, f: &mut dyn FnMut(&str)) {
+        let (fence, newline) = self.layout();
+        self.open(&mut SegmentWriter::new(f), fence).unwrap();
+        self.body.for_each_segment(f);
+        CodeBlock::<T>::close(&mut SegmentWriter::new(f), fence, newline)
+            .unwrap();
+    }
+}
+
+impl<'a, T: Cat + Display> Display for CodeBlock<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (fence, newline) = self.layout();
+        self.open(f, fence)?;
+        Display::fmt(&self.body, f)?;
+        CodeBlock::<T>::close(f, fence, newline)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use CAT;
+    use super::{bullet, code, code_block, heading};
+
+    #[test]
+    fn headings_and_bullets() {
+        let cat = CAT + heading(3, CAT + "Section " + '1') + bullet("item");
+        let s = String::from(cat);
+        assert_eq!(s, "### Section 1\n- item\n");
+        assert_eq!(s.len(), s.capacity());
+        assert_eq!(cat.to_string(), s);
+    }
+
+    #[test]
+    fn code_spans() {
+        assert_eq!(String::from(CAT + code("")), "");
+        assert_eq!(code(CAT + "" + String::new()).to_string(), "");
+        assert_eq!(String::from(CAT + code(" a ")), "`  a  `");
+        let cat = CAT + code(CAT + "a`" + "`b" + '`');
+        let s = String::from(cat);
+        assert_eq!(s, "``` a``b` ```");
+        assert_eq!(s.len(), s.capacity());
+        assert_eq!(cat.to_string(), s);
+    }
+
+    #[test]
+    fn code_blocks() {
+        assert_eq!(String::from(CAT + code_block("", "")), "```\n```\n");
+        let cat = CAT + code_block("md", CAT + "````\n" + "x");
+        let s = String::from(cat);
+        assert_eq!(s, "`````md\n````\nx\n`````\n");
+        assert_eq!(s.len(), s.capacity());
+        assert_eq!(cat.to_string(), s);
+    }
+}
@@ -0,0 +1,109 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use cat::Cat;
+use std::fmt::{self, Display, Write};
+use std::net::{
+    IpAddr,
+    Ipv4Addr,
+    Ipv6Addr,
+    SocketAddr,
+    SocketAddrV4,
+    SocketAddrV6,
+};
+use std::str;
+
+// The longest address is an IPv6 socket address with an embedded
+// IPv4 address and a scope ID, such as
+// `[ffff:ffff:ffff:ffff:ffff:ffff:255.255.255.255%4294967295]:65535`.
+const MAX_LEN: usize = 64;
+
+// A stack buffer that an address is formatted into.
+struct AddrBuf {
+    buf: [u8; MAX_LEN],
+    len: usize,
+}
+
+impl AddrBuf {
+    fn new<T: Display>(addr: &T) -> AddrBuf {
+        let mut buf = AddrBuf {
+            buf: [0; MAX_LEN],
+            len: 0,
+        };
+        write!(buf, "{}", addr).expect("address too long");
+        buf
+    }
+
+    fn as_str(&self) -> &str {
+        str::from_utf8(&self.buf[..self.len]).unwrap()
+    }
+}
+
+impl Write for AddrBuf {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let end = self.len + s.len();
+        if end > MAX_LEN {
+            return Err(fmt::Error);
+        }
+        self.buf[self.len..end].copy_from_slice(s.as_bytes());
+        self.len = end;
+        Ok(())
+    }
+}
+
+// Addresses are formatted into a stack buffer, which is cheap enough
+// that the size hint formats the address to find its exact length.
+macro_rules! net_cat {
+    ($($T:ty)*) => { $(
+        impl Cat for $T {
+            fn size_hint(&self) -> usize {
+                AddrBuf::new(self).len
+            }
+
+            fn append_to(&self, s: &mut String) {
+                s.push_str(AddrBuf::new(self).as_str());
+            }
+
+            fn for_each_segment(&self, f: &mut dyn FnMut(&str)) {
+                f(AddrBuf::new(self).as_str());
+            }
+
+            fn segment_count(&self) -> usize {
+                1
+            }
+        }
+    )* };
+}
+
+net_cat! { IpAddr Ipv4Addr Ipv6Addr SocketAddr SocketAddrV4 SocketAddrV6 }
+
+#[cfg(test)]
+mod tests {
+    use CAT;
+    use cat::Cat;
+    use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV6};
+
+    #[test]
+    fn addresses() {
+        let peer = SocketAddr::from(([192, 168, 0, 10], 8080));
+        let ip = IpAddr::from(Ipv6Addr::LOCALHOST);
+        let s = String::from(CAT + "peer=" + peer + " bind=" + ip);
+        assert_eq!(s, "peer=192.168.0.10:8080 bind=::1");
+        assert_eq!(s.len(), s.capacity());
+        assert_eq!(Ipv4Addr::BROADCAST.size_hint(), 15);
+        let max = Ipv6Addr::from([
+            0xffff, 0xffff, 0xffff, 0xffff, 0xffff, 0xffff, 0xffff, 0xffff,
+        ]);
+        assert_eq!(max.size_hint(), 39);
+        let mapped = Ipv6Addr::from([0, 0, 0, 0, 0, 0xffff, 0xffff, 0xffff]);
+        let scoped = SocketAddrV6::new(mapped, 65535, 0, u32::MAX);
+        let s = String::from(CAT + scoped + "");
+        assert_eq!(s, "[::ffff:255.255.255.255%4294967295]:65535");
+        assert_eq!(scoped.size_hint(), s.len());
+    }
+}
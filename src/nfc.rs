@@ -0,0 +1,189 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use CAT;
+use cat::{Cat, CatMany, CatOne, CatStart};
+use std::fmt::{self, Display};
+use std::iter;
+use std::str;
+use unicode_normalization::char::canonical_combining_class;
+use unicode_normalization::{IsNormalized, UnicodeNormalization, is_nfc_quick};
+
+// Size of the buffer of normalized characters.
+const BUF_LEN: usize = 64;
+
+/// A piece that normalizes another piece to Unicode Normalization
+/// Form C.
+///
+/// This is created by the [`nfc`](fn.nfc.html) function.
+#[derive(Clone, Copy, Debug)]
+pub struct Nfc<T: Cat> {
+    inner: T,
+}
+
+/// Creates a piece that normalizes `value` to NFC.
+///
+/// The text is normalized while it is appended, one run at a time
+/// between the characters that cannot interact with what precedes
+/// them. Only such a run, which is usually a single character, is
+/// buffered. The size hint is exact; it is computed by normalizing
+/// once without storing the result.
+///
+/// # Examples
+///
+/// ```rust
+/// use sconcat::{CAT, nfc};
+///
+/// let base = "cafe";
+/// let accent = "\u{301}";
+/// let cat = CAT + nfc(CAT + base + accent) + '!';
+/// assert_eq!(String::from(cat), "caf\u{e9}!");
+/// ```
+pub fn nfc<T: Cat>(value: T) -> Nfc<T> {
+    Nfc { inner: value }
+}
+
+// Whether no character before `c` can be affected by normalization
+// of `c` and what follows.
+fn is_safe_point(c: char) -> bool {
+    canonical_combining_class(c) == 0
+        && is_nfc_quick(iter::once(c)) == IsNormalized::Yes
+}
+
+// Passes the NFC normalization of `run` to `f`.
+fn flush(run: &str, f: &mut dyn FnMut(&str)) {
+    let mut chars = run.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        if is_safe_point(c) {
+            // A single safe character is already normalized.
+            f(run);
+            return;
+        }
+    }
+    let mut buf = [0u8; BUF_LEN];
+    let mut len = 0;
+    for c in run.chars().nfc() {
+        if len + c.len_utf8() > BUF_LEN {
+            // Only whole characters are encoded into the buffer.
+            f(str::from_utf8(&buf[..len]).unwrap());
+            len = 0;
+        }
+        len += c.encode_utf8(&mut buf[len..]).len();
+    }
+    if len > 0 {
+        f(str::from_utf8(&buf[..len]).unwrap());
+    }
+}
+
+impl<T: Cat> Cat for Nfc<T> {
+    fn size_hint(&self) -> usize {
+        let mut len = 0usize;
+        self.for_each_segment(&mut |segment| len += segment.len());
+        len
+    }
+
+    fn append_to(&self, s: &mut String) {
+        self.for_each_segment(&mut |segment| s.push_str(segment));
+    }
+
+    fn for_each_segment(&self, f: &mut dyn FnMut(&str)) {
+        let mut run = String::new();
+        self.inner.for_each_segment(&mut |segment| {
+            for c in segment.chars() {
+                if is_safe_point(c) && !run.is_empty() {
+                    flush(&run, f);
+                    run.clear();
+                }
+                run.push(c);
+            }
+        });
+        if !run.is_empty() {
+            flush(&run, f);
+        }
+    }
+}
+
+impl<T: Cat> Display for Nfc<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut result = Ok(());
+        self.for_each_segment(&mut |segment| {
+            if result.is_ok() {
+                result = f.write_str(segment);
+            }
+        });
+        result
+    }
+}
+
+impl CatStart {
+    /// Returns an empty string, as the empty concatenation is
+    /// normalized.
+    pub fn normalize_nfc(self) -> String {
+        String::new()
+    }
+}
+
+impl<T: Cat> CatOne<T> {
+    /// Converts the concatenation into an NFC-normalized string.
+    ///
+    /// This is the same as converting `nfc(self)` into a string, and
+    /// allocates only once.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use sconcat::CAT;
+    ///
+    /// let s = (CAT + "A\u{30a}").normalize_nfc();
+    /// assert_eq!(s, "\u{c5}");
+    /// ```
+    pub fn normalize_nfc(self) -> String {
+        String::from(CAT + nfc(self.inner))
+    }
+}
+
+impl<L: Cat, R: Cat> CatMany<L, R> {
+    /// Converts the concatenation into an NFC-normalized string.
+    ///
+    /// This is the same as converting `nfc(self)` into a string, and
+    /// allocates only once.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use sconcat::CAT;
+    ///
+    /// let s = (CAT + "n" + '\u{303}' + "o").normalize_nfc();
+    /// assert_eq!(s, "\u{f1}o");
+    /// ```
+    pub fn normalize_nfc(self) -> String {
+        String::from(CAT + nfc(self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use CAT;
+    use cat::Cat;
+    use super::nfc;
+
+    #[test]
+    fn normalizes() {
+        // The accents are reordered and composed across segments.
+        let value =
+            CAT + "a" + "\u{323}" + "\u{302}b" + "\u{1100}" + '\u{1161}';
+        let s = String::from(CAT + '[' + nfc(value) + ']');
+        assert_eq!(s, "[\u{1ead}b\u{ac00}]");
+        assert_eq!(s.len(), s.capacity());
+        let decomposed = "e\u{301}".repeat(40);
+        assert_eq!(nfc(decomposed.as_str()).to_string(), "\u{e9}".repeat(40));
+        assert_eq!(nfc("\u{212b}").to_string(), "\u{c5}");
+        assert_eq!(nfc("").size_hint(), 0);
+        assert_eq!(CAT.normalize_nfc(), "");
+    }
+}
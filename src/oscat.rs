@@ -0,0 +1,402 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use cat::{self, CatMany, CatOne, CatStart};
+use std::ffi::{OsStr, OsString};
+use std::fmt::{self, Debug};
+use std::ops::{Add, AddAssign};
+
+/// Trait for types that can be concatenated into an `OsString`.
+pub trait OsCat {
+    /// Length of item in bytes of the platform encoding.
+    fn size_hint(&self) -> usize;
+    /// Append item to OsString.
+    fn append_to(&self, s: &mut OsString);
+    /// Converts item to an OsString.
+    fn into_os_string(self, capacity: usize) -> OsString;
+}
+
+impl OsCat for &OsStr {
+    fn size_hint(&self) -> usize {
+        self.len()
+    }
+
+    fn append_to(&self, s: &mut OsString) {
+        s.push(self);
+    }
+
+    fn into_os_string(self, capacity: usize) -> OsString {
+        let mut s = OsString::with_capacity(capacity);
+        s.push(self);
+        s
+    }
+}
+
+impl OsCat for OsString {
+    fn size_hint(&self) -> usize {
+        self.len()
+    }
+
+    fn append_to(&self, s: &mut OsString) {
+        s.push(self);
+    }
+
+    fn into_os_string(mut self, capacity: usize) -> OsString {
+        let len = self.len();
+        if capacity > len {
+            self.reserve(capacity - len);
+        }
+        self
+    }
+}
+
+impl OsCat for &str {
+    fn size_hint(&self) -> usize {
+        self.len()
+    }
+
+    fn append_to(&self, s: &mut OsString) {
+        s.push(self);
+    }
+
+    fn into_os_string(self, capacity: usize) -> OsString {
+        let mut s = OsString::with_capacity(capacity);
+        s.push(self);
+        s
+    }
+}
+
+impl OsCat for String {
+    fn size_hint(&self) -> usize {
+        self.len()
+    }
+
+    fn append_to(&self, s: &mut OsString) {
+        s.push(self);
+    }
+
+    fn into_os_string(self, capacity: usize) -> OsString {
+        OsString::from(self).into_os_string(capacity)
+    }
+}
+
+impl OsCat for char {
+    fn size_hint(&self) -> usize {
+        self.len_utf8()
+    }
+
+    fn append_to(&self, s: &mut OsString) {
+        s.push(self.encode_utf8(&mut [0; 4]));
+    }
+
+    fn into_os_string(self, capacity: usize) -> OsString {
+        let mut s = OsString::with_capacity(capacity);
+        self.append_to(&mut s);
+        s
+    }
+}
+
+fn append_oscat<C: OsCat>(s: &mut OsString, src: &C) {
+    s.reserve(src.size_hint());
+    src.append_to(s);
+}
+
+fn oscat_into_os_string<C: OsCat>(src: C) -> OsString {
+    let capacity = src.size_hint();
+    src.into_os_string(capacity)
+}
+
+#[derive(Clone, Copy)]
+pub struct OsCatMany<L: OsCat, R: OsCat> {
+    lhs: L,
+    rhs: R,
+}
+
+impl<L: OsCat, R: OsCat> OsCat for OsCatMany<L, R> {
+    fn size_hint(&self) -> usize {
+        self.lhs
+            .size_hint()
+            .checked_add(self.rhs.size_hint())
+            .expect("capacity overflow")
+    }
+
+    fn append_to(&self, s: &mut OsString) {
+        self.lhs.append_to(s);
+        self.rhs.append_to(s);
+    }
+
+    fn into_os_string(self, capacity: usize) -> OsString {
+        let mut s = self.lhs.into_os_string(capacity);
+        self.rhs.append_to(&mut s);
+        s
+    }
+}
+
+impl<L: OsCat, R: OsCat> Add<OsCatStart> for OsCatMany<L, R> {
+    type Output = OsCatMany<L, R>;
+    fn add(self, _rhs: OsCatStart) -> OsCatMany<L, R> {
+        self
+    }
+}
+
+impl<L: OsCat, R: OsCat, RR: OsCat> Add<OsCatOne<RR>> for OsCatMany<L, R> {
+    type Output = OsCatMany<OsCatMany<L, R>, RR>;
+    fn add(self, rhs: OsCatOne<RR>) -> OsCatMany<OsCatMany<L, R>, RR> {
+        OsCatMany {
+            lhs: self,
+            rhs: rhs.inner,
+        }
+    }
+}
+
+impl<L: OsCat, R: OsCat, RR: OsCat> Add<RR> for OsCatMany<L, R> {
+    type Output = OsCatMany<OsCatMany<L, R>, RR>;
+    fn add(self, rhs: RR) -> OsCatMany<OsCatMany<L, R>, RR> {
+        OsCatMany { lhs: self, rhs }
+    }
+}
+
+impl<L: OsCat, R: OsCat> AddAssign<OsCatMany<L, R>> for OsString {
+    fn add_assign(&mut self, rhs: OsCatMany<L, R>) {
+        append_oscat(self, &rhs);
+    }
+}
+
+impl<L: OsCat, R: OsCat> From<OsCatMany<L, R>> for OsString {
+    fn from(src: OsCatMany<L, R>) -> OsString {
+        oscat_into_os_string(src)
+    }
+}
+
+impl<L: OsCat + Debug, R: OsCat + Debug> Debug for OsCatMany<L, R> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        Debug::fmt(&self.lhs, f)?;
+        f.write_str(" + ")?;
+        Debug::fmt(&self.rhs, f)
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct OsCatOne<T: OsCat> {
+    inner: T,
+}
+
+impl<T: OsCat> Add<OsCatStart> for OsCatOne<T> {
+    type Output = OsCatOne<T>;
+    fn add(self, _rhs: OsCatStart) -> OsCatOne<T> {
+        self
+    }
+}
+
+impl<L: OsCat, R: OsCat> Add<OsCatOne<R>> for OsCatOne<L> {
+    type Output = OsCatMany<L, R>;
+    fn add(self, rhs: OsCatOne<R>) -> OsCatMany<L, R> {
+        OsCatMany {
+            lhs: self.inner,
+            rhs: rhs.inner,
+        }
+    }
+}
+
+impl<L: OsCat, R: OsCat> Add<R> for OsCatOne<L> {
+    type Output = OsCatMany<L, R>;
+    fn add(self, rhs: R) -> OsCatMany<L, R> {
+        OsCatMany {
+            lhs: self.inner,
+            rhs,
+        }
+    }
+}
+
+impl<T: OsCat> AddAssign<OsCatOne<T>> for OsString {
+    fn add_assign(&mut self, rhs: OsCatOne<T>) {
+        append_oscat(self, &rhs.inner);
+    }
+}
+
+impl<T: OsCat> From<OsCatOne<T>> for OsString {
+    fn from(src: OsCatOne<T>) -> OsString {
+        oscat_into_os_string(src.inner)
+    }
+}
+
+impl<T: OsCat + Debug> Debug for OsCatOne<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        Debug::fmt(&self.inner, f)
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct OsCatStart;
+
+/// A term that is used to start an `OsString` concatenation.
+///
+/// This works like [`CAT`](constant.CAT.html), but concatenates
+/// `&OsStr` and `OsString` pieces, which need not be valid Unicode,
+/// together with `&str`, `String` and `char` pieces. The resulting
+/// `OsString` is allocated at most once, and if the first item is an
+/// `OsString`, its buffer is reused.
+///
+/// # Examples
+///
+/// ```rust
+/// use sconcat::OSCAT;
+/// use std::env;
+/// use std::ffi::OsString;
+///
+/// let home = env::var_os("HOME").unwrap_or_else(|| OsString::from("/"));
+/// let value = OsString::from(OSCAT + "--config=" + home + "/.toolrc");
+/// assert!(value.to_string_lossy().starts_with("--config="));
+///
+/// let mut path = OsString::from("/usr/bin");
+/// path += OSCAT + ':' + "/opt/bin";
+/// assert_eq!(path, "/usr/bin:/opt/bin");
+/// ```
+pub const OSCAT: OsCatStart = OsCatStart;
+
+impl Add<OsCatStart> for OsCatStart {
+    type Output = OsCatStart;
+    fn add(self, _rhs: OsCatStart) -> OsCatStart {
+        self
+    }
+}
+
+impl<T: OsCat> Add<OsCatOne<T>> for OsCatStart {
+    type Output = OsCatOne<T>;
+    fn add(self, rhs: OsCatOne<T>) -> OsCatOne<T> {
+        rhs
+    }
+}
+
+impl<T: OsCat> Add<T> for OsCatStart {
+    type Output = OsCatOne<T>;
+    fn add(self, rhs: T) -> OsCatOne<T> {
+        OsCatOne { inner: rhs }
+    }
+}
+
+impl AddAssign<OsCatStart> for OsString {
+    fn add_assign(&mut self, _rhs: OsCatStart) {}
+}
+
+impl From<OsCatStart> for OsString {
+    fn from(_src: OsCatStart) -> OsString {
+        OsString::new()
+    }
+}
+
+impl Debug for OsCatStart {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("\"\"")
+    }
+}
+
+// Concatenations built with `CAT` are valid UTF-8, so they can be
+// appended to an `OsString` segment by segment without materializing
+// a `String` first.
+fn append_cat<C: cat::Cat>(s: &mut OsString, src: &C) {
+    s.reserve(src.size_hint());
+    src.for_each_segment(&mut |segment| s.push(segment));
+}
+
+impl<L: cat::Cat, R: cat::Cat> AddAssign<CatMany<L, R>> for OsString {
+    fn add_assign(&mut self, rhs: CatMany<L, R>) {
+        append_cat(self, &rhs);
+    }
+}
+
+impl<L: cat::Cat, R: cat::Cat> AddAssign<&CatMany<L, R>> for OsString {
+    fn add_assign(&mut self, rhs: &CatMany<L, R>) {
+        append_cat(self, rhs);
+    }
+}
+
+impl<T: cat::Cat> AddAssign<CatOne<T>> for OsString {
+    fn add_assign(&mut self, rhs: CatOne<T>) {
+        append_cat(self, &rhs.inner);
+    }
+}
+
+impl<T: cat::Cat> AddAssign<&CatOne<T>> for OsString {
+    fn add_assign(&mut self, rhs: &CatOne<T>) {
+        append_cat(self, &rhs.inner);
+    }
+}
+
+impl AddAssign<CatStart> for OsString {
+    fn add_assign(&mut self, _rhs: CatStart) {}
+}
+
+impl AddAssign<&CatStart> for OsString {
+    fn add_assign(&mut self, _rhs: &CatStart) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use CAT;
+    use OSCAT;
+    use std::ffi::{OsStr, OsString};
+
+    #[test]
+    fn it_works() {
+        let os = OsStr::new("bin");
+        let s = OsString::from(OSCAT + "/usr/" + os + '/' + String::from("x"));
+        assert_eq!(s, "/usr/bin/x");
+        assert_eq!(s.len(), s.capacity());
+
+        let mut s = OsString::with_capacity(20);
+        s.push("a");
+        let owned = OsString::from("b");
+        s += OSCAT + owned + OSCAT + 'c';
+        s += OSCAT;
+        assert_eq!(s, "abc");
+        assert_eq!(s.capacity(), 20);
+        assert_eq!(OsString::from(OSCAT), "");
+    }
+
+    #[test]
+    fn reuse_first() {
+        let mut first = OsString::with_capacity(32);
+        first.push("x=");
+        let s = OsString::from(OSCAT + first + "1");
+        assert_eq!(s, "x=1");
+        assert_eq!(s.capacity(), 32);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn not_unicode() {
+        use std::os::unix::ffi::OsStrExt;
+
+        let raw = OsStr::from_bytes(b"\xff\xfe");
+        let s = OsString::from(OSCAT + "<" + raw + ">");
+        assert_eq!(s.as_bytes(), b"<\xff\xfe>");
+    }
+
+    #[test]
+    fn formatting() {
+        let one = OSCAT + OsStr::new("a");
+        assert_eq!(format!("{:?}", one), "\"a\"");
+        assert_eq!(format!("{:?}", one + 'b'), "\"a\" + 'b'");
+        assert_eq!(format!("{:?}", OSCAT), "\"\"");
+    }
+
+    #[test]
+    fn add_assign_cat() {
+        let dir = "/etc";
+        let name = String::from("hosts");
+        let mut s = OsString::from("FILE=");
+        s += CAT + dir + '/' + &*name;
+        s += &(CAT + "");
+        s += CAT;
+        assert_eq!(s, "FILE=/etc/hosts");
+        assert_eq!(s.len(), s.capacity());
+        s += CAT + ::Dbg(1);
+        assert_eq!(s, "FILE=/etc/hosts1");
+    }
+}
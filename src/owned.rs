@@ -0,0 +1,135 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use cat::{Cat, CatMany, CatOne, CatStart};
+use std::rc::Rc;
+use std::sync::Arc;
+
+/// Trait for pieces that can be converted into owned pieces.
+pub trait IntoOwnedPiece: Cat {
+    /// The owned piece.
+    type Owned: Cat + Send + 'static;
+    /// Converts the piece into an owned piece.
+    fn into_owned_piece(self) -> Self::Owned;
+}
+
+impl IntoOwnedPiece for char {
+    type Owned = char;
+    fn into_owned_piece(self) -> char {
+        self
+    }
+}
+
+impl IntoOwnedPiece for &str {
+    type Owned = String;
+    fn into_owned_piece(self) -> String {
+        String::from(self)
+    }
+}
+
+impl IntoOwnedPiece for String {
+    type Owned = String;
+    fn into_owned_piece(self) -> String {
+        self
+    }
+}
+
+impl IntoOwnedPiece for Rc<String> {
+    type Owned = String;
+    fn into_owned_piece(self) -> String {
+        Rc::try_unwrap(self).unwrap_or_else(|shared| (*shared).clone())
+    }
+}
+
+impl IntoOwnedPiece for Arc<String> {
+    type Owned = Arc<String>;
+    fn into_owned_piece(self) -> Arc<String> {
+        self
+    }
+}
+
+impl<L: IntoOwnedPiece, R: IntoOwnedPiece> IntoOwnedPiece for CatMany<L, R> {
+    type Owned = CatMany<L::Owned, R::Owned>;
+    fn into_owned_piece(self) -> CatMany<L::Owned, R::Owned> {
+        self.into_owned()
+    }
+}
+
+impl<L: IntoOwnedPiece, R: IntoOwnedPiece> CatMany<L, R> {
+    /// Converts all the pieces into owned pieces, so that the
+    /// concatenation is `'static` and `Send`.
+    ///
+    /// String slices are copied into `String` pieces, and an
+    /// `Rc<String>` piece is unwrapped if it is the only reference
+    /// and copied otherwise. The concatenation is not materialized.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use sconcat::CAT;
+    /// use std::thread;
+    ///
+    /// let name = String::from("world");
+    /// let cat = (CAT + "Hello, " + name.as_str() + '!').into_owned();
+    /// drop(name);
+    /// let s = thread::spawn(move || String::from(cat)).join().unwrap();
+    /// assert_eq!(s, "Hello, world!");
+    /// ```
+    pub fn into_owned(self) -> CatMany<L::Owned, R::Owned> {
+        CatMany::new(
+            self.lhs.into_owned_piece(),
+            self.rhs.into_owned_piece(),
+        )
+    }
+}
+
+impl<T: IntoOwnedPiece> CatOne<T> {
+    /// Converts the piece into an owned piece, so that the
+    /// concatenation is `'static` and `Send`.
+    pub fn into_owned(self) -> CatOne<T::Owned> {
+        CatOne {
+            inner: self.inner.into_owned_piece(),
+        }
+    }
+}
+
+impl CatStart {
+    /// Returns the empty concatenation, which is already `'static`
+    /// and `Send`.
+    pub fn into_owned(self) -> CatStart {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use CAT;
+    use std::rc::Rc;
+    use std::sync::Arc;
+
+    fn assert_static_send<T: Send + 'static>(t: T) -> T {
+        t
+    }
+
+    #[test]
+    fn owned() {
+        let borrowed = String::from("b");
+        let cat = CAT
+            + "a"
+            + borrowed.as_str()
+            + 'c'
+            + Rc::new(String::from("d"))
+            + Arc::new(String::from("e"));
+        let cat = assert_static_send(cat.into_owned());
+        drop(borrowed);
+        let s = String::from(cat);
+        assert_eq!(s, "abcde");
+        let one = assert_static_send((CAT + "x").into_owned());
+        assert_eq!(String::from(one + CAT.into_owned()), "x");
+    }
+}
@@ -0,0 +1,215 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use cat::Cat;
+use std::fmt::{self, Alignment, Display};
+use std::str;
+
+// Size of the buffer of fill characters.
+const FILL_BUF_LEN: usize = 64;
+
+/// A piece that pads another piece to a minimum width.
+///
+/// This is created by the [`pad`](fn.pad.html) and
+/// [`zero_pad`](fn.zero_pad.html) functions.
+#[derive(Clone, Copy, Debug)]
+pub struct Pad<T: Cat> {
+    inner: T,
+    width: usize,
+    fill: char,
+    align: Alignment,
+    sign_aware: bool,
+}
+
+/// Creates a piece that pads `value` with `fill` to at least `width`
+/// characters.
+///
+/// The padding is placed according to `align` like in the standard
+/// formatting macros; with [`Alignment::Center`], any odd character of
+/// padding goes on the right. The width of `value` is measured in
+/// characters by walking its segments, and the size hint is exact if
+/// the size hint of `value` is exact.
+///
+/// [`Alignment::Center`]: https://doc.rust-lang.org/std/fmt/enum.Alignment.html
+///
+/// # Examples
+///
+/// ```rust
+/// use sconcat::{CAT, pad};
+/// use std::fmt::Alignment;
+///
+/// let cat = CAT + '|' + pad("ab", 6, '*', Alignment::Center) + '|';
+/// assert_eq!(String::from(cat), "|**ab**|");
+/// let cat = CAT + pad(CAT + "n" + 'é', 4, ' ', Alignment::Right) + '|';
+/// assert_eq!(String::from(cat), "  né|");
+/// ```
+pub fn pad<T: Cat>(
+    value: T,
+    width: usize,
+    fill: char,
+    align: Alignment,
+) -> Pad<T> {
+    Pad {
+        inner: value,
+        width,
+        fill,
+        align,
+        sign_aware: false,
+    }
+}
+
+/// Creates a piece that pads `number` with zeros on the left to at
+/// least `width` characters.
+///
+/// If `number` starts with a `+` or `-` sign, the zeros are placed
+/// after the sign, like the `0` flag of the standard formatting
+/// macros.
+///
+/// # Examples
+///
+/// ```rust
+/// use sconcat::{CAT, zero_pad};
+///
+/// let cat = CAT + zero_pad("42", 5) + ' ' + zero_pad("-7", 4);
+/// assert_eq!(String::from(cat), "00042 -007");
+/// ```
+pub fn zero_pad<T: Cat>(number: T, width: usize) -> Pad<T> {
+    Pad {
+        inner: number,
+        width,
+        fill: '0',
+        align: Alignment::Right,
+        sign_aware: true,
+    }
+}
+
+// Passes `count` copies of `fill` to `f`.
+pub(crate) fn fill(f: &mut dyn FnMut(&str), fill: char, mut count: usize) {
+    let mut buf = [0u8; FILL_BUF_LEN];
+    let fill_len = fill.len_utf8();
+    let per_buf = FILL_BUF_LEN / fill_len;
+    for i in 0..per_buf.min(count) {
+        fill.encode_utf8(&mut buf[i * fill_len..]);
+    }
+    while count > 0 {
+        let n = count.min(per_buf);
+        // The buffer contains whole characters only.
+        f(str::from_utf8(&buf[..n * fill_len]).unwrap());
+        count -= n;
+    }
+}
+
+impl<T: Cat> Pad<T> {
+    // Returns the padding before and after the inner piece.
+    fn padding(&self) -> (usize, usize) {
+        let mut chars = 0;
+        self.inner
+            .for_each_segment(&mut |segment| chars += segment.chars().count());
+        let padding = self.width.saturating_sub(chars);
+        match self.align {
+            Alignment::Left => (0, padding),
+            Alignment::Right => (padding, 0),
+            Alignment::Center => (padding / 2, padding - padding / 2),
+        }
+    }
+}
+
+impl<T: Cat> Cat for Pad<T> {
+    fn size_hint(&self) -> usize {
+        let (pre, post) = self.padding();
+        (pre + post)
+            .checked_mul(self.fill.len_utf8())
+            .and_then(|len| len.checked_add(self.inner.size_hint()))
+            .expect("capacity overflow")
+    }
+
+    fn append_to(&self, s: &mut String) {
+        self.for_each_segment(&mut |segment| s.push_str(segment));
+    }
+
+    fn for_each_segment(&self, f: &mut dyn FnMut(&str)) {
+        let (pre, post) = self.padding();
+        if !self.sign_aware || pre == 0 {
+            fill(f, self.fill, pre);
+            self.inner.for_each_segment(f);
+            fill(f, self.fill, post);
+            return;
+        }
+        // The padding goes after the sign, if any.
+        let mut first = true;
+        self.inner.for_each_segment(&mut |segment| {
+            if segment.is_empty() {
+                return;
+            }
+            if !first {
+                f(segment);
+                return;
+            }
+            first = false;
+            let rest = if segment.starts_with(['+', '-']) {
+                f(&segment[..1]);
+                &segment[1..]
+            } else {
+                segment
+            };
+            fill(f, self.fill, pre);
+            if !rest.is_empty() {
+                f(rest);
+            }
+        });
+        if first {
+            fill(f, self.fill, pre);
+        }
+        fill(f, self.fill, post);
+    }
+}
+
+impl<T: Cat> Display for Pad<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut result = Ok(());
+        self.for_each_segment(&mut |segment| {
+            if result.is_ok() {
+                result = f.write_str(segment);
+            }
+        });
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use CAT;
+    use cat::Cat;
+    use std::fmt::Alignment;
+    use super::{pad, zero_pad};
+
+    #[test]
+    fn aligns() {
+        let s = String::from(
+            CAT + pad("ab", 5, '.', Alignment::Left)
+                + pad("ab", 5, '.', Alignment::Right)
+                + pad("ab", 5, '.', Alignment::Center)
+                + pad("abcdef", 5, '.', Alignment::Center),
+        );
+        assert_eq!(s, "ab......ab.ab..abcdef");
+        assert_eq!(s.len(), s.capacity());
+        let wide = pad('x', 100, 'é', Alignment::Right);
+        assert_eq!(wide.size_hint(), 199);
+        assert_eq!(wide.to_string(), "é".repeat(99) + "x");
+    }
+
+    #[test]
+    fn zero_pads() {
+        let s = String::from(CAT + zero_pad(CAT + "" + '-' + "12", 5));
+        assert_eq!(s, "-0012");
+        assert_eq!(s.len(), s.capacity());
+        assert_eq!(zero_pad("+1", 3).to_string(), "+01");
+        assert_eq!(zero_pad("", 2).to_string(), "00");
+        assert_eq!(zero_pad("12345", 2).to_string(), "12345");
+    }
+}
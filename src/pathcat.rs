@@ -0,0 +1,350 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::ffi::{OsStr, OsString};
+use std::fmt::{self, Debug};
+use std::ops::{Add, AddAssign};
+use std::path::{self, Path, PathBuf};
+
+/// Trait for types that can be concatenated into a `PathBuf` as
+/// path components.
+pub trait PathCat {
+    /// Length of item in bytes of the platform encoding, including a
+    /// separator before every component.
+    fn size_hint(&self) -> usize;
+    /// Append item to path, with a separator before every component.
+    fn append_to(&self, s: &mut OsString);
+    /// Converts item to an OsString.
+    fn into_os_string(self, capacity: usize) -> OsString;
+}
+
+// Returns whether `byte` is a separator.
+fn is_separator(byte: Option<&u8>) -> bool {
+    byte.is_some_and(|&b| path::is_separator(char::from(b)))
+}
+
+// Appends a component, inserting a separator unless the path is
+// empty or there is already a separator at the boundary. Empty
+// components are skipped.
+fn push_component(s: &mut OsString, component: &OsStr) {
+    let bytes = component.as_encoded_bytes();
+    if bytes.is_empty() {
+        return;
+    }
+    let last = s.as_encoded_bytes().last();
+    if last.is_some() && !is_separator(last) && !is_separator(bytes.first()) {
+        s.push(path::MAIN_SEPARATOR_STR);
+    }
+    s.push(component);
+}
+
+macro_rules! path_cat {
+    ($($T:ty)*) => { $(
+        impl PathCat for $T {
+            fn size_hint(&self) -> usize {
+                AsRef::<OsStr>::as_ref(self).len() + 1
+            }
+
+            fn append_to(&self, s: &mut OsString) {
+                push_component(s, self.as_ref());
+            }
+
+            fn into_os_string(self, capacity: usize) -> OsString {
+                let mut s = OsString::with_capacity(capacity);
+                self.append_to(&mut s);
+                s
+            }
+        }
+    )* };
+}
+
+path_cat! { &Path &OsStr &str String }
+
+impl PathCat for PathBuf {
+    fn size_hint(&self) -> usize {
+        self.as_os_str().len() + 1
+    }
+
+    fn append_to(&self, s: &mut OsString) {
+        push_component(s, self.as_os_str());
+    }
+
+    fn into_os_string(self, capacity: usize) -> OsString {
+        self.into_os_string().into_os_string(capacity)
+    }
+}
+
+impl PathCat for OsString {
+    fn size_hint(&self) -> usize {
+        self.len() + 1
+    }
+
+    fn append_to(&self, s: &mut OsString) {
+        push_component(s, self);
+    }
+
+    fn into_os_string(mut self, capacity: usize) -> OsString {
+        let len = self.len();
+        if capacity > len {
+            self.reserve(capacity - len);
+        }
+        self
+    }
+}
+
+fn append_pathcat<C: PathCat>(p: &mut PathBuf, src: &C) {
+    p.reserve(src.size_hint());
+    src.append_to(p.as_mut_os_string());
+}
+
+fn pathcat_into_path_buf<C: PathCat>(src: C) -> PathBuf {
+    let capacity = src.size_hint();
+    PathBuf::from(src.into_os_string(capacity))
+}
+
+#[derive(Clone, Copy)]
+pub struct PathCatMany<L: PathCat, R: PathCat> {
+    lhs: L,
+    rhs: R,
+}
+
+impl<L: PathCat, R: PathCat> PathCat for PathCatMany<L, R> {
+    fn size_hint(&self) -> usize {
+        self.lhs
+            .size_hint()
+            .checked_add(self.rhs.size_hint())
+            .expect("capacity overflow")
+    }
+
+    fn append_to(&self, s: &mut OsString) {
+        self.lhs.append_to(s);
+        self.rhs.append_to(s);
+    }
+
+    fn into_os_string(self, capacity: usize) -> OsString {
+        let mut s = self.lhs.into_os_string(capacity);
+        self.rhs.append_to(&mut s);
+        s
+    }
+}
+
+impl<L: PathCat, R: PathCat> Add<PathCatStart> for PathCatMany<L, R> {
+    type Output = PathCatMany<L, R>;
+    fn add(self, _rhs: PathCatStart) -> PathCatMany<L, R> {
+        self
+    }
+}
+
+impl<L, R, RR> Add<PathCatOne<RR>> for PathCatMany<L, R>
+where
+    L: PathCat,
+    R: PathCat,
+    RR: PathCat,
+{
+    type Output = PathCatMany<PathCatMany<L, R>, RR>;
+    fn add(self, rhs: PathCatOne<RR>) -> PathCatMany<PathCatMany<L, R>, RR> {
+        PathCatMany {
+            lhs: self,
+            rhs: rhs.inner,
+        }
+    }
+}
+
+impl<L: PathCat, R: PathCat, RR: PathCat> Add<RR> for PathCatMany<L, R> {
+    type Output = PathCatMany<PathCatMany<L, R>, RR>;
+    fn add(self, rhs: RR) -> PathCatMany<PathCatMany<L, R>, RR> {
+        PathCatMany { lhs: self, rhs }
+    }
+}
+
+impl<L: PathCat, R: PathCat> AddAssign<PathCatMany<L, R>> for PathBuf {
+    fn add_assign(&mut self, rhs: PathCatMany<L, R>) {
+        append_pathcat(self, &rhs);
+    }
+}
+
+impl<L: PathCat, R: PathCat> From<PathCatMany<L, R>> for PathBuf {
+    fn from(src: PathCatMany<L, R>) -> PathBuf {
+        pathcat_into_path_buf(src)
+    }
+}
+
+impl<L: PathCat + Debug, R: PathCat + Debug> Debug for PathCatMany<L, R> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        Debug::fmt(&self.lhs, f)?;
+        f.write_str(" + ")?;
+        Debug::fmt(&self.rhs, f)
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct PathCatOne<T: PathCat> {
+    inner: T,
+}
+
+impl<T: PathCat> Add<PathCatStart> for PathCatOne<T> {
+    type Output = PathCatOne<T>;
+    fn add(self, _rhs: PathCatStart) -> PathCatOne<T> {
+        self
+    }
+}
+
+impl<L: PathCat, R: PathCat> Add<PathCatOne<R>> for PathCatOne<L> {
+    type Output = PathCatMany<L, R>;
+    fn add(self, rhs: PathCatOne<R>) -> PathCatMany<L, R> {
+        PathCatMany {
+            lhs: self.inner,
+            rhs: rhs.inner,
+        }
+    }
+}
+
+impl<L: PathCat, R: PathCat> Add<R> for PathCatOne<L> {
+    type Output = PathCatMany<L, R>;
+    fn add(self, rhs: R) -> PathCatMany<L, R> {
+        PathCatMany {
+            lhs: self.inner,
+            rhs,
+        }
+    }
+}
+
+impl<T: PathCat> AddAssign<PathCatOne<T>> for PathBuf {
+    fn add_assign(&mut self, rhs: PathCatOne<T>) {
+        append_pathcat(self, &rhs.inner);
+    }
+}
+
+impl<T: PathCat> From<PathCatOne<T>> for PathBuf {
+    fn from(src: PathCatOne<T>) -> PathBuf {
+        pathcat_into_path_buf(src.inner)
+    }
+}
+
+impl<T: PathCat + Debug> Debug for PathCatOne<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        Debug::fmt(&self.inner, f)
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct PathCatStart;
+
+/// A term that is used to start a `PathBuf` concatenation.
+///
+/// This works like [`CAT`](constant.CAT.html), but every piece is a
+/// path component, and the platform separator is inserted between
+/// components unless there is already a separator at the end of the
+/// previous component or at the start of the next one. The components themselves are not split or modified,
+/// and empty components are skipped. Unlike `PathBuf::push`, an
+/// absolute component does not replace the path built so far.
+///
+/// The pieces can be `&Path`, `PathBuf`, `&OsStr`, `OsString`,
+/// `&str` and `String`. The capacity for all components and
+/// separators is reserved up front, so the resulting `PathBuf` is
+/// allocated at most once, and if the first item is a `PathBuf` or
+/// `OsString`, its buffer is reused.
+///
+/// # Examples
+///
+/// ```rust
+/// use sconcat::PCAT;
+/// use std::path::{Path, PathBuf};
+///
+/// let base = Path::new("/srv/data/");
+/// let user = String::from("alice");
+/// let path = PathBuf::from(PCAT + base + "users" + user + "notes.txt");
+/// assert_eq!(path, Path::new("/srv/data/users/alice/notes.txt"));
+///
+/// let mut cache = PathBuf::from("/tmp");
+/// cache += PCAT + "app" + "v1/index";
+/// assert_eq!(cache, Path::new("/tmp/app/v1/index"));
+/// ```
+pub const PCAT: PathCatStart = PathCatStart;
+
+impl Add<PathCatStart> for PathCatStart {
+    type Output = PathCatStart;
+    fn add(self, _rhs: PathCatStart) -> PathCatStart {
+        self
+    }
+}
+
+impl<T: PathCat> Add<PathCatOne<T>> for PathCatStart {
+    type Output = PathCatOne<T>;
+    fn add(self, rhs: PathCatOne<T>) -> PathCatOne<T> {
+        rhs
+    }
+}
+
+impl<T: PathCat> Add<T> for PathCatStart {
+    type Output = PathCatOne<T>;
+    fn add(self, rhs: T) -> PathCatOne<T> {
+        PathCatOne { inner: rhs }
+    }
+}
+
+impl AddAssign<PathCatStart> for PathBuf {
+    fn add_assign(&mut self, _rhs: PathCatStart) {}
+}
+
+impl From<PathCatStart> for PathBuf {
+    fn from(_src: PathCatStart) -> PathBuf {
+        PathBuf::new()
+    }
+}
+
+impl Debug for PathCatStart {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("\"\"")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use PCAT;
+    use std::ffi::{OsStr, OsString};
+    use std::path::{MAIN_SEPARATOR_STR as SEP, Path, PathBuf};
+
+    #[test]
+    fn separators() {
+        let p = PathBuf::from(PCAT + "a" + "b/c" + OsStr::new("d") + "");
+        let expected = ["a", SEP, "b/c", SEP, "d"].concat();
+        assert_eq!(p.as_os_str(), OsStr::new(&expected));
+        assert!(p.capacity() >= expected.len());
+
+        let p = PathBuf::from(PCAT + "a" + SEP + "" + "b");
+        assert_eq!(p.as_os_str(), OsStr::new(&["a", SEP, "b"].concat()));
+        let p = PathBuf::from(PCAT + "a" + ["/", "b"].concat());
+        assert_eq!(p.as_os_str(), OsStr::new("a/b"));
+        assert_eq!(PathBuf::from(PCAT), Path::new(""));
+        assert_eq!(PathBuf::from(PCAT + "x" + PCAT), Path::new("x"));
+    }
+
+    #[test]
+    fn reserve_once() {
+        let base = PathBuf::from(OsString::with_capacity(64));
+        let p = PathBuf::from(PCAT + base + "dir" + String::from("f"));
+        assert_eq!(p, Path::new(&["dir", SEP, "f"].concat()));
+        assert_eq!(p.capacity(), 64);
+
+        let mut p = PathBuf::with_capacity(32);
+        p.push("root");
+        p += PCAT + "a" + Path::new("b");
+        p += PCAT;
+        assert_eq!(p, Path::new(&["root", SEP, "a", SEP, "b"].concat()));
+        assert_eq!(p.capacity(), 32);
+    }
+
+    #[test]
+    fn formatting() {
+        let one = PCAT + Path::new("a");
+        assert_eq!(format!("{:?}", one), "\"a\"");
+        assert_eq!(format!("{:?}", one + "b"), "\"a\" + \"b\"");
+        assert_eq!(format!("{:?}", PCAT), "\"\"");
+    }
+}
@@ -0,0 +1,344 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Pretty-printer combinators.
+//!
+//! A [`Doc`](struct.Doc.html) is built from [`text`](fn.text.html)
+//! pieces, [`softline`](fn.softline.html) break points,
+//! [`nest`](fn.nest.html) and [`group`](fn.group.html), and
+//! concatenated with `+`. The document is then laid out against a
+//! target width with [`Doc::pretty`](struct.Doc.html#method.pretty),
+//! which returns a piece that can be concatenated like any other.
+//!
+//! A group is laid out on a single line if its contents, followed by
+//! the text after the group up to the next line break, fit in the
+//! rest of the current line, in which case its soft lines become
+//! spaces. Otherwise its soft lines become line breaks, and the
+//! groups inside it are considered one by one. Soft lines outside
+//! any group always break. This is the layout of Wadler's "A
+//! prettier printer".
+//!
+//! # Examples
+//!
+//! ```rust
+//! use sconcat::CAT;
+//! use sconcat::pretty::{Doc, group, nest, softline, text};
+//!
+//! fn list(items: &[&'static str]) -> Doc<'static> {
+//!     let mut body = Doc::empty();
+//!     for (i, &item) in items.iter().enumerate() {
+//!         if i > 0 {
+//!             body = body + text(",") + softline();
+//!         }
+//!         body = body + text(item);
+//!     }
+//!     group(text("[") + nest(4, softline() + body) + softline() + text("]"))
+//! }
+//!
+//! let short = list(&["a", "b"]);
+//! assert_eq!(String::from(CAT + short.pretty(20)), "[ a, b ]");
+//!
+//! let long = list(&["alpha", "beta", "gamma"]);
+//! let cat = CAT + "x = " + long.pretty(20);
+//! let expected = "x = [\n    alpha,\n    beta,\n    gamma\n]";
+//! assert_eq!(String::from(cat), expected);
+//! ```
+
+use cat::{Cat, pad};
+use std::fmt::{self, Debug, Display};
+use std::ops::Add;
+
+/// A document that can be laid out against a target width.
+pub struct Doc<'a> {
+    node: Node<'a>,
+}
+
+// A document still to be rendered, with its indentation and whether
+// it is laid out on a single line.
+type Item<'d, 'a> = (usize, bool, &'d Doc<'a>);
+
+enum Node<'a> {
+    Text(Box<dyn Cat + 'a>, usize),
+    Softline,
+    Nest(usize, Box<Doc<'a>>),
+    Group(Box<Doc<'a>>, usize),
+    Concat(Vec<Doc<'a>>),
+}
+
+/// Creates a document from a piece.
+///
+/// The width of the piece is measured in characters, so the piece
+/// should not contain line breaks.
+pub fn text<'a, C: Cat + 'a>(piece: C) -> Doc<'a> {
+    let mut width = 0;
+    piece.for_each_segment(&mut |segment| width += segment.chars().count());
+    Doc {
+        node: Node::Text(Box::new(piece), width),
+    }
+}
+
+/// Creates a break point, which is a space if the enclosing group
+/// fits on one line and a line break otherwise.
+///
+/// After a line break, the next line is indented by the nesting
+/// level.
+pub fn softline<'a>() -> Doc<'a> {
+    Doc {
+        node: Node::Softline,
+    }
+}
+
+/// Increases the indentation of line breaks inside `doc` by
+/// `indent` spaces.
+pub fn nest(indent: usize, doc: Doc) -> Doc {
+    Doc {
+        node: Node::Nest(indent, Box::new(doc)),
+    }
+}
+
+/// Marks `doc` as a unit that is laid out on a single line if it
+/// fits.
+pub fn group(doc: Doc) -> Doc {
+    let width = doc.flat_width();
+    Doc {
+        node: Node::Group(Box::new(doc), width),
+    }
+}
+
+impl<'a> Doc<'a> {
+    /// Creates an empty document.
+    pub fn empty() -> Doc<'a> {
+        Doc {
+            node: Node::Concat(Vec::new()),
+        }
+    }
+
+    /// Lays the document out against a target line width.
+    ///
+    /// Layout starts at column zero, regardless of what precedes the
+    /// piece in a concatenation. The returned piece has an exact size
+    /// hint. Lines can still be longer than `width` if they contain
+    /// no break points.
+    pub fn pretty(self, width: usize) -> Pretty<'a> {
+        Pretty { doc: self, width }
+    }
+
+    // The width of the document laid out on a single line.
+    fn flat_width(&self) -> usize {
+        match self.node {
+            Node::Text(_, width) | Node::Group(_, width) => width,
+            Node::Softline => 1,
+            Node::Nest(_, ref doc) => doc.flat_width(),
+            Node::Concat(ref docs) => docs.iter().map(Doc::flat_width).sum(),
+        }
+    }
+
+    fn render(&self, width: usize, f: &mut dyn FnMut(&str)) {
+        let mut column = 0;
+        let mut stack: Vec<Item> = vec![(0, false, self)];
+        while let Some((indent, flat, doc)) = stack.pop() {
+            match doc.node {
+                Node::Text(ref piece, text_width) => {
+                    piece.for_each_segment(f);
+                    column += text_width;
+                }
+                Node::Softline => {
+                    if flat {
+                        f(" ");
+                        column += 1;
+                    } else {
+                        f("\n");
+                        pad(f, indent);
+                        column = indent;
+                    }
+                }
+                Node::Nest(more, ref doc) => {
+                    stack.push((indent + more, flat, doc));
+                }
+                Node::Group(ref doc, group_width) => {
+                    let flat = flat || {
+                        let left = width.saturating_sub(column);
+                        column <= width && fits(left, group_width, &stack)
+                    };
+                    stack.push((indent, flat, doc));
+                }
+                Node::Concat(ref docs) => {
+                    for doc in docs.iter().rev() {
+                        stack.push((indent, flat, doc));
+                    }
+                }
+            }
+        }
+    }
+}
+
+// Returns whether a group of flat width `width`, followed by the
+// items in `rest` up to the next line break, fits in `left` columns.
+// The items are taken from the end of `rest`, and the groups among
+// them are measured as if they were laid out on a single line.
+fn fits(left: usize, width: usize, rest: &[Item]) -> bool {
+    let mut left = match left.checked_sub(width) {
+        Some(left) => left,
+        None => return false,
+    };
+    let mut rest = rest.iter().rev();
+    let mut pending = Vec::new();
+    loop {
+        let (flat, doc) = match pending.pop() {
+            Some(item) => item,
+            None => match rest.next() {
+                Some(&(_, flat, doc)) => (flat, doc),
+                None => return true,
+            },
+        };
+        let width = match doc.node {
+            Node::Text(_, width) | Node::Group(_, width) => width,
+            Node::Softline if flat => 1,
+            Node::Softline => return true,
+            Node::Nest(_, ref doc) => {
+                pending.push((flat, &**doc));
+                continue;
+            }
+            Node::Concat(ref docs) => {
+                pending.extend(docs.iter().rev().map(|doc| (flat, doc)));
+                continue;
+            }
+        };
+        left = match left.checked_sub(width) {
+            Some(left) => left,
+            None => return false,
+        };
+    }
+}
+
+impl<'a> Add for Doc<'a> {
+    type Output = Doc<'a>;
+    fn add(self, rhs: Doc<'a>) -> Doc<'a> {
+        match self.node {
+            Node::Concat(mut docs) => {
+                docs.push(rhs);
+                Doc {
+                    node: Node::Concat(docs),
+                }
+            }
+            node => Doc {
+                node: Node::Concat(vec![Doc { node }, rhs]),
+            },
+        }
+    }
+}
+
+impl<'a> Debug for Doc<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.node {
+            Node::Text(ref piece, _) => {
+                let mut text = String::new();
+                piece.append_to(&mut text);
+                f.debug_tuple("Text").field(&text).finish()
+            }
+            Node::Softline => f.write_str("Softline"),
+            Node::Nest(indent, ref doc) => {
+                f.debug_tuple("Nest").field(&indent).field(doc).finish()
+            }
+            Node::Group(ref doc, _) => {
+                f.debug_tuple("Group").field(doc).finish()
+            }
+            Node::Concat(ref docs) => f.debug_list().entries(docs).finish(),
+        }
+    }
+}
+
+/// A piece that renders a laid out document.
+///
+/// This is created by [`Doc::pretty`](struct.Doc.html#method.pretty).
+#[derive(Debug)]
+pub struct Pretty<'a> {
+    doc: Doc<'a>,
+    width: usize,
+}
+
+impl<'a> Cat for Pretty<'a> {
+    fn size_hint(&self) -> usize {
+        let mut len = 0usize;
+        self.for_each_segment(&mut |segment| len += segment.len());
+        len
+    }
+
+    fn append_to(&self, s: &mut String) {
+        self.for_each_segment(&mut |segment| s.push_str(segment));
+    }
+
+    fn for_each_segment(&self, f: &mut dyn FnMut(&str)) {
+        self.doc.render(self.width, f);
+    }
+}
+
+impl<'a> Display for Pretty<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut result = Ok(());
+        self.for_each_segment(&mut |segment| {
+            if result.is_ok() {
+                result = f.write_str(segment);
+            }
+        });
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use CAT;
+    use super::{Doc, group, nest, softline, text};
+
+    fn call(name: &'static str, args: &[&'static str]) -> Doc<'static> {
+        let mut body = Doc::empty();
+        for (i, &arg) in args.iter().enumerate() {
+            if i > 0 {
+                body = body + text(',') + softline();
+            }
+            body = body + group(text(arg));
+        }
+        group(text(name) + text('(') + nest(2, body) + text(')'))
+    }
+
+    #[test]
+    fn fits() {
+        let doc = call("f", &["a", "b"]);
+        assert_eq!(doc.pretty(8).to_string(), "f(a, b)");
+    }
+
+    #[test]
+    fn breaks() {
+        let call = call("f", &["a", "b"]);
+        let doc = text("let x =") + nest(4, softline() + call);
+        let s = String::from(CAT + doc.pretty(10) + ';');
+        assert_eq!(s, "let x =\n    f(a,\n      b);");
+        assert_eq!(s.len(), s.capacity());
+    }
+
+    #[test]
+    fn nested_groups() {
+        let inner = group(text("[") + nest(2, softline() + text("1")));
+        let doc = group(text("xxxxxxx") + softline() + inner);
+        assert_eq!(doc.pretty(10).to_string(), "xxxxxxx\n[ 1");
+        assert_eq!(Doc::empty().pretty(0).to_string(), "");
+    }
+
+    #[test]
+    fn trailing_text() {
+        let args = || group(text("f(") + nest(2, softline() + text("x")));
+        let doc = args() + text(")") + text(";;;;;");
+        assert_eq!(doc.pretty(9).to_string(), "f(\n  x);;;;;");
+        let doc = args() + text(")") + softline() + text(";;;;;");
+        assert_eq!(doc.pretty(9).to_string(), "f( x)\n;;;;;");
+        let inner = || group(text("y") + softline() + text("z"));
+        let doc = || args() + text(")") + inner() + text(";");
+        assert_eq!(doc().pretty(9).to_string(), "f( x)y z;");
+        assert_eq!(doc().pretty(8).to_string(), "f(\n  x)y z;");
+    }
+}
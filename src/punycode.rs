@@ -0,0 +1,205 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use cat::Cat;
+use std::fmt::{self, Display, Write};
+
+// Parameters from RFC 3492.
+const BASE: u64 = 36;
+const TMIN: u64 = 1;
+const TMAX: u64 = 26;
+const SKEW: u64 = 38;
+const DAMP: u64 = 700;
+const INITIAL_BIAS: u64 = 72;
+const INITIAL_N: u32 = 0x80;
+const ACE_PREFIX: &str = "xn--";
+
+/// A piece that encodes a host name with punycode.
+///
+/// This is created by the [`punycode`](fn.punycode.html) function.
+#[derive(Clone, Copy, Debug)]
+pub struct Punycode<'a> {
+    host: &'a str,
+}
+
+/// Creates a piece that encodes every non-ASCII label of `host` in
+/// its ASCII-compatible form, for example `bücher.de` becomes
+/// `xn--bcher-kva.de`.
+///
+/// Labels are separated by `.`, and labels that contain only ASCII
+/// characters are appended unchanged. No IDNA mapping takes place,
+/// so `host` should already be lowercase and normalized. The size
+/// hint is exact.
+///
+/// # Examples
+///
+/// ```rust
+/// use sconcat::{CAT, punycode};
+///
+/// let cat = CAT + "https://" + punycode("www.bücher.de") + "/index.html";
+/// assert_eq!(String::from(cat), "https://www.xn--bcher-kva.de/index.html");
+/// ```
+pub fn punycode(host: &str) -> Punycode<'_> {
+    Punycode { host }
+}
+
+fn digit(d: u64) -> char {
+    if d < 26 {
+        char::from(b'a' + d as u8)
+    } else {
+        char::from(b'0' + (d - 26) as u8)
+    }
+}
+
+fn adapt(delta: u64, num_points: u64, first: bool) -> u64 {
+    let mut delta = if first { delta / DAMP } else { delta / 2 };
+    delta += delta / num_points;
+    let mut k = 0;
+    while delta > ((BASE - TMIN) * TMAX) / 2 {
+        delta /= BASE - TMIN;
+        k += BASE;
+    }
+    k + (BASE - TMIN + 1) * delta / (delta + SKEW)
+}
+
+// Passes the punycode encoding of `label` to `out`, without the ACE
+// prefix.
+fn encode_label<F: FnMut(char)>(label: &str, out: &mut F) {
+    let mut basic = 0;
+    for c in label.chars().filter(char::is_ascii) {
+        out(c);
+        basic += 1;
+    }
+    if basic > 0 {
+        out('-');
+    }
+    let len = label.chars().count() as u64;
+    let mut handled = basic;
+    let mut n = INITIAL_N;
+    let mut delta = 0u64;
+    let mut bias = INITIAL_BIAS;
+    while handled < len {
+        let m = label
+            .chars()
+            .map(u32::from)
+            .filter(|&c| c >= n)
+            .min()
+            .unwrap();
+        delta += u64::from(m - n) * (handled + 1);
+        n = m;
+        for c in label.chars().map(u32::from) {
+            if c < n {
+                delta += 1;
+            }
+            if c == n {
+                let mut q = delta;
+                let mut k = BASE;
+                loop {
+                    let t = if k <= bias {
+                        TMIN
+                    } else if k >= bias + TMAX {
+                        TMAX
+                    } else {
+                        k - bias
+                    };
+                    if q < t {
+                        break;
+                    }
+                    out(digit(t + (q - t) % (BASE - t)));
+                    q = (q - t) / (BASE - t);
+                    k += BASE;
+                }
+                out(digit(q));
+                bias = adapt(delta, handled + 1, handled == basic);
+                delta = 0;
+                handled += 1;
+            }
+        }
+        delta += 1;
+        n += 1;
+    }
+}
+
+impl<'a> Punycode<'a> {
+    // Passes every character of the encoded host to `out`.
+    fn encode<F: FnMut(char)>(&self, mut out: F) {
+        for (i, label) in self.host.split('.').enumerate() {
+            if i > 0 {
+                out('.');
+            }
+            if label.is_ascii() {
+                label.chars().for_each(&mut out);
+            } else {
+                ACE_PREFIX.chars().for_each(&mut out);
+                encode_label(label, &mut out);
+            }
+        }
+    }
+}
+
+impl<'a> Cat for Punycode<'a> {
+    fn size_hint(&self) -> usize {
+        if self.host.is_ascii() {
+            return self.host.len();
+        }
+        let mut len = 0;
+        self.encode(|_| len += 1);
+        len
+    }
+
+    fn append_to(&self, s: &mut String) {
+        if self.host.is_ascii() {
+            s.push_str(self.host);
+        } else {
+            self.encode(|c| s.push(c));
+        }
+    }
+
+    fn for_each_segment(&self, f: &mut dyn FnMut(&str)) {
+        if self.host.is_ascii() {
+            f(self.host);
+        } else {
+            self.encode(|c| f(c.encode_utf8(&mut [0; 4])));
+        }
+    }
+}
+
+impl<'a> Display for Punycode<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut result = Ok(());
+        self.encode(|c| {
+            if result.is_ok() {
+                result = f.write_char(c);
+            }
+        });
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use CAT;
+    use super::punycode;
+
+    #[test]
+    fn labels() {
+        assert_eq!(punycode("bücher").to_string(), "xn--bcher-kva");
+        assert_eq!(punycode("münchen").to_string(), "xn--mnchen-3ya");
+        assert_eq!(punycode("中国").to_string(), "xn--fiqs8s");
+        assert_eq!(punycode("example.com").to_string(), "example.com");
+        assert_eq!(punycode("").to_string(), "");
+    }
+
+    #[test]
+    fn hosts() {
+        let cat = CAT + "http://" + punycode("münchen.中国") + '/';
+        let s = String::from(cat);
+        assert_eq!(s, "http://xn--mnchen-3ya.xn--fiqs8s/");
+        assert_eq!(s.len(), s.capacity());
+    }
+}
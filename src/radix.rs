@@ -0,0 +1,169 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use cat::{Cat, SegmentWriter};
+use count::CountingWriter;
+use std::fmt::{self, Binary, Display, LowerHex, Octal, Write};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Base {
+    Hex,
+    Oct,
+    Bin,
+}
+
+/// A piece that formats an integer in hexadecimal, octal or binary.
+///
+/// This is created by the [`hex_int`](fn.hex_int.html),
+/// [`oct`](fn.oct.html) and [`bin`](fn.bin.html) functions.
+#[derive(Clone, Copy, Debug)]
+pub struct Radix<T> {
+    value: T,
+    width: usize,
+    base: Base,
+    prefix: bool,
+}
+
+/// Creates a piece that formats `value` in lowercase hexadecimal,
+/// padded with zeros to at least `width` digits.
+///
+/// Negative values are formatted in two's complement, like with the
+/// `{:x}` format. The integer is formatted into the destination, and
+/// as formatting an integer is cheap, the size hint formats it too to
+/// find its exact length.
+///
+/// # Examples
+///
+/// ```rust
+/// use sconcat::{CAT, hex_int};
+///
+/// let cat = CAT + "pc=" + hex_int(0x1a2bu16, 8).with_prefix();
+/// assert_eq!(String::from(cat), "pc=0x00001a2b");
+/// assert_eq!(hex_int(-1i8, 0).to_string(), "ff");
+/// ```
+pub fn hex_int<T: LowerHex>(value: T, width: usize) -> Radix<T> {
+    Radix::new(value, width, Base::Hex)
+}
+
+/// Creates a piece that formats `value` in octal, padded with zeros
+/// to at least `width` digits.
+///
+/// # Examples
+///
+/// ```rust
+/// use sconcat::{CAT, oct};
+///
+/// let cat = CAT + "mode=" + oct(0o644u32, 4);
+/// assert_eq!(String::from(cat), "mode=0644");
+/// ```
+pub fn oct<T: Octal>(value: T, width: usize) -> Radix<T> {
+    Radix::new(value, width, Base::Oct)
+}
+
+/// Creates a piece that formats `value` in binary, padded with zeros
+/// to at least `width` digits.
+///
+/// # Examples
+///
+/// ```rust
+/// use sconcat::{CAT, bin};
+///
+/// let flags = 0b101u8;
+/// let cat = CAT + "flags=0b" + bin(flags, 8);
+/// assert_eq!(String::from(cat), "flags=0b00000101");
+/// ```
+pub fn bin<T: Binary>(value: T, width: usize) -> Radix<T> {
+    Radix::new(value, width, Base::Bin)
+}
+
+impl<T> Radix<T> {
+    fn new(value: T, width: usize, base: Base) -> Radix<T> {
+        Radix {
+            value,
+            width,
+            base,
+            prefix: false,
+        }
+    }
+
+    /// Adds the `0x`, `0o` or `0b` prefix before the digits.
+    ///
+    /// The prefix does not count towards the width.
+    pub fn with_prefix(self) -> Radix<T> {
+        Radix {
+            prefix: true,
+            ..self
+        }
+    }
+
+    /// Returns the wrapped integer.
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T: Binary + LowerHex + Octal> Radix<T> {
+    fn write<W: Write>(&self, w: &mut W) -> fmt::Result {
+        let width = self.width;
+        let value = &self.value;
+        match (self.base, self.prefix) {
+            (Base::Hex, false) => write!(w, "{:01$x}", value, width),
+            (Base::Hex, true) => write!(w, "0x{:01$x}", value, width),
+            (Base::Oct, false) => write!(w, "{:01$o}", value, width),
+            (Base::Oct, true) => write!(w, "0o{:01$o}", value, width),
+            (Base::Bin, false) => write!(w, "{:01$b}", value, width),
+            (Base::Bin, true) => write!(w, "0b{:01$b}", value, width),
+        }
+    }
+}
+
+impl<T: Binary + LowerHex + Octal> Cat for Radix<T> {
+    fn size_hint(&self) -> usize {
+        let mut w = CountingWriter::new();
+        self.write(&mut w).expect("capacity overflow");
+        w.count()
+    }
+
+    fn append_to(&self, s: &mut String) {
+        self.write(s).unwrap();
+    }
+
+    fn for_each_segment(&self, f: &mut dyn FnMut(&str)) {
+        self.write(&mut SegmentWriter::new(f)).unwrap();
+    }
+}
+
+impl<T: Binary + LowerHex + Octal> Display for Radix<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.write(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use CAT;
+    use cat::Cat;
+    use super::{bin, hex_int, oct};
+
+    #[test]
+    fn radixes() {
+        let cat = CAT
+            + hex_int(0xbeefu32, 2)
+            + ' '
+            + oct(8u8, 0).with_prefix()
+            + ' '
+            + bin(-2i8, 4).with_prefix();
+        let s = String::from(cat);
+        assert_eq!(s, "beef 0o10 0b11111110");
+        assert_eq!(s.len(), s.capacity());
+        assert_eq!(hex_int(u128::MAX, 0).size_hint(), 32);
+        assert_eq!(bin(0u8, 0).to_string(), "0");
+        assert_eq!(hex_int(0u8, 4).with_prefix().size_hint(), 6);
+        assert_eq!(oct(7u8, 1).into_inner(), 7);
+    }
+}
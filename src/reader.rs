@@ -0,0 +1,253 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use cat::{Cat, CatMany, CatOne, CatStart};
+use segments::Cursor;
+use std::io::{self, BufRead, Read};
+
+// Fills the buffer of `r`, retrying when interrupted.
+fn fill<R: BufRead>(r: &mut R) -> io::Result<&[u8]> {
+    loop {
+        match r.fill_buf() {
+            Ok(_) => break,
+            Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    r.fill_buf()
+}
+
+// Reads `bytes.len()` bytes from `r` and compares them with `bytes`,
+// stopping at the first difference.
+fn matches_bytes<R: BufRead>(r: &mut R, mut bytes: &[u8]) -> io::Result<bool> {
+    while !bytes.is_empty() {
+        let n = {
+            let buf = fill(r)?;
+            let n = buf.len().min(bytes.len());
+            if n == 0 || buf[..n] != bytes[..n] {
+                return Ok(false);
+            }
+            n
+        };
+        r.consume(n);
+        bytes = &bytes[n..];
+    }
+    Ok(true)
+}
+
+fn matches<C: Cat, R: BufRead>(cat: &C, r: &mut R) -> io::Result<bool> {
+    let mut result = Ok(true);
+    cat.for_each_segment(&mut |segment| {
+        if let Ok(true) = result {
+            result = matches_bytes(r, segment.as_bytes());
+        }
+    });
+    if !result? {
+        return Ok(false);
+    }
+    Ok(fill(r)?.is_empty())
+}
+
+impl<L: Cat, R: Cat> CatMany<L, R> {
+    /// Checks whether the bytes read from `r` are equal to the
+    /// concatenation.
+    ///
+    /// The concatenation is compared segment by segment against the
+    /// buffer of `r`, so it is never materialized and reading stops
+    /// at the first difference. The reader must be at its end after
+    /// the concatenation for the result to be `true`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use sconcat::CAT;
+    /// use std::io::Cursor;
+    ///
+    /// let name = String::from("Point");
+    /// let code = CAT + "pub struct " + name + ";\n";
+    /// let on_disk = Cursor::new("pub struct Point;\n");
+    /// assert!(code.matches_reader(on_disk).unwrap());
+    /// let stale = Cursor::new("pub struct Point;\n\n");
+    /// assert!(!code.matches_reader(stale).unwrap());
+    /// ```
+    pub fn matches_reader<B: BufRead>(&self, mut r: B) -> io::Result<bool> {
+        matches(self, &mut r)
+    }
+}
+
+impl<T: Cat> CatOne<T> {
+    /// Checks whether the bytes read from `r` are equal to the
+    /// concatenation.
+    pub fn matches_reader<B: BufRead>(&self, mut r: B) -> io::Result<bool> {
+        matches(&self.inner, &mut r)
+    }
+}
+
+impl CatStart {
+    /// Checks whether `r` is at its end, that is whether the bytes
+    /// read from `r` are equal to the empty concatenation.
+    pub fn matches_reader<B: BufRead>(&self, mut r: B) -> io::Result<bool> {
+        Ok(fill(&mut r)?.is_empty())
+    }
+}
+
+/// A reader that streams the bytes of a concatenation.
+///
+/// This is created by the `into_reader` methods of the concatenation
+/// types. The segments are copied into the buffers passed to `read`
+/// one after the other, so the concatenation is never materialized.
+/// The reader keeps its position in the segments between calls, like
+/// the `segments` iterators.
+#[derive(Clone, Debug)]
+pub struct CatReader<C: Cat> {
+    cat: C,
+    cursor: Cursor,
+    offset: usize,
+}
+
+impl<C: Cat> CatReader<C> {
+    fn new(cat: C) -> CatReader<C> {
+        CatReader {
+            cat,
+            cursor: Cursor::new(false),
+            offset: 0,
+        }
+    }
+
+    /// Returns the concatenation.
+    pub fn into_inner(self) -> C {
+        self.cat
+    }
+}
+
+impl<C: Cat> Read for CatReader<C> {
+    fn read(&mut self, mut out: &mut [u8]) -> io::Result<usize> {
+        let mut read = 0;
+        while !out.is_empty() {
+            let (n, done) = {
+                let segment = match self.cursor.peek(&self.cat) {
+                    Some(segment) => segment,
+                    None => break,
+                };
+                let rest = &segment.as_bytes()[self.offset..];
+                let n = rest.len().min(out.len());
+                out[..n].copy_from_slice(&rest[..n]);
+                (n, n == rest.len())
+            };
+            out = &mut out[n..];
+            read += n;
+            if done {
+                self.cursor.advance();
+                self.offset = 0;
+            } else {
+                self.offset += n;
+            }
+        }
+        Ok(read)
+    }
+}
+
+impl<L: Cat, R: Cat> CatMany<L, R> {
+    /// Converts the concatenation into a reader that streams its
+    /// bytes.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use sconcat::CAT;
+    /// use std::io;
+    ///
+    /// let body = CAT + "{\"id\":" + String::from("7") + '}';
+    /// let mut sink = Vec::new();
+    /// let copied = io::copy(&mut body.into_reader(), &mut sink).unwrap();
+    /// assert_eq!(copied, 8);
+    /// assert_eq!(sink, b"{\"id\":7}");
+    /// ```
+    pub fn into_reader(self) -> CatReader<CatMany<L, R>> {
+        CatReader::new(self)
+    }
+}
+
+impl<T: Cat> CatOne<T> {
+    /// Converts the concatenation into a reader that streams its
+    /// bytes.
+    pub fn into_reader(self) -> CatReader<T> {
+        CatReader::new(self.inner)
+    }
+}
+
+impl CatStart {
+    /// Converts the empty concatenation into a reader that is
+    /// already at its end.
+    pub fn into_reader(self) -> CatReader<&'static str> {
+        CatReader::new("")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use CAT;
+    use Dbg;
+    use std::io::{self, BufReader, Cursor, Read};
+
+    #[test]
+    fn compares() {
+        let cat = CAT + "ab" + 'é' + String::from("cdefgh") + "";
+        let small = |s: &'static str| BufReader::with_capacity(3, s.as_bytes());
+        assert!(cat.matches_reader(small("abécdefgh")).unwrap());
+        assert!(!cat.matches_reader(small("abécdefg")).unwrap());
+        assert!(!cat.matches_reader(small("abécdefgX")).unwrap());
+        assert!(!cat.matches_reader(small("abécdefghi")).unwrap());
+        assert!((CAT + "x").matches_reader(small("x")).unwrap());
+        assert!(CAT.matches_reader(small("")).unwrap());
+        assert!(!CAT.matches_reader(small("x")).unwrap());
+    }
+
+    #[test]
+    fn stops_at_difference() {
+        let mut r = Cursor::new("abXdef");
+        assert!(!(CAT + "ab" + "cdef").matches_reader(&mut r).unwrap());
+        let mut rest = String::new();
+        r.read_to_string(&mut rest).unwrap();
+        assert_eq!(rest, "Xdef");
+    }
+
+    #[test]
+    fn streams() {
+        let cat = CAT + "ab" + 'é' + Dbg([1, 2]) + String::from("") + "z";
+        let expected = cat.to_string();
+        let mut r = cat.into_reader();
+        let mut buf = [0; 3];
+        let mut read = Vec::new();
+        loop {
+            let n = r.read(&mut buf).unwrap();
+            if n == 0 {
+                break;
+            }
+            read.extend_from_slice(&buf[..n]);
+        }
+        assert_eq!(read, expected.as_bytes());
+        assert_eq!(r.read(&mut buf).unwrap(), 0);
+
+        let mut s = String::new();
+        (CAT + "x").into_reader().read_to_string(&mut s).unwrap();
+        CAT.into_reader().read_to_string(&mut s).unwrap();
+        assert_eq!(s, "x");
+    }
+
+    #[test]
+    fn long_list() {
+        let items: Vec<String> = (0..40_000).map(|i| i.to_string()).collect();
+        let expected = items.concat();
+        let mut sink = Vec::new();
+        let mut r = (CAT + "[" + items + "]").into_reader();
+        let copied = io::copy(&mut r, &mut sink).unwrap();
+        assert_eq!(copied as usize, expected.len() + 2);
+        assert_eq!(&sink[1..sink.len() - 1], expected.as_bytes());
+    }
+}
@@ -0,0 +1,101 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use cat::{self, Cat, CatMany, CatOne, CatStart};
+
+impl<L: Cat, R: Cat> CatMany<L, R> {
+    /// Converts the concatenation to a `String` using the buffer of
+    /// `buf`.
+    ///
+    /// The contents of `buf` are cleared, and its buffer is only
+    /// grown if its capacity is smaller than the size hint. Passing
+    /// the same buffer back in a loop amortizes the allocations to
+    /// zero.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use sconcat::CAT;
+    ///
+    /// let mut buf = String::with_capacity(64);
+    /// let ptr = buf.as_ptr();
+    /// for id in &["1", "2"] {
+    ///     buf = (CAT + "item " + *id).into_string_with(buf);
+    ///     assert!(buf.starts_with("item "));
+    /// }
+    /// assert_eq!(buf, "item 2");
+    /// assert_eq!(buf.as_ptr(), ptr);
+    /// ```
+    pub fn into_string_with(self, mut buf: String) -> String {
+        self.append_cleared_to(&mut buf);
+        buf
+    }
+
+    /// Replaces the contents of `s` with the concatenation, keeping
+    /// its buffer.
+    pub fn append_cleared_to(&self, s: &mut String) {
+        s.clear();
+        cat::append_cat(s, self);
+    }
+}
+
+impl<T: Cat> CatOne<T> {
+    /// Converts the concatenation to a `String` using the buffer of
+    /// `buf`.
+    ///
+    /// The contents of `buf` are cleared, and its buffer is only
+    /// grown if its capacity is smaller than the size hint.
+    pub fn into_string_with(self, mut buf: String) -> String {
+        self.append_cleared_to(&mut buf);
+        buf
+    }
+
+    /// Replaces the contents of `s` with the concatenation, keeping
+    /// its buffer.
+    pub fn append_cleared_to(&self, s: &mut String) {
+        s.clear();
+        cat::append_cat(s, &self.inner);
+    }
+}
+
+impl CatStart {
+    /// Returns `buf` after clearing it, keeping its buffer.
+    pub fn into_string_with(self, mut buf: String) -> String {
+        buf.clear();
+        buf
+    }
+
+    /// Clears `s`, keeping its buffer.
+    pub fn append_cleared_to(&self, s: &mut String) {
+        s.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use CAT;
+
+    #[test]
+    fn recycle() {
+        let mut buf = String::with_capacity(16);
+        buf.push_str("old contents");
+        let ptr = buf.as_ptr();
+        let cat = CAT + "ab" + 'c' + String::from("d");
+        buf = cat.into_string_with(buf);
+        assert_eq!((buf.as_str(), buf.as_ptr()), ("abcd", ptr));
+        (CAT + "x").append_cleared_to(&mut buf);
+        assert_eq!((buf.as_str(), buf.as_ptr()), ("x", ptr));
+        buf = (CAT + "y").into_string_with(buf);
+        assert_eq!(buf, "y");
+        CAT.append_cleared_to(&mut buf);
+        assert_eq!(buf, "");
+        buf.push('z');
+        buf = CAT.into_string_with(buf);
+        assert_eq!((buf.as_str(), buf.capacity()), ("", 16));
+    }
+}
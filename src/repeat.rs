@@ -0,0 +1,233 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use cat::{Cat, CatMany, CatOne, SegmentBuf};
+use pad;
+use std::fmt::{self, Display};
+use std::ops::Mul;
+
+/// A piece that repeats a piece a number of times.
+///
+/// This is created by the [`repeat`](fn.repeat.html) function, or by
+/// multiplying a concatenation by a `usize`.
+#[derive(Clone, Copy, Debug)]
+pub struct Repeat<T: Cat> {
+    piece: T,
+    count: usize,
+}
+
+/// Creates a piece that repeats `piece` `count` times.
+///
+/// The size hint is `count` times the size hint of `piece`, and
+/// `piece` is appended `count` times without any intermediate
+/// `String`. A concatenation can also be repeated by multiplying it
+/// by a `usize`.
+///
+/// # Examples
+///
+/// ```rust
+/// use sconcat::{CAT, repeat};
+///
+/// let cat = CAT + repeat('-', 5) + " title " + repeat('-', 5);
+/// assert_eq!(String::from(cat), "----- title -----");
+///
+/// let indent = (CAT + "| " + "  ") * 2;
+/// assert_eq!(String::from(CAT + indent + "leaf"), "|   |   leaf");
+/// ```
+pub fn repeat<T: Cat>(piece: T, count: usize) -> Repeat<T> {
+    Repeat { piece, count }
+}
+
+impl<T: Cat> Cat for Repeat<T> {
+    fn size_hint(&self) -> usize {
+        self.piece
+            .size_hint()
+            .checked_mul(self.count)
+            .expect("capacity overflow")
+    }
+
+    fn append_to(&self, s: &mut String) {
+        for _ in 0..self.count {
+            self.piece.append_to(s);
+        }
+    }
+
+    fn for_each_segment(&self, f: &mut dyn FnMut(&str)) {
+        for _ in 0..self.count {
+            self.piece.for_each_segment(f);
+        }
+    }
+
+    fn pieces(&self) -> usize {
+        self.piece.pieces()
+    }
+
+    fn segment_count(&self) -> usize {
+        self.piece.segment_count() * self.count
+    }
+
+    fn child_count(&self) -> usize {
+        self.count
+    }
+
+    fn child_at(&self, _index: usize) -> &dyn Cat {
+        &self.piece
+    }
+
+    fn segment_at<'s>(
+        &'s self,
+        index: usize,
+        buf: &'s mut SegmentBuf,
+    ) -> &'s str {
+        let count = self.piece.segment_count();
+        self.piece.segment_at(index % count, buf)
+    }
+}
+
+impl<T: Cat + Display> Display for Repeat<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for _ in 0..self.count {
+            Display::fmt(&self.piece, f)?;
+        }
+        Ok(())
+    }
+}
+
+/// A piece that repeats a character a number of times.
+///
+/// This is created by the [`repeat_char`](fn.repeat_char.html) and
+/// [`spaces`](fn.spaces.html) functions.
+#[derive(Clone, Copy, Debug)]
+pub struct RepeatChar {
+    c: char,
+    count: usize,
+}
+
+/// Creates a piece that repeats the character `c` `count` times.
+///
+/// The size hint is exact, and the characters are copied in runs from
+/// a small stack buffer rather than pushed one by one or built in a
+/// temporary `String`.
+///
+/// # Examples
+///
+/// ```rust
+/// use sconcat::{CAT, repeat_char};
+///
+/// let cat = CAT + "name" + repeat_char('─', 4) + '┐';
+/// let s = String::from(cat);
+/// assert_eq!(s, "name────┐");
+/// assert_eq!(s.len(), s.capacity());
+/// ```
+pub fn repeat_char(c: char, count: usize) -> RepeatChar {
+    RepeatChar { c, count }
+}
+
+/// Creates a piece of `count` spaces.
+///
+/// This is the same as `repeat_char(' ', count)`.
+///
+/// # Examples
+///
+/// ```rust
+/// use sconcat::{CAT, spaces};
+///
+/// let depth = 2;
+/// let cat = CAT + spaces(depth * 4) + "return;";
+/// assert_eq!(String::from(cat), "        return;");
+/// ```
+pub fn spaces(count: usize) -> RepeatChar {
+    repeat_char(' ', count)
+}
+
+impl Cat for RepeatChar {
+    fn size_hint(&self) -> usize {
+        self.c
+            .len_utf8()
+            .checked_mul(self.count)
+            .expect("capacity overflow")
+    }
+
+    fn append_to(&self, s: &mut String) {
+        pad::fill(&mut |segment| s.push_str(segment), self.c, self.count);
+    }
+
+    fn for_each_segment(&self, f: &mut dyn FnMut(&str)) {
+        pad::fill(f, self.c, self.count);
+    }
+}
+
+impl Display for RepeatChar {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut result = Ok(());
+        self.for_each_segment(&mut |segment| {
+            if result.is_ok() {
+                result = f.write_str(segment);
+            }
+        });
+        result
+    }
+}
+
+impl<T: Cat> Mul<usize> for CatOne<T> {
+    type Output = CatOne<Repeat<T>>;
+    fn mul(self, rhs: usize) -> CatOne<Repeat<T>> {
+        CatOne {
+            inner: repeat(self.inner, rhs),
+        }
+    }
+}
+
+impl<L: Cat, R: Cat> Mul<usize> for CatMany<L, R> {
+    type Output = CatOne<Repeat<CatMany<L, R>>>;
+    fn mul(self, rhs: usize) -> CatOne<Repeat<CatMany<L, R>>> {
+        CatOne {
+            inner: repeat(self, rhs),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use CAT;
+    use cat::Cat;
+    use super::{repeat, repeat_char, spaces};
+
+    #[test]
+    fn repeated() {
+        let cat = CAT + '<' + repeat("ab", 3) + repeat('x', 0) + '>';
+        let s = String::from(cat);
+        assert_eq!(s, "<ababab>");
+        assert_eq!(s.len(), s.capacity());
+        let line = (CAT + '=') * 4;
+        assert_eq!(line.to_string(), "====");
+        let pair = (CAT + "a" + 'b') * 2;
+        let mut segments = pair.segments();
+        let mut v = Vec::new();
+        while let Some(segment) = segments.next_segment() {
+            v.push(String::from(segment));
+        }
+        assert_eq!(v, ["a", "b", "a", "b"]);
+        let mut s = String::from("x");
+        s += pair;
+        assert_eq!(s, "xabab");
+    }
+
+    #[test]
+    fn repeated_chars() {
+        let s = String::from(CAT + '|' + spaces(3) + repeat_char('é', 2) + '|');
+        assert_eq!(s, "|   éé|");
+        assert_eq!(s.len(), s.capacity());
+        let long = repeat_char('€', 100);
+        assert_eq!(long.size_hint(), 300);
+        assert_eq!(long.to_string(), "€".repeat(100));
+        assert!(long.segment_count() > 1);
+        assert_eq!(String::from(CAT + spaces(0) + "x"), "x");
+        assert_eq!(format!("[{}]", spaces(2)), "[  ]");
+    }
+}
@@ -0,0 +1,253 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use cat::{Cat, CatMany, CatOne, CatStart, SegmentBuf};
+use std::fmt::{self, Display};
+
+// Owned strings shorter than this are copied, as a chunk of their own
+// would cost more than the copy.
+const ADOPT_MIN: usize = 64;
+
+/// A string stored as a sequence of chunks.
+///
+/// A concatenation can be converted into a `Rope` without copying its
+/// large owned pieces: every `String`, `Box<str>` or owned `Cow<str>`
+/// piece of at least 64 bytes becomes a chunk of its own, and only
+/// the other pieces, such as string slices and characters, are copied
+/// into chunks shared with their neighbours. This avoids copying
+/// large documents composed of many owned pieces into one flat
+/// `String`.
+///
+/// A `Rope` is itself a piece, with one segment per chunk, so it can
+/// be written to a sink or appended to a `String` later.
+///
+/// # Examples
+///
+/// ```rust
+/// use sconcat::{CAT, Rope};
+///
+/// let body = "x".repeat(1000);
+/// let ptr = body.as_ptr();
+/// let rope = Rope::from(CAT + "<p>" + body + "</p>");
+/// assert_eq!(rope.len(), 1007);
+/// let chunks = rope.chunks().collect::<Vec<_>>();
+/// assert_eq!(chunks.len(), 3);
+/// assert_eq!(chunks[1].as_ptr(), ptr);
+/// assert!(String::from(rope).ends_with("xx</p>"));
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Rope {
+    chunks: Vec<String>,
+    len: usize,
+    // The last chunk is not appended to if it was adopted, as growing
+    // it could copy it.
+    adopted: bool,
+}
+
+impl Rope {
+    /// Creates an empty rope.
+    pub fn new() -> Rope {
+        Rope::default()
+    }
+
+    /// Returns the length of the rope in bytes.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the rope has a length of zero bytes.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns an iterator over the chunks of the rope.
+    pub fn chunks(&self) -> impl Iterator<Item = &str> {
+        self.chunks.iter().map(String::as_str)
+    }
+
+    /// Appends a copy of `s` to the rope.
+    pub fn push_str(&mut self, s: &str) {
+        if s.is_empty() {
+            return;
+        }
+        match self.chunks.last_mut() {
+            Some(last) if !self.adopted => last.push_str(s),
+            _ => {
+                self.chunks.push(String::from(s));
+                self.adopted = false;
+            }
+        }
+        self.len += s.len();
+    }
+
+    /// Appends `s` to the rope, adopting it as a chunk without
+    /// copying it unless it is short.
+    pub fn push_string(&mut self, s: String) {
+        if s.len() < ADOPT_MIN {
+            self.push_str(&s);
+            return;
+        }
+        self.len += s.len();
+        self.chunks.push(s);
+        self.adopted = true;
+    }
+}
+
+impl Cat for Rope {
+    fn size_hint(&self) -> usize {
+        self.len
+    }
+
+    fn append_to(&self, s: &mut String) {
+        for chunk in &self.chunks {
+            s.push_str(chunk);
+        }
+    }
+
+    fn into_string(self, capacity: usize) -> String {
+        let mut chunks = self.chunks.into_iter();
+        let mut s = match chunks.next() {
+            Some(first) => first.into_string(capacity),
+            None => String::with_capacity(capacity),
+        };
+        for chunk in chunks {
+            s.push_str(&chunk);
+        }
+        s
+    }
+
+    fn for_each_segment(&self, f: &mut dyn FnMut(&str)) {
+        for chunk in &self.chunks {
+            f(chunk);
+        }
+    }
+
+    fn segment_count(&self) -> usize {
+        self.chunks.len()
+    }
+
+    fn child_count(&self) -> usize {
+        self.chunks.len()
+    }
+
+    fn child_at(&self, index: usize) -> &dyn Cat {
+        &self.chunks[index]
+    }
+
+    fn segment_at<'s>(
+        &'s self,
+        index: usize,
+        _buf: &'s mut SegmentBuf,
+    ) -> &'s str {
+        &self.chunks[index]
+    }
+
+    fn single_str(&self) -> Option<&str> {
+        match self.chunks.len() {
+            0 => Some(""),
+            1 => Some(&self.chunks[0]),
+            _ => None,
+        }
+    }
+
+    fn append_to_rope(self, rope: &mut Rope) {
+        for chunk in self.chunks {
+            rope.push_string(chunk);
+        }
+    }
+}
+
+impl Display for Rope {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for chunk in &self.chunks {
+            f.write_str(chunk)?;
+        }
+        Ok(())
+    }
+}
+
+impl From<Rope> for String {
+    fn from(src: Rope) -> String {
+        let capacity = src.len;
+        src.into_string(capacity)
+    }
+}
+
+impl<L: Cat, R: Cat> From<CatMany<L, R>> for Rope {
+    fn from(src: CatMany<L, R>) -> Rope {
+        let mut rope = Rope::new();
+        src.append_to_rope(&mut rope);
+        rope
+    }
+}
+
+impl<T: Cat> From<CatOne<T>> for Rope {
+    fn from(src: CatOne<T>) -> Rope {
+        let mut rope = Rope::new();
+        src.inner.append_to_rope(&mut rope);
+        rope
+    }
+}
+
+impl From<CatStart> for Rope {
+    fn from(_src: CatStart) -> Rope {
+        Rope::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use CAT;
+    use Dbg;
+    use cat::Cat;
+    use rope::Rope;
+    use std::borrow::Cow;
+
+    #[test]
+    fn adopts_owned() {
+        let long = "a".repeat(100);
+        let ptr = long.as_ptr();
+        let cow: Cow<str> = Cow::Owned("b".repeat(70));
+        let rope = Rope::from(
+            CAT + "[" + long + 'é' + Dbg(5) + cow + String::from("short"),
+        );
+        let chunks = rope.chunks().collect::<Vec<_>>();
+        assert_eq!(chunks.len(), 5);
+        assert_eq!(chunks[0], "[");
+        assert_eq!(chunks[1].as_ptr(), ptr);
+        assert_eq!(chunks[2], "é5");
+        assert_eq!(chunks[3].len(), 70);
+        assert_eq!(chunks[4], "short");
+        assert_eq!(rope.len(), 1 + 100 + 2 + 1 + 70 + 5);
+        assert_eq!(rope.segment_count(), 5);
+        let s = String::from(rope.clone());
+        assert_eq!(s.len(), rope.len());
+        assert_eq!(s, rope.to_string());
+        assert!(s.starts_with("[aaa") && s.ends_with("bbshort"));
+    }
+
+    #[test]
+    fn nested() {
+        let parts = vec!["c".repeat(64), String::from("d")];
+        let rope = Rope::from(CAT + ("x", Some("y".repeat(80))) + parts);
+        assert_eq!(rope.chunks().count(), 4);
+        assert_eq!(rope.len(), 1 + 80 + 64 + 1);
+        let outer = Rope::from(CAT + "<" + rope + '>');
+        assert_eq!(outer.chunks().count(), 4);
+        assert_eq!(String::from(CAT + "" + outer).len(), 148);
+    }
+
+    #[test]
+    fn empty() {
+        let rope = Rope::from(CAT);
+        assert!(rope.is_empty());
+        assert_eq!(rope.single_str(), Some(""));
+        assert_eq!(String::from(Rope::from(CAT + "" + String::new())), "");
+        assert_eq!(Rope::from(CAT + "x").single_str(), Some("x"));
+    }
+}
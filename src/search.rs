@@ -0,0 +1,235 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Searches across segment boundaries. Only the last bytes of the
+// text seen so far are kept, at most as many as the pattern, so the
+// concatenation is never materialized and pieces that are formatted
+// on the fly are formatted only once.
+
+use cat::{Cat, CatMany, CatOne, CatStart};
+
+fn starts_with_segments<C: Cat>(cat: &C, pat: &str) -> bool {
+    let mut rest = Some(pat.as_bytes());
+    cat.for_each_segment(&mut |segment| {
+        let bytes = segment.as_bytes();
+        rest = match rest {
+            Some(r) if r.len() <= bytes.len() => {
+                if bytes.starts_with(r) {
+                    Some(&[])
+                } else {
+                    None
+                }
+            }
+            Some(r) if r.starts_with(bytes) => Some(&r[bytes.len()..]),
+            _ => None,
+        };
+    });
+    rest == Some(&[])
+}
+
+fn ends_with_segments<C: Cat>(cat: &C, pat: &str) -> bool {
+    let pat = pat.as_bytes();
+    let mut tail = Vec::with_capacity(pat.len());
+    cat.for_each_segment(&mut |segment| {
+        keep_tail(&mut tail, segment.as_bytes(), pat.len());
+    });
+    tail == pat
+}
+
+// Appends `bytes` to `tail` keeping only the last `max` bytes.
+fn keep_tail(tail: &mut Vec<u8>, bytes: &[u8], max: usize) {
+    if bytes.len() >= max {
+        tail.clear();
+        tail.extend_from_slice(&bytes[bytes.len() - max..]);
+        return;
+    }
+    let excess = (tail.len() + bytes.len()).saturating_sub(max);
+    tail.drain(..excess);
+    tail.extend_from_slice(bytes);
+}
+
+fn find_segments<C: Cat>(cat: &C, pat: &str) -> Option<usize> {
+    if pat.is_empty() {
+        return Some(0);
+    }
+    // A match that straddles a boundary starts in the last
+    // `pat.len() - 1` bytes before it.
+    let keep = pat.len() - 1;
+    let mut carry = Vec::with_capacity(keep);
+    let mut joint = Vec::with_capacity(2 * keep);
+    let mut pos = 0;
+    let mut found = None;
+    cat.for_each_segment(&mut |segment| {
+        if found.is_some() {
+            return;
+        }
+        let bytes = segment.as_bytes();
+        if !carry.is_empty() {
+            joint.clear();
+            joint.extend_from_slice(&carry);
+            joint.extend_from_slice(&bytes[..keep.min(bytes.len())]);
+            let mut windows = joint.windows(pat.len());
+            if let Some(i) = windows.position(|w| w == pat.as_bytes()) {
+                found = Some(pos - carry.len() + i);
+                return;
+            }
+        }
+        if let Some(i) = segment.find(pat) {
+            found = Some(pos + i);
+            return;
+        }
+        keep_tail(&mut carry, bytes, keep);
+        pos += bytes.len();
+    });
+    found
+}
+
+impl<L: Cat, R: Cat> CatMany<L, R> {
+    /// Returns `true` if the concatenation contains `pat`, including
+    /// across the boundaries between pieces.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use sconcat::CAT;
+    ///
+    /// let cat = CAT + "Content-" + "Type: " + "text/plain";
+    /// assert!(cat.contains("Type: text"));
+    /// assert!(!cat.contains("json"));
+    /// ```
+    pub fn contains(&self, pat: &str) -> bool {
+        find_segments(self, pat).is_some()
+    }
+
+    /// Returns the byte index of the first match of `pat` in the
+    /// concatenation, like `str::find`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use sconcat::CAT;
+    ///
+    /// let cat = CAT + "key" + '=' + "value";
+    /// assert_eq!(cat.find("y=v"), Some(2));
+    /// assert_eq!(cat.find("=="), None);
+    /// ```
+    pub fn find(&self, pat: &str) -> Option<usize> {
+        find_segments(self, pat)
+    }
+
+    /// Returns `true` if the concatenation starts with `pat`.
+    ///
+    /// Segments after the prefix are not needed to decide, but are
+    /// still walked, as a piece cannot stop early.
+    pub fn starts_with(&self, pat: &str) -> bool {
+        starts_with_segments(self, pat)
+    }
+
+    /// Returns `true` if the concatenation ends with `pat`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use sconcat::CAT;
+    ///
+    /// let header = CAT + "Host: " + "example.com" + '\r' + '\n';
+    /// assert!(header.starts_with("Host:"));
+    /// assert!(header.ends_with("\r\n"));
+    /// ```
+    pub fn ends_with(&self, pat: &str) -> bool {
+        ends_with_segments(self, pat)
+    }
+}
+
+impl<T: Cat> CatOne<T> {
+    /// Returns `true` if the concatenation contains `pat`.
+    pub fn contains(&self, pat: &str) -> bool {
+        find_segments(&self.inner, pat).is_some()
+    }
+
+    /// Returns the byte index of the first match of `pat` in the
+    /// concatenation.
+    pub fn find(&self, pat: &str) -> Option<usize> {
+        find_segments(&self.inner, pat)
+    }
+
+    /// Returns `true` if the concatenation starts with `pat`.
+    pub fn starts_with(&self, pat: &str) -> bool {
+        starts_with_segments(&self.inner, pat)
+    }
+
+    /// Returns `true` if the concatenation ends with `pat`.
+    pub fn ends_with(&self, pat: &str) -> bool {
+        ends_with_segments(&self.inner, pat)
+    }
+}
+
+impl CatStart {
+    /// Returns `true` if `pat` is empty.
+    pub fn contains(&self, pat: &str) -> bool {
+        pat.is_empty()
+    }
+
+    /// Returns `Some(0)` if `pat` is empty, and `None` otherwise.
+    pub fn find(&self, pat: &str) -> Option<usize> {
+        "".find(pat)
+    }
+
+    /// Returns `true` if `pat` is empty.
+    pub fn starts_with(&self, pat: &str) -> bool {
+        pat.is_empty()
+    }
+
+    /// Returns `true` if `pat` is empty.
+    pub fn ends_with(&self, pat: &str) -> bool {
+        pat.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use CAT;
+    use Dbg;
+
+    #[test]
+    fn find() {
+        let cat = CAT + "ab" + 'c' + "" + "dabc" + Dbg(12) + "abcd";
+        let s = "abcdabc12abcd";
+        for pat in &["", "a", "cd", "bcda", "c12a", "2abcd", "abcdabc12abcd"] {
+            assert_eq!(cat.find(pat), s.find(pat), "{}", pat);
+            assert!(cat.contains(pat));
+        }
+        for pat in &["abcdabc12abcde", "ca", "dd", "x"] {
+            assert_eq!(cat.find(pat), None, "{}", pat);
+            assert!(!cat.contains(pat));
+        }
+        assert_eq!((CAT + "aa" + 'a' + "ab").find("aab"), Some(2));
+        assert_eq!((CAT + 'é' + 'x').find("éx"), Some(0));
+        assert!(CAT.contains("") && !CAT.contains("a"));
+        assert_eq!(CAT.find(""), Some(0));
+    }
+
+    #[test]
+    fn starts_and_ends() {
+        let cat = CAT + "ab" + 'c' + "" + "de";
+        for pat in &["", "a", "abc", "abcd", "abcde"] {
+            assert!(cat.starts_with(pat), "{}", pat);
+        }
+        for pat in &["b", "abd", "abcdef"] {
+            assert!(!cat.starts_with(pat), "{}", pat);
+        }
+        for pat in &["", "e", "de", "cde", "abcde"] {
+            assert!(cat.ends_with(pat), "{}", pat);
+        }
+        for pat in &["d", "bde", "xabcde"] {
+            assert!(!cat.ends_with(pat), "{}", pat);
+        }
+        assert!((CAT + "x").starts_with("x") && (CAT + "x").ends_with("x"));
+        assert!(CAT.starts_with("") && !CAT.ends_with("a"));
+    }
+}
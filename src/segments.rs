@@ -0,0 +1,406 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use cat::{Cat, CatMany, CatOne, CatStart, SegmentBuf};
+
+// Number of steps of a path stored inline.
+const INLINE_STEPS: usize = 24;
+
+// A path from the root of a concatenation to one of its pieces. Each
+// step is the side of a concatenation, 0 or 1, or the index of a
+// child of a list. The first steps are stored inline, so that walking
+// a concatenation of ordinary depth does not allocate.
+#[derive(Clone, Debug, Default)]
+struct Path {
+    inline: [usize; INLINE_STEPS],
+    len: usize,
+    spilled: Vec<usize>,
+}
+
+impl Path {
+    fn push(&mut self, step: usize) {
+        if self.len < INLINE_STEPS {
+            self.inline[self.len] = step;
+        } else {
+            self.spilled.push(step);
+        }
+        self.len += 1;
+    }
+
+    fn pop(&mut self) -> Option<usize> {
+        self.len = self.len.checked_sub(1)?;
+        if self.len < INLINE_STEPS {
+            Some(self.inline[self.len])
+        } else {
+            self.spilled.pop()
+        }
+    }
+
+    fn steps(&self) -> impl Iterator<Item = &usize> {
+        let inline = &self.inline[..self.len.min(INLINE_STEPS)];
+        inline.iter().chain(&self.spilled)
+    }
+}
+
+// Finds the piece at the end of `path` below `root`.
+fn locate<'c>(root: &'c dyn Cat, path: &Path) -> &'c dyn Cat {
+    let mut piece = root;
+    for &step in path.steps() {
+        piece = match piece.split() {
+            Some((lhs, rhs)) => {
+                if step == 0 {
+                    lhs
+                } else {
+                    rhs
+                }
+            }
+            None => piece.child_at(step),
+        };
+    }
+    piece
+}
+
+// A position in the segments of a concatenation.
+//
+// The cursor does not borrow the concatenation, which is passed to
+// every call instead, so it can be kept between calls by readers and
+// futures that own or borrow the concatenation themselves. It stores
+// the path from the root to the current leaf piece, so moving to the
+// next segment costs the depth of the tree, not its size, and walking
+// borrowed segments does not allocate. When the cursor reaches a leaf
+// with more than one segment, such as a piece formatted on the fly,
+// the segments are counted, which can format the piece, and then
+// formatted again and copied into `leaf`.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Cursor {
+    path: Path,
+    // The number of segments of the current leaf, or `None` if the
+    // cursor is not at a leaf yet.
+    count: Option<usize>,
+    // The number of segments of the current leaf that were passed.
+    index: usize,
+    leaf: String,
+    ends: Vec<usize>,
+    buf: SegmentBuf,
+    back: bool,
+    done: bool,
+}
+
+impl Cursor {
+    // Creates a cursor before the first segment, or after the last
+    // segment if `back` is true, in which case the cursor moves
+    // backwards.
+    pub(crate) fn new(back: bool) -> Cursor {
+        Cursor {
+            back,
+            ..Cursor::default()
+        }
+    }
+
+    // Descends from the piece at the end of the path to its first
+    // leaf, or last leaf when moving backwards.
+    fn descend(&mut self, root: &dyn Cat) {
+        let mut piece = locate(root, &self.path);
+        loop {
+            if let Some((lhs, rhs)) = piece.split() {
+                self.path.push(self.back as usize);
+                piece = if self.back { rhs } else { lhs };
+                continue;
+            }
+            let children = piece.child_count();
+            if children == 0 {
+                break;
+            }
+            let index = if self.back { children - 1 } else { 0 };
+            self.path.push(index);
+            piece = piece.child_at(index);
+        }
+        self.leaf.clear();
+        self.ends.clear();
+        let mut count = piece.segment_count();
+        if count > 1 {
+            let leaf = &mut self.leaf;
+            let ends = &mut self.ends;
+            piece.for_each_segment(&mut |segment| {
+                leaf.push_str(segment);
+                ends.push(leaf.len());
+            });
+            count = ends.len();
+        }
+        self.count = Some(count);
+        self.index = 0;
+    }
+
+    // Moves the path to the next sibling of the current leaf or of
+    // one of its ancestors, returning false at the end.
+    fn ascend(&mut self, root: &dyn Cat) -> bool {
+        self.count = None;
+        while let Some(step) = self.path.pop() {
+            let parent = locate(root, &self.path);
+            let next = if parent.split().is_some() {
+                if step == self.back as usize {
+                    Some(1 - step)
+                } else {
+                    None
+                }
+            } else if self.back {
+                step.checked_sub(1)
+            } else if step + 1 < parent.child_count() {
+                Some(step + 1)
+            } else {
+                None
+            };
+            if let Some(next) = next {
+                self.path.push(next);
+                return true;
+            }
+        }
+        self.done = true;
+        false
+    }
+
+    // Moves to the next segment unless the cursor is already at a
+    // segment, returning false at the end.
+    fn seek(&mut self, root: &dyn Cat) -> bool {
+        loop {
+            match self.count {
+                Some(count) if self.index < count => return true,
+                Some(_) => {
+                    if !self.ascend(root) {
+                        return false;
+                    }
+                }
+                None if self.done => return false,
+                None => self.descend(root),
+            }
+        }
+    }
+
+    // Returns segment number `index` of the current leaf, counted
+    // from the end when moving backwards.
+    fn segment<'s>(&'s mut self, root: &'s dyn Cat, index: usize) -> &'s str {
+        let count = self.count.expect("cursor not at a segment");
+        let index = if self.back { count - 1 - index } else { index };
+        if self.ends.is_empty() {
+            return locate(root, &self.path).segment_at(index, &mut self.buf);
+        }
+        let start = if index == 0 { 0 } else { self.ends[index - 1] };
+        &self.leaf[start..self.ends[index]]
+    }
+
+    // Returns the next segment without moving past it.
+    pub(crate) fn peek<'s>(&'s mut self, root: &'s dyn Cat) -> Option<&'s str> {
+        if !self.seek(root) {
+            return None;
+        }
+        let index = self.index;
+        Some(self.segment(root, index))
+    }
+
+    // Returns the segment returned by `peek` if it is borrowed from
+    // `root`, so that it can be kept while the cursor is used. This
+    // returns `None` if the segment is encoded or formatted into the
+    // cursor, or if `peek` was not called.
+    pub(crate) fn borrowed<'c>(&self, root: &'c dyn Cat) -> Option<&'c str> {
+        match self.count {
+            Some(count) if self.index < count && self.ends.is_empty() => {
+                locate(root, &self.path).single_str()
+            }
+            _ => None,
+        }
+    }
+
+    // Moves past the segment returned by `peek`.
+    pub(crate) fn advance(&mut self) {
+        self.index += 1;
+    }
+
+    // Returns the next segment and moves past it.
+    pub(crate) fn next<'s>(&'s mut self, root: &'s dyn Cat) -> Option<&'s str> {
+        if !self.seek(root) {
+            return None;
+        }
+        let index = self.index;
+        self.index += 1;
+        Some(self.segment(root, index))
+    }
+}
+
+/// A lending iterator over the string slices of a concatenation.
+///
+/// This is created by the `segments` methods of the concatenation
+/// types. Since a returned slice can borrow from the iterator, for
+/// example when a `char` is encoded into an inline buffer, this
+/// cannot implement `Iterator`; use
+/// [`next_segment`](#method.next_segment) in a `while let` loop
+/// instead.
+///
+/// String slices are borrowed from the concatenation. When the
+/// iterator reaches a piece with more than one segment, such as a
+/// piece formatted on the fly, it counts the segments, which can
+/// format the piece, and then formats the piece again to copy its
+/// segments into a buffer owned by the iterator, so for such pieces
+/// the callback-based `visit_segments` is more efficient. The
+/// iterator keeps the path to the current piece, so moving to the
+/// next segment does not depend on the number of pieces before it.
+/// The path is stored inline up to a depth of 24 and on the heap
+/// beyond that.
+pub struct Segments<'a> {
+    root: &'a dyn Cat,
+    cursor: Cursor,
+}
+
+impl<'a> Segments<'a> {
+    fn new(root: &'a dyn Cat) -> Segments<'a> {
+        Segments {
+            root,
+            cursor: Cursor::new(false),
+        }
+    }
+
+    // Returns the next segment without advancing past it.
+    pub(crate) fn peek(&mut self) -> Option<&str> {
+        self.cursor.peek(self.root)
+    }
+
+    // Advances past the segment returned by `peek`.
+    pub(crate) fn advance(&mut self) {
+        self.cursor.advance();
+    }
+
+    /// Returns the next segment, or `None` when all the segments
+    /// have been returned.
+    pub fn next_segment(&mut self) -> Option<&str> {
+        self.cursor.next(self.root)
+    }
+}
+
+impl<L: Cat, R: Cat> CatMany<L, R> {
+    /// Returns a lending iterator over the string slices of the
+    /// concatenation.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use sconcat::CAT;
+    ///
+    /// let cat = CAT + "Hello" + ',' + String::from(" world");
+    /// let mut segments = cat.segments();
+    /// let mut lens = Vec::new();
+    /// while let Some(segment) = segments.next_segment() {
+    ///     lens.push(segment.len());
+    /// }
+    /// assert_eq!(lens, [5, 1, 6]);
+    /// ```
+    pub fn segments(&self) -> Segments<'_> {
+        Segments::new(self)
+    }
+
+    /// Passes the string slices of the concatenation to `f` in
+    /// order.
+    ///
+    /// Unlike [`segments`](#method.segments), this recurses into the
+    /// pieces instead of keeping a path to the current piece, so it
+    /// neither allocates for deep concatenations nor copies segments
+    /// into a buffer, and pieces that are formatted on the fly are
+    /// formatted only once.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use sconcat::CAT;
+    ///
+    /// let cat = CAT + "Hello" + ',' + String::from(" world");
+    /// let mut lens = Vec::new();
+    /// cat.visit_segments(|segment| lens.push(segment.len()));
+    /// assert_eq!(lens, [5, 1, 6]);
+    /// ```
+    pub fn visit_segments<F: FnMut(&str)>(&self, mut f: F) {
+        Cat::for_each_segment(self, &mut f);
+    }
+}
+
+impl<T: Cat> CatOne<T> {
+    /// Returns a lending iterator over the string slices of the
+    /// concatenation.
+    pub fn segments(&self) -> Segments<'_> {
+        Segments::new(&self.inner)
+    }
+
+    /// Passes the string slices of the concatenation to `f` in
+    /// order.
+    pub fn visit_segments<F: FnMut(&str)>(&self, mut f: F) {
+        self.inner.for_each_segment(&mut f);
+    }
+}
+
+impl CatStart {
+    /// Returns a lending iterator over the string slices of the empty
+    /// concatenation, which yields nothing.
+    pub fn segments(&self) -> Segments<'static> {
+        Segments::new(&None::<char>)
+    }
+
+    /// Does nothing, as the empty concatenation has no string
+    /// slices.
+    pub fn visit_segments<F: FnMut(&str)>(&self, _f: F) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use CAT;
+    use Dbg;
+    use super::Segments;
+
+    fn collect(mut segments: Segments) -> Vec<String> {
+        let mut v = Vec::new();
+        while let Some(segment) = segments.next_segment() {
+            v.push(String::from(segment));
+        }
+        v
+    }
+
+    #[test]
+    fn in_order() {
+        let inner = CAT + 'b' + "c";
+        let cat = CAT + "a" + inner + Dbg("d") + String::from("") + 'é';
+        let all = collect(cat.segments());
+        assert_eq!(all[..3], ["a", "b", "c"]);
+        assert_eq!(all[all.len() - 2..], ["", "é"]);
+        assert_eq!(all.concat(), cat.to_string());
+        assert_eq!(collect((CAT + "x").segments()), ["x"]);
+        assert!(collect(CAT.segments()).is_empty());
+    }
+
+    #[test]
+    fn long_list() {
+        let items: Vec<String> = (0..40_000).map(|i| i.to_string()).collect();
+        let cat = CAT + "[" + items.clone() + (CAT + 'x' + Dbg(1)) + "]";
+        let all = collect(cat.segments());
+        assert_eq!(all.len(), 40_004);
+        let mut s = String::new();
+        cat.append_to(&mut s);
+        assert_eq!(all.concat(), s);
+        let nested = vec![items.clone(), Vec::new(), items];
+        let cat = CAT + nested + "!";
+        assert_eq!(collect(cat.segments()).len(), 80_001);
+    }
+
+    #[test]
+    fn visited() {
+        let cat = CAT + "a" + (CAT + 'b' + "c") + Dbg("d");
+        let mut all = Vec::new();
+        cat.visit_segments(|segment| all.push(String::from(segment)));
+        assert_eq!(all.concat(), cat.to_string());
+        assert_eq!(all, collect(cat.segments()));
+        let mut count = 0;
+        (CAT + 'x').visit_segments(|_| count += 1);
+        CAT.visit_segments(|_| count += 1);
+        assert_eq!(count, 1);
+    }
+}
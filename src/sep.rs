@@ -0,0 +1,143 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use cat::{Cat, CatMany, CatOne, CatStart};
+use std::fmt::{self, Debug, Display};
+use std::ops::{Add, AddAssign};
+
+/// A concatenation that inserts a separator between its pieces.
+///
+/// This is created by the [`sep`](struct.CatStart.html#method.sep)
+/// method of [`CAT`](constant.CAT.html). Every piece added after the
+/// first one is preceded by the separator, which is stored as a
+/// piece of its own, so the size hint of the concatenation includes
+/// the separators and it is still materialized with one allocation.
+#[derive(Clone, Copy)]
+pub struct Sep<'a, C> {
+    sep: &'a str,
+    cat: C,
+}
+
+impl CatStart {
+    /// Starts a concatenation that inserts `sep` between its pieces.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use sconcat::CAT;
+    ///
+    /// let cat = CAT.sep(", ") + "a" + String::from("b") + 'c';
+    /// assert_eq!(String::from(cat), "a, b, c");
+    ///
+    /// let fields = CAT.sep(":") + "x" + "y";
+    /// let cat = CAT + '[' + fields.into_cat() + ']';
+    /// assert_eq!(String::from(cat), "[x:y]");
+    /// ```
+    pub fn sep(self, sep: &str) -> Sep<'_, CatStart> {
+        Sep { sep, cat: self }
+    }
+}
+
+impl<'a, C> Sep<'a, C> {
+    /// Returns the separator.
+    pub fn separator(&self) -> &'a str {
+        self.sep
+    }
+
+    /// Returns the concatenation with the separators inserted as
+    /// pieces.
+    pub fn into_cat(self) -> C {
+        self.cat
+    }
+}
+
+impl<'a, T: Cat> Add<T> for Sep<'a, CatStart> {
+    type Output = Sep<'a, CatOne<T>>;
+    fn add(self, rhs: T) -> Sep<'a, CatOne<T>> {
+        Sep {
+            sep: self.sep,
+            cat: CatOne { inner: rhs },
+        }
+    }
+}
+
+impl<'a, L: Cat, R: Cat> Add<R> for Sep<'a, CatOne<L>> {
+    type Output = Sep<'a, CatMany<CatMany<L, &'a str>, R>>;
+    fn add(self, rhs: R) -> Sep<'a, CatMany<CatMany<L, &'a str>, R>> {
+        Sep {
+            sep: self.sep,
+            cat: self.cat + self.sep + rhs,
+        }
+    }
+}
+
+impl<'a, L: Cat, R: Cat, RR: Cat> Add<RR> for Sep<'a, CatMany<L, R>> {
+    type Output = Sep<'a, CatMany<CatMany<CatMany<L, R>, &'a str>, RR>>;
+    fn add(
+        self,
+        rhs: RR,
+    ) -> Sep<'a, CatMany<CatMany<CatMany<L, R>, &'a str>, RR>> {
+        Sep {
+            sep: self.sep,
+            cat: self.cat + self.sep + rhs,
+        }
+    }
+}
+
+impl<'a, C> AddAssign<Sep<'a, C>> for String
+where
+    String: AddAssign<C>,
+{
+    fn add_assign(&mut self, rhs: Sep<'a, C>) {
+        *self += rhs.cat;
+    }
+}
+
+impl<'a, C> From<Sep<'a, C>> for String
+where
+    String: From<C>,
+{
+    fn from(src: Sep<'a, C>) -> String {
+        String::from(src.cat)
+    }
+}
+
+impl<'a, C: Debug> Debug for Sep<'a, C> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        Debug::fmt(&self.cat, f)
+    }
+}
+
+impl<'a, C: Display> Display for Sep<'a, C> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        Display::fmt(&self.cat, f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use CAT;
+
+    #[test]
+    fn separated() {
+        let empty = CAT.sep(", ");
+        assert_eq!(String::from(empty), "");
+        let one = empty + "a";
+        assert_eq!(one.to_string(), "a");
+        let many = one + 'b' + String::from("c");
+        assert_eq!(many.separator(), ", ");
+        let debug = "\"a\" + \", \" + 'b' + \", \" + \"c\"";
+        assert_eq!(format!("{:?}", many), debug);
+        let s = String::from(many);
+        assert_eq!(s, "a, b, c");
+        assert_eq!(s.len(), s.capacity());
+        let mut s = String::from("x=");
+        s += CAT.sep("") + "1" + "2";
+        assert_eq!(s, "x=12");
+    }
+}
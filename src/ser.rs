@@ -0,0 +1,75 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Integration with the serde crate. Concatenations are serialized
+// as strings through `collect_str`, so serializers that can stream
+// a string do not need the concatenation to be materialized.
+
+use cat::{Cat, CatMany, CatOne, CatStart};
+use serde::{Serialize, Serializer};
+use std::fmt::{self, Display, Formatter};
+
+// Displays the segments of any concatenation, even if its pieces
+// do not implement `Display` themselves.
+struct Segments<'a, C: 'a>(&'a C);
+
+impl<'a, C: Cat> Display for Segments<'a, C> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        self.0.write_to(f)
+    }
+}
+
+impl<L: Cat, R: Cat> Serialize for CatMany<L, R> {
+    fn serialize<S: Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(&Segments(self))
+    }
+}
+
+impl<T: Cat> Serialize for CatOne<T> {
+    fn serialize<S: Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(&Segments(&self.inner))
+    }
+}
+
+impl Serialize for CatStart {
+    fn serialize<S: Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str("")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use CAT;
+    use Dbg;
+    use serde_json;
+
+    #[test]
+    fn json() {
+        let cat = CAT + "say \"" + 'é' + Dbg(Some(1)) + String::from("\"");
+        let json = serde_json::to_string(&cat).unwrap();
+        assert_eq!(json, r#""say \"éSome(1)\"""#);
+        assert_eq!(serde_json::to_string(&(CAT + "x")).unwrap(), r#""x""#);
+        assert_eq!(serde_json::to_string(&CAT).unwrap(), r#""""#);
+    }
+
+    #[test]
+    fn field() {
+        let record = vec![("msg", CAT + "user " + "42" + " logged in")];
+        let json = serde_json::to_string(&record).unwrap();
+        assert_eq!(json, r#"[["msg","user 42 logged in"]]"#);
+    }
+}
@@ -0,0 +1,218 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use cat::{Cat, CatMany, CatOne, CatStart};
+use std::convert::Infallible;
+use std::fmt;
+
+/// A sink that a concatenation can be written to.
+///
+/// Unlike `fmt::Write`, the sink is told the size hint of the
+/// concatenation before anything is written, so that it can reserve
+/// space, and it has its own error type. This is implemented for
+/// `String` and `Vec<u8>`, and [`FmtSink`][FmtSink] adapts any
+/// `fmt::Write`.
+///
+/// # Examples
+///
+/// ```rust
+/// use sconcat::{CAT, Write};
+///
+/// struct Lines(Vec<String>);
+///
+/// impl Write for Lines {
+///     type Error = ();
+///     fn reserve(&mut self, additional: usize) {
+///         self.0.push(String::with_capacity(additional));
+///     }
+///     fn write_str(&mut self, s: &str) -> Result<(), ()> {
+///         self.0.last_mut().ok_or(())?.push_str(s);
+///         Ok(())
+///     }
+/// }
+///
+/// let mut lines = Lines(Vec::new());
+/// (CAT + "one").write_to_sink(&mut lines).unwrap();
+/// (CAT + "two" + '!').write_to_sink(&mut lines).unwrap();
+/// assert_eq!(lines.0, ["one", "two!"]);
+/// ```
+///
+/// [FmtSink]: struct.FmtSink.html
+pub trait Write {
+    /// The error returned when writing fails.
+    type Error;
+
+    /// Writes a string to the sink.
+    fn write_str(&mut self, s: &str) -> Result<(), Self::Error>;
+
+    /// Tells the sink that about `additional` more bytes will be
+    /// written.
+    ///
+    /// The default implementation does nothing.
+    fn reserve(&mut self, additional: usize) {
+        let _ = additional;
+    }
+}
+
+impl Write for String {
+    type Error = Infallible;
+    fn write_str(&mut self, s: &str) -> Result<(), Infallible> {
+        self.push_str(s);
+        Ok(())
+    }
+    fn reserve(&mut self, additional: usize) {
+        String::reserve(self, additional);
+    }
+}
+
+impl Write for Vec<u8> {
+    type Error = Infallible;
+    fn write_str(&mut self, s: &str) -> Result<(), Infallible> {
+        self.extend_from_slice(s.as_bytes());
+        Ok(())
+    }
+    fn reserve(&mut self, additional: usize) {
+        Vec::reserve(self, additional);
+    }
+}
+
+/// Adapts a `fmt::Write` to a [`Write`][Write] sink.
+///
+/// The size hint is ignored.
+///
+/// [Write]: trait.Write.html
+#[derive(Clone, Debug, Default)]
+pub struct FmtSink<W>(pub W);
+
+impl<W: fmt::Write> Write for FmtSink<W> {
+    type Error = fmt::Error;
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.0.write_str(s)
+    }
+}
+
+// Reserves the size hint, then writes the segments, stopping at the
+// first error.
+pub(crate) fn write_cat<C: Cat, W: Write + ?Sized>(
+    cat: &C,
+    w: &mut W,
+) -> Result<(), W::Error> {
+    w.reserve(cat.size_hint());
+    let mut result = Ok(());
+    cat.for_each_segment(&mut |segment| {
+        if result.is_ok() {
+            result = w.write_str(segment);
+        }
+    });
+    result
+}
+
+impl<L: Cat, R: Cat> CatMany<L, R> {
+    /// Writes the concatenation to a [`Write`][Write] sink, passing
+    /// it the size hint first.
+    ///
+    /// Writing stops at the first error.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use sconcat::CAT;
+    ///
+    /// let mut buf = b"> ".to_vec();
+    /// let cat = CAT + "key: " + 'k';
+    /// cat.write_to_sink(&mut buf).unwrap();
+    /// assert_eq!(buf, b"> key: k");
+    /// ```
+    ///
+    /// [Write]: trait.Write.html
+    pub fn write_to_sink<W: Write + ?Sized>(
+        &self,
+        w: &mut W,
+    ) -> Result<(), W::Error> {
+        write_cat(self, w)
+    }
+}
+
+impl<T: Cat> CatOne<T> {
+    /// Writes the concatenation to a [`Write`][Write] sink, passing
+    /// it the size hint first.
+    ///
+    /// [Write]: trait.Write.html
+    pub fn write_to_sink<W: Write + ?Sized>(
+        &self,
+        w: &mut W,
+    ) -> Result<(), W::Error> {
+        write_cat(&self.inner, w)
+    }
+}
+
+impl CatStart {
+    /// Writes nothing to a [`Write`][Write] sink.
+    ///
+    /// [Write]: trait.Write.html
+    pub fn write_to_sink<W: Write + ?Sized>(
+        &self,
+        _w: &mut W,
+    ) -> Result<(), W::Error> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use CAT;
+    use super::{FmtSink, Write};
+
+    struct Failing {
+        written: String,
+        reserved: usize,
+        room: usize,
+    }
+
+    impl Write for Failing {
+        type Error = usize;
+        fn write_str(&mut self, s: &str) -> Result<(), usize> {
+            if self.written.len() + s.len() > self.room {
+                return Err(self.written.len());
+            }
+            self.written.push_str(s);
+            Ok(())
+        }
+        fn reserve(&mut self, additional: usize) {
+            self.reserved += additional;
+        }
+    }
+
+    #[test]
+    fn sinks() {
+        let cat = CAT + "abc" + 'd' + String::from("ef");
+        let mut s = String::from(">");
+        cat.write_to_sink(&mut s).unwrap();
+        assert_eq!(s, ">abcdef");
+        let mut v = Vec::new();
+        (CAT + "x").write_to_sink(&mut v).unwrap();
+        CAT.write_to_sink(&mut v).unwrap();
+        assert_eq!(v, b"x");
+        let mut f = FmtSink(String::new());
+        cat.write_to_sink(&mut f).unwrap();
+        assert_eq!(f.0, "abcdef");
+    }
+
+    #[test]
+    fn errors() {
+        let cat = CAT + "abc" + 'd' + String::from("ef");
+        let mut sink = Failing {
+            written: String::new(),
+            reserved: 0,
+            room: 4,
+        };
+        assert_eq!(cat.write_to_sink(&mut sink), Err(4));
+        assert_eq!(sink.written, "abcd");
+        assert_eq!(sink.reserved, 6);
+    }
+}
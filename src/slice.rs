@@ -0,0 +1,217 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use cat::{Cat, CatMany, CatOne, CatStart};
+use std::fmt::{self, Display};
+use std::ops::{Bound, RangeBounds};
+
+/// A piece that is a byte range of a concatenation.
+///
+/// This is created by the `slice` methods of the concatenation
+/// types.
+#[derive(Clone, Copy)]
+pub struct Slice<'a> {
+    cat: &'a dyn Cat,
+    start: usize,
+    end: usize,
+}
+
+impl<'a> Slice<'a> {
+    fn new<R: RangeBounds<usize>>(cat: &'a dyn Cat, range: R) -> Slice<'a> {
+        let mut len = 0usize;
+        let mut boundaries = [false, false];
+        let start = match range.start_bound() {
+            Bound::Included(&start) => start,
+            Bound::Excluded(&start) => start.checked_add(1).expect("overflow"),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&end) => end.checked_add(1).expect("overflow"),
+            Bound::Excluded(&end) => end,
+            Bound::Unbounded => {
+                cat.for_each_segment(&mut |segment| len += segment.len());
+                len
+            }
+        };
+        assert!(
+            start <= end,
+            "slice index starts at {} but ends at {}",
+            start,
+            end
+        );
+        len = 0;
+        cat.for_each_segment(&mut |segment| {
+            for (i, &index) in [start, end].iter().enumerate() {
+                if index >= len && index < len + segment.len() {
+                    boundaries[i] = segment.is_char_boundary(index - len);
+                }
+            }
+            len += segment.len();
+        });
+        for (i, &index) in [start, end].iter().enumerate() {
+            assert!(index <= len, "byte index {} is out of bounds", index);
+            assert!(
+                index == len || boundaries[i],
+                "byte index {} is not a char boundary",
+                index
+            );
+        }
+        Slice { cat, start, end }
+    }
+
+    // Passes the parts of the segments of `piece` that are in range
+    // to `f`, skipping pieces after the end.
+    fn visit(
+        &self,
+        piece: &dyn Cat,
+        pos: &mut usize,
+        f: &mut dyn FnMut(&str),
+    ) {
+        if *pos >= self.end {
+            return;
+        }
+        if let Some((lhs, rhs)) = piece.split() {
+            self.visit(lhs, pos, f);
+            self.visit(rhs, pos, f);
+            return;
+        }
+        piece.for_each_segment(&mut |segment| {
+            let segment_start = *pos;
+            *pos += segment.len();
+            let lo = self.start.max(segment_start);
+            let hi = self.end.min(*pos);
+            if lo < hi {
+                f(&segment[lo - segment_start..hi - segment_start]);
+            }
+        });
+    }
+}
+
+impl<'a> Cat for Slice<'a> {
+    fn size_hint(&self) -> usize {
+        self.end - self.start
+    }
+
+    fn append_to(&self, s: &mut String) {
+        self.for_each_segment(&mut |segment| s.push_str(segment));
+    }
+
+    fn for_each_segment(&self, f: &mut dyn FnMut(&str)) {
+        self.visit(self.cat, &mut 0, f);
+    }
+}
+
+impl<'a> Display for Slice<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut result = Ok(());
+        self.for_each_segment(&mut |segment| {
+            if result.is_ok() {
+                result = f.write_str(segment);
+            }
+        });
+        result
+    }
+}
+
+impl<'a> fmt::Debug for Slice<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Slice")
+            .field("start", &self.start)
+            .field("end", &self.end)
+            .finish()
+    }
+}
+
+impl<L: Cat, R: Cat> CatMany<L, R> {
+    /// Returns a piece that is the byte range `range` of the
+    /// concatenation, without materializing the concatenation.
+    ///
+    /// The range is validated when the slice is created, which walks
+    /// the segments once. When the slice is appended, pieces that
+    /// follow the range are skipped entirely.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start or end of the range is out of bounds or
+    /// not on a char boundary, or if the start is greater than the
+    /// end.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use sconcat::CAT;
+    ///
+    /// let doc = CAT + "# Title\n" + String::from("Body ☺ text\n") + "…";
+    /// let preview = String::from(CAT + doc.slice(2..16) + "...");
+    /// assert_eq!(preview, "Title\nBody ☺...");
+    /// ```
+    pub fn slice<B: RangeBounds<usize>>(&self, range: B) -> Slice<'_> {
+        Slice::new(self, range)
+    }
+}
+
+impl<T: Cat> CatOne<T> {
+    /// Returns a piece that is the byte range `range` of the
+    /// concatenation.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start or end of the range is out of bounds or
+    /// not on a char boundary, or if the start is greater than the
+    /// end.
+    pub fn slice<B: RangeBounds<usize>>(&self, range: B) -> Slice<'_> {
+        Slice::new(&self.inner, range)
+    }
+}
+
+impl CatStart {
+    /// Returns an empty piece for the empty range of the empty
+    /// concatenation.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is not empty.
+    pub fn slice<B: RangeBounds<usize>>(&self, range: B) -> Slice<'static> {
+        Slice::new(&"", range)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use CAT;
+
+    #[test]
+    fn ranges() {
+        let inner = CAT + "cd" + 'é';
+        let cat = CAT + "ab" + inner + String::from("fgh") + "";
+        let s = cat.to_string();
+        let ranges = [(0, 0), (0, 9), (1, 3), (2, 6), (4, 4), (7, 9)];
+        for &(start, end) in &ranges {
+            let slice = cat.slice(start..end);
+            let sliced = String::from(CAT + slice);
+            assert_eq!(sliced, &s[start..end]);
+            assert_eq!(sliced.len(), sliced.capacity());
+        }
+        assert_eq!(cat.slice(..).to_string(), s);
+        assert_eq!(cat.slice(6..).to_string(), "fgh");
+        assert_eq!((CAT + "xyz").slice(..=1).to_string(), "xy");
+        assert_eq!(CAT.slice(..).to_string(), "");
+    }
+
+    #[test]
+    #[should_panic(expected = "not a char boundary")]
+    fn split_char() {
+        (CAT + "a" + 'é').slice(2..);
+    }
+
+    #[test]
+    #[should_panic(expected = "out of bounds")]
+    fn past_end() {
+        (CAT + "a" + 'b').slice(1..3);
+    }
+}
@@ -0,0 +1,263 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use cat::{Cat, CatMany, CatOne, CatStart};
+use fixed::{self, CapacityError};
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::fmt::{self, Debug, Display};
+use std::hash::{Hash, Hasher};
+use std::ops::Deref;
+use std::str;
+
+/// A string stored inline in a buffer of `N` bytes.
+///
+/// A concatenation can be converted into a `StackString` with its
+/// `try_into_stack_string` method without any allocations and
+/// without any optional dependencies, which suits short strings
+/// composed in hot loops. A `StackString` dereferences to `str`.
+///
+/// # Examples
+///
+/// ```rust
+/// use sconcat::{CAT, StackString};
+///
+/// let id = 42;
+/// let key: StackString<16> =
+///     (CAT + "user:" + &*id.to_string()).try_into_stack_string().unwrap();
+/// assert_eq!(&*key, "user:42");
+/// assert_eq!(key.len(), 7);
+/// assert!(key.starts_with("user:"));
+/// let err = (CAT + "a very long key " + "that does not fit")
+///     .try_into_stack_string::<16>()
+///     .unwrap_err();
+/// assert_eq!((err.size(), err.available()), (33, 16));
+/// ```
+#[derive(Clone, Copy)]
+pub struct StackString<const N: usize> {
+    buf: [u8; N],
+    len: usize,
+}
+
+impl<const N: usize> StackString<N> {
+    /// Creates an empty string.
+    pub fn new() -> StackString<N> {
+        StackString {
+            buf: [0; N],
+            len: 0,
+        }
+    }
+
+    /// Returns the capacity of the string in bytes, which is `N`.
+    pub fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Returns the length of the string in bytes.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the string has a length of zero bytes.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the string as a string slice.
+    pub fn as_str(&self) -> &str {
+        // Only whole segments are copied into the buffer, so this
+        // cannot fail.
+        str::from_utf8(&self.buf[..self.len]).unwrap()
+    }
+
+    /// Truncates the string to a length of zero bytes.
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+}
+
+impl<const N: usize> Default for StackString<N> {
+    fn default() -> StackString<N> {
+        StackString::new()
+    }
+}
+
+impl<const N: usize> Deref for StackString<N> {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<const N: usize> AsRef<str> for StackString<N> {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<const N: usize> Borrow<str> for StackString<N> {
+    fn borrow(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<const N: usize> Debug for StackString<N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl<const N: usize> Display for StackString<N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        Display::fmt(self.as_str(), f)
+    }
+}
+
+impl<const N: usize> PartialEq for StackString<N> {
+    fn eq(&self, other: &StackString<N>) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl<const N: usize> Eq for StackString<N> {}
+
+impl<const N: usize> PartialOrd for StackString<N> {
+    fn partial_cmp(&self, other: &StackString<N>) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<const N: usize> Ord for StackString<N> {
+    fn cmp(&self, other: &StackString<N>) -> Ordering {
+        self.as_str().cmp(other.as_str())
+    }
+}
+
+impl<const N: usize> Hash for StackString<N> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state);
+    }
+}
+
+impl<const N: usize> PartialEq<str> for StackString<N> {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl<'a, const N: usize> PartialEq<&'a str> for StackString<N> {
+    fn eq(&self, other: &&'a str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+fn stack_string<C: Cat, const N: usize>(
+    cat: &C,
+) -> Result<StackString<N>, CapacityError> {
+    let mut s = StackString::new();
+    let size = fixed::push_segments(cat, N, |segment| {
+        let end = s.len + segment.len();
+        s.buf[s.len..end].copy_from_slice(segment.as_bytes());
+        s.len = end;
+    });
+    if size > N {
+        return Err(CapacityError { size, available: N });
+    }
+    Ok(s)
+}
+
+impl<L: Cat, R: Cat> CatMany<L, R> {
+    /// Converts the concatenation into a `StackString` with a
+    /// capacity of `N` bytes.
+    ///
+    /// No allocations take place. If the concatenation does not fit,
+    /// an error is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use sconcat::CAT;
+    ///
+    /// let s = (CAT + "x=" + '1').try_into_stack_string::<8>().unwrap();
+    /// assert_eq!(s, "x=1");
+    /// ```
+    pub fn try_into_stack_string<const N: usize>(
+        self,
+    ) -> Result<StackString<N>, CapacityError> {
+        stack_string(&self)
+    }
+}
+
+impl<T: Cat> CatOne<T> {
+    /// Converts the concatenation into a `StackString` with a
+    /// capacity of `N` bytes.
+    ///
+    /// No allocations take place. If the concatenation does not fit,
+    /// an error is returned.
+    pub fn try_into_stack_string<const N: usize>(
+        self,
+    ) -> Result<StackString<N>, CapacityError> {
+        stack_string(&self.inner)
+    }
+}
+
+impl CatStart {
+    /// Returns an empty `StackString`.
+    ///
+    /// This never fails.
+    pub fn try_into_stack_string<const N: usize>(
+        self,
+    ) -> Result<StackString<N>, CapacityError> {
+        Ok(StackString::new())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use CAT;
+    use Dbg;
+    use stack::StackString;
+    use std::collections::HashSet;
+
+    #[test]
+    fn fits() {
+        let s = (CAT + "ab" + 'é' + Dbg(7)).try_into_stack_string::<5>();
+        let s = s.unwrap();
+        assert_eq!(s, "abé7");
+        assert_eq!((s.len(), s.capacity()), (5, 5));
+        assert_eq!(format!("{} {:?}", s, s), "abé7 \"abé7\"");
+        let s = (CAT + "x").try_into_stack_string::<1>().unwrap();
+        assert_eq!(s, "x");
+        let s = CAT.try_into_stack_string::<0>().unwrap();
+        assert!(s.is_empty());
+    }
+
+    #[test]
+    fn too_long() {
+        let cat = CAT + "ab" + 'é' + Dbg(7);
+        let err = cat.try_into_stack_string::<4>().unwrap_err();
+        assert_eq!((err.size(), err.available()), (5, 4));
+        let err = (CAT + "abc").try_into_stack_string::<2>().unwrap_err();
+        assert_eq!((err.size(), err.available()), (3, 2));
+    }
+
+    #[test]
+    fn compare() {
+        let mut a = (CAT + "k" + '1').try_into_stack_string::<8>().unwrap();
+        let b = (CAT + "k1").try_into_stack_string::<8>().unwrap();
+        assert_eq!(a, b);
+        assert!(a < (CAT + "k2").try_into_stack_string().unwrap());
+        let set = [a, b].iter().cloned().collect::<HashSet<_>>();
+        assert_eq!(set.len(), 1);
+        assert!(set.contains("k1"));
+        a.clear();
+        assert_eq!(a, StackString::<8>::default());
+        assert_eq!(a, "");
+    }
+}
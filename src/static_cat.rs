@@ -0,0 +1,93 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/// Concatenates constant string pieces at compile time.
+///
+/// Every piece must be a constant expression of type `&'static str`,
+/// such as a string literal or a `const` item. Unlike `concat!`,
+/// pieces need not be literals. The result is a `&'static str` that
+/// is computed during compilation, so it costs nothing at run time
+/// and can itself be used to initialize a `const`.
+///
+/// # Examples
+///
+/// ```rust
+/// #[macro_use]
+/// extern crate sconcat;
+///
+/// const NAME: &str = "sconcat";
+/// const VERSION: &str = "0.1";
+/// const USER_AGENT: &str = static_cat!(NAME, "/", VERSION, " (rust)");
+///
+/// # fn main() {
+/// assert_eq!(USER_AGENT, "sconcat/0.1 (rust)");
+/// assert_eq!(static_cat!(), "");
+/// # }
+/// ```
+#[macro_export]
+macro_rules! static_cat {
+    ($($piece:expr),* $(,)*) => {{
+        const PIECES: &[&str] = &[$($piece),*];
+        const LEN: usize = $crate::__static_cat_len(PIECES);
+        const BYTES: [u8; LEN] = $crate::__static_cat_bytes(PIECES);
+        const STR: &str = $crate::__static_cat_str(&BYTES);
+        STR
+    }};
+}
+
+#[doc(hidden)]
+pub const fn __static_cat_len(pieces: &[&str]) -> usize {
+    let mut len = 0;
+    let mut i = 0;
+    while i < pieces.len() {
+        len += pieces[i].len();
+        i += 1;
+    }
+    len
+}
+
+#[doc(hidden)]
+pub const fn __static_cat_bytes<const N: usize>(pieces: &[&str]) -> [u8; N] {
+    let mut bytes = [0u8; N];
+    let mut len = 0;
+    let mut i = 0;
+    while i < pieces.len() {
+        let piece = pieces[i].as_bytes();
+        let mut j = 0;
+        while j < piece.len() {
+            bytes[len] = piece[j];
+            len += 1;
+            j += 1;
+        }
+        i += 1;
+    }
+    bytes
+}
+
+#[doc(hidden)]
+pub const fn __static_cat_str(bytes: &[u8]) -> &str {
+    // The bytes are a concatenation of whole strings.
+    match ::std::str::from_utf8(bytes) {
+        Ok(s) => s,
+        Err(_) => panic!("invalid UTF-8"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    const GREETING: &str = static_cat!("Hello", ", ", WORLD, "!");
+    const WORLD: &str = "wörld";
+
+    #[test]
+    fn concatenates_constants() {
+        assert_eq!(GREETING, "Hello, wörld!");
+        let nested: &'static str = static_cat!(GREETING, " ", GREETING,);
+        assert_eq!(nested, "Hello, wörld! Hello, wörld!");
+        assert_eq!(static_cat!(""), "");
+    }
+}
@@ -0,0 +1,234 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use cat::{Cat, pad};
+use std::fmt::{self, Debug, Display};
+
+/// The alignment of a table column.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Align {
+    /// Cells are padded on the right.
+    Left,
+    /// Cells are padded on the left.
+    Right,
+}
+
+/// A table of pieces with aligned columns.
+///
+/// Column widths are measured in characters from the cells
+/// themselves, without materializing them. The table is a piece, so
+/// it can be concatenated, and the whole table is rendered with at
+/// most one allocation.
+///
+/// Left-aligned cells in the last column are not padded, so that
+/// lines have no trailing spaces.
+///
+/// # Examples
+///
+/// ```rust
+/// use sconcat::{Align, CAT, Table};
+///
+/// let mut table = Table::new();
+/// table.push_row(vec!["name", "size"]);
+/// table.start_row().push_cell("Cargo.toml").push_cell(CAT + "10" + "24");
+/// table.push_row(vec!["src", "0"]);
+/// table.align(1, Align::Right);
+/// let cat = CAT + "Files:\n" + table;
+/// let expected = "Files:\n\
+///                 name        size\n\
+///                 Cargo.toml  1024\n\
+///                 src            0\n";
+/// assert_eq!(String::from(cat), expected);
+/// ```
+pub struct Table<'a> {
+    rows: Vec<Vec<Cell<'a>>>,
+    widths: Vec<usize>,
+    aligns: Vec<Align>,
+    separator: &'a str,
+}
+
+struct Cell<'a> {
+    piece: Box<dyn Cat + 'a>,
+    width: usize,
+}
+
+impl<'a> Default for Table<'a> {
+    fn default() -> Table<'a> {
+        Table::new()
+    }
+}
+
+impl<'a> Cell<'a> {
+    fn new<C: Cat + 'a>(piece: C) -> Cell<'a> {
+        let mut width = 0;
+        piece.for_each_segment(&mut |segment| width += segment.chars().count());
+        Cell {
+            piece: Box::new(piece),
+            width,
+        }
+    }
+}
+
+impl<'a> Table<'a> {
+    /// Creates an empty table with columns separated by two spaces.
+    pub fn new() -> Table<'a> {
+        Table {
+            rows: Vec::new(),
+            widths: Vec::new(),
+            aligns: Vec::new(),
+            separator: "  ",
+        }
+    }
+
+    /// Appends a row with the given cells.
+    pub fn push_row<I>(&mut self, cells: I) -> &mut Table<'a>
+    where
+        I: IntoIterator,
+        I::Item: Cat + 'a,
+    {
+        self.start_row();
+        for cell in cells {
+            self.push_cell(cell);
+        }
+        self
+    }
+
+    /// Appends an empty row.
+    ///
+    /// Cells can then be added with
+    /// [`push_cell`](#method.push_cell), which can be used to build
+    /// rows with cells of different types.
+    pub fn start_row(&mut self) -> &mut Table<'a> {
+        self.rows.push(Vec::new());
+        self
+    }
+
+    /// Appends a cell to the last row, or to a new row if the table
+    /// is empty.
+    pub fn push_cell<C: Cat + 'a>(&mut self, cell: C) -> &mut Table<'a> {
+        if self.rows.is_empty() {
+            self.rows.push(Vec::new());
+        }
+        let cell = Cell::new(cell);
+        let row = self.rows.last_mut().unwrap();
+        let column = row.len();
+        if self.widths.len() <= column {
+            self.widths.push(cell.width);
+        } else if self.widths[column] < cell.width {
+            self.widths[column] = cell.width;
+        }
+        row.push(cell);
+        self
+    }
+
+    /// Sets the alignment of a column, which is left by default.
+    pub fn align(&mut self, column: usize, align: Align) -> &mut Table<'a> {
+        if self.aligns.len() <= column {
+            self.aligns.resize(column + 1, Align::Left);
+        }
+        self.aligns[column] = align;
+        self
+    }
+
+    /// Sets the separator between columns.
+    pub fn separator(&mut self, separator: &'a str) -> &mut Table<'a> {
+        self.separator = separator;
+        self
+    }
+
+    fn column_align(&self, column: usize) -> Align {
+        self.aligns.get(column).cloned().unwrap_or(Align::Left)
+    }
+}
+
+impl<'a> Cat for Table<'a> {
+    fn size_hint(&self) -> usize {
+        let mut len = 0usize;
+        self.for_each_segment(&mut |segment| len += segment.len());
+        len
+    }
+
+    fn append_to(&self, s: &mut String) {
+        self.for_each_segment(&mut |segment| s.push_str(segment));
+    }
+
+    fn for_each_segment(&self, f: &mut dyn FnMut(&str)) {
+        for row in &self.rows {
+            for (column, cell) in row.iter().enumerate() {
+                if column > 0 {
+                    f(self.separator);
+                }
+                let padding = self.widths[column] - cell.width;
+                match self.column_align(column) {
+                    Align::Left => {
+                        cell.piece.for_each_segment(f);
+                        if column + 1 < row.len() {
+                            pad(f, padding);
+                        }
+                    }
+                    Align::Right => {
+                        pad(f, padding);
+                        cell.piece.for_each_segment(f);
+                    }
+                }
+            }
+            f("\n");
+        }
+    }
+
+    fn pieces(&self) -> usize {
+        self.rows.iter().map(Vec::len).sum()
+    }
+}
+
+impl<'a> Display for Table<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut result = Ok(());
+        self.for_each_segment(&mut |segment| {
+            if result.is_ok() {
+                result = f.write_str(segment);
+            }
+        });
+        result
+    }
+}
+
+impl<'a> Debug for Table<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Table")
+            .field("rows", &self.rows.len())
+            .field("widths", &self.widths)
+            .field("aligns", &self.aligns)
+            .field("separator", &self.separator)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use CAT;
+    use super::{Align, Table};
+
+    #[test]
+    fn alignment() {
+        let mut table = Table::new();
+        table.push_row(vec!["a", "b", "c"]);
+        table.start_row().push_cell('☺').push_cell(CAT + "long" + "er");
+        table.push_row(vec!["xyz"]);
+        table.align(0, Align::Right).separator(" | ");
+        let s = String::from(CAT + &table.to_string()[..] + table);
+        let expected = "  a | b      | c\n  ☺ | longer\nxyz\n";
+        assert_eq!(s, format!("{}{}", expected, expected));
+        assert_eq!(s.len(), s.capacity());
+    }
+
+    #[test]
+    fn empty() {
+        assert_eq!(String::from(CAT + Table::new()), "");
+    }
+}
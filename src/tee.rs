@@ -0,0 +1,206 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use cat::{Cat, CatMany, CatOne, CatStart};
+use std::fmt::{self, Debug};
+use std::hash::Hasher;
+use std::io::{self, Write};
+
+enum Sink<'a> {
+    String(&'a mut String),
+    Writer(&'a mut dyn Write),
+    Hasher(&'a mut dyn Hasher),
+}
+
+/// A set of destinations that a concatenation is written to in a
+/// single pass.
+///
+/// Destinations are added with the builder methods, and the
+/// concatenation is written to all of them by the `tee` methods of
+/// the concatenation types. Every segment is passed to each
+/// destination in the order in which the destinations were added.
+///
+/// # Examples
+///
+/// ```rust
+/// use sconcat::{CAT, Tee};
+/// use std::collections::hash_map::DefaultHasher;
+/// use std::hash::Hasher;
+///
+/// let mut s = String::new();
+/// let mut file = Vec::new();
+/// let mut hasher = DefaultHasher::new();
+/// let cat = CAT + "key = " + String::from("value") + '\n';
+/// cat.tee(
+///     Tee::new()
+///         .string(&mut s)
+///         .writer(&mut file)
+///         .hasher(&mut hasher),
+/// )
+/// .unwrap();
+/// assert_eq!(s, "key = value\n");
+/// assert_eq!(file, s.as_bytes());
+///
+/// let mut expected = DefaultHasher::new();
+/// expected.write(s.as_bytes());
+/// assert_eq!(hasher.finish(), expected.finish());
+/// ```
+#[derive(Default)]
+pub struct Tee<'a> {
+    sinks: Vec<Sink<'a>>,
+}
+
+impl<'a> Tee<'a> {
+    /// Creates a set with no destinations.
+    pub fn new() -> Tee<'a> {
+        Tee { sinks: Vec::new() }
+    }
+
+    /// Adds a `String` that the concatenation is appended to.
+    ///
+    /// The space required is reserved before anything is written.
+    pub fn string(&mut self, s: &'a mut String) -> &mut Tee<'a> {
+        self.sinks.push(Sink::String(s));
+        self
+    }
+
+    /// Adds an `io::Write` that the concatenation is written to.
+    pub fn writer(&mut self, w: &'a mut dyn Write) -> &mut Tee<'a> {
+        self.sinks.push(Sink::Writer(w));
+        self
+    }
+
+    /// Adds a `Hasher` that the bytes of the concatenation are
+    /// written to.
+    pub fn hasher(&mut self, h: &'a mut dyn Hasher) -> &mut Tee<'a> {
+        self.sinks.push(Sink::Hasher(h));
+        self
+    }
+
+    fn write<C: Cat + ?Sized>(&mut self, cat: &C) -> io::Result<()> {
+        let mut result = Ok(());
+        let additional = cat.size_hint();
+        for sink in &mut self.sinks {
+            if let Sink::String(ref mut s) = *sink {
+                s.reserve(additional);
+            }
+        }
+        cat.for_each_segment(&mut |segment| {
+            if result.is_err() {
+                return;
+            }
+            for sink in &mut self.sinks {
+                match *sink {
+                    Sink::String(ref mut s) => s.push_str(segment),
+                    Sink::Writer(ref mut w) => {
+                        result = w.write_all(segment.as_bytes());
+                        if result.is_err() {
+                            return;
+                        }
+                    }
+                    Sink::Hasher(ref mut h) => h.write(segment.as_bytes()),
+                }
+            }
+        });
+        result
+    }
+}
+
+impl<'a> Debug for Tee<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Tee")
+            .field("sinks", &self.sinks.len())
+            .finish()
+    }
+}
+
+impl<L: Cat, R: Cat> CatMany<L, R> {
+    /// Writes the concatenation to all the destinations of `tee` in
+    /// a single traversal of the pieces.
+    ///
+    /// Pieces that are formatted on the fly are formatted only once.
+    /// Writing stops at the first error, in which case the
+    /// destinations may contain partial output.
+    pub fn tee(&self, tee: &mut Tee) -> io::Result<()> {
+        tee.write(self)
+    }
+}
+
+impl<T: Cat> CatOne<T> {
+    /// Writes the concatenation to all the destinations of `tee` in
+    /// a single traversal of the piece.
+    pub fn tee(&self, tee: &mut Tee) -> io::Result<()> {
+        tee.write(&self.inner)
+    }
+}
+
+impl CatStart {
+    /// Writes nothing to the destinations of `tee`.
+    pub fn tee(&self, _tee: &mut Tee) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use CAT;
+    use Dbg;
+    use std::io::{self, Write};
+    use super::Tee;
+
+    // Fails after accepting `limit` bytes.
+    struct Limited {
+        bytes: Vec<u8>,
+        limit: usize,
+    }
+
+    impl Write for Limited {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            if self.bytes.len() + buf.len() > self.limit {
+                return Err(io::Error::other("full"));
+            }
+            self.bytes.extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn writes_all() {
+        let mut a = String::from(">");
+        let mut b = String::new();
+        let mut w = Vec::new();
+        let cat = CAT + "x=" + Dbg([1, 2]) + 'é';
+        cat.tee(Tee::new().string(&mut a).writer(&mut w).string(&mut b))
+            .unwrap();
+        assert_eq!(a, ">x=[1, 2]é");
+        assert_eq!(b, "x=[1, 2]é");
+        assert_eq!(b.len(), b.capacity());
+        assert_eq!(w, b.as_bytes());
+        (CAT + "!").tee(Tee::new().string(&mut b)).unwrap();
+        CAT.tee(Tee::new().string(&mut b)).unwrap();
+        assert_eq!(b, "x=[1, 2]é!");
+    }
+
+    #[test]
+    fn stops_at_error() {
+        let mut s = String::new();
+        let mut w = Limited {
+            bytes: Vec::new(),
+            limit: 3,
+        };
+        let cat = CAT + "ab" + "cd" + "ef";
+        let err = cat.tee(Tee::new().writer(&mut w).string(&mut s));
+        assert_eq!(err.unwrap_err().kind(), io::ErrorKind::Other);
+        assert_eq!(w.bytes, b"ab");
+        assert_eq!(s, "ab");
+    }
+}
@@ -0,0 +1,178 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Helpers to check allocation counts in tests.
+//!
+//! The counts are kept by [`CountingAllocator`], which must be
+//! installed as the global allocator of the test binary. Only the
+//! allocations and reallocations made by the current thread are
+//! counted, so tests running in parallel do not disturb each other.
+//! Without the counting allocator, every count is zero.
+//!
+//! [`CountingAllocator`]: struct.CountingAllocator.html
+//!
+//! # Examples
+//!
+//! ```rust
+//! #[macro_use]
+//! extern crate sconcat;
+//! use sconcat::CAT;
+//! use sconcat::testing::CountingAllocator;
+//!
+//! #[global_allocator]
+//! static ALLOC: CountingAllocator = CountingAllocator;
+//!
+//! # fn main() {
+//! let name = String::from("world");
+//! let s = debug_assert_single_alloc!(CAT + "Hello, " + &name[..] + '!');
+//! assert_eq!(s, "Hello, world!");
+//! # }
+//! ```
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+fn count() {
+    // The count is lost if the thread is being torn down.
+    let _ = ALLOCATIONS.try_with(|a| a.set(a.get().wrapping_add(1)));
+}
+
+/// A global allocator that counts the allocations and reallocations
+/// of every thread.
+///
+/// The memory itself is managed by the system allocator.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CountingAllocator;
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        count();
+        System.alloc(layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        count();
+        System.alloc_zeroed(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(
+        &self,
+        ptr: *mut u8,
+        layout: Layout,
+        new_size: usize,
+    ) -> *mut u8 {
+        count();
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+/// Returns the number of allocations and reallocations made by the
+/// current thread so far.
+pub fn allocations() -> usize {
+    ALLOCATIONS.with(Cell::get)
+}
+
+/// Calls `f` and returns its result together with the number of
+/// allocations and reallocations it made on the current thread.
+///
+/// # Examples
+///
+/// ```rust
+/// use sconcat::CAT;
+/// use sconcat::testing::{CountingAllocator, count_allocations};
+///
+/// #[global_allocator]
+/// static ALLOC: CountingAllocator = CountingAllocator;
+///
+/// let cat = CAT + "a" + "b" + 'c';
+/// let (s, count) = count_allocations(|| String::from(cat));
+/// assert_eq!(s, "abc");
+/// assert_eq!(count, 1);
+/// ```
+pub fn count_allocations<T, F: FnOnce() -> T>(f: F) -> (T, usize) {
+    let before = allocations();
+    let value = f();
+    (value, allocations().wrapping_sub(before))
+}
+
+/// Converts a concatenation into a `String`, panicking in debug
+/// builds if the conversion made more than one allocation or any
+/// reallocation.
+///
+/// The concatenation itself is evaluated before counting starts, so
+/// allocations made while building its pieces are not counted. The
+/// check needs [`CountingAllocator`] as the global allocator; see
+/// the [`testing`] module.
+///
+/// [`CountingAllocator`]: testing/struct.CountingAllocator.html
+/// [`testing`]: testing/index.html
+#[macro_export]
+macro_rules! debug_assert_single_alloc {
+    ($cat:expr) => {{
+        let cat = $cat;
+        let (s, count) = $crate::testing::count_allocations(|| {
+            ::std::string::String::from(cat)
+        });
+        debug_assert!(
+            count <= 1,
+            "concatenation made {} allocations instead of at most one",
+            count
+        );
+        s
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use CAT;
+    use super::{CountingAllocator, count_allocations};
+
+    #[global_allocator]
+    static ALLOC: CountingAllocator = CountingAllocator;
+
+    #[test]
+    fn counts() {
+        let owned = String::from("b");
+        let s = debug_assert_single_alloc!(CAT + "a" + &owned[..] + 'c');
+        assert_eq!(s, "abc");
+        let (s, count) = count_allocations(|| String::from(CAT + ""));
+        assert_eq!((s.as_str(), count), ("", 0));
+    }
+
+    // With alloc-check, reallocations panic before they are counted.
+    #[cfg(not(feature = "alloc-check"))]
+    #[test]
+    fn counts_reallocations() {
+        use disp_hint;
+        let (_, count) = count_allocations(|| {
+            let mut s = String::new();
+            s += CAT + "x" + disp_hint("0123456789abcdef", 0);
+            s
+        });
+        assert!(count > 1);
+    }
+
+    // The check is skipped in release builds.
+    #[cfg(all(debug_assertions, not(feature = "alloc-check")))]
+    #[test]
+    #[should_panic(expected = "made 2 allocations")]
+    fn panics_on_realloc() {
+        use disp_hint;
+        debug_assert_single_alloc!(
+            CAT + "x" + disp_hint("0123456789abcdef", 0)
+        );
+    }
+}
@@ -0,0 +1,92 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use cat::{Cat, CatMany, CatOne, CatStart};
+use std::fmt::{self, Display};
+use tracing::field::{self, DisplayValue};
+
+/// A concatenation borrowed for recording as a `tracing` field.
+///
+/// This is created by the `field` methods of the concatenation
+/// types. It implements `Display` by writing the pieces one by one,
+/// so the concatenation is never materialized.
+#[derive(Debug)]
+pub struct CatField<'a, C: Cat + 'a> {
+    cat: &'a C,
+}
+
+impl<'a, C: Cat + 'a> Clone for CatField<'a, C> {
+    fn clone(&self) -> CatField<'a, C> {
+        *self
+    }
+}
+
+impl<'a, C: Cat + 'a> Copy for CatField<'a, C> {}
+
+impl<'a, C: Cat + 'a> Display for CatField<'a, C> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut result = Ok(());
+        self.cat.for_each_segment(&mut |segment| {
+            if result.is_ok() {
+                result = f.write_str(segment);
+            }
+        });
+        result
+    }
+}
+
+impl<L: Cat, R: Cat> CatMany<L, R> {
+    /// Returns a `tracing` field value for the concatenation.
+    ///
+    /// The value is only formatted if a subscriber records it, and
+    /// even then nothing is allocated.
+    ///
+    /// `tracing::field::Value` is sealed, so it cannot be
+    /// implemented for the concatenation types directly.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// use sconcat::CAT;
+    ///
+    /// let path = CAT + dir + '/' + file;
+    /// tracing::info!(path = path.field(), "opening file");
+    /// ```
+    pub fn field(&self) -> DisplayValue<CatField<'_, CatMany<L, R>>> {
+        field::display(CatField { cat: self })
+    }
+}
+
+impl<T: Cat> CatOne<T> {
+    /// Returns a `tracing` field value for the concatenation.
+    ///
+    /// The value is only formatted if a subscriber records it.
+    pub fn field(&self) -> DisplayValue<CatField<'_, T>> {
+        field::display(CatField { cat: &self.inner })
+    }
+}
+
+impl CatStart {
+    /// Returns a `tracing` field value for the empty concatenation.
+    pub fn field(&self) -> DisplayValue<CatField<'static, &'static str>> {
+        field::display(CatField { cat: &"" })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use CAT;
+
+    #[test]
+    fn fields() {
+        let cat = CAT + "a" + String::from("b") + 'c';
+        assert_eq!(format!("{:?}", cat.field()), "abc");
+        assert_eq!(format!("{:?}", (CAT + "x").field()), "x");
+        assert_eq!(format!("{:?}", CAT.field()), "");
+    }
+}
@@ -0,0 +1,230 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use cat::Cat;
+use std::cmp;
+use std::fmt::{self, Display};
+
+/// A piece that truncates another piece to a number of bytes.
+///
+/// This is created by the [`truncate`](fn.truncate.html) function.
+#[derive(Clone, Copy, Debug)]
+pub struct Truncate<'a, T: Cat> {
+    inner: T,
+    max: usize,
+    ellipsis: &'a str,
+}
+
+/// Creates a piece that is `value` truncated to at most `max_bytes`
+/// bytes.
+///
+/// The text is cut at a character boundary, so the result is always
+/// valid UTF-8 and can be a few bytes shorter than `max_bytes`. An
+/// ellipsis can be appended to truncated text with
+/// [`with_ellipsis`](struct.Truncate.html#method.with_ellipsis). The
+/// size hint is the smaller of the size hint of `value` and
+/// `max_bytes`, so a long value does not reserve more than the limit.
+///
+/// # Examples
+///
+/// ```rust
+/// use sconcat::{CAT, truncate};
+///
+/// let msg = "café au lait";
+/// let cat = CAT + "msg=" + truncate(msg, 4);
+/// assert_eq!(String::from(cat), "msg=caf");
+/// let piece = truncate(msg, 10).with_ellipsis("...");
+/// assert!(piece.is_truncated());
+/// assert_eq!(String::from(CAT + piece + ';'), "café a...;");
+/// ```
+pub fn truncate<T: Cat>(value: T, max_bytes: usize) -> Truncate<'static, T> {
+    Truncate {
+        inner: value,
+        max: max_bytes,
+        ellipsis: "",
+    }
+}
+
+impl<'a, T: Cat> Truncate<'a, T> {
+    /// Sets the text appended when the piece is truncated.
+    ///
+    /// The ellipsis counts towards the limit. If it is longer than
+    /// the limit, it is not appended.
+    pub fn with_ellipsis<'b>(self, ellipsis: &'b str) -> Truncate<'b, T> {
+        Truncate {
+            inner: self.inner,
+            max: self.max,
+            ellipsis,
+        }
+    }
+
+    /// Returns `true` if the piece is longer than the limit and is
+    /// truncated.
+    ///
+    /// This walks the segments of the piece, so pieces that are
+    /// formatted on the fly are formatted to find their length. To
+    /// append the piece and find whether it was truncated in one
+    /// pass, use
+    /// [`append_and_report`](#method.append_and_report).
+    pub fn is_truncated(&self) -> bool {
+        let mut len = 0usize;
+        self.inner.for_each_segment(&mut |segment| {
+            len = len.saturating_add(segment.len())
+        });
+        len > self.max
+    }
+
+    /// Appends the piece to `s` and returns `true` if it was
+    /// truncated.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use sconcat::{CAT, truncate};
+    ///
+    /// let mut s = String::from("msg=");
+    /// let piece = truncate(CAT + "hello" + ' ' + "world", 8);
+    /// assert!(piece.with_ellipsis("...").append_and_report(&mut s));
+    /// assert_eq!(s, "msg=hello...");
+    /// ```
+    pub fn append_and_report(&self, s: &mut String) -> bool {
+        s.reserve(self.size_hint());
+        self.emit(&mut |segment| s.push_str(segment))
+    }
+
+    // Passes the segments to `f` in one pass, returning whether the
+    // piece was truncated. Text up to where the ellipsis would start
+    // is passed on at once, and the text after that is held back
+    // until it is known whether the piece fits, so nothing is held
+    // back without an ellipsis.
+    fn emit(&self, f: &mut dyn FnMut(&str)) -> bool {
+        let ellipsis = if self.ellipsis.len() <= self.max {
+            self.ellipsis
+        } else {
+            ""
+        };
+        let keep = self.max - ellipsis.len();
+        let mut len = 0;
+        let mut held = String::new();
+        let mut truncated = false;
+        self.inner.for_each_segment(&mut |segment| {
+            if truncated {
+                return;
+            }
+            let start = len;
+            let cut = if held.is_empty() && start < keep {
+                let mut end = cmp::min(segment.len(), keep - start);
+                while !segment.is_char_boundary(end) {
+                    end -= 1;
+                }
+                end
+            } else {
+                0
+            };
+            if cut > 0 {
+                f(&segment[..cut]);
+            }
+            if segment.len() > self.max - start {
+                truncated = true;
+                f(ellipsis);
+                return;
+            }
+            held.push_str(&segment[cut..]);
+            len += segment.len();
+        });
+        if !truncated && !held.is_empty() {
+            f(&held);
+        }
+        truncated
+    }
+}
+
+impl<'a, T: Cat> Cat for Truncate<'a, T> {
+    fn size_hint(&self) -> usize {
+        cmp::min(self.inner.size_hint(), self.max)
+    }
+
+    fn append_to(&self, s: &mut String) {
+        self.for_each_segment(&mut |segment| s.push_str(segment));
+    }
+
+    fn for_each_segment(&self, f: &mut dyn FnMut(&str)) {
+        self.emit(f);
+    }
+}
+
+impl<'a, T: Cat> Display for Truncate<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut result = Ok(());
+        self.for_each_segment(&mut |segment| {
+            if result.is_ok() {
+                result = f.write_str(segment);
+            }
+        });
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use CAT;
+    use cat::Cat;
+    use lazy::lazy;
+    use std::cell::Cell;
+    use super::truncate;
+
+    #[test]
+    fn truncated() {
+        let cat = CAT + "ab" + 'é' + String::from("cd");
+        let piece = truncate(cat.clone(), 3);
+        assert!(piece.is_truncated());
+        assert_eq!(piece.size_hint(), 3);
+        assert_eq!(piece.to_string(), "ab");
+        assert_eq!(truncate(cat.clone(), 4).to_string(), "abé");
+        assert_eq!(truncate(cat.clone(), 5).to_string(), "abéc");
+        let piece = truncate(cat.clone(), 5).with_ellipsis("…");
+        assert_eq!(piece.to_string(), "ab…");
+        let piece = truncate(cat.clone(), 2).with_ellipsis("…");
+        assert_eq!(piece.to_string(), "ab");
+        assert_eq!(truncate(cat.clone(), 0).to_string(), "");
+    }
+
+    #[test]
+    fn one_pass() {
+        let calls = Cell::new(0);
+        let piece = lazy(|s: &mut String| {
+            calls.set(calls.get() + 1);
+            s.push_str("abcdef");
+        });
+        let cat = CAT + "xy" + piece + "z";
+        let mut s = String::new();
+        let piece = truncate(cat, 6).with_ellipsis("..");
+        assert!(piece.append_and_report(&mut s));
+        assert_eq!(s, "xyab..");
+        assert_eq!(calls.get(), 1);
+        let cat = CAT + "ab" + 'é' + String::from("cd");
+        let mut s = String::new();
+        let piece = truncate(cat.clone(), 6).with_ellipsis("...");
+        assert!(!piece.append_and_report(&mut s));
+        assert_eq!(s, "abécd");
+        let piece = truncate(cat, 5).with_ellipsis("..");
+        assert_eq!(piece.to_string(), "ab..");
+    }
+
+    #[test]
+    fn not_truncated() {
+        let cat = CAT + "ab" + 'é' + String::from("cd");
+        let piece = truncate(cat, 6).with_ellipsis("...");
+        assert!(!piece.is_truncated());
+        assert_eq!(piece.size_hint(), 6);
+        let s = String::from(CAT + '[' + piece + ']');
+        assert_eq!(s, "[abécd]");
+        assert_eq!(s.len(), s.capacity());
+        assert_eq!(truncate("", 0).to_string(), "");
+    }
+}
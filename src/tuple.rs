@@ -0,0 +1,107 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Tuples of up to twelve pieces are pieces themselves, so that
+// helpers can return a few pieces without naming a concatenation
+// type. As tuples are foreign types, they do not implement `Display`,
+// so a concatenation containing a tuple can be converted into a
+// `String` but cannot be formatted with `to_string`.
+
+use cat::Cat;
+#[cfg(feature = "rope")]
+use rope::Rope;
+
+macro_rules! tuple_cat {
+    ($($T:ident $i:tt),*) => {
+        impl<$($T: Cat),*> Cat for ($($T,)*) {
+            fn size_hint(&self) -> usize {
+                self.checked_size_hint().expect("capacity overflow")
+            }
+
+            fn append_to(&self, s: &mut String) {
+                $(self.$i.append_to(s);)*
+            }
+
+            fn for_each_segment(&self, f: &mut dyn FnMut(&str)) {
+                $(self.$i.for_each_segment(f);)*
+            }
+
+            fn checked_size_hint(&self) -> Option<usize> {
+                let len = Some(0usize);
+                $(let len = len?.checked_add(self.$i.checked_size_hint()?);)*
+                len
+            }
+
+            fn pieces(&self) -> usize {
+                0 $(+ self.$i.pieces())*
+            }
+
+            fn segment_count(&self) -> usize {
+                0 $(+ self.$i.segment_count())*
+            }
+
+            fn child_count(&self) -> usize {
+                [$($i),*].len()
+            }
+
+            fn child_at(&self, index: usize) -> &dyn Cat {
+                match index {
+                    $($i => &self.$i,)*
+                    _ => unreachable!("child index out of range"),
+                }
+            }
+
+            #[cfg(feature = "rope")]
+            fn append_to_rope(self, rope: &mut Rope) {
+                $(self.$i.append_to_rope(rope);)*
+            }
+
+            #[cfg(feature = "secrecy")]
+            fn for_each_exposed_segment(&self, f: &mut dyn FnMut(&str)) {
+                $(self.$i.for_each_exposed_segment(f);)*
+            }
+        }
+    };
+}
+
+tuple_cat! { A 0 }
+tuple_cat! { A 0, B 1 }
+tuple_cat! { A 0, B 1, C 2 }
+tuple_cat! { A 0, B 1, C 2, D 3 }
+tuple_cat! { A 0, B 1, C 2, D 3, E 4 }
+tuple_cat! { A 0, B 1, C 2, D 3, E 4, F 5 }
+tuple_cat! { A 0, B 1, C 2, D 3, E 4, F 5, G 6 }
+tuple_cat! { A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7 }
+tuple_cat! { A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8 }
+tuple_cat! { A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9 }
+tuple_cat! { A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10 }
+tuple_cat! { A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10, L 11 }
+
+#[cfg(test)]
+mod tests {
+    use CAT;
+    use cat::Cat;
+
+    fn prefix(level: &str) -> (char, &str, &'static str) {
+        ('[', level, "] ")
+    }
+
+    #[test]
+    fn tuples() {
+        let s = String::from(CAT + prefix("warn") + "disk " + ("low",));
+        assert_eq!(s, "[warn] disk low");
+        assert_eq!(s.len(), s.capacity());
+        let nested = (("a", 'b'), String::from("c"), CAT + "d" + "e");
+        assert_eq!(nested.size_hint(), 5);
+        assert_eq!(nested.pieces(), 5);
+        assert_eq!(String::from(CAT + nested), "abcde");
+        let twelve =
+            ("1", "2", "3", "4", "5", "6", "7", "8", "9", "a", "b", 'c');
+        assert_eq!(String::from(CAT + twelve), "123456789abc");
+    }
+}
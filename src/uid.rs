@@ -0,0 +1,72 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Integration with the uuid crate. A `Uuid` is written in its
+// hyphenated form like its `Display` implementation; the formatter
+// types can be used for the other forms. Every form has a fixed
+// length, so the size hint is exact without formatting.
+
+use cat::Cat;
+use uuid::Uuid;
+use uuid::fmt::{Braced, Hyphenated, Simple, Urn};
+
+macro_rules! uuid_cat {
+    ($($T:ty => $F:ident $(. $conv:ident)*;)*) => { $(
+        impl Cat for $T {
+            fn size_hint(&self) -> usize {
+                $F::LENGTH
+            }
+
+            fn append_to(&self, s: &mut String) {
+                let mut buf = Uuid::encode_buffer();
+                s.push_str(self $(.$conv())*.encode_lower(&mut buf));
+            }
+
+            fn for_each_segment(&self, f: &mut dyn FnMut(&str)) {
+                let mut buf = Uuid::encode_buffer();
+                f(self $(.$conv())*.encode_lower(&mut buf));
+            }
+
+            fn segment_count(&self) -> usize {
+                1
+            }
+        }
+    )* };
+}
+
+uuid_cat! {
+    Uuid => Hyphenated.hyphenated;
+    Hyphenated => Hyphenated;
+    Simple => Simple;
+    Braced => Braced;
+    Urn => Urn;
+}
+
+#[cfg(test)]
+mod tests {
+    use CAT;
+    use cat::Cat;
+    use uuid::Uuid;
+
+    #[test]
+    fn uuids() {
+        let id = Uuid::from_u128(0x67e5_5044_10b1_426f_9247_bb68_0e5f_e0c8);
+        let s = String::from(CAT + "req=" + id + ' ');
+        assert_eq!(s, "req=67e55044-10b1-426f-9247-bb680e5fe0c8 ");
+        assert_eq!(s.len(), s.capacity());
+        let s = String::from(CAT + id.simple() + '|' + id.braced());
+        assert_eq!(
+            s,
+            "67e5504410b1426f9247bb680e5fe0c8|\
+             {67e55044-10b1-426f-9247-bb680e5fe0c8}"
+        );
+        assert_eq!(s.len(), s.capacity());
+        assert_eq!(id.urn().size_hint(), 45);
+        assert_eq!(id.hyphenated().size_hint(), id.size_hint());
+    }
+}
@@ -0,0 +1,288 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use cat::Cat;
+use std::error::Error;
+use std::fmt::{self, Display};
+use std::str;
+
+const HEX: &[u8; 16] = b"0123456789ABCDEF";
+// Characters other than alphanumerics allowed in a path segment.
+const SAFE: &[u8] = b"-._~!$&'()*+,;=:@";
+// Characters other than alphanumerics left unencoded in a query.
+const UNRESERVED: &[u8] = b"-._~";
+
+/// A piece that joins URL path segments.
+///
+/// This is created by the [`url_path`](fn.url_path.html) function.
+#[derive(Clone, Debug)]
+pub struct UrlPath<T: AsRef<str>> {
+    segments: Vec<T>,
+}
+
+/// An error which can be returned when joining URL path segments.
+///
+/// This is returned by the [`url_path`](fn.url_path.html) function.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DotSegmentError {
+    index: usize,
+}
+
+impl DotSegmentError {
+    /// Returns the index of the first segment that is `.` or `..`.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+}
+
+impl Display for DotSegmentError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "URL path segment {} is a dot segment", self.index)
+    }
+}
+
+impl Error for DotSegmentError {}
+
+/// Creates a piece that joins path segments with `/`,
+/// percent-encoding each segment.
+///
+/// Leading and trailing slashes are trimmed from every segment and
+/// empty segments are skipped, so that the path has no duplicate
+/// slashes. Slashes inside a segment are encoded as `%2F`. The path
+/// always starts with `/`. The size hint is exact.
+///
+/// # Errors
+///
+/// Returns an error if a segment is `.` or `..` after trimming
+/// slashes. These dot segments cannot be escaped, as URL parsers also
+/// treat `%2E` and `%2E%2E` as dot segments and resolve them, so a
+/// segment taken from user input could otherwise move the path up a
+/// level.
+///
+/// # Examples
+///
+/// ```rust
+/// use sconcat::{CAT, url_path};
+///
+/// let base = "https://example.com";
+/// let user = "jo/ann e";
+/// let path = url_path(vec!["/api/", "v1", "", "users", user]).unwrap();
+/// let cat = CAT + base + path + "?page=2";
+/// let expected = "https://example.com/api/v1/users/jo%2Fann%20e?page=2";
+/// assert_eq!(String::from(cat), expected);
+/// assert!(url_path(vec!["users", ".."]).is_err());
+/// ```
+pub fn url_path<I>(segments: I) -> Result<UrlPath<I::Item>, DotSegmentError>
+where
+    I: IntoIterator,
+    I::Item: AsRef<str>,
+{
+    let segments: Vec<I::Item> = segments.into_iter().collect();
+    for (index, segment) in segments.iter().enumerate() {
+        let segment = segment.as_ref().trim_matches('/');
+        if segment == "." || segment == ".." {
+            return Err(DotSegmentError { index });
+        }
+    }
+    Ok(UrlPath { segments })
+}
+
+/// A piece that renders key/value pairs as a URL query.
+///
+/// This is created by the [`query`](fn.query.html) function.
+#[derive(Clone, Debug)]
+pub struct Query<K: Cat, V: Cat> {
+    pairs: Vec<(K, V)>,
+}
+
+/// Creates a piece that renders key/value pairs as a URL query
+/// `k1=v1&k2=v2`, percent-encoding both keys and values.
+///
+/// Only alphanumerics and `-._~` are left unencoded, so that keys
+/// and values can contain `&`, `=`, `+` and `#`. The leading `?` is
+/// not included. The pairs are collected, but the pieces are not
+/// materialized; they are encoded segment by segment. The size hint
+/// is exact.
+///
+/// # Examples
+///
+/// ```rust
+/// use sconcat::{CAT, query};
+///
+/// let term = String::from("fish & chips");
+/// let page = 2.to_string();
+/// let q = query(vec![("q", term), ("page", page)]);
+/// let url = String::from(CAT + "/search?" + q);
+/// assert_eq!(url, "/search?q=fish%20%26%20chips&page=2");
+/// ```
+pub fn query<I, K, V>(pairs: I) -> Query<K, V>
+where
+    I: IntoIterator<Item = (K, V)>,
+    K: Cat,
+    V: Cat,
+{
+    Query {
+        pairs: pairs.into_iter().collect(),
+    }
+}
+
+fn is_safe(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || SAFE.contains(&b)
+}
+
+fn is_unreserved(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || UNRESERVED.contains(&b)
+}
+
+// Passes `s` to `f` in runs of characters for which `keep` is true
+// and escapes.
+pub(crate) fn encode(s: &str, keep: fn(u8) -> bool, f: &mut dyn FnMut(&str)) {
+    let bytes = s.as_bytes();
+    let mut start = 0;
+    for (i, &b) in bytes.iter().enumerate() {
+        if keep(b) {
+            continue;
+        }
+        if start < i {
+            f(&s[start..i]);
+        }
+        let escape = [
+            b'%',
+            HEX[usize::from(b >> 4)],
+            HEX[usize::from(b & 0xf)],
+        ];
+        // The escape contains only ASCII characters.
+        f(str::from_utf8(&escape).unwrap());
+        start = i + 1;
+    }
+    if start < bytes.len() {
+        f(&s[start..]);
+    }
+}
+
+impl<T: AsRef<str>> UrlPath<T> {
+    fn trimmed(&self) -> impl Iterator<Item = &str> {
+        self.segments
+            .iter()
+            .map(|segment| segment.as_ref().trim_matches('/'))
+            .filter(|segment| !segment.is_empty())
+    }
+}
+
+impl<T: AsRef<str>> Cat for UrlPath<T> {
+    fn size_hint(&self) -> usize {
+        let mut len = 0usize;
+        self.for_each_segment(&mut |segment| len += segment.len());
+        len
+    }
+
+    fn append_to(&self, s: &mut String) {
+        self.for_each_segment(&mut |segment| s.push_str(segment));
+    }
+
+    fn for_each_segment(&self, f: &mut dyn FnMut(&str)) {
+        let mut empty = true;
+        for segment in self.trimmed() {
+            f("/");
+            encode(segment, is_safe, f);
+            empty = false;
+        }
+        if empty {
+            f("/");
+        }
+    }
+}
+
+impl<T: AsRef<str>> Display for UrlPath<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut result = Ok(());
+        self.for_each_segment(&mut |segment| {
+            if result.is_ok() {
+                result = f.write_str(segment);
+            }
+        });
+        result
+    }
+}
+
+impl<K: Cat, V: Cat> Cat for Query<K, V> {
+    fn size_hint(&self) -> usize {
+        let mut len = 0usize;
+        self.for_each_segment(&mut |segment| len += segment.len());
+        len
+    }
+
+    fn append_to(&self, s: &mut String) {
+        self.for_each_segment(&mut |segment| s.push_str(segment));
+    }
+
+    fn for_each_segment(&self, f: &mut dyn FnMut(&str)) {
+        for (i, (key, value)) in self.pairs.iter().enumerate() {
+            if i > 0 {
+                f("&");
+            }
+            key.for_each_segment(&mut |part| encode(part, is_unreserved, f));
+            f("=");
+            value.for_each_segment(&mut |part| encode(part, is_unreserved, f));
+        }
+    }
+}
+
+impl<K: Cat, V: Cat> Display for Query<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut result = Ok(());
+        self.for_each_segment(&mut |segment| {
+            if result.is_ok() {
+                result = f.write_str(segment);
+            }
+        });
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use CAT;
+    use super::{query, url_path};
+
+    #[test]
+    fn joins_and_encodes() {
+        let owned = vec![String::from("a?b#c"), String::from("ü")];
+        let s = String::from(CAT + url_path(owned).unwrap() + '?');
+        assert_eq!(s, "/a%3Fb%23c/%C3%BC?");
+        assert_eq!(s.len(), s.capacity());
+        let path = url_path(vec!["//x//", "...", ".a", "a:b@c"]).unwrap();
+        assert_eq!(path.to_string(), "/x/.../.a/a:b@c");
+    }
+
+    #[test]
+    fn rejects_dot_segments() {
+        let err = url_path(vec!["a", "/../"]).unwrap_err();
+        assert_eq!(err.index(), 1);
+        assert_eq!(err.to_string(), "URL path segment 1 is a dot segment");
+        assert_eq!(url_path(vec![".", ".."]).unwrap_err().index(), 0);
+    }
+
+    #[test]
+    fn empty() {
+        assert_eq!(url_path(Vec::<&str>::new()).unwrap().to_string(), "/");
+        assert_eq!(url_path(vec!["/", ""]).unwrap().to_string(), "/");
+    }
+
+    #[test]
+    fn query_pairs() {
+        let value = CAT + "a=b" + '+' + String::from("ü#");
+        let q = query(vec![("k&1", value)]);
+        let s = String::from(CAT + '?' + q);
+        assert_eq!(s, "?k%261=a%3Db%2B%C3%BC%23");
+        assert_eq!(s.len(), s.capacity());
+        let q = query(vec![("x", "1"), ("y", ""), ("-._~", "2")]);
+        assert_eq!(q.to_string(), "x=1&y=&-._~=2");
+        assert_eq!(query(Vec::<(char, char)>::new()).to_string(), "");
+    }
+}
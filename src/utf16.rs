@@ -0,0 +1,83 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use cat::{Cat, CatMany, CatOne, CatStart};
+
+// The code units are counted first, as the byte size hint can be up
+// to three times the number of code units, for example for CJK text.
+fn encode_utf16<C: Cat>(cat: &C) -> Vec<u16> {
+    let mut units = 0usize;
+    cat.for_each_segment(&mut |segment| {
+        units += segment.chars().map(char::len_utf16).sum::<usize>()
+    });
+    let mut v = Vec::with_capacity(units);
+    cat.for_each_segment(&mut |segment| v.extend(segment.encode_utf16()));
+    v
+}
+
+impl<L: Cat, R: Cat> CatMany<L, R> {
+    /// Encodes the concatenation as UTF-16.
+    ///
+    /// The segments are walked twice, once to count the UTF-16 code
+    /// units and once to encode them directly into a vector with
+    /// exactly that capacity, so the concatenation is never
+    /// materialized as a `String`. Pieces that are formatted on the
+    /// fly are formatted twice.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use sconcat::CAT;
+    ///
+    /// let dir = "C:\\Temp";
+    /// let wide = (CAT + dir + '\\' + "log.txt").to_utf16();
+    /// assert_eq!(String::from_utf16(&wide).unwrap(), "C:\\Temp\\log.txt");
+    /// ```
+    pub fn to_utf16(&self) -> Vec<u16> {
+        encode_utf16(self)
+    }
+}
+
+impl<T: Cat> CatOne<T> {
+    /// Encodes the concatenation as UTF-16.
+    pub fn to_utf16(&self) -> Vec<u16> {
+        encode_utf16(&self.inner)
+    }
+}
+
+impl CatStart {
+    /// Returns an empty vector.
+    pub fn to_utf16(&self) -> Vec<u16> {
+        Vec::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use CAT;
+    use Dbg;
+
+    #[test]
+    fn utf16() {
+        let cat = CAT + "ab" + 'é' + "€" + '𝄞' + Dbg(7);
+        let v = cat.to_utf16();
+        let expected: Vec<u16> = "abé€𝄞7".encode_utf16().collect();
+        assert_eq!(v, expected);
+        assert_eq!(v.len(), v.capacity());
+        assert_eq!((CAT + "x").to_utf16(), [u16::from(b'x')]);
+        assert!(CAT.to_utf16().is_empty());
+    }
+
+    #[test]
+    fn capacity() {
+        let v = (CAT + "abc" + String::from("de")).to_utf16();
+        assert_eq!((v.len(), v.capacity()), (5, 5));
+        let v = (CAT + "漢字" + 'か' + String::from("な")).to_utf16();
+        assert_eq!((v.len(), v.capacity()), (4, 4));
+    }
+}
@@ -0,0 +1,327 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use cat::{Cat, CatMany, CatOne, CatStart};
+use std::convert::Infallible;
+use std::fmt::{self, Display};
+use std::str;
+use ufmt::{Formatter, uDebug, uDisplay, uWrite};
+
+const HEX: &[u8; 16] = b"0123456789abcdef";
+
+/// A piece that formats a value with its `ufmt::uDisplay`
+/// implementation.
+///
+/// The value is formatted twice, once with a counting writer to
+/// compute an exact size hint and once to append it, so that no
+/// intermediate `String` is allocated.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use sconcat::{CAT, UDisp};
+///
+/// let cat = CAT + "temp=" + UDisp(sensor.read()) + "\r\n";
+/// cat.write_to_ufmt(&mut serial)?;
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct UDisp<T: uDisplay>(pub T);
+
+// Counts the bytes written.
+struct Counter(usize);
+
+impl uWrite for Counter {
+    type Error = Infallible;
+
+    fn write_str(&mut self, s: &str) -> Result<(), Infallible> {
+        self.0 = self.0.checked_add(s.len()).expect("capacity overflow");
+        Ok(())
+    }
+}
+
+// Passes the strings written to a closure.
+struct Segments<'a>(&'a mut dyn FnMut(&str));
+
+impl<'a> uWrite for Segments<'a> {
+    type Error = Infallible;
+
+    fn write_str(&mut self, s: &str) -> Result<(), Infallible> {
+        (self.0)(s);
+        Ok(())
+    }
+}
+
+impl<T: uDisplay> Cat for UDisp<T> {
+    fn size_hint(&self) -> usize {
+        let mut w = Counter(0);
+        let Ok(()) = uDisplay::fmt(&self.0, &mut Formatter::new(&mut w));
+        w.0
+    }
+
+    fn append_to(&self, s: &mut String) {
+        self.for_each_segment(&mut |segment| s.push_str(segment));
+    }
+
+    fn for_each_segment(&self, f: &mut dyn FnMut(&str)) {
+        let mut w = Segments(f);
+        let Ok(()) = uDisplay::fmt(&self.0, &mut Formatter::new(&mut w));
+    }
+}
+
+impl<T: uDisplay> Display for UDisp<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_segments(self, &mut |segment| f.write_str(segment))
+    }
+}
+
+// Writes the segments one by one, skipping the rest after an error.
+fn write_segments<C: Cat, E>(
+    cat: &C,
+    write: &mut dyn FnMut(&str) -> Result<(), E>,
+) -> Result<(), E> {
+    let mut result = Ok(());
+    cat.for_each_segment(&mut |segment| {
+        if result.is_ok() {
+            result = write(segment);
+        }
+    });
+    result
+}
+
+// Writes the segments as a quoted string. Only quotes, backslashes
+// and ASCII control characters are escaped, as `ufmt` avoids the
+// tables that `char::escape_debug` needs.
+fn write_quoted<C: Cat, E>(
+    cat: &C,
+    write: &mut dyn FnMut(&str) -> Result<(), E>,
+) -> Result<(), E> {
+    write("\"")?;
+    write_segments(cat, &mut |segment| {
+        let mut start = 0;
+        for (i, b) in segment.bytes().enumerate() {
+            let mut buf = *b"\\u{00}";
+            let escape = match b {
+                b'"' => "\\\"",
+                b'\\' => "\\\\",
+                b'\0' => "\\0",
+                b'\t' => "\\t",
+                b'\n' => "\\n",
+                b'\r' => "\\r",
+                0..=0x1f | 0x7f => {
+                    // Like `Debug`, leave out the leading zero.
+                    let len = if b < 0x10 {
+                        buf[3] = HEX[usize::from(b)];
+                        buf[4] = b'}';
+                        5
+                    } else {
+                        buf[3] = HEX[usize::from(b >> 4)];
+                        buf[4] = HEX[usize::from(b & 0xf)];
+                        6
+                    };
+                    // The escape contains only ASCII characters.
+                    str::from_utf8(&buf[..len]).unwrap()
+                }
+                _ => continue,
+            };
+            if start < i {
+                write(&segment[start..i])?;
+            }
+            write(escape)?;
+            start = i + 1;
+        }
+        if start < segment.len() {
+            write(&segment[start..])?;
+        }
+        Ok(())
+    })?;
+    write("\"")
+}
+
+impl<L: Cat, R: Cat> CatMany<L, R> {
+    /// Writes the concatenation to a `ufmt::uWrite` sink segment by
+    /// segment.
+    ///
+    /// The concatenation is never materialized and `core::fmt` is
+    /// not involved, so this suits targets that use `ufmt` to keep
+    /// code small. Writing stops at the first error.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// use sconcat::CAT;
+    ///
+    /// let mut serial = board.serial();
+    /// (CAT + "state=" + state_name + "\r\n").write_to_ufmt(&mut serial)?;
+    /// ```
+    pub fn write_to_ufmt<W: uWrite + ?Sized>(
+        &self,
+        w: &mut W,
+    ) -> Result<(), W::Error> {
+        write_segments(self, &mut |segment| w.write_str(segment))
+    }
+}
+
+impl<T: Cat> CatOne<T> {
+    /// Writes the concatenation to a `ufmt::uWrite` sink.
+    ///
+    /// The concatenation is never materialized, so no allocations
+    /// take place.
+    pub fn write_to_ufmt<W: uWrite + ?Sized>(
+        &self,
+        w: &mut W,
+    ) -> Result<(), W::Error> {
+        write_segments(&self.inner, &mut |segment| w.write_str(segment))
+    }
+}
+
+impl CatStart {
+    /// Writes nothing to a `ufmt::uWrite` sink.
+    pub fn write_to_ufmt<W: uWrite + ?Sized>(
+        &self,
+        _w: &mut W,
+    ) -> Result<(), W::Error> {
+        Ok(())
+    }
+}
+
+// The concatenation is written segment by segment.
+impl<L: Cat, R: Cat> uDisplay for CatMany<L, R> {
+    fn fmt<W>(&self, f: &mut Formatter<W>) -> Result<(), W::Error>
+    where
+        W: uWrite + ?Sized,
+    {
+        write_segments(self, &mut |segment| f.write_str(segment))
+    }
+}
+
+// Unlike `Debug`, which shows the pieces, this shows the
+// concatenation as one quoted string, as `ufmt` does not implement
+// `uDebug` for strings.
+impl<L: Cat, R: Cat> uDebug for CatMany<L, R> {
+    fn fmt<W>(&self, f: &mut Formatter<W>) -> Result<(), W::Error>
+    where
+        W: uWrite + ?Sized,
+    {
+        write_quoted(self, &mut |segment| f.write_str(segment))
+    }
+}
+
+impl<T: Cat> uDisplay for CatOne<T> {
+    fn fmt<W>(&self, f: &mut Formatter<W>) -> Result<(), W::Error>
+    where
+        W: uWrite + ?Sized,
+    {
+        write_segments(&self.inner, &mut |segment| f.write_str(segment))
+    }
+}
+
+impl<T: Cat> uDebug for CatOne<T> {
+    fn fmt<W>(&self, f: &mut Formatter<W>) -> Result<(), W::Error>
+    where
+        W: uWrite + ?Sized,
+    {
+        write_quoted(&self.inner, &mut |segment| f.write_str(segment))
+    }
+}
+
+impl uDisplay for CatStart {
+    fn fmt<W>(&self, _f: &mut Formatter<W>) -> Result<(), W::Error>
+    where
+        W: uWrite + ?Sized,
+    {
+        Ok(())
+    }
+}
+
+impl uDebug for CatStart {
+    fn fmt<W>(&self, f: &mut Formatter<W>) -> Result<(), W::Error>
+    where
+        W: uWrite + ?Sized,
+    {
+        f.write_str("\"\"")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use CAT;
+    use std::convert::Infallible;
+    use super::UDisp;
+    use ufmt::{Formatter, uDisplay, uWrite, uwrite};
+
+    struct Sink {
+        text: String,
+        writes: usize,
+    }
+
+    impl uWrite for Sink {
+        type Error = Infallible;
+
+        fn write_str(&mut self, s: &str) -> Result<(), Infallible> {
+            self.text.push_str(s);
+            self.writes += 1;
+            Ok(())
+        }
+    }
+
+    fn new_sink() -> Sink {
+        Sink {
+            text: String::new(),
+            writes: 0,
+        }
+    }
+
+    #[test]
+    fn writes_segments() {
+        let mut sink = new_sink();
+        let cat = CAT + "state=" + String::from("idle") + '\r' + '\n';
+        cat.write_to_ufmt(&mut sink).unwrap();
+        (CAT + "ok").write_to_ufmt(&mut sink).unwrap();
+        CAT.write_to_ufmt(&mut sink).unwrap();
+        assert_eq!(sink.text, "state=idle\r\nok");
+        assert_eq!(sink.writes, 5);
+    }
+
+    #[test]
+    fn formats() {
+        let mut sink = new_sink();
+        let cat = CAT + "say \"hi\"" + '\n' + "\\\u{1}\u{1b}é";
+        uwrite!(sink, "{} {:?}", cat, cat).unwrap();
+        let expected = "say \"hi\"\n\\\u{1}\u{1b}é \
+                        \"say \\\"hi\\\"\\n\\\\\\u{1}\\u{1b}é\"";
+        assert_eq!(sink.text, expected);
+        assert_eq!(sink.text, format!("{} {:?}", cat, cat.to_string()));
+        let mut sink = new_sink();
+        uwrite!(sink, "[{}{:?}|{}{:?}]", CAT, CAT, CAT + 'x', CAT + 'x')
+            .unwrap();
+        assert_eq!(sink.text, "[\"\"|x\"x\"]");
+    }
+
+    struct Celsius(i16);
+
+    impl uDisplay for Celsius {
+        fn fmt<W>(&self, f: &mut Formatter<W>) -> Result<(), W::Error>
+        where
+            W: uWrite + ?Sized,
+        {
+            uwrite!(f, "{}", self.0)?;
+            f.write_str(" °C")
+        }
+    }
+
+    #[test]
+    fn udisp_pieces() {
+        let s = String::from(CAT + "temp=" + UDisp(Celsius(-12)) + ';');
+        assert_eq!(s, "temp=-12 °C;");
+        assert_eq!(s.len(), s.capacity());
+        assert_eq!(UDisp(Celsius(7)).to_string(), "7 °C");
+        let mut sink = new_sink();
+        (CAT + UDisp(Celsius(0))).write_to_ufmt(&mut sink).unwrap();
+        assert_eq!(sink.text, "0 °C");
+    }
+}
@@ -0,0 +1,412 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::fmt::{self, Debug};
+use std::ops::{Add, AddAssign};
+
+/// Trait for types that can be concatenated into a `Vec`.
+pub trait VCat {
+    /// Element type.
+    type Item: Clone;
+    /// Length of item in elements.
+    fn size_hint(&self) -> usize;
+    /// Append item to Vec.
+    fn append_to(&self, v: &mut Vec<Self::Item>);
+    /// Converts item to a Vec.
+    fn into_vec(self, capacity: usize) -> Vec<Self::Item>;
+}
+
+impl<T: Clone> VCat for &[T] {
+    type Item = T;
+
+    fn size_hint(&self) -> usize {
+        self.len()
+    }
+
+    fn append_to(&self, v: &mut Vec<T>) {
+        v.extend_from_slice(self);
+    }
+
+    fn into_vec(self, capacity: usize) -> Vec<T> {
+        let mut v = Vec::with_capacity(capacity);
+        v.extend_from_slice(self);
+        v
+    }
+}
+
+impl<T: Clone> VCat for Vec<T> {
+    type Item = T;
+
+    fn size_hint(&self) -> usize {
+        self.len()
+    }
+
+    fn append_to(&self, v: &mut Vec<T>) {
+        v.extend_from_slice(self);
+    }
+
+    fn into_vec(mut self, capacity: usize) -> Vec<T> {
+        let len = self.len();
+        if capacity > len {
+            self.reserve(capacity - len);
+        }
+        self
+    }
+}
+
+impl<T: Clone, const N: usize> VCat for &[T; N] {
+    type Item = T;
+
+    fn size_hint(&self) -> usize {
+        N
+    }
+
+    fn append_to(&self, v: &mut Vec<T>) {
+        v.extend_from_slice(&self[..]);
+    }
+
+    fn into_vec(self, capacity: usize) -> Vec<T> {
+        let mut v = Vec::with_capacity(capacity);
+        v.extend_from_slice(&self[..]);
+        v
+    }
+}
+
+impl VCat for u8 {
+    type Item = u8;
+
+    fn size_hint(&self) -> usize {
+        1
+    }
+
+    fn append_to(&self, v: &mut Vec<u8>) {
+        v.push(*self);
+    }
+
+    fn into_vec(self, capacity: usize) -> Vec<u8> {
+        let mut v = Vec::with_capacity(capacity);
+        v.push(self);
+        v
+    }
+}
+
+// Text can be mixed into byte concatenations as its UTF-8 bytes.
+impl VCat for &str {
+    type Item = u8;
+
+    fn size_hint(&self) -> usize {
+        self.len()
+    }
+
+    fn append_to(&self, v: &mut Vec<u8>) {
+        v.extend_from_slice(self.as_bytes());
+    }
+
+    fn into_vec(self, capacity: usize) -> Vec<u8> {
+        let mut v = Vec::with_capacity(capacity);
+        v.extend_from_slice(self.as_bytes());
+        v
+    }
+}
+
+fn append_vcat<C: VCat>(v: &mut Vec<C::Item>, src: &C) {
+    v.reserve(src.size_hint());
+    src.append_to(v);
+}
+
+fn vcat_into_vec<C: VCat>(src: C) -> Vec<C::Item> {
+    let capacity = src.size_hint();
+    src.into_vec(capacity)
+}
+
+#[derive(Clone, Copy)]
+pub struct VCatMany<L: VCat, R: VCat<Item = L::Item>> {
+    lhs: L,
+    rhs: R,
+}
+
+impl<L, R> VCat for VCatMany<L, R>
+where
+    L: VCat,
+    R: VCat<Item = L::Item>,
+{
+    type Item = L::Item;
+
+    fn size_hint(&self) -> usize {
+        self.lhs
+            .size_hint()
+            .checked_add(self.rhs.size_hint())
+            .expect("capacity overflow")
+    }
+
+    fn append_to(&self, v: &mut Vec<L::Item>) {
+        self.lhs.append_to(v);
+        self.rhs.append_to(v);
+    }
+
+    fn into_vec(self, capacity: usize) -> Vec<L::Item> {
+        let mut v = self.lhs.into_vec(capacity);
+        self.rhs.append_to(&mut v);
+        v
+    }
+}
+
+impl<L, R> Add<VCatStart> for VCatMany<L, R>
+where
+    L: VCat,
+    R: VCat<Item = L::Item>,
+{
+    type Output = VCatMany<L, R>;
+    fn add(self, _rhs: VCatStart) -> VCatMany<L, R> {
+        self
+    }
+}
+
+impl<L, R, RR> Add<VCatOne<RR>> for VCatMany<L, R>
+where
+    L: VCat,
+    R: VCat<Item = L::Item>,
+    RR: VCat<Item = L::Item>,
+{
+    type Output = VCatMany<VCatMany<L, R>, RR>;
+    fn add(self, rhs: VCatOne<RR>) -> VCatMany<VCatMany<L, R>, RR> {
+        VCatMany {
+            lhs: self,
+            rhs: rhs.inner,
+        }
+    }
+}
+
+impl<L, R, RR> Add<RR> for VCatMany<L, R>
+where
+    L: VCat,
+    R: VCat<Item = L::Item>,
+    RR: VCat<Item = L::Item>,
+{
+    type Output = VCatMany<VCatMany<L, R>, RR>;
+    fn add(self, rhs: RR) -> VCatMany<VCatMany<L, R>, RR> {
+        VCatMany { lhs: self, rhs }
+    }
+}
+
+impl<L, R> AddAssign<VCatMany<L, R>> for Vec<L::Item>
+where
+    L: VCat,
+    R: VCat<Item = L::Item>,
+{
+    fn add_assign(&mut self, rhs: VCatMany<L, R>) {
+        append_vcat(self, &rhs);
+    }
+}
+
+impl<L, R> From<VCatMany<L, R>> for Vec<L::Item>
+where
+    L: VCat,
+    R: VCat<Item = L::Item>,
+{
+    fn from(src: VCatMany<L, R>) -> Vec<L::Item> {
+        vcat_into_vec(src)
+    }
+}
+
+impl<L, R> Debug for VCatMany<L, R>
+where
+    L: VCat + Debug,
+    R: VCat<Item = L::Item> + Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        Debug::fmt(&self.lhs, f)?;
+        f.write_str(" + ")?;
+        Debug::fmt(&self.rhs, f)
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct VCatOne<T: VCat> {
+    inner: T,
+}
+
+impl<T: VCat> Add<VCatStart> for VCatOne<T> {
+    type Output = VCatOne<T>;
+    fn add(self, _rhs: VCatStart) -> VCatOne<T> {
+        self
+    }
+}
+
+impl<L: VCat, R: VCat<Item = L::Item>> Add<VCatOne<R>> for VCatOne<L> {
+    type Output = VCatMany<L, R>;
+    fn add(self, rhs: VCatOne<R>) -> VCatMany<L, R> {
+        VCatMany {
+            lhs: self.inner,
+            rhs: rhs.inner,
+        }
+    }
+}
+
+impl<L: VCat, R: VCat<Item = L::Item>> Add<R> for VCatOne<L> {
+    type Output = VCatMany<L, R>;
+    fn add(self, rhs: R) -> VCatMany<L, R> {
+        VCatMany {
+            lhs: self.inner,
+            rhs,
+        }
+    }
+}
+
+impl<T: VCat> AddAssign<VCatOne<T>> for Vec<T::Item> {
+    fn add_assign(&mut self, rhs: VCatOne<T>) {
+        append_vcat(self, &rhs.inner);
+    }
+}
+
+impl<T: VCat> From<VCatOne<T>> for Vec<T::Item> {
+    fn from(src: VCatOne<T>) -> Vec<T::Item> {
+        vcat_into_vec(src.inner)
+    }
+}
+
+impl<T: VCat + Debug> Debug for VCatOne<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        Debug::fmt(&self.inner, f)
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct VCatStart;
+
+/// A term that is used to start a `Vec` concatenation.
+///
+/// This works like [`CAT`](constant.CAT.html), but concatenates
+/// slices and vectors of any `Clone` element type. The resulting
+/// vector is allocated at most once, and if the first item is a
+/// `Vec`, its buffer is reused.
+///
+/// # Examples
+///
+/// ```rust
+/// use sconcat::VCAT;
+///
+/// let header: &[u8] = b"GIF89a";
+/// let mut body = Vec::with_capacity(64);
+/// body.extend_from_slice(&[1, 2, 3]);
+/// let ptr = body.as_ptr();
+/// let v = Vec::from(VCAT + body + header + vec![0]);
+/// assert_eq!(v, b"\x01\x02\x03GIF89a\x00");
+/// assert_eq!(v.as_ptr(), ptr);
+///
+/// let mut tokens = vec!["let", "x"];
+/// tokens += VCAT + &["=", "1"][..] + vec![";"];
+/// assert_eq!(tokens, ["let", "x", "=", "1", ";"]);
+/// ```
+pub const VCAT: VCatStart = VCatStart;
+
+/// A term that is used to start a byte concatenation.
+///
+/// This is the same as [`VCAT`](constant.VCAT.html), and reads
+/// better when building binary data such as protocol frames. Byte
+/// slices, byte string literals, `Vec<u8>` buffers, single `u8`
+/// bytes and `&str` text can be mixed, and the result is a
+/// `Vec<u8>` that is allocated at most once.
+///
+/// # Examples
+///
+/// ```rust
+/// use sconcat::BCAT;
+///
+/// let payload = vec![0xde, 0xad];
+/// let len = payload.len() as u8;
+/// let frame = Vec::from(BCAT + b"\x02HDR" + len + payload + 0x03u8);
+/// assert_eq!(frame, b"\x02HDR\x02\xde\xad\x03");
+///
+/// let mut out = b"GET ".to_vec();
+/// out += BCAT + "/index.html" + b" HTTP/1.1\r\n";
+/// assert_eq!(out, b"GET /index.html HTTP/1.1\r\n");
+/// ```
+pub const BCAT: VCatStart = VCatStart;
+
+impl Add<VCatStart> for VCatStart {
+    type Output = VCatStart;
+    fn add(self, _rhs: VCatStart) -> VCatStart {
+        self
+    }
+}
+
+impl<T: VCat> Add<VCatOne<T>> for VCatStart {
+    type Output = VCatOne<T>;
+    fn add(self, rhs: VCatOne<T>) -> VCatOne<T> {
+        rhs
+    }
+}
+
+impl<T: VCat> Add<T> for VCatStart {
+    type Output = VCatOne<T>;
+    fn add(self, rhs: T) -> VCatOne<T> {
+        VCatOne { inner: rhs }
+    }
+}
+
+impl<T> AddAssign<VCatStart> for Vec<T> {
+    fn add_assign(&mut self, _rhs: VCatStart) {}
+}
+
+impl<T> From<VCatStart> for Vec<T> {
+    fn from(_src: VCatStart) -> Vec<T> {
+        Vec::new()
+    }
+}
+
+impl Debug for VCatStart {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("[]")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use BCAT;
+    use VCAT;
+
+    #[test]
+    fn it_works() {
+        let v = Vec::from(VCAT + &[1, 2][..] + vec![3] + VCAT + &[4][..]);
+        assert_eq!(v, [1, 2, 3, 4]);
+        assert_eq!(v.len(), v.capacity());
+
+        let mut v = Vec::with_capacity(10);
+        v.push(String::from("a"));
+        let ptr = v.as_ptr();
+        let owned = vec![String::from("b")];
+        v += VCAT + owned + &[String::from("c")][..];
+        assert_eq!(v, ["a", "b", "c"]);
+        assert_eq!(v.as_ptr(), ptr);
+        assert_eq!(Vec::<u8>::from(VCAT), [0u8; 0]);
+    }
+
+    #[test]
+    fn formatting() {
+        let one = VCAT + &[1][..];
+        assert_eq!(format!("{:?}", one), "[1]");
+        assert_eq!(format!("{:?}", one + vec![2, 3]), "[1] + [2, 3]");
+        assert_eq!(format!("{:?}", VCAT), "[]");
+    }
+
+    #[test]
+    fn bytes() {
+        let body = vec![b'x', b'y'];
+        let v = Vec::from(BCAT + b'<' + b"ab" + "é" + body + &b"!"[..]);
+        assert_eq!(v, "<abéxy!".as_bytes());
+        assert_eq!(v.len(), v.capacity());
+
+        let mut v = b"-".to_vec();
+        v += BCAT + 0u8;
+        v += BCAT + "z" + 1u8 + VCAT;
+        v += BCAT;
+        assert_eq!(v, b"-\x00z\x01");
+        assert_eq!(format!("{:?}", BCAT + 1u8 + b"\x02"), "1 + [2]");
+    }
+}
@@ -0,0 +1,301 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Integration with the unicode-width crate. Widths are measured
+// character by character, as a multi-character sequence can be split
+// across segments. Zero-width characters such as combining marks
+// always fit, so when text is truncated they stay with the character
+// before them and are dropped with a dropped character.
+
+use cat::Cat;
+use pad;
+use std::fmt::{self, Alignment, Display};
+use unicode_width::UnicodeWidthChar;
+
+fn char_width(c: char) -> usize {
+    c.width().unwrap_or(0)
+}
+
+fn str_width(s: &str) -> usize {
+    s.chars().map(char_width).sum()
+}
+
+fn cat_width<C: Cat>(cat: &C) -> usize {
+    let mut width = 0usize;
+    cat.for_each_segment(&mut |segment| {
+        width = width.saturating_add(str_width(segment))
+    });
+    width
+}
+
+/// A piece that pads another piece to a number of terminal columns.
+///
+/// This is created by the
+/// [`pad_display_width`](fn.pad_display_width.html) function.
+#[derive(Clone, Copy, Debug)]
+pub struct PadWidth<T: Cat> {
+    inner: T,
+    cols: usize,
+    align: Alignment,
+}
+
+/// Creates a piece that pads `value` with spaces on the right to at
+/// least `cols` terminal columns.
+///
+/// Unlike [`pad`](fn.pad.html), which counts characters, this
+/// measures the display width of `value`, so wide characters such as
+/// CJK ideographs and emoji count as two columns and combining marks
+/// count as none. This keeps table columns aligned when they contain
+/// such text.
+///
+/// # Examples
+///
+/// ```rust
+/// use sconcat::{CAT, pad_display_width};
+///
+/// let a = CAT + '|' + pad_display_width("東京", 6) + '|';
+/// let b = CAT + '|' + pad_display_width("Paris", 6) + '|';
+/// assert_eq!(String::from(a), "|東京  |");
+/// assert_eq!(String::from(b), "|Paris |");
+/// ```
+pub fn pad_display_width<T: Cat>(value: T, cols: usize) -> PadWidth<T> {
+    PadWidth {
+        inner: value,
+        cols,
+        align: Alignment::Left,
+    }
+}
+
+impl<T: Cat> PadWidth<T> {
+    /// Sets where the value is placed within the padding.
+    ///
+    /// The default is [`Alignment::Left`]; with
+    /// [`Alignment::Center`], any odd column of padding goes on the
+    /// right.
+    ///
+    /// [`Alignment::Center`]: https://doc.rust-lang.org/std/fmt/enum.Alignment.html
+    /// [`Alignment::Left`]: https://doc.rust-lang.org/std/fmt/enum.Alignment.html
+    pub fn with_align(self, align: Alignment) -> PadWidth<T> {
+        PadWidth { align, ..self }
+    }
+
+    // Returns the padding before and after the inner piece.
+    fn padding(&self) -> (usize, usize) {
+        let padding = self.cols.saturating_sub(cat_width(&self.inner));
+        match self.align {
+            Alignment::Left => (0, padding),
+            Alignment::Right => (padding, 0),
+            Alignment::Center => (padding / 2, padding - padding / 2),
+        }
+    }
+}
+
+impl<T: Cat> Cat for PadWidth<T> {
+    fn size_hint(&self) -> usize {
+        let (pre, post) = self.padding();
+        (pre + post)
+            .checked_add(self.inner.size_hint())
+            .expect("capacity overflow")
+    }
+
+    fn append_to(&self, s: &mut String) {
+        self.for_each_segment(&mut |segment| s.push_str(segment));
+    }
+
+    fn for_each_segment(&self, f: &mut dyn FnMut(&str)) {
+        let (pre, post) = self.padding();
+        pad::fill(f, ' ', pre);
+        self.inner.for_each_segment(f);
+        pad::fill(f, ' ', post);
+    }
+}
+
+impl<T: Cat> Display for PadWidth<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut result = Ok(());
+        self.for_each_segment(&mut |segment| {
+            if result.is_ok() {
+                result = f.write_str(segment);
+            }
+        });
+        result
+    }
+}
+
+/// A piece that truncates another piece to a number of terminal
+/// columns.
+///
+/// This is created by the
+/// [`truncate_display_width`](fn.truncate_display_width.html)
+/// function.
+#[derive(Clone, Copy, Debug)]
+pub struct TruncateWidth<'a, T: Cat> {
+    inner: T,
+    cols: usize,
+    ellipsis: &'a str,
+}
+
+/// Creates a piece that is `value` truncated to at most `cols`
+/// terminal columns.
+///
+/// Unlike [`truncate`](fn.truncate.html), which counts bytes, this
+/// measures the display width of `value`. A wide character that
+/// would straddle the limit is dropped, so the result can be one
+/// column narrower than `cols`, and combining marks are kept or
+/// dropped together with the character they follow. An ellipsis can
+/// be appended to truncated text with
+/// [`with_ellipsis`](struct.TruncateWidth.html#method.with_ellipsis).
+///
+/// # Examples
+///
+/// ```rust
+/// use sconcat::{CAT, truncate_display_width};
+///
+/// let cat = CAT + '|' + truncate_display_width("日本語テキスト", 5) + '|';
+/// assert_eq!(String::from(cat), "|日本|");
+/// let piece = truncate_display_width("déjà vu", 5).with_ellipsis("…");
+/// assert_eq!(String::from(CAT + piece), "déjà…");
+/// ```
+pub fn truncate_display_width<T: Cat>(
+    value: T,
+    cols: usize,
+) -> TruncateWidth<'static, T> {
+    TruncateWidth {
+        inner: value,
+        cols,
+        ellipsis: "",
+    }
+}
+
+impl<'a, T: Cat> TruncateWidth<'a, T> {
+    /// Sets the text appended when the piece is truncated.
+    ///
+    /// The width of the ellipsis counts towards the limit. If it is
+    /// wider than the limit, it is not appended.
+    pub fn with_ellipsis<'b>(self, ellipsis: &'b str) -> TruncateWidth<'b, T> {
+        TruncateWidth {
+            inner: self.inner,
+            cols: self.cols,
+            ellipsis,
+        }
+    }
+
+    /// Returns `true` if the piece is wider than the limit and is
+    /// truncated.
+    pub fn is_truncated(&self) -> bool {
+        cat_width(&self.inner) > self.cols
+    }
+}
+
+impl<'a, T: Cat> Cat for TruncateWidth<'a, T> {
+    fn size_hint(&self) -> usize {
+        if !self.is_truncated() {
+            return self.inner.size_hint();
+        }
+        let mut len = 0;
+        self.for_each_segment(&mut |segment| len += segment.len());
+        len
+    }
+
+    fn append_to(&self, s: &mut String) {
+        self.for_each_segment(&mut |segment| s.push_str(segment));
+    }
+
+    fn for_each_segment(&self, f: &mut dyn FnMut(&str)) {
+        if !self.is_truncated() {
+            self.inner.for_each_segment(f);
+            return;
+        }
+        let ellipsis_width = str_width(self.ellipsis);
+        let ellipsis = if ellipsis_width <= self.cols {
+            self.ellipsis
+        } else {
+            ""
+        };
+        let mut left = Some(self.cols - str_width(ellipsis));
+        self.inner.for_each_segment(&mut |segment| {
+            let cols = match left {
+                Some(cols) => cols,
+                None => return,
+            };
+            let mut cols = cols;
+            let mut end = segment.len();
+            for (i, c) in segment.char_indices() {
+                let width = char_width(c);
+                if width > cols {
+                    end = i;
+                    left = None;
+                    break;
+                }
+                cols -= width;
+            }
+            if left.is_some() {
+                left = Some(cols);
+            }
+            if end > 0 {
+                f(&segment[..end]);
+            }
+        });
+        f(ellipsis);
+    }
+}
+
+impl<'a, T: Cat> Display for TruncateWidth<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut result = Ok(());
+        self.for_each_segment(&mut |segment| {
+            if result.is_ok() {
+                result = f.write_str(segment);
+            }
+        });
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use CAT;
+    use cat::Cat;
+    use std::fmt::Alignment;
+    use super::{pad_display_width, truncate_display_width};
+
+    #[test]
+    fn pads() {
+        let s = String::from(
+            CAT + pad_display_width("日本", 6)
+                + '|'
+                + pad_display_width("e\u{301}", 3).with_align(Alignment::Right)
+                + '|'
+                + pad_display_width(CAT + "🦀" + 'x', 6)
+                    .with_align(Alignment::Center)
+                + '|'
+                + pad_display_width("toolong", 3),
+        );
+        assert_eq!(s, "日本  |  e\u{301}| 🦀x  |toolong");
+        assert_eq!(s.len(), s.capacity());
+    }
+
+    #[test]
+    fn truncates() {
+        let cat = CAT + "ab" + "日本" + "e\u{301}" + 'f';
+        let piece = truncate_display_width(cat, 3);
+        assert!(piece.is_truncated());
+        assert_eq!(piece.to_string(), "ab");
+        assert_eq!(piece.size_hint(), 2);
+        let piece = truncate_display_width(cat, 7);
+        assert_eq!(piece.to_string(), "ab日本e\u{301}");
+        let piece = truncate_display_width(cat, 8);
+        assert!(!piece.is_truncated());
+        assert_eq!(piece.to_string(), "ab日本e\u{301}f");
+        let piece = truncate_display_width(cat, 6).with_ellipsis("…");
+        assert_eq!(piece.to_string(), "ab日…");
+        let piece = truncate_display_width(cat, 1).with_ellipsis("..");
+        assert_eq!(piece.to_string(), "a");
+        assert_eq!(truncate_display_width(cat, 0).to_string(), "");
+    }
+}
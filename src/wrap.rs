@@ -0,0 +1,49 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use cat::{Cat, CatMany, CatOne};
+
+/// Creates a concatenation of `prefix`, `inner` and `suffix`.
+///
+/// This is the same as `CAT + prefix + inner + suffix`, but it reads
+/// better when a value is enclosed in quotes or brackets inside a
+/// longer concatenation. The result is itself a piece, so its size
+/// hint is the sum of the three size hints.
+///
+/// # Examples
+///
+/// ```rust
+/// use sconcat::{CAT, wrap};
+///
+/// let value = "x y";
+/// let cat = CAT + "key=" + wrap(value, '"', '"') + ' ' + wrap("v2", '(', ')');
+/// assert_eq!(String::from(cat), "key=\"x y\" (v2)");
+/// ```
+pub fn wrap<T: Cat, P: Cat, S: Cat>(
+    inner: T,
+    prefix: P,
+    suffix: S,
+) -> CatMany<CatMany<P, T>, S> {
+    CatOne { inner: prefix } + inner + suffix
+}
+
+#[cfg(test)]
+mod tests {
+    use CAT;
+    use super::wrap;
+
+    #[test]
+    fn wraps() {
+        let inner = CAT + 'a' + "bc";
+        let wrapped = wrap(inner, "[[", String::from("]]"));
+        assert_eq!(wrapped.size_hint(), 7);
+        let s = String::from(CAT + '<' + wrap(wrapped, "", ""));
+        assert_eq!(s, "<[[abc]]");
+        assert_eq!(s.len(), s.capacity());
+    }
+}
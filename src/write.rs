@@ -0,0 +1,281 @@
+// Copyright © 2017 Trevor Spiteri
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use cat::{Cat, CatMany, CatOne, CatStart, SegmentBuf};
+use std::io::{self, IoSlice, Write};
+
+// Size of the stack buffer that small segments are staged in.
+const STAGE_SIZE: usize = 1024;
+
+// Writes the segments through a stack buffer, so that a concatenation
+// of up to `STAGE_SIZE` bytes is written with a single `write_all`.
+// Segments that do not fit in the buffer are written directly.
+pub(crate) fn write_staged<C: Cat, W: Write>(cat: &C, w: &mut W) -> io::Result<usize> {
+    let mut stage = [0u8; STAGE_SIZE];
+    let mut staged = 0;
+    let mut written = 0;
+    let mut result = Ok(());
+    cat.for_each_segment(&mut |segment| {
+        if result.is_err() {
+            return;
+        }
+        let bytes = segment.as_bytes();
+        if staged + bytes.len() > STAGE_SIZE {
+            result = w.write_all(&stage[..staged]);
+            staged = 0;
+            if result.is_err() {
+                return;
+            }
+        }
+        if bytes.len() > STAGE_SIZE {
+            result = w.write_all(bytes);
+        } else {
+            stage[staged..staged + bytes.len()].copy_from_slice(bytes);
+            staged += bytes.len();
+        }
+        written += bytes.len();
+    });
+    result?;
+    w.write_all(&stage[..staged])?;
+    Ok(written)
+}
+
+/// Storage for the segments of a concatenation that are not
+/// borrowed from it, such as encoded characters, when it is exposed
+/// as `IoSlice`s.
+///
+/// This is passed to the `io_slices` methods of the concatenation
+/// types. It can be reused, in which case it only allocates when a
+/// concatenation has more segments than any previous one.
+#[derive(Clone, Debug, Default)]
+pub struct IoSliceStorage {
+    bufs: Vec<SegmentBuf>,
+}
+
+impl IoSliceStorage {
+    /// Creates empty storage.
+    pub fn new() -> IoSliceStorage {
+        IoSliceStorage { bufs: Vec::new() }
+    }
+}
+
+// Finds the pieces and indices of all the segments in order. Lists
+// are visited child by child, so that each segment is found without
+// walking the segments of the list before it.
+fn leaves<'a>(cat: &'a dyn Cat, out: &mut Vec<(&'a dyn Cat, usize)>) {
+    if let Some((lhs, rhs)) = cat.split() {
+        leaves(lhs, out);
+        leaves(rhs, out);
+        return;
+    }
+    let children = cat.child_count();
+    if children > 0 {
+        for index in 0..children {
+            leaves(cat.child_at(index), out);
+        }
+        return;
+    }
+    for index in 0..cat.segment_count() {
+        out.push((cat, index));
+    }
+}
+
+fn io_slices<'a>(
+    cat: &'a dyn Cat,
+    storage: &'a mut IoSliceStorage,
+) -> Vec<IoSlice<'a>> {
+    let mut segments = Vec::new();
+    leaves(cat, &mut segments);
+    if storage.bufs.len() < segments.len() {
+        storage.bufs.resize(segments.len(), SegmentBuf::default());
+    }
+    segments
+        .into_iter()
+        .zip(storage.bufs.iter_mut())
+        .map(|((piece, index), buf)| {
+            IoSlice::new(piece.segment_at(index, buf).as_bytes())
+        })
+        .collect()
+}
+
+impl<L: Cat, R: Cat> CatMany<L, R> {
+    /// Writes the concatenation to an `io::Write` target without
+    /// materializing it, and returns the number of bytes written.
+    ///
+    /// The segments are staged in a buffer on the stack, so that a
+    /// concatenation of up to 1024 bytes, such as a typical log line,
+    /// is written with a single `write_all` and no heap allocation.
+    /// Longer concatenations are written in several calls. Writing
+    /// stops at the first error.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use sconcat::CAT;
+    ///
+    /// let mut log = Vec::new();
+    /// let msg = String::from("ok");
+    /// let line = CAT + "level=" + "info" + " msg=" + msg + '\n';
+    /// let n = line.write_to_io(&mut log).unwrap();
+    /// assert_eq!(n, 18);
+    /// assert_eq!(log, b"level=info msg=ok\n");
+    /// ```
+    pub fn write_to_io<W: Write>(&self, w: &mut W) -> io::Result<usize> {
+        write_staged(self, w)
+    }
+
+    /// Returns the segments of the concatenation as `IoSlice`s for
+    /// `Write::write_vectored`.
+    ///
+    /// Segments are borrowed from the concatenation where possible,
+    /// and are otherwise stored in `storage`, for example a `char`
+    /// is encoded into a small buffer. Pieces that are formatted on
+    /// the fly are formatted again for each of their segments.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use sconcat::{CAT, IoSliceStorage};
+    /// use std::io::Write;
+    ///
+    /// let value = String::from("text/plain");
+    /// let head = CAT + "Content-Type: " + value + '\r' + '\n';
+    /// let mut storage = IoSliceStorage::new();
+    /// let slices = head.io_slices(&mut storage);
+    /// assert_eq!(slices.len(), 4);
+    /// let mut socket = Vec::new();
+    /// let n = socket.write_vectored(&slices).unwrap();
+    /// assert_eq!(n, 26);
+    /// assert_eq!(socket, b"Content-Type: text/plain\r\n");
+    /// ```
+    pub fn io_slices<'a>(
+        &'a self,
+        storage: &'a mut IoSliceStorage,
+    ) -> Vec<IoSlice<'a>> {
+        io_slices(self, storage)
+    }
+}
+
+impl<T: Cat> CatOne<T> {
+    /// Writes the concatenation to an `io::Write` target without
+    /// materializing it, and returns the number of bytes written.
+    pub fn write_to_io<W: Write>(&self, w: &mut W) -> io::Result<usize> {
+        write_staged(&self.inner, w)
+    }
+
+    /// Returns the segments of the concatenation as `IoSlice`s for
+    /// `Write::write_vectored`.
+    pub fn io_slices<'a>(
+        &'a self,
+        storage: &'a mut IoSliceStorage,
+    ) -> Vec<IoSlice<'a>> {
+        io_slices(&self.inner, storage)
+    }
+}
+
+impl CatStart {
+    /// Writes nothing to an `io::Write` target and returns zero.
+    pub fn write_to_io<W: Write>(&self, _w: &mut W) -> io::Result<usize> {
+        Ok(0)
+    }
+
+    /// Returns no `IoSlice`s, as the empty concatenation has no
+    /// segments.
+    pub fn io_slices(
+        &self,
+        _storage: &mut IoSliceStorage,
+    ) -> Vec<IoSlice<'static>> {
+        Vec::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use CAT;
+    use Dbg;
+    use std::io::{self, Write};
+    use super::{IoSliceStorage, STAGE_SIZE};
+
+    // Records the length of every call to `write`.
+    struct Calls {
+        bytes: Vec<u8>,
+        writes: Vec<usize>,
+    }
+
+    impl Write for Calls {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.bytes.extend_from_slice(buf);
+            self.writes.push(buf.len());
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn calls() -> Calls {
+        Calls {
+            bytes: Vec::new(),
+            writes: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn staged() {
+        let mut w = calls();
+        let cat = CAT + "ab" + 'é' + String::from("cd");
+        assert_eq!(cat.write_to_io(&mut w).unwrap(), 6);
+        assert_eq!(w.bytes, "abécd".as_bytes());
+        assert_eq!(w.writes, [6]);
+
+        let big = "x".repeat(STAGE_SIZE + 1);
+        let mut w = calls();
+        let cat = CAT + "a" + big.as_str() + "b" + 'c';
+        let n = cat.write_to_io(&mut w).unwrap();
+        assert_eq!(n, STAGE_SIZE + 4);
+        assert_eq!(w.bytes, cat.to_string().as_bytes());
+        assert_eq!(w.writes, [1, STAGE_SIZE + 1, 2]);
+
+        let mut w = calls();
+        assert_eq!((CAT + "x").write_to_io(&mut w).unwrap(), 1);
+        assert_eq!(CAT.write_to_io(&mut w).unwrap(), 0);
+        assert_eq!(w.bytes, b"x");
+    }
+
+    #[test]
+    fn vectored() {
+        let inner = CAT + 'é' + "fg";
+        let cat = CAT + "ab" + inner + Dbg([1]) + String::from("z");
+        let mut storage = IoSliceStorage::new();
+        let slices = cat.io_slices(&mut storage);
+        let mut joined = Vec::new();
+        for slice in &slices {
+            joined.extend_from_slice(slice);
+        }
+        assert_eq!(joined, cat.to_string().as_bytes());
+        assert_eq!(&*slices[0], b"ab");
+        assert_eq!(&*slices[1], "é".as_bytes());
+        let one = CAT + 'x';
+        let slices = one.io_slices(&mut storage);
+        assert_eq!(slices.len(), 1);
+        assert_eq!(&*slices[0], b"x");
+        assert!(CAT.io_slices(&mut storage).is_empty());
+    }
+
+    #[test]
+    fn vectored_list() {
+        let items: Vec<String> = (0..40_000).map(|i| i.to_string()).collect();
+        let cat = CAT + '[' + &items[..] + ']';
+        let mut storage = IoSliceStorage::new();
+        let slices = cat.io_slices(&mut storage);
+        assert_eq!(slices.len(), 40_002);
+        assert_eq!(slices[1].as_ptr(), items[0].as_ptr());
+        assert_eq!(&*slices[40_000], b"39999");
+    }
+}